@@ -5,7 +5,8 @@
  * Feature Flag(taffyGrid)가 활성화된 경우에만 사용됩니다.
  *
  * CSS Grid 속성 → TaffyStyle 변환 포함:
- * - gridTemplateColumns / gridTemplateRows (트랙 배열)
+ * - gridTemplateColumns / gridTemplateRows (트랙 배열, `subgrid [name]...` 포함)
+ * - gridTemplateRows: masonry (열 트랙 크기는 grid 와 동일, 아이템은 가장 짧은 열에 배치)
  * - gridAutoFlow, gridAutoColumns, gridAutoRows
  * - gridColumn / gridRow (line 기반 배치: "1 / 3", "span 2")
  * - gridArea (숫자 기반 shorthand: "row-start / col-start / row-end / col-end")
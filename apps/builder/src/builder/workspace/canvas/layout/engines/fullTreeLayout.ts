@@ -568,6 +568,8 @@ function taffyStyleToRecord(style: TaffyStyle): Record<string, unknown> {
   if (style.position !== undefined) result.position = style.position;
   if (style.overflowX !== undefined) result.overflowX = style.overflowX;
   if (style.overflowY !== undefined) result.overflowY = style.overflowY;
+  if (style.direction !== undefined) result.direction = style.direction;
+  if (style.writingMode !== undefined) result.writingMode = style.writingMode;
 
   // Flex container
   if (style.flexDirection !== undefined)
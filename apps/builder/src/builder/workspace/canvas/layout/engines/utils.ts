@@ -3946,6 +3946,19 @@ export function applyCommonTaffyStyle(
   if (style.overflowX) result.overflowX = style.overflowX;
   if (style.overflowY) result.overflowY = style.overflowY;
 
+  // Direction — 상속 속성이므로 명시된 ltr/rtl만 전달 (inherit는 부모 값 사용)
+  if (style.direction === "ltr" || style.direction === "rtl") {
+    result.direction = style.direction;
+  }
+
+  // Writing mode — 상속 속성. sideways-*는 같은 방향의 vertical-*로 배치
+  const writingMode = style.writingMode as string | undefined;
+  if (writingMode === "horizontal-tb" || writingMode === "vertical-rl" || writingMode === "vertical-lr") {
+    result.writingMode = writingMode;
+  } else if (writingMode === "sideways-rl" || writingMode === "sideways-lr") {
+    result.writingMode = writingMode.replace("sideways", "vertical");
+  }
+
   // Aspect ratio — CSS 형식("16 / 9")을 숫자로 변환
   if (
     style.aspectRatio !== undefined &&
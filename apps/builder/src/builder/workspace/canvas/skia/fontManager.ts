@@ -13,6 +13,12 @@
 
 import type { FontMgr, Typeface } from "canvaskit-wasm";
 import { getCanvasKit } from "./initCanvasKit";
+import {
+  clearFontMetrics,
+  registerFontMetrics,
+  unregisterFontMetrics,
+} from "../wasm-bindings/fontMetrics";
+import { isRustWasmReady } from "../wasm-bindings/rustWasm";
 
 const IDB_NAME = "composition-fonts";
 const IDB_VERSION = 2; // v2: 이전 잘못된 서브셋 캐시 무효화
@@ -40,6 +46,45 @@ function familyFromKey(key: string): string {
   return key.split("::")[0];
 }
 
+/** CSS font-weight 문자열 → 숫자 (normal=400, bold=700) */
+function parseWeight(weight: string | undefined): number {
+  if (weight === "bold") return 700;
+  const n = Number(weight);
+  return Number.isFinite(n) && n > 0 ? n : 400;
+}
+
+/** advance 측정 기준 크기. font unit 대신 이 크기(px)로 잰 값을 그대로 등록한다 */
+const METRICS_UNITS_PER_EM = 1000;
+
+/**
+ * 레이아웃 텍스트 측정용 advance 테이블에 담을 코드 포인트 범위 (모두 BMP).
+ * Basic Latin, Latin-1, 일반 구두점, CJK 구두점, 한글 호환 자모, 한글 음절, 전각/반각.
+ */
+const METRICS_RANGES: ReadonlyArray<readonly [number, number]> = [
+  [0x20, 0x7e],
+  [0xa0, 0xff],
+  [0x2000, 0x206f],
+  [0x3000, 0x303f],
+  [0x3130, 0x318f],
+  [0xac00, 0xd7a3],
+  [0xff00, 0xffef],
+];
+
+/** METRICS_RANGES의 코드 포인트 배열과 같은 순서의 문자열 (한 번만 생성) */
+let metricsCodepoints: Uint32Array | null = null;
+let metricsText = "";
+
+function getMetricsCodepoints(): Uint32Array {
+  if (metricsCodepoints) return metricsCodepoints;
+  const cps: number[] = [];
+  for (const [start, end] of METRICS_RANGES) {
+    for (let cp = start; cp <= end; cp++) cps.push(cp);
+  }
+  metricsCodepoints = Uint32Array.from(cps);
+  metricsText = cps.map((cp) => String.fromCharCode(cp)).join("");
+  return metricsCodepoints;
+}
+
 /**
  * CanvasKit용 폰트 매니저.
  *
@@ -99,6 +144,7 @@ export class SkiaFontManager {
     this.typefaces.set(key, typeface);
     this.buffers.set(key, buffer);
     this.extractAndMapName(family, buffer);
+    this.registerLayoutMetrics(key, typeface);
     this.dirty = true;
   }
 
@@ -124,6 +170,7 @@ export class SkiaFontManager {
     this.typefaces.set(key, typeface);
     this.buffers.set(key, buffer);
     this.extractAndMapName(family, buffer);
+    this.registerLayoutMetrics(key, typeface);
     this.dirty = true;
 
     // IndexedDB 캐싱 (fire-and-forget)
@@ -150,6 +197,9 @@ export class SkiaFontManager {
       this.typefaces.get(key)!.delete();
       this.typefaces.delete(key);
       this.buffers.delete(key);
+      if (key.endsWith("::normal")) {
+        unregisterFontMetrics(family, parseWeight(key.split("::")[1]));
+      }
     }
     if (keys.length > 0) {
       this.nameMap.delete(family);
@@ -244,6 +294,7 @@ export class SkiaFontManager {
     this.typefaces.clear();
     this.buffers.clear();
     this.nameMap.clear();
+    clearFontMetrics();
 
     if (this.fontMgr) {
       this.fontMgr.delete();
@@ -252,6 +303,56 @@ export class SkiaFontManager {
     this.dirty = true;
   }
 
+  /**
+   * 로드된 모든 폰트를 Rust WASM 메트릭 레지스트리에 (재)등록한다.
+   * CanvasKit이 Rust WASM보다 먼저 준비되어 이미 로드된 폰트가 있을 때 사용.
+   */
+  syncLayoutMetrics(): void {
+    for (const [key, typeface] of this.typefaces) {
+      this.registerLayoutMetrics(key, typeface);
+    }
+  }
+
+  /**
+   * Typeface의 advance width·수직 메트릭을 Rust WASM 레지스트리에 등록한다.
+   * 레이아웃 엔진이 이 폰트의 텍스트를 JS 콜백 없이 측정하게 된다.
+   * 레지스트리는 style을 구분하지 않으므로 normal(upright) 변형만 등록한다.
+   * Rust WASM 미로드 시 no-op (syncLayoutMetrics()로 나중에 채움).
+   */
+  private registerLayoutMetrics(key: string, typeface: Typeface): void {
+    const [family, weight, style] = key.split("::");
+    if (style !== "normal" || !isRustWasmReady()) return;
+    try {
+      const ck = getCanvasKit();
+      const codepoints = getMetricsCodepoints();
+      const font = new ck.Font(typeface, METRICS_UNITS_PER_EM);
+      const glyphIds = font.getGlyphIDs(metricsText);
+      const widths = font.getGlyphWidths(glyphIds);
+      const { ascent, descent, leading } = font.getMetrics();
+      font.delete();
+
+      // glyph 0 (.notdef)은 폰트에 없는 문자 — 테이블에서 제외해 Rust 기본값을 쓰게 한다
+      const covered: number[] = [];
+      const advances: number[] = [];
+      for (let i = 0; i < codepoints.length; i++) {
+        if (glyphIds[i] === 0) continue;
+        covered.push(codepoints[i]);
+        advances.push(widths[i]);
+      }
+
+      registerFontMetrics(family, parseWeight(weight), {
+        unitsPerEm: METRICS_UNITS_PER_EM,
+        ascent,
+        descent,
+        lineGap: leading,
+        codepoints: Uint32Array.from(covered),
+        advances: Float32Array.from(advances),
+      });
+    } catch {
+      // 메트릭 추출 실패 시 레이아웃은 JS/문자 수 측정기로 폴백
+    }
+  }
+
   /**
    * 폰트 바이너리에서 내장 패밀리 이름을 추출하여 nameMap에 저장.
    * CanvasKit FontMgr.FromData()는 바이너리 name 테이블의 이름을 사용하므로
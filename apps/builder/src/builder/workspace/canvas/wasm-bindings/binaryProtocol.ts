@@ -10,6 +10,8 @@
  * @since 2026-02-28
  */
 
+import type { TaffyLeafContent } from './taffyLayout';
+
 // ─── 공개 타입 ────────────────────────────────────────────────────────
 
 /**
@@ -19,6 +21,8 @@
 export interface BinaryBatchInput {
   style: Record<string, unknown>;
   children: number[];
+  /** 리프의 측정 콘텐츠. 사이드밴드 JSON의 "content" 키로 전달 (자식이 있으면 무시) */
+  content?: TaffyLeafContent;
 }
 
 // ─── 매직 바이트 / 버전 상수 ─────────────────────────────────────────
@@ -78,6 +82,8 @@ const FIELD_MAP: Record<string, number> = {
   gridColumnEnd: 42,
   gridRowStart: 43,
   gridRowEnd: 44,
+  direction: 45,
+  writingMode: 46,
 };
 
 /** JSON 사이드밴드로 처리하는 Grid 배열 필드 집합 */
@@ -86,8 +92,50 @@ const GRID_SIDEBAND_KEYS = new Set([
   'gridTemplateRows',
   'gridAutoColumns',
   'gridAutoRows',
+  'gridTemplateAreas',
+]);
+
+/** 글꼴/뷰포트 상대 단위 (em, rem, vw, vh, vmin, vmax) */
+const RELATIVE_UNIT_RE = /\d(r?em|vw|vh|vmin|vmax)\b/;
+
+/** 레이아웃 시점에 해석하는 CSS 수학 함수 */
+const MATH_FUNCTION_RE = /^(calc|min|max|clamp)\(/;
+
+/**
+ * 바이너리 인코딩이 없는 길이 값(calc()/min()/max()/clamp(), 상대 단위)인지 확인.
+ * 해당 필드는 비트맵에서 제외하고 JSON 사이드밴드에 문자열로 전달한다.
+ */
+function isSidebandLength(value: unknown): value is string {
+  if (typeof value !== 'string') return false;
+  const v = value.trim();
+  return MATH_FUNCTION_RE.test(v) || RELATIVE_UNIT_RE.test(v);
+}
+
+/** Grid placement 필드 (비트 41~44) */
+const GRID_PLACEMENT_KEYS = new Set([
+  'gridColumnStart',
+  'gridColumnEnd',
+  'gridRowStart',
+  'gridRowEnd',
 ]);
 
+/**
+ * 이름 있는 grid placement("main-start", "span name", "2 col")인지 확인.
+ * 바이너리 인코딩은 line/span 정수만 표현하므로 JSON 사이드밴드로 전달한다.
+ */
+function isNamedGridPlacement(key: string, value: unknown): value is string {
+  if (!GRID_PLACEMENT_KEYS.has(key) || typeof value !== 'string') return false;
+  return value
+    .trim()
+    .split(/\s+/)
+    .some((t) => t !== 'span' && t !== 'auto' && Number.isNaN(Number(t)));
+}
+
+/** 비트맵 대신 JSON 사이드밴드로 전달하는 필드 값인지 확인 */
+function isSidebandValue(key: string, value: unknown): value is string {
+  return isSidebandLength(value) || isNamedGridPlacement(key, value);
+}
+
 // ─── 필드 범위 상수 ───────────────────────────────────────────────────
 
 /** Enum 필드: 비트 0~12 (1바이트 u8) */
@@ -114,6 +162,10 @@ const LP_BIT_MAX = 40;
 const GRID_PLACE_BIT_MIN = 41;
 const GRID_PLACE_BIT_MAX = 44;
 
+/** 추가 enum 필드: 비트 45~46 (1바이트 u8) — Grid 뒤에 추가되어 범위가 분리됨 */
+const LATE_ENUM_BIT_MIN = 45;
+const LATE_ENUM_BIT_MAX = 46;
+
 // ─── Dimension/LP/LPA type 상수 ────────────────────────────────────────
 
 const TAG_AUTO = 0;
@@ -137,6 +189,7 @@ const GP_SPAN = 2;
  * - LPA   (23~30): 5바이트 (type u8 + value f32)
  * - LP    (31~40): 5바이트 (type u8 + value f32)
  * - Grid  (41~44): 3바이트 (type u8 + value i16)
+ * - Enum  (45~46): 1바이트
  */
 function fieldSizeForBit(bit: number): number {
   if (bit >= ENUM_BIT_MIN && bit <= ENUM_BIT_MAX) return 1;
@@ -145,6 +198,7 @@ function fieldSizeForBit(bit: number): number {
   if (bit >= LPA_BIT_MIN && bit <= LPA_BIT_MAX) return 5;
   if (bit >= LP_BIT_MIN && bit <= LP_BIT_MAX) return 5;
   if (bit >= GRID_PLACE_BIT_MIN && bit <= GRID_PLACE_BIT_MAX) return 3;
+  if (bit >= LATE_ENUM_BIT_MIN && bit <= LATE_ENUM_BIT_MAX) return 1;
   return 0;
 }
 
@@ -156,6 +210,15 @@ const DISPLAY_MAP: Record<string, number> = {
   grid: 1,
   block: 2,
   none: 3,
+  table: 4,
+  'table-header-group': 5,
+  'table-row-group': 6,
+  'table-footer-group': 7,
+  'table-row': 8,
+  'table-cell': 9,
+  'table-caption': 10,
+  'table-column': 11,
+  'table-column-group': 11,
 };
 
 /** position 값 → u8 */
@@ -252,6 +315,19 @@ const GRID_AUTO_FLOW_MAP: Record<string, number> = {
   'column dense': 3,
 };
 
+/** direction 값 → u8 ("inherit"는 필드 자체를 생략) */
+const DIRECTION_MAP: Record<string, number> = {
+  ltr: 0,
+  rtl: 1,
+};
+
+/** writingMode 값 → u8 */
+const WRITING_MODE_MAP: Record<string, number> = {
+  'horizontal-tb': 0,
+  'vertical-rl': 1,
+  'vertical-lr': 2,
+};
+
 /**
  * 비트 위치에 맞는 enum 매핑 테이블을 반환한다.
  * 해당 비트가 enum 필드가 아니거나 테이블이 없으면 null 반환.
@@ -271,6 +347,8 @@ function getEnumMap(bit: number): Record<string, number> | null {
     case 10: return ALIGN_SELF_MAP;
     case 11: return JUSTIFY_SELF_MAP;
     case 12: return GRID_AUTO_FLOW_MAP;
+    case 45: return DIRECTION_MAP;
+    case 46: return WRITING_MODE_MAP;
     default: return null;
   }
 }
@@ -341,13 +419,19 @@ function parseGridPlacement(value: unknown): { type: number; value: number } {
 
 // ─── Grid JSON 사이드밴드 ─────────────────────────────────────────────
 
+/** 비트맵 필드가 없어 사이드밴드로 보내는 table 속성 */
+const TABLE_SIDEBAND_KEYS = ['tableLayout', 'borderSpacing', 'captionSide', 'colSpan', 'rowSpan'] as const;
+
 /**
  * Grid 배열 필드가 존재하면 JSON 사이드밴드 바이트 배열을 생성.
  * 없으면 빈 Uint8Array 반환.
  *
- * 포함 필드: gridTemplateColumns, gridTemplateRows, gridAutoColumns, gridAutoRows
+ * 포함 필드: gridTemplateColumns, gridTemplateRows, gridAutoColumns, gridAutoRows,
+ * gridTemplateAreas, calc() 길이 필드·이름 있는 grid placement (키 그대로, 문자열 값),
+ * table 속성 (tableLayout, borderSpacing, captionSide, colSpan, rowSpan),
+ * 리프 콘텐츠 (content)
  */
-function buildGridJson(style: Record<string, unknown>): Uint8Array {
+function buildGridJson(style: Record<string, unknown>, content?: TaffyLeafContent): Uint8Array {
   const obj: Record<string, unknown> = {};
 
   if (style.gridTemplateColumns !== undefined) {
@@ -362,6 +446,26 @@ function buildGridJson(style: Record<string, unknown>): Uint8Array {
   if (style.gridAutoRows !== undefined) {
     obj.gridAutoRows = style.gridAutoRows;
   }
+  if (style.gridTemplateAreas !== undefined) {
+    obj.gridTemplateAreas = style.gridTemplateAreas;
+  }
+  for (const key of Object.keys(style)) {
+    if (FIELD_MAP[key] !== undefined && isSidebandValue(key, style[key])) {
+      obj[key] = style[key];
+    }
+  }
+  for (const key of TABLE_SIDEBAND_KEYS) {
+    if (style[key] !== undefined && style[key] !== null) {
+      obj[key] = style[key];
+    }
+  }
+  // fontSize: em 기준값. 비트맵 필드가 없으므로 항상 사이드밴드로 전달
+  if (style.fontSize !== undefined && style.fontSize !== null) {
+    obj.fontSize = typeof style.fontSize === 'number' ? `${style.fontSize}px` : style.fontSize;
+  }
+  if (content !== undefined) {
+    obj.content = content;
+  }
 
   if (Object.keys(obj).length === 0) {
     return new Uint8Array(0);
@@ -411,6 +515,8 @@ function planNode(node: BinaryBatchInput): NodePlan {
     // 값이 유효한지 검증 (undefined/null 제외)
     const val = node.style[key];
     if (val === undefined || val === null) continue;
+    // calc()·이름 있는 grid placement는 사이드밴드로 전달
+    if (isSidebandValue(key, val)) continue;
 
     // 비트맵 설정 (u64를 low/high 두 u32로 표현)
     if (bit < 32) {
@@ -425,7 +531,7 @@ function planNode(node: BinaryBatchInput): NodePlan {
   presentBits.sort((a, b) => a - b);
 
   // Grid JSON 사이드밴드
-  const gridJsonBytes = buildGridJson(node.style);
+  const gridJsonBytes = buildGridJson(node.style, node.content);
 
   // 바이트 크기 계산:
   // 8 (bitmap u64) + 2 (childCount u16) + 2*N (childIndices) + 2 (gridJsonLen) + gridJson.length
@@ -538,6 +644,10 @@ function writeField(
     writeGridPlacementField(view, offset, value);
     return offset + 3;
   }
+  if (bit >= LATE_ENUM_BIT_MIN && bit <= LATE_ENUM_BIT_MAX) {
+    writeEnumField(view, offset, bit, value);
+    return offset + 1;
+  }
   return offset;
 }
 
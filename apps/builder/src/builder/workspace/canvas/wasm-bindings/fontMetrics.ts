@@ -0,0 +1,134 @@
+/**
+ * Font Metrics Registry TypeScript 래퍼
+ *
+ * Rust WASM font_metrics 레지스트리에 폰트 face의 advance width 테이블과
+ * 수직 메트릭을 등록한다. 등록 후 TaffyLayoutEngine·block_layout의 텍스트
+ * 측정이 JS 경계를 넘지 않고 Rust 안에서 수행된다.
+ *
+ * 레지스트리는 WASM 인스턴스당 하나다. Rust WASM이 아직 로드되지 않았으면
+ * 모든 함수가 no-op이며, 로드 후 skiaFontManager.syncLayoutMetrics()로 채운다.
+ *
+ * @see wasm/src/font_metrics.rs
+ */
+
+import { getRustWasm } from './rustWasm';
+
+// ─── Types ────────────────────────────────────────────────────────────
+
+/** 한 폰트 face의 메트릭. 모든 값은 font unit 기준. */
+export interface FontFaceMetrics {
+  unitsPerEm: number;
+  /** baseline 위 거리 (부호 무관) */
+  ascent: number;
+  /** baseline 아래 거리 (부호 무관, hhea는 음수로 저장) */
+  descent: number;
+  lineGap: number;
+  /** 테이블에 없는 코드 포인트의 advance. 미지정 시 unitsPerEm * 0.5 */
+  missingAdvance?: number;
+  /** 테이블이 다루는 Unicode 코드 포인트 */
+  codepoints: Uint32Array;
+  /** codepoints와 같은 순서의 advance width */
+  advances: Float32Array;
+}
+
+/** px 단위 수직 메트릭. */
+export interface FontVerticalMetrics {
+  ascent: number;
+  descent: number;
+  normalLineHeight: number;
+}
+
+// ─── WASM module interface ───────────────────────────────────────────
+
+/** wasm-bindgen이 생성한 font_metrics 자유 함수 시그니처. */
+interface WasmFontMetricsModule {
+  register_font_metrics(
+    family: string,
+    weight: number,
+    metrics: Float32Array,
+    codepoints: Uint32Array,
+    advances: Float32Array,
+  ): boolean;
+  unregister_font_metrics(family: string, weight: number): boolean;
+  clear_font_metrics(): void;
+  has_font_metrics(family: string): boolean;
+  measure_text_width(text: string, family: string, fontSize: number, weight: number): number;
+  font_vertical_metrics(family: string, fontSize: number, weight: number): Float32Array;
+}
+
+function getModule(): WasmFontMetricsModule | null {
+  return getRustWasm() as WasmFontMetricsModule | null;
+}
+
+// ─── Registry ────────────────────────────────────────────────────────
+
+/**
+ * 폰트 face 메트릭 등록 (같은 family/weight가 있으면 교체).
+ * @returns 등록 성공 여부. WASM 미로드 또는 unitsPerEm이 양수가 아니면 false
+ */
+export function registerFontMetrics(
+  family: string,
+  weight: number,
+  face: FontFaceMetrics,
+): boolean {
+  const wasm = getModule();
+  if (!wasm) return false;
+
+  const metrics = new Float32Array(face.missingAdvance !== undefined ? 5 : 4);
+  metrics[0] = face.unitsPerEm;
+  metrics[1] = face.ascent;
+  metrics[2] = face.descent;
+  metrics[3] = face.lineGap;
+  if (face.missingAdvance !== undefined) metrics[4] = face.missingAdvance;
+
+  return wasm.register_font_metrics(family, weight, metrics, face.codepoints, face.advances);
+}
+
+/** face 하나 제거. 존재했으면 true */
+export function unregisterFontMetrics(family: string, weight: number): boolean {
+  return getModule()?.unregister_font_metrics(family, weight) ?? false;
+}
+
+/** 등록된 모든 face 제거 */
+export function clearFontMetrics(): void {
+  getModule()?.clear_font_metrics();
+}
+
+/** family(아무 weight)가 등록되어 있는지 확인 */
+export function hasFontMetrics(family: string): boolean {
+  return getModule()?.has_font_metrics(family) ?? false;
+}
+
+// ─── Measurement ─────────────────────────────────────────────────────
+
+/**
+ * 등록된 메트릭으로 단일 줄 텍스트 너비(px) 측정.
+ * @returns family 미등록 또는 WASM 미로드 시 null
+ */
+export function measureTextWidth(
+  text: string,
+  family: string,
+  fontSize: number,
+  weight = 400,
+): number | null {
+  const wasm = getModule();
+  if (!wasm) return null;
+  const width = wasm.measure_text_width(text, family, fontSize, weight);
+  return width < 0 ? null : width;
+}
+
+/**
+ * px 단위 ascent / descent / normal line-height.
+ * @returns family 미등록 또는 WASM 미로드 시 null
+ */
+export function fontVerticalMetrics(
+  family: string,
+  fontSize: number,
+  weight = 400,
+): FontVerticalMetrics | null {
+  const wasm = getModule();
+  if (!wasm) return null;
+  const raw = wasm.font_vertical_metrics(family, fontSize, weight);
+  if (raw.length < 3) return null;
+  return { ascent: raw[0], descent: raw[1], normalLineHeight: raw[2] };
+}
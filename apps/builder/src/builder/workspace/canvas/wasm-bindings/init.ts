@@ -37,6 +37,15 @@ export async function initAllWasm(): Promise<void> {
     await Promise.all(tasks);
     wasmReady = true;
 
+    // CanvasKit이 먼저 준비되어 이미 로드된 폰트의 메트릭을 Rust 레지스트리에 채운다
+    if (WASM_FLAGS.CANVASKIT_RENDERER && WASM_FLAGS.LAYOUT_ENGINE) {
+      const { isRustWasmReady } = await import("./rustWasm");
+      if (isRustWasmReady()) {
+        const { skiaFontManager } = await import("../skia/fontManager");
+        skiaFontManager.syncLayoutMetrics();
+      }
+    }
+
     // Phase 4: Layout Worker (Rust WASM 초기화 후)
     if (WASM_FLAGS.LAYOUT_WORKER) {
       const { isRustWasmReady } = await import("./rustWasm");
@@ -11,21 +11,69 @@
 import { getRustWasm } from './rustWasm';
 
 /** Block layout field count per child (must match Rust FIELD_COUNT) */
-export const BLOCK_FIELD_COUNT = 19;
+export const BLOCK_FIELD_COUNT = 30;
+
+/** Trailing metadata count after child rects (must match Rust META_FIELDS) */
+export const BLOCK_META_COUNT = 3;
 
 /** Display type constants (must match Rust) */
 export const DISPLAY = {
   BLOCK: 0,
   INLINE_BLOCK: 1,
   EMPTY_BLOCK: 2,
+  TEXT: 3,
 } as const;
 
-/** Vertical align constants (must match Rust) */
+/** Vertical align constants (must match Rust). LENGTH 은 verticalAlignLength 만큼 baseline 을 올린다 */
 export const VALIGN = {
   BASELINE: 0,
   TOP: 1,
   MIDDLE: 2,
   BOTTOM: 3,
+  TEXT_TOP: 4,
+  TEXT_BOTTOM: 5,
+  SUB: 6,
+  SUPER: 7,
+  LENGTH: 8,
+} as const;
+
+/** Float constants (must match Rust) */
+export const FLOAT = {
+  NONE: 0,
+  LEFT: 1,
+  RIGHT: 2,
+} as const;
+
+/** Clear constants (must match Rust) */
+export const CLEAR = {
+  NONE: 0,
+  LEFT: 1,
+  RIGHT: 2,
+  BOTH: 3,
+} as const;
+
+/** Position constants (must match Rust). fixed 는 ABSOLUTE 로 전달 */
+export const POSITION = {
+  STATIC: 0,
+  RELATIVE: 1,
+  ABSOLUTE: 2,
+} as const;
+
+/** Writing mode constants (must match Rust) */
+export const WRITING_MODE = {
+  HORIZONTAL_TB: 0,
+  VERTICAL_RL: 1,
+  VERTICAL_LR: 2,
+} as const;
+
+/** text-align constants (must match Rust). start/end 는 direction 을 따른다 */
+export const TEXT_ALIGN = {
+  START: 0,
+  END: 1,
+  LEFT: 2,
+  RIGHT: 3,
+  CENTER: 4,
+  JUSTIFY: 5,
 } as const;
 
 /** Sentinel for "auto" value */
@@ -34,39 +82,99 @@ export const AUTO = -1;
 /** Sentinel for "fit-content" value (must match Rust FIT_CONTENT) */
 export const FIT_CONTENT = -2;
 
+/** Sentinel for "auto" inset (must match Rust INSET_AUTO). inset 은 음수가 가능해 NaN 사용 */
+export const INSET_AUTO = NaN;
+
+/** Sentinel for "auto" margin (must match Rust MARGIN_AUTO). 좌우 auto 는 블록을 가운데/한쪽으로 민다 */
+export const MARGIN_AUTO = NaN;
+
+/** Text fragment field count (must match Rust FRAGMENT_FIELDS) */
+export const FRAGMENT_FIELD_COUNT = 7;
+
 // ── Block Layout ──
 
+/**
+ * px 값 또는 퍼센트 문자열 ("50%"). 퍼센트는 Rust에서 해석한다:
+ * margin/padding·width 는 컨테이너 너비, height 는 definite availableHeight 기준
+ */
+export type BlockLength = number | `${number}%`;
+
 export interface BlockLayoutInput {
   display: number; // DISPLAY constant
-  width: number; // AUTO for auto
-  height: number; // AUTO for auto
-  marginTop: number;
-  marginRight: number;
-  marginBottom: number;
-  marginLeft: number;
+  width: BlockLength; // AUTO for auto
+  height: BlockLength; // AUTO for auto
+  marginTop: BlockLength;
+  marginRight: BlockLength; // MARGIN_AUTO for auto
+  marginBottom: BlockLength;
+  marginLeft: BlockLength; // MARGIN_AUTO for auto
   bfcFlag: number; // 0 or 1
-  padBorderV: number; // padding + border vertical
-  padBorderH: number; // padding + border horizontal
-  minWidth: number; // AUTO for none
-  maxWidth: number; // AUTO for none
-  minHeight: number; // AUTO for none
-  maxHeight: number; // AUTO for none
+  padBorderV: number; // padding + border vertical (px 부분)
+  padBorderH: number; // padding + border horizontal (px 부분)
+  paddingPercentV?: number; // 상하 퍼센트 padding 합 (10% + 5% → 15)
+  paddingPercentH?: number; // 좌우 퍼센트 padding 합
+  minWidth: BlockLength; // AUTO for none
+  maxWidth: BlockLength; // AUTO for none
+  minHeight: BlockLength; // AUTO for none
+  maxHeight: BlockLength; // AUTO for none
   contentWidth: number;
   contentHeight: number;
   verticalAlign: number; // VALIGN constant
+  verticalAlignLength?: BlockLength; // VALIGN.LENGTH 의 값. 퍼센트는 lineHeight 기준 (AUTO 면 부모 lineHeight)
   baseline: number;
   lineHeight: number; // AUTO for auto
+  float?: number; // FLOAT constant, 생략 시 none
+  clear?: number; // CLEAR constant, 생략 시 none
+  position?: number; // POSITION constant, 생략 시 static
+  insetTop?: BlockLength; // 생략 시 INSET_AUTO
+  insetRight?: BlockLength;
+  insetBottom?: BlockLength;
+  insetLeft?: BlockLength;
 }
 
+/** percent_mask 비트 (must match Rust PERCENT_FIELDS 순서) */
+const PERCENT_BIT = {
+  width: 1 << 0,
+  height: 1 << 1,
+  marginTop: 1 << 2,
+  marginRight: 1 << 3,
+  marginBottom: 1 << 4,
+  marginLeft: 1 << 5,
+  minWidth: 1 << 6,
+  maxWidth: 1 << 7,
+  minHeight: 1 << 8,
+  maxHeight: 1 << 9,
+  insetTop: 1 << 10,
+  insetRight: 1 << 11,
+  insetBottom: 1 << 12,
+  insetLeft: 1 << 13,
+  verticalAlignLength: 1 << 14,
+} as const;
+
+/** 컨테이너 padding [top, right, bottom, left]. absolute 자식의 inset 기준 (padding box) */
+export type BlockPadding = readonly [number, number, number, number];
+
+const NO_PADDING: BlockPadding = [0, 0, 0, 0];
+
+/**
+ * 부모 폰트 [fontSize, ascent, descent, lineHeight].
+ * vertical-align text-top/text-bottom/sub/super 와 퍼센트 verticalAlignLength 기준
+ */
+export type BlockFont = readonly [number, number, number, number];
+
 export interface BlockLayoutResult {
   positions: Float32Array;
   firstChildMarginTop: number;
   lastChildMarginBottom: number;
+  /** 가장 아래 float margin box 의 하단 (float 없으면 0). BFC 루트는 auto 높이를 여기까지 늘린다 */
+  floatBottom: number;
 }
 
 /**
  * WASM block layout 실행.
  * JS에서 전처리된 children 데이터를 받아 WASM 레이아웃 계산 후 결과 반환.
+ * availableHeight >= 0 이면 definite 컨테이너 높이로 퍼센트 height 와
+ * position: absolute 자식의 inset 기준이 되고, AUTO 면 in-flow 콘텐츠 높이를 쓴다.
+ * textAlign 은 line box 안의 inline-block/텍스트 정렬 (justify 는 마지막 줄 제외)
  */
 export function wasmBlockLayout(
   children: BlockLayoutInput[],
@@ -75,6 +183,11 @@ export function wasmBlockLayout(
   canCollapseTop: boolean,
   canCollapseBottom: boolean,
   prevSiblingMarginBottom: number,
+  rtl = false,
+  writingMode: number = WRITING_MODE.HORIZONTAL_TB,
+  textAlign: number = TEXT_ALIGN.START,
+  font?: BlockFont,
+  padding: BlockPadding = NO_PADDING,
 ): BlockLayoutResult | null {
   const wasm = getRustWasm();
   if (!wasm) return null;
@@ -85,34 +198,11 @@ export function wasmBlockLayout(
       positions: new Float32Array(0),
       firstChildMarginTop: 0,
       lastChildMarginBottom: 0,
+      floatBottom: 0,
     };
   }
 
-  // Serialize to flat Float32Array
-  const data = new Float32Array(count * BLOCK_FIELD_COUNT);
-  for (let i = 0; i < count; i++) {
-    const c = children[i];
-    const off = i * BLOCK_FIELD_COUNT;
-    data[off] = c.display;
-    data[off + 1] = c.width;
-    data[off + 2] = c.height;
-    data[off + 3] = c.marginTop;
-    data[off + 4] = c.marginRight;
-    data[off + 5] = c.marginBottom;
-    data[off + 6] = c.marginLeft;
-    data[off + 7] = c.bfcFlag;
-    data[off + 8] = c.padBorderV;
-    data[off + 9] = c.padBorderH;
-    data[off + 10] = c.minWidth;
-    data[off + 11] = c.maxWidth;
-    data[off + 12] = c.minHeight;
-    data[off + 13] = c.maxHeight;
-    data[off + 14] = c.contentWidth;
-    data[off + 15] = c.contentHeight;
-    data[off + 16] = c.verticalAlign;
-    data[off + 17] = c.baseline;
-    data[off + 18] = c.lineHeight;
-  }
+  const data = serializeBlockChildren(children);
 
   const result = wasm.block_layout(
     data,
@@ -121,6 +211,11 @@ export function wasmBlockLayout(
     canCollapseTop,
     canCollapseBottom,
     prevSiblingMarginBottom,
+    rtl,
+    writingMode,
+    textAlign,
+    new Float32Array(font ?? []),
+    new Float32Array(padding),
   );
 
   const metaOff = count * 4;
@@ -128,9 +223,294 @@ export function wasmBlockLayout(
     positions: result.slice(0, metaOff),
     firstChildMarginTop: result[metaOff] ?? 0,
     lastChildMarginBottom: result[metaOff + 1] ?? 0,
+    floatBottom: result[metaOff + 2] ?? 0,
   };
 }
 
+/** DISPLAY.TEXT 자식에 순서대로 대응하는 텍스트 런 */
+export interface TextRunInput {
+  text: string;
+  fontFamily?: string;
+  fontSize?: number;
+  fontWeight?: number;
+  lineHeight?: number; // px, 생략 시 normal
+  whiteSpace?: 'normal' | 'nowrap' | 'pre' | 'pre-wrap' | 'pre-line' | 'break-spaces';
+  wordBreak?: 'normal' | 'break-all' | 'keep-all' | 'break-word';
+  overflowWrap?: 'normal' | 'break-word' | 'anywhere';
+}
+
+/** fitText 입력: 텍스트 런 + 말줄임 옵션 */
+export interface TextFitInput extends TextRunInput {
+  textOverflow?: 'clip' | 'ellipsis';
+  lineClamp?: number; // -webkit-line-clamp
+}
+
+export interface TextFitResult {
+  /** 화면에 표시할 텍스트 (잘린 경우 '…' 포함) */
+  text: string;
+  cut: number; // UTF-16 offset
+  truncated: boolean;
+  lineCount: number;
+  height: number;
+  lastBaseline: number;
+  lastLineWidth: number;
+}
+
+/** 한 줄에 배치된 텍스트 런의 일부 */
+export interface TextFragment {
+  childIndex: number;
+  start: number; // UTF-16 offset (text.slice(start, end))
+  end: number;
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export interface InlineLayoutResult extends BlockLayoutResult {
+  fragments: TextFragment[];
+}
+
+/**
+ * WASM inline formatting context 포함 block layout 실행.
+ * DISPLAY.TEXT 자식은 textRuns[k]의 텍스트를 줄 단위 fragment로 분할해
+ * inline-block과 같은 line box에 배치한다.
+ */
+export function wasmBlockLayoutWithText(
+  children: BlockLayoutInput[],
+  textRuns: TextRunInput[],
+  availableWidth: number,
+  availableHeight: number,
+  canCollapseTop: boolean,
+  canCollapseBottom: boolean,
+  prevSiblingMarginBottom: number,
+  rtl = false,
+  writingMode: number = WRITING_MODE.HORIZONTAL_TB,
+  textAlign: number = TEXT_ALIGN.START,
+  font?: BlockFont,
+  padding: BlockPadding = NO_PADDING,
+): InlineLayoutResult | null {
+  const wasm = getRustWasm();
+  if (!wasm) return null;
+
+  const count = children.length;
+  const result = wasm.block_layout_with_text(
+    serializeBlockChildren(children),
+    JSON.stringify(textRuns),
+    availableWidth,
+    availableHeight,
+    canCollapseTop,
+    canCollapseBottom,
+    prevSiblingMarginBottom,
+    rtl,
+    writingMode,
+    textAlign,
+    new Float32Array(font ?? []),
+    new Float32Array(padding),
+  );
+
+  const metaOff = count * 4;
+  const fragments: TextFragment[] = [];
+  for (let off = metaOff + BLOCK_META_COUNT; off + FRAGMENT_FIELD_COUNT <= result.length; off += FRAGMENT_FIELD_COUNT) {
+    fragments.push({
+      childIndex: result[off],
+      start: result[off + 1],
+      end: result[off + 2],
+      x: result[off + 3],
+      y: result[off + 4],
+      width: result[off + 5],
+      height: result[off + 6],
+    });
+  }
+  return {
+    positions: result.slice(0, metaOff),
+    firstChildMarginTop: result[metaOff] ?? 0,
+    lastChildMarginBottom: result[metaOff + 1] ?? 0,
+    floatBottom: result[metaOff + 2] ?? 0,
+    fragments,
+  };
+}
+
+/**
+ * WASM 텍스트 말줄임 / line clamp.
+ * maxWidth 안에서 줄바꿈 후 lineClamp, textOverflow를 적용한 결과를 반환.
+ * maxWidth < 0 이면 줄바꿈 없음.
+ */
+export function wasmFitText(input: TextFitInput, maxWidth: number): TextFitResult | null {
+  const wasm = getRustWasm();
+  if (!wasm) return null;
+
+  const r = wasm.fit_text_run(JSON.stringify(input), maxWidth);
+  const cut = r[0];
+  const truncated = r[1] === 1;
+  return {
+    text: truncated ? input.text.slice(0, cut) + '\u2026' : input.text.slice(0, cut),
+    cut,
+    truncated,
+    lineCount: r[2],
+    height: r[3],
+    lastBaseline: r[4],
+    lastLineWidth: r[5],
+  };
+}
+
+// ── Multi-column Layout ──
+
+/** column-fill 상수 (must match Rust) */
+export const COLUMN_FILL = {
+  BALANCE: 0,
+  AUTO: 1,
+} as const;
+
+export interface MulticolOptions {
+  columnCount: number; // AUTO for auto
+  columnWidth: number; // AUTO for auto
+  columnGap: number;
+  columnRuleWidth: number; // 0 = rule 없음
+  columnFill: number; // COLUMN_FILL constant
+  /** children과 같은 순서, column-span: all 여부 */
+  spans: boolean[];
+}
+
+export interface ColumnRect {
+  x: number;
+  y: number;
+  width: number;
+  height: number;
+}
+
+export interface MulticolLayoutResult {
+  positions: Float32Array;
+  /** 컨테이너 content box 높이 (column row + spanner 합) */
+  contentHeight: number;
+  /** column box (overflow column 포함) */
+  columns: ColumnRect[];
+  /** 내용이 있는 인접 column 사이의 column-rule */
+  rules: ColumnRect[];
+  fragments: TextFragment[];
+}
+
+/**
+ * WASM multi-column layout 실행.
+ * block layout 결과를 column 단위로 잘라 배치하고 column box / rule 좌표를 반환.
+ * availableHeight가 AUTO면 column-fill과 관계없이 balance로 동작한다.
+ */
+export function wasmMulticolLayout(
+  children: BlockLayoutInput[],
+  textRuns: TextRunInput[],
+  options: MulticolOptions,
+  availableWidth: number,
+  availableHeight: number,
+  rtl = false,
+): MulticolLayoutResult | null {
+  const wasm = getRustWasm();
+  if (!wasm) return null;
+
+  const count = children.length;
+  const spans = new Uint8Array(count);
+  options.spans.forEach((span, i) => {
+    if (i < count) spans[i] = span ? 1 : 0;
+  });
+
+  const result = wasm.multicol_layout(
+    serializeBlockChildren(children),
+    JSON.stringify(textRuns),
+    spans,
+    availableWidth,
+    availableHeight,
+    options.columnCount,
+    options.columnWidth,
+    options.columnGap,
+    options.columnRuleWidth,
+    options.columnFill,
+    rtl,
+  );
+
+  const metaOff = count * 4;
+  const boxCount = result[metaOff + 1] ?? 0;
+  const ruleCount = result[metaOff + 2] ?? 0;
+  const readRects = (off: number, n: number): ColumnRect[] => {
+    const rects: ColumnRect[] = [];
+    for (let k = 0; k < n; k++) {
+      const o = off + k * 4;
+      rects.push({ x: result[o], y: result[o + 1], width: result[o + 2], height: result[o + 3] });
+    }
+    return rects;
+  };
+
+  const boxOff = metaOff + 3;
+  const ruleOff = boxOff + boxCount * 4;
+  const fragments: TextFragment[] = [];
+  for (let off = ruleOff + ruleCount * 4; off + FRAGMENT_FIELD_COUNT <= result.length; off += FRAGMENT_FIELD_COUNT) {
+    fragments.push({
+      childIndex: result[off],
+      start: result[off + 1],
+      end: result[off + 2],
+      x: result[off + 3],
+      y: result[off + 4],
+      width: result[off + 5],
+      height: result[off + 6],
+    });
+  }
+  return {
+    positions: result.slice(0, metaOff),
+    contentHeight: result[metaOff] ?? 0,
+    columns: readRects(boxOff, boxCount),
+    rules: readRects(ruleOff, ruleCount),
+    fragments,
+  };
+}
+
+/** BlockLayoutInput[] → Rust FIELD_COUNT 레이아웃의 Float32Array */
+function serializeBlockChildren(children: BlockLayoutInput[]): Float32Array {
+  const count = children.length;
+  const data = new Float32Array(count * BLOCK_FIELD_COUNT);
+  for (let i = 0; i < count; i++) {
+    const c = children[i];
+    const off = i * BLOCK_FIELD_COUNT;
+    // 퍼센트 문자열은 숫자만 남기고 percent_mask 비트를 세운다
+    let mask = 0;
+    const len = (key: keyof typeof PERCENT_BIT, value: BlockLength | undefined, fallback: number): number => {
+      if (typeof value === 'string') {
+        mask |= PERCENT_BIT[key];
+        return parseFloat(value);
+      }
+      return value ?? fallback;
+    };
+    data[off] = c.display;
+    data[off + 1] = len('width', c.width, AUTO);
+    data[off + 2] = len('height', c.height, AUTO);
+    data[off + 3] = len('marginTop', c.marginTop, 0);
+    data[off + 4] = len('marginRight', c.marginRight, 0);
+    data[off + 5] = len('marginBottom', c.marginBottom, 0);
+    data[off + 6] = len('marginLeft', c.marginLeft, 0);
+    data[off + 7] = c.bfcFlag;
+    data[off + 8] = c.padBorderV;
+    data[off + 9] = c.padBorderH;
+    data[off + 10] = len('minWidth', c.minWidth, AUTO);
+    data[off + 11] = len('maxWidth', c.maxWidth, AUTO);
+    data[off + 12] = len('minHeight', c.minHeight, AUTO);
+    data[off + 13] = len('maxHeight', c.maxHeight, AUTO);
+    data[off + 14] = c.contentWidth;
+    data[off + 15] = c.contentHeight;
+    data[off + 16] = c.verticalAlign;
+    data[off + 17] = c.baseline;
+    data[off + 18] = c.lineHeight;
+    data[off + 19] = c.float ?? FLOAT.NONE;
+    data[off + 20] = c.clear ?? CLEAR.NONE;
+    data[off + 21] = c.position ?? POSITION.STATIC;
+    data[off + 22] = len('insetTop', c.insetTop, INSET_AUTO);
+    data[off + 23] = len('insetRight', c.insetRight, INSET_AUTO);
+    data[off + 24] = len('insetBottom', c.insetBottom, INSET_AUTO);
+    data[off + 25] = len('insetLeft', c.insetLeft, INSET_AUTO);
+    data[off + 27] = c.paddingPercentV ?? 0;
+    data[off + 28] = c.paddingPercentH ?? 0;
+    data[off + 29] = len('verticalAlignLength', c.verticalAlignLength, 0);
+    data[off + 26] = mask; // 모든 len() 호출 뒤에 기록
+  }
+  return data;
+}
+
 // ── Grid Layout ──
 
 /**
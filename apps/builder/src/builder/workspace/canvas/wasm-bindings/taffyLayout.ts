@@ -12,7 +12,20 @@ import { getRustWasm, isRustWasmReady } from './rustWasm';
 
 // ─── Style types ─────────────────────────────────────────────────────
 
-export type TaffyDisplay = 'flex' | 'grid' | 'block' | 'none';
+export type TaffyDisplay =
+  | 'flex'
+  | 'grid'
+  | 'block'
+  | 'none'
+  | 'table'
+  | 'table-header-group'
+  | 'table-row-group'
+  | 'table-footer-group'
+  | 'table-row'
+  | 'table-cell'
+  | 'table-caption'
+  | 'table-column'
+  | 'table-column-group';
 export type TaffyPosition = 'relative' | 'absolute';
 export type TaffyOverflow = 'visible' | 'hidden' | 'clip' | 'scroll';
 export type TaffyFlexDirection = 'row' | 'column' | 'row-reverse' | 'column-reverse';
@@ -92,6 +105,10 @@ export interface TaffyStyle {
   position?: TaffyPosition;
   overflowX?: TaffyOverflow;
   overflowY?: TaffyOverflow;
+  /** 인라인 방향. 미지정 시 부모에서 상속 */
+  direction?: 'ltr' | 'rtl';
+  /** 미지정 시 부모에서 상속 */
+  writingMode?: 'horizontal-tb' | 'vertical-rl' | 'vertical-lr';
 
   // Flex container
   flexDirection?: TaffyFlexDirection;
@@ -110,11 +127,15 @@ export interface TaffyStyle {
   order?: number;
 
   // Grid container
+  /** 트랙 목록. `["subgrid", "[a]", "[b]"]` 이면 부모 grid 의 트랙을 따른다 */
   gridTemplateColumns?: TaffyTrackValue[];
+  /** `["masonry"]` 이면 아이템을 가장 짧은 열에 쌓는다 */
   gridTemplateRows?: TaffyTrackValue[];
   gridAutoFlow?: TaffyGridAutoFlow;
   gridAutoColumns?: TaffyTrackValue[];
   gridAutoRows?: TaffyTrackValue[];
+  /** CSS 텍스트(`"a a" "b c"`) 또는 행 문자열 배열 */
+  gridTemplateAreas?: string | string[];
 
   // Grid item
   gridColumnStart?: TaffyGridPlacement;
@@ -160,8 +181,64 @@ export interface TaffyStyle {
 
   // Aspect ratio
   aspectRatio?: number;
+
+  /** em 단위 기준 글꼴 크기. 미지정 시 부모에서 상속 */
+  fontSize?: TaffyDimensionValue;
+
+  // Table
+  tableLayout?: 'auto' | 'fixed';
+  /** "4px" 또는 "4px 2px" (가로 세로). px 만 지원 */
+  borderSpacing?: string;
+  captionSide?: 'top' | 'bottom';
+  /** table-cell 의 colspan / rowspan. rowSpan 0 은 섹션 끝까지 */
+  colSpan?: number;
+  rowSpan?: number;
+}
+
+/**
+ * 텍스트 리프 콘텐츠. 레이아웃 중 측정기로 크기를 잰다.
+ * 필드 구성은 Rust 측 text_measure.rs의 `TextContent`와 일치해야 한다.
+ */
+export interface TaffyTextContent {
+  kind: 'text';
+  text: string;
+  /** 기본값 "Pretendard" */
+  fontFamily?: string;
+  /** px. 기본값 14 */
+  fontSize?: number;
+  /** 기본값 400 */
+  fontWeight?: number;
+  /** px. 미지정 시 `normal` (측정기가 결정) */
+  lineHeight?: number;
+  whiteSpace?: 'normal' | 'nowrap' | 'pre' | 'pre-wrap' | 'pre-line' | 'break-spaces';
+  wordBreak?: 'normal' | 'break-all' | 'keep-all' | 'break-word';
+  overflowWrap?: 'normal' | 'break-word' | 'anywhere';
+  textOverflow?: 'clip' | 'ellipsis';
+  /** -webkit-line-clamp: 최대 표시 줄 수 */
+  lineClamp?: number;
 }
 
+/** 고유 크기를 가진 대체 콘텐츠 (이미지, 아이콘, 임베드). */
+export interface TaffyIntrinsicContent {
+  kind: 'intrinsic';
+  width: number;
+  height: number;
+}
+
+/** Measurable content attached to a leaf node. */
+export type TaffyLeafContent = TaffyTextContent | TaffyIntrinsicContent;
+
+/**
+ * 단일 줄 텍스트 advance width 측정 콜백.
+ * WASM이 레이아웃 중 동기적으로 호출한다.
+ */
+export type TaffyTextMeasurer = (
+  text: string,
+  fontFamily: string,
+  fontSize: number,
+  fontWeight: number,
+) => number;
+
 /** Computed layout result for a single node. */
 export interface LayoutResult {
   x: number;
@@ -170,6 +247,14 @@ export interface LayoutResult {
   height: number;
 }
 
+/** Layout result with baselines (offsets from the border-box top). */
+export interface LayoutResultWithBaseline extends LayoutResult {
+  /** 첫 줄 baseline. 텍스트가 없으면 border-box 하단(= height) */
+  firstBaseline: number;
+  /** 마지막 줄 baseline. 텍스트가 없으면 border-box 하단(= height) */
+  lastBaseline: number;
+}
+
 // ─── Node handle type ────────────────────────────────────────────────
 
 /** Opaque handle to a Taffy node. */
@@ -184,15 +269,22 @@ export type TaffyNodeHandle = number;
 interface WasmTaffyLayoutEngine {
   create_node(style_json: string): number;
   create_node_with_children(style_json: string, children_handles: Uint32Array): number;
+  create_leaf_with_content(style_json: string, content_json: string): number;
+  set_leaf_content(handle: number, content_json: string): void;
+  set_text_measurer(callback: TaffyTextMeasurer): void;
+  reset_text_measurer(): void;
   update_style(handle: number, style_json: string): void;
   set_children(handle: number, children_handles: Uint32Array): void;
   compute_layout(handle: number, available_width: number, available_height: number): void;
   get_layout(handle: number): string;
   get_layouts_batch(handles: Uint32Array): Float32Array;
+  get_layouts_batch_with_baselines(handles: Uint32Array): Float32Array;
   build_tree_batch(nodes_json: string): Uint32Array;
   build_tree_batch_binary(data: Uint8Array): Uint32Array;
   remove_node(handle: number): void;
   mark_dirty(handle: number): void;
+  set_viewport(width: number, height: number): void;
+  set_root_font_size(px: number): void;
   clear(): void;
   node_count(): number;
   free(): void;
@@ -219,6 +311,8 @@ function normalizeStyle(style: TaffyStyle): Record<string, unknown> {
   if (style.position !== undefined) result.position = style.position;
   if (style.overflowX !== undefined) result.overflowX = style.overflowX;
   if (style.overflowY !== undefined) result.overflowY = style.overflowY;
+  if (style.direction !== undefined) result.direction = style.direction;
+  if (style.writingMode !== undefined) result.writingMode = style.writingMode;
   if (style.flexDirection !== undefined) result.flexDirection = style.flexDirection;
   if (style.flexWrap !== undefined) result.flexWrap = style.flexWrap;
   if (style.justifyContent !== undefined) result.justifyContent = style.justifyContent;
@@ -259,6 +353,7 @@ function normalizeStyle(style: TaffyStyle): Record<string, unknown> {
   if (style.insetLeft !== undefined) result.insetLeft = dimToString(style.insetLeft);
   if (style.columnGap !== undefined) result.columnGap = dimToString(style.columnGap);
   if (style.rowGap !== undefined) result.rowGap = dimToString(style.rowGap);
+  if (style.fontSize !== undefined) result.fontSize = dimToString(style.fontSize);
 
   // Grid track arrays (string[] → string[])
   if (style.gridTemplateColumns !== undefined)
@@ -266,6 +361,7 @@ function normalizeStyle(style: TaffyStyle): Record<string, unknown> {
   if (style.gridTemplateRows !== undefined) result.gridTemplateRows = style.gridTemplateRows;
   if (style.gridAutoColumns !== undefined) result.gridAutoColumns = style.gridAutoColumns;
   if (style.gridAutoRows !== undefined) result.gridAutoRows = style.gridAutoRows;
+  if (style.gridTemplateAreas !== undefined) result.gridTemplateAreas = style.gridTemplateAreas;
 
   // Grid placement (number → string)
   if (style.gridColumnStart !== undefined)
@@ -358,6 +454,41 @@ export class TaffyLayout {
     return this.engine.create_node_with_children(json, arr);
   }
 
+  /**
+   * Create a content leaf (text or intrinsic box). Returns a node handle.
+   *
+   * 콘텐츠 크기는 computeLayout() 중 측정되므로 width/height를 지정하지 않아도 된다.
+   */
+  createLeafWithContent(style: TaffyStyle, content: TaffyLeafContent): TaffyNodeHandle {
+    if (!this.engine) throw new Error('TaffyLayout: WASM engine not initialized');
+    const json = JSON.stringify(normalizeStyle(style));
+    return this.engine.create_leaf_with_content(json, JSON.stringify(content));
+  }
+
+  /**
+   * Replace the content of a leaf. `null` clears it (plain leaf).
+   * 다음 computeLayout()에서 재측정된다.
+   */
+  setLeafContent(handle: TaffyNodeHandle, content: TaffyLeafContent | null): void {
+    if (!this.engine) throw new Error('TaffyLayout: WASM engine not initialized');
+    this.engine.set_leaf_content(handle, content ? JSON.stringify(content) : '');
+  }
+
+  /**
+   * 텍스트 측정 콜백 설치. 기본값은 글자 수 근사 측정기.
+   * 모든 콘텐츠 리프가 dirty 처리된다.
+   */
+  setTextMeasurer(measurer: TaffyTextMeasurer): void {
+    if (!this.engine) throw new Error('TaffyLayout: WASM engine not initialized');
+    this.engine.set_text_measurer(measurer);
+  }
+
+  /** Revert to the built-in character-count text measurer. */
+  resetTextMeasurer(): void {
+    if (!this.engine) throw new Error('TaffyLayout: WASM engine not initialized');
+    this.engine.reset_text_measurer();
+  }
+
   /** Update the style of an existing node. */
   updateStyle(handle: TaffyNodeHandle, style: TaffyStyle): void {
     if (!this.engine) throw new Error('TaffyLayout: WASM engine not initialized');
@@ -390,6 +521,21 @@ export class TaffyLayout {
     this.engine.compute_layout(root, availableWidth, availableHeight);
   }
 
+  /**
+   * vw/vh/vmin/vmax 기준 뷰포트 설정.
+   * 해당 단위를 쓰는 노드만 다음 computeLayout()에서 재계산된다.
+   */
+  setViewport(width: number, height: number): void {
+    if (!this.engine) throw new Error('TaffyLayout: WASM engine not initialized');
+    this.engine.set_viewport(width, height);
+  }
+
+  /** rem 기준 루트 글꼴 크기 설정 (기본 16px). 레이아웃 루트도 이 값을 상속한다. */
+  setRootFontSize(px: number): void {
+    if (!this.engine) throw new Error('TaffyLayout: WASM engine not initialized');
+    this.engine.set_root_font_size(px);
+  }
+
   /** Get the computed layout for a single node. */
   getLayout(handle: TaffyNodeHandle): LayoutResult {
     if (!this.engine) throw new Error('TaffyLayout: WASM engine not initialized');
@@ -421,6 +567,33 @@ export class TaffyLayout {
     return result;
   }
 
+  /**
+   * Batch retrieve layouts together with first/last baselines.
+   * `align-items: baseline` 정렬과 block_layout의 `baseline` 필드 입력에 사용.
+   */
+  getLayoutsBatchWithBaselines(
+    handles: TaffyNodeHandle[],
+  ): Map<TaffyNodeHandle, LayoutResultWithBaseline> {
+    if (!this.engine) throw new Error('TaffyLayout: WASM engine not initialized');
+    const arr = new Uint32Array(handles);
+    const flat = this.engine.get_layouts_batch_with_baselines(arr);
+    const result = new Map<TaffyNodeHandle, LayoutResultWithBaseline>();
+
+    for (let i = 0; i < handles.length; i++) {
+      const off = i * 6;
+      result.set(handles[i], {
+        x: flat[off],
+        y: flat[off + 1],
+        width: flat[off + 2],
+        height: flat[off + 3],
+        firstBaseline: flat[off + 4],
+        lastBaseline: flat[off + 5],
+      });
+    }
+
+    return result;
+  }
+
   /**
    * Batch-build an entire node tree from a JSON array.
    *
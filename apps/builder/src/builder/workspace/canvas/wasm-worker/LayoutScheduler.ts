@@ -27,6 +27,11 @@ export interface BlockLayoutParams {
   canCollapseTop: boolean;
   canCollapseBottom: boolean;
   prevSiblingMarginBottom: number;
+  rtl?: boolean;
+  writingMode?: number;
+  textAlign?: number;
+  font?: readonly [number, number, number, number];
+  padding?: readonly [number, number, number, number];
 }
 
 export interface GridLayoutParams {
@@ -48,6 +53,7 @@ interface CachedResult {
   layouts: ComputedLayout[];
   firstChildMarginTop?: number;
   lastChildMarginBottom?: number;
+  floatBottom?: number;
 }
 
 // ── Scheduler ──
@@ -98,6 +104,11 @@ export class LayoutScheduler {
         params.canCollapseTop,
         params.canCollapseBottom,
         params.prevSiblingMarginBottom,
+        params.rtl ?? false,
+        params.writingMode ?? 0,
+        params.textAlign ?? 0,
+        params.font,
+        params.padding,
       ).then((res) => {
         if (res.type !== WorkerResponseType.BLOCK_LAYOUT_RESULT) return;
 
@@ -108,6 +119,7 @@ export class LayoutScheduler {
           layouts,
           firstChildMarginTop: res.firstChildMarginTop,
           lastChildMarginBottom: res.lastChildMarginBottom,
+          floatBottom: res.floatBottom,
         };
 
         this.cache.set(key, result);
@@ -169,7 +181,7 @@ export class LayoutScheduler {
   private buildKey(params: LayoutParams): string {
     const base = `${params.parentId}:${params.childIds.join(',')}`;
     if (params.kind === 'block') {
-      return `${base}:${params.availableWidth}:${params.availableHeight}`;
+      return `${base}:${params.availableWidth}:${params.availableHeight}:${params.rtl ? 'rtl' : 'ltr'}:${params.writingMode ?? 0}:${params.textAlign ?? 0}:${params.font?.join(',') ?? ''}:${params.padding?.join(',') ?? ''}`;
     }
     return `${base}:${params.availableWidth}:${params.availableHeight}:${params.colTemplate}:${params.rowTemplate}`;
   }
@@ -84,6 +84,11 @@ export class WasmWorkerBridge {
     canCollapseTop: boolean,
     canCollapseBottom: boolean,
     prevSiblingMarginBottom: number,
+    rtl = false,
+    writingMode = 0,
+    textAlign = 0,
+    font?: readonly [number, number, number, number],
+    padding?: readonly [number, number, number, number],
   ): Promise<WorkerBlockLayoutResponse> {
     const requestId = this.allocId();
 
@@ -100,6 +105,11 @@ export class WasmWorkerBridge {
       canCollapseTop,
       canCollapseBottom,
       prevSiblingMarginBottom,
+      rtl,
+      writingMode,
+      textAlign,
+      font,
+      padding,
     };
 
     return this.send<WorkerBlockLayoutResponse>(req, [transferData.buffer]);
@@ -79,6 +79,11 @@ function handleBlockLayout(
     req.canCollapseTop,
     req.canCollapseBottom,
     req.prevSiblingMarginBottom,
+    req.rtl ?? false,
+    req.writingMode ?? 0,
+    req.textAlign ?? 0,
+    new Float32Array(req.font ?? []),
+    new Float32Array(req.padding ?? [0, 0, 0, 0]),
   );
 
   // WASM linear memory에서 복사 (Transferable로 전송하기 위함)
@@ -86,6 +91,7 @@ function handleBlockLayout(
   const positions = new Float32Array(result.slice(0, metaOff));
   const firstChildMarginTop = result[metaOff] ?? 0;
   const lastChildMarginBottom = result[metaOff + 1] ?? 0;
+  const floatBottom = result[metaOff + 2] ?? 0;
 
   const response: WorkerResponse = {
     type: WorkerResponseType.BLOCK_LAYOUT_RESULT,
@@ -93,6 +99,7 @@ function handleBlockLayout(
     positions,
     firstChildMarginTop,
     lastChildMarginBottom,
+    floatBottom,
   };
 
   // Transfer the positions buffer
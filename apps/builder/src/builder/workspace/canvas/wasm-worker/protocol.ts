@@ -23,7 +23,7 @@ export interface WorkerInitRequest {
 export interface WorkerBlockLayoutRequest {
   type: WorkerRequestType.BLOCK_LAYOUT;
   requestId: number;
-  /** Flat Float32Array: 19 fields per child */
+  /** Flat Float32Array: 30 fields per child */
   data: Float32Array;
   childCount: number;
   availableWidth: number;
@@ -31,6 +31,16 @@ export interface WorkerBlockLayoutRequest {
   canCollapseTop: boolean;
   canCollapseBottom: boolean;
   prevSiblingMarginBottom: number;
+  /** direction: rtl 컨테이너 */
+  rtl?: boolean;
+  /** WRITING_MODE 상수 (생략 시 horizontal-tb) */
+  writingMode?: number;
+  /** TEXT_ALIGN 상수 (생략 시 start) */
+  textAlign?: number;
+  /** 부모 폰트 [fontSize, ascent, descent, lineHeight] (vertical-align 기준, 생략 시 없음) */
+  font?: readonly [number, number, number, number];
+  /** 컨테이너 padding [top, right, bottom, left] (absolute 자식 기준) */
+  padding?: readonly [number, number, number, number];
 }
 
 export interface WorkerGridLayoutRequest {
@@ -71,6 +81,8 @@ export interface WorkerBlockLayoutResponse {
   positions: Float32Array;
   firstChildMarginTop: number;
   lastChildMarginBottom: number;
+  /** 가장 아래 float margin box 의 하단 (float 없으면 0) */
+  floatBottom: number;
 }
 
 export interface WorkerGridLayoutResponse {
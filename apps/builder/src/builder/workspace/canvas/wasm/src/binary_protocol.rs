@@ -30,16 +30,44 @@
 //!   "gridTemplateColumns": ["1fr", "100px", ...],
 //!   "gridTemplateRows":    ["auto", ...],
 //!   "gridAutoColumns":     [...],
-//!   "gridAutoRows":        [...]
+//!   "gridAutoRows":        [...],
+//!   "gridTemplateAreas":   "\"a a\" \"b c\""
 //! }
 //! ```
-//! Parsed using `taffy_bridge::parse_track_as_template` and
-//! `taffy_bridge::parse_track_sizing`.
+//! Parsed using `taffy_bridge::parse_template`,
+//! `taffy_bridge::parse_track_sizing` and `grid_names`. A template of
+//! `["subgrid", "[name]", ...]` makes the axis a subgrid, recorded in
+//! `DecodedNode::subgrid`; rows of `["masonry"]` set `DecodedNode::masonry`.
+//!
+//! Table properties ride along too: `"tableLayout"`, `"borderSpacing"`,
+//! `"captionSide"`, and `"colSpan"` / `"rowSpan"` as numbers, recorded in
+//! `DecodedNode::table`. The table `display` values have their own codes.
+//!
+//! A leaf's measurable content (`LeafContent`) rides along under
+//! `"content"`, in the same shape `create_leaf_with_content` accepts, e.g.
+//! `"content": {"kind": "text", "text": "Label", "fontSize": 14}`. It is
+//! recorded in `DecodedNode::content` and ignored on nodes with children.
+//!
+//! Grid placements naming a line (`"main-start"`, `"span col"`) are sent the
+//! same way under their style key.
+//!
+//! Length fields whose value has no binary encoding (`calc()`, relative
+//! units) are left out of the bitmap and sent here as strings under their
+//! style key, e.g. `"width": "calc(100% - 24px)"`, applied via
+//! `taffy_bridge::apply_length_field`. Relative-unit lengths and `fontSize`
+//! are also recorded in `DecodedNode::relative`.
 
 use taffy::prelude::*;
 use taffy::style::Overflow;
 
-use crate::taffy_bridge::{parse_track_as_template, parse_track_sizing};
+use crate::layout_tree::{Direction, Flow, WritingMode};
+use composition_core::grid_names::{parse_grid_placement, parse_template_areas};
+use composition_core::masonry::is_masonry;
+use composition_core::subgrid::{parse_subgrid, Subgrid};
+use crate::table::{parse_border_spacing, TableLayout, TableRole, TableStyle};
+use crate::text_measure::LeafContent;
+use crate::taffy_bridge::{apply_length_field, parse_template, parse_track_sizing};
+use composition_core::units::RelativeStyle;
 
 // ─── Magic constant ───────────────────────────────────────────────────
 
@@ -114,8 +142,12 @@ pub mod field_id {
     pub const GRID_ROW_START: u8 = 43;
     pub const GRID_ROW_END: u8 = 44;
 
+    // Late enum fields (1 byte each)
+    pub const DIRECTION: u8 = 45;
+    pub const WRITING_MODE: u8 = 46;
+
     /// Total number of defined field IDs.
-    pub const FIELD_COUNT: u8 = 45;
+    pub const FIELD_COUNT: u8 = 47;
 }
 
 // ─── Decoded output ───────────────────────────────────────────────────
@@ -126,6 +158,18 @@ pub mod field_id {
 pub struct DecodedNode {
     pub style: Style,
     pub children: Vec<usize>,
+    /// Specified `direction` / `writing-mode` (not part of `taffy::Style`).
+    pub flow: Flow,
+    /// `font-size` and relative-unit lengths, resolved before layout.
+    pub relative: RelativeStyle,
+    /// Subgridded axes (not part of `taffy::Style`).
+    pub subgrid: Subgrid,
+    /// `grid-template-rows: masonry` (not part of `taffy::Style`).
+    pub masonry: bool,
+    /// Table role and properties (not part of `taffy::Style`).
+    pub table: TableStyle,
+    /// Measurable leaf content (not part of `taffy::Style`).
+    pub content: Option<LeafContent>,
 }
 
 // ─── Cursor ───────────────────────────────────────────────────────────
@@ -267,28 +311,67 @@ struct GridJson {
     grid_auto_columns: Vec<String>,
     #[serde(default)]
     grid_auto_rows: Vec<String>,
+    #[serde(default)]
+    grid_template_areas: Option<serde_json::Value>,
+    #[serde(default)]
+    table_layout: Option<String>,
+    #[serde(default)]
+    border_spacing: Option<String>,
+    #[serde(default)]
+    caption_side: Option<String>,
+    #[serde(default)]
+    col_span: Option<u16>,
+    #[serde(default)]
+    row_span: Option<u16>,
+    #[serde(default)]
+    content: Option<LeafContent>,
+    /// Length fields and grid placements the binary encoding cannot express
+    /// (e.g. `calc()`, line names), keyed by style property name.
+    #[serde(flatten)]
+    lengths: std::collections::HashMap<String, String>,
 }
 
-/// Parse grid sideband JSON bytes and apply the track definitions to `style`.
-fn apply_grid_json(style: &mut Style, json_bytes: &[u8]) -> Result<(), String> {
+/// Parse sideband JSON bytes and apply the track definitions and string
+/// length fields to `style`, recording relative ones in `relative`,
+/// subgridded axes in `subgrid`, masonry rows in `masonry` and table
+/// properties in `table`. Returns the leaf content, if any.
+fn apply_grid_json(
+    style: &mut Style,
+    relative: &mut RelativeStyle,
+    subgrid: &mut Subgrid,
+    masonry: &mut bool,
+    table: &mut TableStyle,
+    json_bytes: &[u8],
+) -> Result<Option<LeafContent>, String> {
     let text = core::str::from_utf8(json_bytes)
         .map_err(|e| format!("binary_protocol: grid JSON is not valid UTF-8: {e}"))?;
     let grid: GridJson = serde_json::from_str(text)
         .map_err(|e| format!("binary_protocol: grid JSON parse error: {e}"))?;
 
+    *subgrid = Subgrid::new(
+        parse_subgrid(grid.grid_template_columns.iter().map(String::as_str)),
+        parse_subgrid(grid.grid_template_rows.iter().map(String::as_str)),
+    );
+    *masonry = is_masonry(grid.grid_template_rows.iter().map(String::as_str));
+    if grid.table_layout.as_deref() == Some("fixed") {
+        table.layout = TableLayout::Fixed;
+    }
+    if let Some(ref spacing) = grid.border_spacing {
+        table.spacing = parse_border_spacing(spacing);
+    }
+    table.caption_bottom = grid.caption_side.as_deref() == Some("bottom");
+    table.col_span = grid.col_span.unwrap_or(1);
+    table.row_span = grid.row_span.unwrap_or(1);
     if !grid.grid_template_columns.is_empty() {
-        style.grid_template_columns = grid
-            .grid_template_columns
-            .iter()
-            .map(|s| parse_track_as_template(s))
-            .collect();
+        (style.grid_template_columns, style.grid_template_column_names) =
+            parse_template(&grid.grid_template_columns);
     }
     if !grid.grid_template_rows.is_empty() {
-        style.grid_template_rows = grid
-            .grid_template_rows
-            .iter()
-            .map(|s| parse_track_as_template(s))
-            .collect();
+        (style.grid_template_rows, style.grid_template_row_names) =
+            parse_template(&grid.grid_template_rows);
+    }
+    if let Some(ref areas) = grid.grid_template_areas {
+        style.grid_template_areas = parse_template_areas(areas).unwrap_or_default();
     }
     if !grid.grid_auto_columns.is_empty() {
         style.grid_auto_columns = grid
@@ -304,7 +387,19 @@ fn apply_grid_json(style: &mut Style, json_bytes: &[u8]) -> Result<(), String> {
             .map(|s| parse_track_sizing(s))
             .collect();
     }
-    Ok(())
+    for (key, value) in &grid.lengths {
+        match key.as_str() {
+            "gridColumnStart" => style.grid_column.start = parse_grid_placement(value),
+            "gridColumnEnd" => style.grid_column.end = parse_grid_placement(value),
+            "gridRowStart" => style.grid_row.start = parse_grid_placement(value),
+            "gridRowEnd" => style.grid_row.end = parse_grid_placement(value),
+            _ => {
+                apply_length_field(style, key, value);
+                relative.record(key, value);
+            }
+        }
+    }
+    Ok(grid.content)
 }
 
 // ─── Node decoder ─────────────────────────────────────────────────────
@@ -353,6 +448,7 @@ fn decode_node(cur: &mut Cursor, node_index: usize) -> Result<DecodedNode, Strin
     let has = |id: u8| -> bool { (bitmap >> id) & 1 == 1 };
 
     // ── Enum fields (1 byte each) ─────────────────────────────────────
+    let mut table = TableStyle::default();
     if has(field_id::DISPLAY) {
         let v = cur.read_u8().map_err(|e| format!("node[{node_index}].display: {e}"))?;
         style.display = match v {
@@ -360,8 +456,12 @@ fn decode_node(cur: &mut Cursor, node_index: usize) -> Result<DecodedNode, Strin
             1 => Display::Grid,
             2 => Display::Block,
             3 => Display::None,
+            4..=10 => Display::Block,
+            11 => Display::None, // table-column(-group)
             _ => Display::Flex,
         };
+        table.role = decode_table_role(v);
+        style.item_is_table = table.role == TableRole::Table;
     }
 
     if has(field_id::POSITION) {
@@ -420,7 +520,7 @@ fn decode_node(cur: &mut Cursor, node_index: usize) -> Result<DecodedNode, Strin
 
     if has(field_id::JUSTIFY_ITEMS) {
         let v = cur.read_u8().map_err(|e| format!("node[{node_index}].justifyItems: {e}"))?;
-        // justifyItems uses AlignItems type in Taffy 0.9
+        // justifyItems uses AlignItems type in Taffy
         style.justify_items = Some(decode_align_items(v));
     }
 
@@ -594,17 +694,58 @@ fn decode_node(cur: &mut Cursor, node_index: usize) -> Result<DecodedNode, Strin
         };
     }
 
+    // ── Late enum fields (1 byte each) ───────────────────────────────
+    let mut flow = Flow::default();
+    if has(field_id::DIRECTION) {
+        let v = cur.read_u8().map_err(|e| format!("node[{node_index}].direction: {e}"))?;
+        flow.direction = match v {
+            0 => Some(Direction::Ltr),
+            1 => Some(Direction::Rtl),
+            _ => None,
+        };
+    }
+    if has(field_id::WRITING_MODE) {
+        let v = cur.read_u8().map_err(|e| format!("node[{node_index}].writingMode: {e}"))?;
+        flow.writing_mode = match v {
+            0 => Some(WritingMode::HorizontalTb),
+            1 => Some(WritingMode::VerticalRl),
+            2 => Some(WritingMode::VerticalLr),
+            _ => None,
+        };
+    }
+
     // ── Apply grid sideband ───────────────────────────────────────────
+    let mut relative = RelativeStyle::default();
+    let mut subgrid = Subgrid::default();
+    let mut masonry = false;
+    let mut content = None;
     if let Some(json_bytes) = grid_json_bytes {
-        apply_grid_json(&mut style, json_bytes)
+        content = apply_grid_json(&mut style, &mut relative, &mut subgrid, &mut masonry, &mut table, json_bytes)
             .map_err(|e| format!("node[{node_index}]: {e}"))?;
     }
 
-    Ok(DecodedNode { style, children })
+    Ok(DecodedNode { style, children, flow, relative, subgrid, masonry, table, content })
 }
 
 // ─── Small decode helpers ─────────────────────────────────────────────
 
+/// Table role of a `display` code (4..=10: table, table-header-group,
+/// table-row-group, table-footer-group, table-row, table-cell,
+/// table-caption).
+#[inline]
+fn decode_table_role(v: u8) -> TableRole {
+    match v {
+        4 => TableRole::Table,
+        5 => TableRole::HeaderGroup,
+        6 => TableRole::RowGroup,
+        7 => TableRole::FooterGroup,
+        8 => TableRole::Row,
+        9 => TableRole::Cell,
+        10 => TableRole::Caption,
+        _ => TableRole::None,
+    }
+}
+
 #[inline]
 fn decode_overflow(v: u8) -> Overflow {
     match v {
@@ -995,6 +1136,18 @@ pub(crate) mod encode {
             self
         }
 
+        // ── Late enum fields (1 byte each) ────────────────────────────
+
+        pub fn direction(mut self, v: u8) -> Self {
+            self.add_field(field_id::DIRECTION, vec![v]);
+            self
+        }
+
+        pub fn writing_mode(mut self, v: u8) -> Self {
+            self.add_field(field_id::WRITING_MODE, vec![v]);
+            self
+        }
+
         /// Serialise this node to bytes (without global header).
         ///
         /// Fields are emitted in ascending bit-position order so that the byte
@@ -1242,6 +1395,134 @@ mod tests {
         );
     }
 
+    /// Test that string length fields in the sideband (calc) are applied.
+    #[test]
+    fn test_decode_calc_lengths_in_sideband() {
+        let node_bytes = NodeEncoder::new()
+            .display(0)
+            .grid_json(r#"{"width":"calc(100% - 24px)","paddingLeft":"calc(4px * 2)"}"#)
+            .build();
+
+        let buf = build_taff(&[node_bytes]);
+        let decoded = decode_batch_binary(&buf).expect("decode should succeed");
+
+        let style = &decoded[0].style;
+        assert!(style.size.width.into_raw().is_calc(), "mixed calc stays a calc value");
+        assert_eq!(style.padding.left, LengthPercentage::length(8.0));
+    }
+
+    #[test]
+    fn test_decode_relative_lengths_in_sideband() {
+        let node_bytes = NodeEncoder::new()
+            .grid_json(r#"{"width":"2rem","fontSize":"1.5em","marginTop":"4px"}"#)
+            .build();
+
+        let buf = build_taff(&[node_bytes]);
+        let decoded = decode_batch_binary(&buf).expect("decode should succeed");
+
+        let relative = &decoded[0].relative;
+        assert!(relative.font_size.is_some());
+        assert_eq!(relative.lengths.len(), 1, "only relative lengths are recorded");
+        assert_eq!(decoded[0].style.margin.top, LengthPercentageAuto::length(4.0));
+    }
+
+    #[test]
+    fn test_decode_grid_names_in_sideband() {
+        let node_bytes = NodeEncoder::new()
+            .grid_json(
+                r#"{"gridTemplateColumns":["[full-start] 1fr","[main-start]","2fr [main-end full-end]"],"gridTemplateAreas":["a b"],"gridColumnStart":"main-start","gridRowEnd":"span a"}"#,
+            )
+            .build();
+
+        let buf = build_taff(&[node_bytes]);
+        let decoded = decode_batch_binary(&buf).expect("decode should succeed");
+
+        let style = &decoded[0].style;
+        assert_eq!(style.grid_template_columns.len(), 2);
+        assert_eq!(style.grid_template_column_names.len(), 3);
+        assert_eq!(style.grid_template_column_names[1], ["main-start"]);
+        assert_eq!(style.grid_template_areas.len(), 2);
+        assert_eq!(style.grid_column.start, GridPlacement::NamedLine("main-start".to_string(), 0));
+        assert_eq!(style.grid_row.end, GridPlacement::NamedSpan("a".to_string(), 1));
+    }
+
+    #[test]
+    fn test_decode_subgrid_in_sideband() {
+        let node_bytes = NodeEncoder::new()
+            .grid_json(r#"{"gridTemplateColumns":["subgrid","[a]","[b]"],"gridTemplateRows":["1fr"]}"#)
+            .build();
+
+        let buf = build_taff(&[node_bytes]);
+        let decoded = decode_batch_binary(&buf).expect("decode should succeed");
+
+        let node = &decoded[0];
+        assert!(node.style.grid_template_columns.is_empty());
+        assert_eq!(node.subgrid.columns, Some(vec![vec!["a".to_string()], vec!["b".to_string()]]));
+        assert_eq!(node.subgrid.rows, None);
+        assert_eq!(node.style.grid_template_rows.len(), 1);
+        assert!(!node.masonry);
+    }
+
+    #[test]
+    fn test_decode_masonry_in_sideband() {
+        let node_bytes = NodeEncoder::new()
+            .grid_json(r#"{"gridTemplateColumns":["repeat(3, 1fr)"],"gridTemplateRows":["masonry"]}"#)
+            .build();
+
+        let buf = build_taff(&[node_bytes]);
+        let decoded = decode_batch_binary(&buf).expect("decode should succeed");
+
+        assert!(decoded[0].masonry);
+        assert!(decoded[0].style.grid_template_rows.is_empty());
+    }
+
+    #[test]
+    fn test_decode_content_in_sideband() {
+        let text = NodeEncoder::new()
+            .grid_json(r#"{"content":{"kind":"text","text":"Label","fontSize":12,"whiteSpace":"nowrap"},"width":"calc(100% - 8px)"}"#)
+            .build();
+        let image = NodeEncoder::new()
+            .grid_json(r#"{"content":{"kind":"intrinsic","width":160,"height":90}}"#)
+            .build();
+
+        let buf = build_taff(&[text, image, NodeEncoder::new().build()]);
+        let decoded = decode_batch_binary(&buf).expect("decode should succeed");
+
+        match &decoded[0].content {
+            Some(LeafContent::Text(text)) => {
+                assert_eq!(text.text, "Label");
+                assert_eq!(text.font.font_size, 12.0);
+            }
+            other => panic!("expected text content, got {other:?}"),
+        }
+        assert!(decoded[0].style.size.width.into_raw().is_calc(), "lengths still apply alongside content");
+        assert_eq!(decoded[1].content, Some(LeafContent::Intrinsic { width: 160.0, height: 90.0 }));
+        assert_eq!(decoded[2].content, None);
+    }
+
+    #[test]
+    fn test_decode_table_roles() {
+        let cell = NodeEncoder::new()
+            .display(9)
+            .grid_json(r#"{"colSpan":2,"rowSpan":0}"#)
+            .build();
+        let table = NodeEncoder::new()
+            .display(4)
+            .grid_json(r#"{"tableLayout":"fixed","borderSpacing":"4px 2px"}"#)
+            .children(&[0])
+            .build();
+
+        let buf = build_taff(&[cell, table]);
+        let decoded = decode_batch_binary(&buf).expect("decode should succeed");
+
+        assert_eq!(decoded[0].style.display, Display::Block);
+        assert_eq!(decoded[0].table.role, TableRole::Cell);
+        assert_eq!((decoded[0].table.col_span, decoded[0].table.row_span), (2, 0));
+        assert!(decoded[1].style.item_is_table);
+        assert_eq!(decoded[1].table.layout, TableLayout::Fixed);
+        assert_eq!(decoded[1].table.spacing, Size { width: 4.0, height: 2.0 });
+    }
+
     /// Test error cases: invalid magic, truncated buffer, wrong version.
     #[test]
     fn test_decode_error_invalid_magic() {
@@ -1309,6 +1590,58 @@ mod tests {
         assert_eq!(style.justify_self, None, "justify_self=0 should be None (auto)");
     }
 
+    /// Test the remaining enum fields and the box sides not covered above:
+    /// position, overflow, flexWrap, justifyItems, alignContent,
+    /// gridAutoFlow, min/max height, margin-bottom, insets, padding, border.
+    #[test]
+    fn test_decode_position_and_box_sides() {
+        let node_bytes = NodeEncoder::new()
+            .position(1)             // absolute
+            .overflow_x(1)           // hidden
+            .overflow_y(3)           // scroll
+            .flex_wrap(2)            // wrap-reverse
+            .justify_items(2)        // center
+            .align_content(4)        // space-between
+            .grid_auto_flow(3)       // column dense
+            .min_height(1, 20.0)     // length 20px
+            .max_height(2, 0.5)      // percent 50%
+            .margin_bottom(1, 4.0)   // length 4px
+            .inset_top(1, 10.0)      // length 10px
+            .inset_right(0, 0.0)     // auto
+            .inset_bottom(2, 0.25)   // percent 25%
+            .inset_left(1, -5.0)     // length -5px
+            .padding_bottom(1, 6.0)  // length 6px
+            .padding_left(2, 0.05)   // percent 5%
+            .border_right(1, 1.0)    // length 1px
+            .border_bottom(1, 3.0)   // length 3px
+            .border_left(2, 0.01)    // percent 1%
+            .build();
+
+        let buf = build_taff(&[node_bytes]);
+        let decoded = decode_batch_binary(&buf).expect("decode should succeed");
+
+        let style = &decoded[0].style;
+        assert_eq!(style.position, Position::Absolute);
+        assert_eq!(style.overflow.x, Overflow::Hidden);
+        assert_eq!(style.overflow.y, Overflow::Scroll);
+        assert_eq!(style.flex_wrap, FlexWrap::WrapReverse);
+        assert_eq!(style.justify_items, Some(AlignItems::Center));
+        assert_eq!(style.align_content, Some(AlignContent::SpaceBetween));
+        assert_eq!(style.grid_auto_flow, GridAutoFlow::ColumnDense);
+        assert_eq!(style.min_size.height, Dimension::length(20.0));
+        assert_eq!(style.max_size.height, Dimension::percent(0.5));
+        assert_eq!(style.margin.bottom, LengthPercentageAuto::length(4.0));
+        assert_eq!(style.inset.top, LengthPercentageAuto::length(10.0));
+        assert_eq!(style.inset.right, LengthPercentageAuto::auto());
+        assert_eq!(style.inset.bottom, LengthPercentageAuto::percent(0.25));
+        assert_eq!(style.inset.left, LengthPercentageAuto::length(-5.0));
+        assert_eq!(style.padding.bottom, LengthPercentage::length(6.0));
+        assert_eq!(style.padding.left, LengthPercentage::percent(0.05));
+        assert_eq!(style.border.right, LengthPercentage::length(1.0));
+        assert_eq!(style.border.bottom, LengthPercentage::length(3.0));
+        assert_eq!(style.border.left, LengthPercentage::percent(0.01));
+    }
+
     /// Test flex item properties: flexGrow, flexShrink, flexBasis, aspectRatio.
     #[test]
     fn test_decode_flex_item_properties() {
@@ -1334,6 +1667,34 @@ mod tests {
         assert!((ar - 16.0 / 9.0).abs() < 1e-5, "aspect_ratio should be ~1.777");
     }
 
+    /// Test that direction (bit 45, after the grid placements) round-trips.
+    #[test]
+    fn test_decode_direction() {
+        let rtl = NodeEncoder::new().direction(1).grid_row_end(1, 3).build();
+        let unset = NodeEncoder::new().build();
+
+        let buf = build_taff(&[rtl, unset]);
+        let decoded = decode_batch_binary(&buf).expect("decode should succeed");
+
+        assert_eq!(decoded[0].flow.direction, Some(Direction::Rtl));
+        assert_eq!(decoded[0].style.grid_row.end, GridPlacement::from_line_index(3));
+        assert_eq!(decoded[1].flow.direction, None, "unset inherits");
+    }
+
+    #[test]
+    fn test_decode_writing_mode() {
+        let node = NodeEncoder::new().writing_mode(1).direction(1).build();
+        let lr = NodeEncoder::new().writing_mode(2).build();
+
+        let buf = build_taff(&[node, lr]);
+        let decoded = decode_batch_binary(&buf).expect("decode should succeed");
+
+        assert_eq!(decoded[0].flow.writing_mode, Some(WritingMode::VerticalRl));
+        assert_eq!(decoded[0].flow.direction, Some(Direction::Rtl));
+        assert_eq!(decoded[1].flow.writing_mode, Some(WritingMode::VerticalLr));
+        assert_eq!(decoded[1].flow.direction, None);
+    }
+
     /// Test that an empty batch (nodeCount=0) decodes to an empty Vec.
     #[test]
     fn test_decode_empty_batch() {
@@ -1,32 +1,96 @@
+use std::borrow::Cow;
+
 use wasm_bindgen::prelude::*;
 
+use crate::floats::{
+    shrink_to_fit, FloatBox, Floats, PendingFloat, CLEAR_LEFT, CLEAR_RIGHT, FLOAT_LEFT, FLOAT_NONE, FLOAT_RIGHT,
+};
+use crate::font_metrics::MetricsTextMeasurer;
+use crate::text_layout::{is_collapsible_space, segment_text, split_segment};
+use crate::text_measure::{resolve_line_height, FallbackTextMeasurer, TextContent, TextMeasurer};
+
 /// Block layout field count per child element.
 /// Fields: display, width, height, m_top, m_right, m_bottom, m_left,
 ///         bfc_flag, pad_v, border_v, min_w, max_w, min_h, max_h,
-///         content_w, content_h, vertical_align, baseline, line_height
-pub const FIELD_COUNT: usize = 19;
+///         content_w, content_h, vertical_align, baseline, line_height,
+///         float, clear, position, inset_top, inset_right, inset_bottom,
+///         inset_left, percent_mask, pad_pct_v, pad_pct_h, valign_length
+pub const FIELD_COUNT: usize = 30;
 
 /// Output fields per child: x, y, width, height
-const OUT_FIELDS: usize = 4;
+pub(crate) const OUT_FIELDS: usize = 4;
+
+/// Trailing output fields after the child rects:
+/// firstChildMarginTop, lastChildMarginBottom, floatBottom
+pub(crate) const META_FIELDS: usize = 3;
+
+/// Output fields per text fragment:
+/// child_index, start, end (UTF-16 offsets), x, y, width, height
+pub const FRAGMENT_FIELDS: usize = 7;
 
 // Display types
 #[allow(dead_code)]
 const DISPLAY_BLOCK: u8 = 0;
 const DISPLAY_INLINE_BLOCK: u8 = 1;
 const DISPLAY_EMPTY_BLOCK: u8 = 2; // pre-classified empty block
+pub(crate) const DISPLAY_TEXT: u8 = 3; // text run (inline formatting context)
+
+// Positions (`fixed` is sent as absolute)
+#[allow(dead_code)]
+const POSITION_STATIC: u8 = 0;
+const POSITION_RELATIVE: u8 = 1;
+const POSITION_ABSOLUTE: u8 = 2;
 
-// Vertical align
+// Writing modes
+const WRITING_HORIZONTAL_TB: u8 = 0;
+const WRITING_VERTICAL_RL: u8 = 1;
+#[allow(dead_code)]
+const WRITING_VERTICAL_LR: u8 = 2;
+
+// text-align (`start` / `end` follow `direction`)
+pub(crate) const TEXT_ALIGN_START: u8 = 0;
+const TEXT_ALIGN_END: u8 = 1;
+const TEXT_ALIGN_LEFT: u8 = 2;
+const TEXT_ALIGN_RIGHT: u8 = 3;
+const TEXT_ALIGN_CENTER: u8 = 4;
+const TEXT_ALIGN_JUSTIFY: u8 = 5;
+
+// Vertical align (`valign_length` holds the raise of VALIGN_LENGTH)
+#[allow(dead_code)]
 const VALIGN_BASELINE: u8 = 0;
 const VALIGN_TOP: u8 = 1;
 const VALIGN_MIDDLE: u8 = 2;
 const VALIGN_BOTTOM: u8 = 3;
+const VALIGN_TEXT_TOP: u8 = 4;
+const VALIGN_TEXT_BOTTOM: u8 = 5;
+const VALIGN_SUB: u8 = 6;
+const VALIGN_SUPER: u8 = 7;
+const VALIGN_LENGTH: u8 = 8;
 
 /// Sentinel for "auto" (no explicit value)
-const AUTO: f32 = -1.0;
+pub(crate) const AUTO: f32 = -1.0;
 
 /// Sentinel for "fit-content" (use content intrinsic size)
 const FIT_CONTENT: f32 = -2.0;
 
+/// Fields that can hold a percentage (50 = 50%), by `percent_mask` bit:
+/// width, height, margins (top, right, bottom, left), min_w, max_w, min_h,
+/// max_h, insets (top, right, bottom, left), valign_length. `pad_pct_v` /
+/// `pad_pct_h` are the summed percentage paddings of each axis, on top of
+/// `pad_border_v/h`.
+const PERCENT_FIELDS: [usize; 15] = [1, 2, 3, 4, 5, 6, 10, 11, 12, 13, 22, 23, 24, 25, 29];
+
+/// Sentinel for an `auto` inset. Insets may be negative, so NaN instead of
+/// AUTO; test with `is_nan()`.
+pub const INSET_AUTO: f32 = f32::NAN;
+
+/// Sentinel for an `auto` margin (NaN, as for insets). Auto margins are
+/// zero except the inline ones of blocks, which share the free space.
+pub const MARGIN_AUTO: f32 = f32::NAN;
+
+/// Tolerance for float accumulation in line-fit checks of text segments.
+pub(crate) const FIT_EPSILON: f32 = 0.01;
+
 /// CSS margin collapse (CSS 2.1 §8.3.1)
 #[inline]
 fn collapse_margins(a: f32, b: f32) -> f32 {
@@ -53,6 +117,7 @@ fn clamp_size(value: f32, min_val: f32, max_val: f32) -> f32 {
 }
 
 /// Inline-block item in a line box (temporary storage during calculation)
+#[derive(Clone, Copy)]
 struct LineItem {
     /// Index into output array
     out_index: usize,
@@ -65,6 +130,138 @@ struct LineItem {
     vertical_align: u8,
     baseline: f32,
     line_height: f32,
+    /// Raise of the baseline above the line's for baseline-relative
+    /// `vertical_align` values (see `baseline_shift`)
+    shift: f32,
+    /// Preceded by a collapsed space: a justification opportunity
+    space_before: bool,
+}
+
+/// Inline extent of the current line box, narrowed by floats. Line items
+/// are positioned relative to `left`.
+#[derive(Default)]
+struct LineBox {
+    left: f32,
+    width: f32,
+    /// The line has been fitted beside the floats and takes items
+    open: bool,
+    /// Output offsets of block-level absolutely positioned children whose
+    /// static position is below this line
+    waiting: Vec<usize>,
+    /// Container `text-align`, with left / right mirrored under rtl
+    align: u8,
+}
+
+impl LineBox {
+    /// Open a line whose first item is `width` wide and `height` high,
+    /// moving `y` down past floats until it fits beside them. No-op while
+    /// the line is open.
+    fn open(&mut self, floats: &Floats, y: &mut f32, width: f32, height: f32, available: f32) {
+        if self.open {
+            return;
+        }
+        let (top, (left, right)) = floats.fit(*y, width, height, available);
+        *y = top;
+        self.left = left;
+        self.width = right - left;
+        self.open = true;
+    }
+
+    /// Narrow the open line around a float placed beside it.
+    fn exclude(&mut self, float: FloatBox) {
+        let right = self.left + self.width;
+        if float.left {
+            self.left = float.x + float.width;
+            self.width = right - self.left;
+        } else {
+            self.width = float.x - self.left;
+        }
+    }
+}
+
+/// Padding box of the container: the containing block of its absolutely
+/// positioned children, around the content box the children are laid out in.
+#[derive(Clone, Copy)]
+pub(crate) struct PaddingBox {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+    /// Content box height; AUTO = the height of the laid-out content
+    pub height: f32,
+}
+
+impl PaddingBox {
+    /// No padding, auto height.
+    pub(crate) const NONE: PaddingBox = PaddingBox { top: 0.0, right: 0.0, bottom: 0.0, left: 0.0, height: AUTO };
+
+    /// `padding` is [top, right, bottom, left]; empty for none. A negative
+    /// `height` is auto.
+    fn new(padding: &[f32], height: f32) -> Self {
+        let side = |k: usize| padding.get(k).copied().unwrap_or(0.0);
+        let height = if height >= 0.0 { height } else { AUTO };
+        PaddingBox { top: side(0), right: side(1), bottom: side(2), left: side(3), height }
+    }
+}
+
+/// Font metrics of the container, the reference of the `vertical-align`
+/// values relative to the parent's font.
+#[derive(Clone, Copy)]
+pub(crate) struct ParentFont {
+    pub size: f32,
+    pub ascent: f32,
+    pub descent: f32,
+    /// Used line height: the basis of percentage `valign_length`s of
+    /// children with an auto line height
+    pub line_height: f32,
+}
+
+impl ParentFont {
+    /// No font: text-top / text-bottom align to the baseline, sub / super
+    /// do not shift.
+    pub(crate) const NONE: ParentFont = ParentFont { size: 0.0, ascent: 0.0, descent: 0.0, line_height: 0.0 };
+
+    /// `font` is [size, ascent, descent, line_height]; empty for none.
+    fn new(font: &[f32]) -> Self {
+        match *font {
+            [size, ascent, descent, line_height, ..] => ParentFont { size, ascent, descent, line_height },
+            _ => ParentFont::NONE,
+        }
+    }
+}
+
+/// A margin field as used outside the block inline axis: auto is zero.
+#[inline]
+fn used_margin(value: f32) -> f32 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value
+    }
+}
+
+/// Used start (left) margin of a box with `free` px left beside it in its
+/// containing block (CSS 2.1 §10.3.3, §10.3.7). Auto margins (counted as
+/// zero in `margin_start` / `margin_end`) share the free space, or are zero
+/// when the box overflows; an over-constrained box ignores its end margin.
+fn used_start_margin(free: f32, margin_start: f32, margin_end: f32, auto_start: bool, auto_end: bool) -> f32 {
+    let free = (free - margin_start - margin_end).max(0.0);
+    match (auto_start, auto_end) {
+        (true, true) => free / 2.0,
+        (true, false) => free,
+        _ => margin_start,
+    }
+}
+
+/// Swap the left and right values of a `float` / `clear` field.
+fn mirror_side(value: u8, left: u8, right: u8) -> u8 {
+    if value == left {
+        right
+    } else if value == right {
+        left
+    } else {
+        value
+    }
 }
 
 /// Calculate block layout for pre-processed children.
@@ -72,15 +269,50 @@ struct LineItem {
 /// # Arguments
 /// * `data` - Flat Float32Array with FIELD_COUNT fields per child
 /// * `available_width` - Parent's available content width
-/// * `available_height` - Parent's available content height
+/// * `available_height` - Parent's content height: definite when >= 0, AUTO
+///   (negative) for auto. Percentage heights and absolutely positioned
+///   children resolve against it. The inline size in vertical writing modes,
+///   where auto lays the children out at their max-content size
 /// * `can_collapse_top` - Whether first child can collapse with parent top
 /// * `can_collapse_bottom` - Whether last child can collapse with parent bottom
 /// * `prev_sibling_margin_bottom` - Previous sibling's margin bottom (context)
+/// * `rtl` - Parent has `direction: rtl`: line boxes fill from the right edge
+///   and over-constrained block margins resolve against the left one
+/// * `writing_mode` - Parent's writing mode: 0 = horizontal-tb,
+///   1 = vertical-rl, 2 = vertical-lr. Vertical modes stack blocks
+///   horizontally (from the right edge of `available_width` for
+///   vertical-rl) and run lines top to bottom
+/// * `text_align` - Parent's `text-align`: 0 = start, 1 = end, 2 = left,
+///   3 = right, 4 = center, 5 = justify. Aligns the items of each line box
+///   in the room the floats leave; justify stretches the spaces between
+///   them on every line but the last one and those ending in a forced
+///   break. Lines too wide for their box stay start-aligned
+/// * `font` - Parent's font [size, ascent, descent, line_height] (empty for
+///   none): text-top / text-bottom align with its ascent / descent around
+///   the baseline, sub / super lower / raise the baseline by 1/5 / 1/3 of
+///   its size, and percentage `valign_length`s fall back to its line height
+/// * `padding` - Parent's padding [top, right, bottom, left] (empty for
+///   none): absolutely positioned children resolve their insets against
+///   the padding box
+///
+/// Percentage fields (`percent_mask`) resolve against the parent: margins
+/// and padding against its inline size, widths against its width and
+/// heights against a definite `available_height` (auto otherwise).
+///
+/// Relatively positioned children are shifted by their insets after
+/// layout. Absolutely positioned ones are out of flow: placed by their
+/// insets, or at their static position when both insets of an axis are
+/// auto, and sized shrink-to-fit unless width (height) or both insets are
+/// set. Rects stay relative to the content box.
 ///
 /// # Returns
-/// Float32Array: [x, y, w, h, ...] for each child, plus 2 trailing values:
-/// [firstChildMarginTop, lastChildMarginBottom]
+/// Float32Array: [x, y, w, h, ...] for each child, plus META_FIELDS trailing
+/// values: [firstChildMarginTop, lastChildMarginBottom, floatBottom] —
+/// block-start / block-end margins in vertical modes. `floatBottom` is the
+/// block-end edge of the lowest float's margin box (0 without floats); a
+/// container that is a BFC root grows its auto height to it
 #[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
 pub fn block_layout(
     data: &[f32],
     available_width: f32,
@@ -88,15 +320,289 @@ pub fn block_layout(
     can_collapse_top: bool,
     can_collapse_bottom: bool,
     prev_sibling_margin_bottom: f32,
+    rtl: bool,
+    writing_mode: u8,
+    text_align: u8,
+    font: &[f32],
+    padding: &[f32],
 ) -> Box<[f32]> {
-    let _ = available_height; // reserved for future use
+    layout_in_writing_mode(
+        data,
+        &[],
+        available_width,
+        available_height,
+        can_collapse_top,
+        can_collapse_bottom,
+        prev_sibling_margin_bottom,
+        rtl,
+        writing_mode,
+        text_align,
+        ParentFont::new(font),
+        PaddingBox::new(padding, available_height),
+    )
+}
+
+/// Block layout with an inline formatting context: `DISPLAY_TEXT` children
+/// are broken into fragments that share line boxes with inline-blocks.
+///
+/// # Arguments
+/// * `text_runs_json` - JSON array of text runs (`TextContent`), consumed in
+///   order by the `DISPLAY_TEXT` children of `data`. Only `display`,
+///   `m_left`, `m_right`, `vertical_align` and `valign_length` are read
+///   from a text child's fields.
+/// * other arguments as in `block_layout`
+///
+/// # Returns
+/// The `block_layout` output (a text child's rect is the union of its
+/// fragments), followed by FRAGMENT_FIELDS values per text fragment:
+/// [childIndex, start, end, x, y, w, h, ...]
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn block_layout_with_text(
+    data: &[f32],
+    text_runs_json: &str,
+    available_width: f32,
+    available_height: f32,
+    can_collapse_top: bool,
+    can_collapse_bottom: bool,
+    prev_sibling_margin_bottom: f32,
+    rtl: bool,
+    writing_mode: u8,
+    text_align: u8,
+    font: &[f32],
+    padding: &[f32],
+) -> Box<[f32]> {
+    let runs: Vec<TextContent> = serde_json::from_str(text_runs_json).unwrap_or_default();
+    layout_in_writing_mode(
+        data,
+        &runs,
+        available_width,
+        available_height,
+        can_collapse_top,
+        can_collapse_bottom,
+        prev_sibling_margin_bottom,
+        rtl,
+        writing_mode,
+        text_align,
+        ParentFont::new(font),
+        PaddingBox::new(padding, available_height),
+    )
+}
+
+/// Vertical writing modes: lay out the transposed children (inline axis
+/// horizontal, as in horizontal-tb) and transpose the rects back.
+#[allow(clippy::too_many_arguments)]
+fn layout_in_writing_mode(
+    data: &[f32],
+    runs: &[TextContent],
+    available_width: f32,
+    available_height: f32,
+    can_collapse_top: bool,
+    can_collapse_bottom: bool,
+    prev_sibling_margin_bottom: f32,
+    rtl: bool,
+    writing_mode: u8,
+    text_align: u8,
+    font: ParentFont,
+    padding: PaddingBox,
+) -> Box<[f32]> {
+    if writing_mode == WRITING_HORIZONTAL_TB {
+        return layout_children(
+            data,
+            runs,
+            available_width,
+            can_collapse_top,
+            can_collapse_bottom,
+            prev_sibling_margin_bottom,
+            rtl,
+            text_align,
+            font,
+            padding,
+        )
+        .into_boxed_slice();
+    }
+
+    let vertical_rl = writing_mode == WRITING_VERTICAL_RL;
+    let mut logical = data.to_vec();
+    for row in logical.chunks_exact_mut(FIELD_COUNT) {
+        // Percentage flags follow their fields
+        let mask = row[26] as u32;
+        let mut percent = [false; FIELD_COUNT];
+        for (bit, &field) in PERCENT_FIELDS.iter().enumerate() {
+            percent[field] = mask & (1 << bit) != 0;
+        }
+        to_logical_row(row, vertical_rl);
+        to_logical_row(&mut percent, vertical_rl);
+        let mask = PERCENT_FIELDS.iter().enumerate().filter(|&(_, &field)| percent[field]).fold(0, |mask, (bit, _)| mask | 1 << bit);
+        row[26] = mask as f32;
+    }
+    let mut sides = [padding.top, padding.right, padding.bottom, padding.left];
+    to_logical_sides(&mut sides, vertical_rl);
+    let [top, right, bottom, left] = sides;
+    // An auto inline size is not a length: the children's max-content size
+    let inline_size = if available_height >= 0.0 { available_height } else { max_content_inline_size(&logical, runs) };
+    let mut out = layout_children(
+        &logical,
+        runs,
+        inline_size,
+        can_collapse_top,
+        can_collapse_bottom,
+        prev_sibling_margin_bottom,
+        rtl,
+        text_align,
+        font,
+        PaddingBox { top, right, bottom, left, height: available_width },
+    );
+
+    let child_count = data.len() / FIELD_COUNT;
+    let transpose = |rect: &mut [f32]| {
+        let (inline_pos, block_pos, inline_size, block_size) = (rect[0], rect[1], rect[2], rect[3]);
+        rect[0] = if vertical_rl { available_width - block_pos - block_size } else { block_pos };
+        rect[1] = inline_pos;
+        rect[2] = block_size;
+        rect[3] = inline_size;
+    };
+    for rect in out[..child_count * OUT_FIELDS].chunks_exact_mut(OUT_FIELDS) {
+        transpose(rect);
+    }
+    let frag_base = child_count * OUT_FIELDS + META_FIELDS;
+    for frag in out[frag_base..].chunks_exact_mut(FRAGMENT_FIELDS) {
+        transpose(&mut frag[3..]);
+    }
+    out.into_boxed_slice()
+}
+
+/// Max-content inline size of the logical children (CSS Sizing §5.1): the
+/// widest margin box among the block-level children and the runs of inline
+/// content between them, each run kept on one line. Percentages are
+/// cyclic here and behave as auto (zero for margins).
+fn max_content_inline_size(data: &[f32], runs: &[TextContent]) -> f32 {
+    let measurer = MetricsTextMeasurer { fallback: Box::new(FallbackTextMeasurer) };
+    let (mut widest, mut line) = (0.0f32, 0.0f32);
+    let mut next_run = 0usize;
+    for row in data.chunks_exact(FIELD_COUNT) {
+        let mask = row[26] as u32;
+        let len = |field: usize, auto: f32| {
+            let percent = PERCENT_FIELDS.iter().position(|&f| f == field).is_some_and(|bit| mask & (1 << bit) != 0);
+            if percent || row[field].is_nan() {
+                auto
+            } else {
+                row[field]
+            }
+        };
+        let margins = len(4, 0.0) + len(6, 0.0);
+        let display = row[0] as u8;
+        if display == DISPLAY_TEXT {
+            let Some(run) = runs.get(next_run) else {
+                continue;
+            };
+            next_run += 1;
+            line += margins;
+            for seg in segment_text(run, &measurer) {
+                line += seg.width;
+                if seg.mandatory {
+                    widest = widest.max(line);
+                    line = 0.0;
+                } else {
+                    line += seg.space_after;
+                }
+            }
+            continue;
+        }
+        if row[21] as u8 == POSITION_ABSOLUTE {
+            continue;
+        }
+        let width = len(1, AUTO);
+        let content = if width >= 0.0 { width } else { row[14] };
+        let margin_box = clamp_size(content, len(10, AUTO), len(11, AUTO)) + row[9] + margins;
+        if display == DISPLAY_INLINE_BLOCK || row[19] as u8 != FLOAT_NONE {
+            line += margin_box;
+        } else {
+            widest = widest.max(line).max(margin_box);
+            line = 0.0;
+        }
+    }
+    widest.max(line)
+}
+
+/// Physical child fields to logical ones (inline axis horizontal) in a
+/// vertical writing mode.
+fn to_logical_row<T: Copy>(row: &mut [T], vertical_rl: bool) {
+    to_logical_sides(&mut row[3..7], vertical_rl); // margins
+    to_logical_sides(&mut row[22..26], vertical_rl); // insets
+    row.swap(1, 2); // width <-> height
+    row.swap(8, 9); // pad_border_v <-> pad_border_h
+    row.swap(10, 12); // min_w <-> min_h
+    row.swap(11, 13); // max_w <-> max_h
+    row.swap(14, 15); // content_w <-> content_h
+    row.swap(27, 28); // pad_pct_v <-> pad_pct_h
+}
+
+/// Physical [top, right, bottom, left] to [block-start, inline-end,
+/// block-end, inline-start] in a vertical writing mode.
+fn to_logical_sides<T: Copy>(sides: &mut [T], vertical_rl: bool) {
+    let (top, right, bottom, left) = (sides[0], sides[1], sides[2], sides[3]);
+    sides[0] = if vertical_rl { right } else { left };
+    sides[1] = bottom;
+    sides[2] = if vertical_rl { left } else { right };
+    sides[3] = top;
+}
+
+/// Shared implementation of `block_layout` / `block_layout_with_text`, also
+/// used by `multicol_layout` for the content of each column row.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn layout_children(
+    data: &[f32],
+    runs: &[TextContent],
+    available_width: f32,
+    can_collapse_top: bool,
+    can_collapse_bottom: bool,
+    prev_sibling_margin_bottom: f32,
+    rtl: bool,
+    text_align: u8,
+    font: ParentFont,
+    padding: PaddingBox,
+) -> Vec<f32> {
     let child_count = data.len() / FIELD_COUNT;
     if child_count == 0 {
-        return vec![0.0, 0.0].into_boxed_slice();
+        return vec![0.0; META_FIELDS];
+    }
+    let data = &*resolve_percentages(data, available_width, &padding, &font);
+
+    // RTL: lay out the mirror image (left/right margins swapped) and flip
+    // every x back, so physical margins keep their side.
+    if rtl {
+        let mut mirrored = data.to_vec();
+        for row in mirrored.chunks_exact_mut(FIELD_COUNT) {
+            row.swap(4, 6); // m_right <-> m_left
+            row[19] = mirror_side(row[19] as u8, FLOAT_LEFT, FLOAT_RIGHT) as f32;
+            row[20] = mirror_side(row[20] as u8, CLEAR_LEFT, CLEAR_RIGHT) as f32;
+            row.swap(23, 25); // inset_right <-> inset_left
+        }
+        let mut out = layout_children(
+            &mirrored,
+            runs,
+            available_width,
+            can_collapse_top,
+            can_collapse_bottom,
+            prev_sibling_margin_bottom,
+            false,
+            mirror_side(text_align, TEXT_ALIGN_LEFT, TEXT_ALIGN_RIGHT),
+            font,
+            PaddingBox { left: padding.right, right: padding.left, ..padding },
+        );
+        for rect in out[..child_count * OUT_FIELDS].chunks_exact_mut(OUT_FIELDS) {
+            rect[0] = available_width - rect[0] - rect[2];
+        }
+        let frag_base = child_count * OUT_FIELDS + META_FIELDS;
+        for frag in out[frag_base..].chunks_exact_mut(FRAGMENT_FIELDS) {
+            frag[3] = available_width - frag[3] - frag[5];
+        }
+        return out;
     }
 
-    // Output: 4 values per child + 2 trailing metadata
-    let mut out = vec![0.0f32; child_count * OUT_FIELDS + 2];
+    // Output: 4 values per child + META_FIELDS trailing metadata
+    let mut out = vec![0.0f32; child_count * OUT_FIELDS + META_FIELDS];
 
     let mut current_y: f32 = 0.0;
     let mut current_x: f32 = 0.0;
@@ -105,18 +611,27 @@ pub fn block_layout(
     let mut last_child_margin_bottom: f32 = 0.0;
     let mut is_first_block = true;
 
-    // LineBox accumulator for inline-block elements
+    // LineBox accumulator for inline-block elements and text fragments
     let mut line_items: Vec<LineItem> = Vec::new();
+    let mut line = LineBox { align: text_align, ..LineBox::default() };
+    let mut floats = Floats::default();
+
+    // Inline formatting context state
+    let measurer = MetricsTextMeasurer { fallback: Box::new(FallbackTextMeasurer) };
+    let frag_base = child_count * OUT_FIELDS + META_FIELDS;
+    let mut next_run = 0usize;
+    let mut pending_space: f32 = 0.0; // collapsed space before the next inline item
+    let mut text_children: Vec<(usize, std::ops::Range<usize>)> = Vec::new();
 
     for i in 0..child_count {
         let off = i * FIELD_COUNT;
         let display = data[off] as u8;
         let width_val = data[off + 1]; // AUTO = -1
         let height_val = data[off + 2]; // AUTO = -1
-        let m_top = data[off + 3];
-        let m_right = data[off + 4];
-        let m_bottom = data[off + 5];
-        let m_left = data[off + 6];
+        let m_top = used_margin(data[off + 3]);
+        let m_right = used_margin(data[off + 4]);
+        let m_bottom = used_margin(data[off + 5]);
+        let m_left = used_margin(data[off + 6]);
         let bfc_flag = data[off + 7] as u8; // 1 = creates BFC
         let pad_border_v = data[off + 8]; // padding_v + border_v combined
         let pad_border_h = data[off + 9]; // padding_h + border_h combined
@@ -129,11 +644,81 @@ pub fn block_layout(
         let vertical_align = data[off + 16] as u8;
         let baseline = data[off + 17];
         let line_height = data[off + 18]; // AUTO = -1
+        let valign_length = data[off + 29];
+        let float_side = data[off + 19] as u8;
+        let clear = data[off + 20] as u8;
+        let position = data[off + 21] as u8;
 
         let child_creates_bfc = bfc_flag == 1;
         let out_off = i * OUT_FIELDS;
 
-        if display == DISPLAY_INLINE_BLOCK {
+        if position == POSITION_ABSOLUTE && display != DISPLAY_TEXT {
+            // Out of flow: record the static position of the margin box,
+            // resolved against the insets after layout
+            let block_level = display != DISPLAY_INLINE_BLOCK;
+            if line.open && block_level {
+                out[out_off] = 0.0;
+                line.waiting.push(out_off);
+            } else if line.open {
+                // An empty line item, so the static position follows the
+                // line's alignment
+                let gap = if current_x > 0.0 { pending_space } else { 0.0 };
+                line_items.push(LineItem {
+                    out_index: out_off,
+                    x: current_x + gap,
+                    width: 0.0,
+                    height: 0.0,
+                    margin_top: 0.0,
+                    margin_bottom: 0.0,
+                    vertical_align: VALIGN_TOP,
+                    baseline: 0.0,
+                    line_height: AUTO,
+                    shift: 0.0,
+                    space_before: false,
+                });
+            } else {
+                out[out_off] = 0.0;
+                out[out_off + 1] = if block_level {
+                    current_y + collapse_margins(prev_margin_bottom, m_top) - m_top
+                } else {
+                    current_y
+                };
+            }
+        } else if float_side != FLOAT_NONE && display != DISPLAY_TEXT {
+            // Float: out of flow, shrink-to-fit, margins never collapse
+            let child_w = clamp_size(
+                shrink_to_fit(width_val, content_w, available_width - m_left - m_right - pad_border_h),
+                min_w, max_w,
+            ) + pad_border_h;
+            let child_h = clamp_size(
+                if height_val != AUTO && height_val != FIT_CONTENT { height_val } else { content_h },
+                min_h, max_h,
+            ) + pad_border_v;
+            out[out_off + 2] = child_w;
+            out[out_off + 3] = child_h;
+
+            let float = PendingFloat {
+                out_index: out_off,
+                left: float_side == FLOAT_LEFT,
+                clear,
+                width: child_w + m_left + m_right,
+                height: child_h + m_top + m_bottom,
+                margin_left: m_left,
+                margin_top: m_top,
+            };
+            if line.open {
+                // Beside the current line if it still fits, else below it
+                let extent = (line.left, line.left + line.width);
+                match floats.place_beside_line(&float, current_y, extent, current_x, available_width, &mut out) {
+                    Some(placed) => line.exclude(placed),
+                    None => floats.pending.push(float),
+                }
+            } else {
+                // Below the bottom margin of the preceding block
+                let min_y = if is_first_block { current_y } else { current_y + prev_margin_bottom.max(0.0) };
+                floats.place(&float, min_y, available_width, &mut out);
+            }
+        } else if display == DISPLAY_INLINE_BLOCK {
             // Inline-block: accumulate into line box
             // fit-content behaves same as auto for inline-block (use content size)
             let child_content_w = clamp_size(
@@ -147,14 +732,19 @@ pub fn block_layout(
             let child_w = child_content_w + pad_border_h;
             let child_h = child_content_h + pad_border_v;
             let total_width = child_w + m_left + m_right;
+            let total_height = child_h + m_top + m_bottom;
+            let mut gap = if current_x > 0.0 { pending_space } else { 0.0 };
+            line.open(&floats, &mut current_y, current_x + gap + total_width, total_height, available_width);
 
             // Line wrap check
-            if current_x + total_width > available_width && current_x > 0.0 {
-                flush_line_box(&line_items, current_y, &mut out);
-                current_y += calculate_line_box_height(&line_items);
+            if current_x + gap + total_width > line.width && current_x > 0.0 {
+                end_line(&mut line_items, &mut current_y, &mut out, &mut line, &mut floats, available_width, current_x, true);
                 current_x = 0.0;
-                line_items.clear();
+                gap = 0.0;
+                line.open(&floats, &mut current_y, total_width, total_height, available_width);
             }
+            current_x += gap;
+            pending_space = 0.0;
 
             line_items.push(LineItem {
                 out_index: out_off,
@@ -166,6 +756,8 @@ pub fn block_layout(
                 vertical_align,
                 baseline,
                 line_height,
+                shift: baseline_shift(vertical_align, valign_length, &font, m_top + baseline, child_h - baseline + m_bottom),
+                space_before: gap > 0.0,
             });
 
             // Write width/height (x/y will be set by flush_line_box)
@@ -175,17 +767,132 @@ pub fn block_layout(
             current_x += total_width;
             prev_margin_bottom = 0.0;
             is_first_block = false;
+        } else if display == DISPLAY_TEXT {
+            // Text run: flow segments into the shared line boxes
+            let Some(run) = runs.get(next_run) else {
+                continue;
+            };
+            next_run += 1;
+
+            let line_h = resolve_line_height(run, &measurer);
+            let (ascent, descent) = measurer.vertical_metrics(&run.font);
+            let text_baseline = (line_h - (ascent + descent)) / 2.0 + ascent;
+            // Line item of every fragment of the run
+            let strut = LineItem {
+                out_index: 0,
+                x: 0.0,
+                width: 0.0,
+                height: line_h,
+                margin_top: 0.0,
+                margin_bottom: 0.0,
+                vertical_align,
+                baseline: text_baseline,
+                line_height: line_h,
+                shift: baseline_shift(vertical_align, valign_length, &font, text_baseline, line_h - text_baseline),
+                space_before: false,
+            };
+            let space_w = measurer.measure_width(" ", &run.font);
+            let collapse = run.white_space.collapses_spaces();
+            let wraps = run.white_space.wraps();
+            if collapse && run.text.starts_with(is_collapsible_space) {
+                pending_space = space_w;
+            }
+
+            let first_frag = (out.len() - frag_base) / FRAGMENT_FIELDS;
+            let mut frag = TextFragment::default();
+            let mut frag_open = false;
+            current_x += m_left;
+
+            // (segment, starts a fresh line)
+            let mut segments = Vec::new();
+            for seg in segment_text(run, &measurer) {
+                if wraps && run.emergency_wrap().is_some() && seg.width > available_width + FIT_EPSILON {
+                    // overflow-wrap: the word takes a fresh line, then breaks anywhere
+                    let pieces = split_segment(run, &seg, &measurer);
+                    segments.extend(pieces.into_iter().enumerate().map(|(k, p)| (p, k == 0)));
+                } else {
+                    segments.push((seg, false));
+                }
+            }
+
+            for (seg, fresh_line) in segments {
+                if fresh_line && current_x > 0.0 {
+                    if frag_open {
+                        push_fragment(&frag, i, run, &strut, &mut out, &mut line_items);
+                        frag_open = false;
+                    }
+                    end_line(&mut line_items, &mut current_y, &mut out, &mut line, &mut floats, available_width, current_x, true);
+                    current_x = 0.0;
+                }
+                let mut gap = if current_x > 0.0 { pending_space } else { 0.0 };
+                line.open(&floats, &mut current_y, current_x + gap + seg.width, line_h, available_width);
+                if wraps && current_x > 0.0 && current_x + gap + seg.width > line.width + FIT_EPSILON {
+                    if frag_open {
+                        push_fragment(&frag, i, run, &strut, &mut out, &mut line_items);
+                        frag_open = false;
+                    }
+                    end_line(&mut line_items, &mut current_y, &mut out, &mut line, &mut floats, available_width, current_x, true);
+                    current_x = 0.0;
+                    gap = 0.0;
+                    line.open(&floats, &mut current_y, seg.width, line_h, available_width);
+                }
+                if frag_open && gap > 0.0 && line.align == TEXT_ALIGN_JUSTIFY {
+                    // Justified lines stretch between words: one fragment each
+                    push_fragment(&frag, i, run, &strut, &mut out, &mut line_items);
+                    frag_open = false;
+                }
+                if frag_open {
+                    frag.width += gap + seg.width;
+                    frag.end = seg.end;
+                } else {
+                    frag = TextFragment {
+                        start: seg.start,
+                        end: seg.end,
+                        x: current_x + gap,
+                        width: seg.width,
+                        space_before: gap > 0.0,
+                    };
+                    frag_open = true;
+                }
+                current_x += gap + seg.width;
+                pending_space = seg.space_after;
+
+                if seg.mandatory {
+                    push_fragment(&frag, i, run, &strut, &mut out, &mut line_items);
+                    frag_open = false;
+                    end_line(&mut line_items, &mut current_y, &mut out, &mut line, &mut floats, available_width, current_x, false);
+                    current_x = 0.0;
+                    pending_space = 0.0;
+                }
+            }
+            if frag_open {
+                push_fragment(&frag, i, run, &strut, &mut out, &mut line_items);
+            }
+            current_x += m_right;
+            if collapse && run.text.ends_with(is_collapsible_space) {
+                pending_space = space_w;
+            }
+
+            let last_frag = (out.len() - frag_base) / FRAGMENT_FIELDS;
+            text_children.push((out_off, first_frag..last_frag));
+            prev_margin_bottom = 0.0;
+            is_first_block = false;
         } else if display == DISPLAY_EMPTY_BLOCK {
             // Empty block: self-collapse top/bottom margins
-            if !line_items.is_empty() {
-                current_y += calculate_line_box_height(&line_items);
-                flush_line_box(&line_items, current_y - calculate_line_box_height(&line_items), &mut out);
+            if line.open {
+                end_line(&mut line_items, &mut current_y, &mut out, &mut line, &mut floats, available_width, current_x, false);
                 current_x = 0.0;
-                line_items.clear();
             }
-
-            let collapsed_self = collapse_margins(m_top, m_bottom);
-            let final_margin = collapse_margins(prev_margin_bottom, collapsed_self);
+            pending_space = 0.0;
+
+            let mut collapsed_self = collapse_margins(m_top, m_bottom);
+            let mut final_margin = collapse_margins(prev_margin_bottom, collapsed_self);
+            if let Some(clear_y) = floats.clearance(clear).filter(|&y| y > current_y + final_margin) {
+                // Clearance: the bottom margin no longer collapses through
+                current_y = clear_y;
+                final_margin = 0.0;
+                collapsed_self = m_bottom;
+            }
 
             out[out_off] = m_left;
             out[out_off + 1] = current_y + final_margin;
@@ -199,13 +906,11 @@ pub fn block_layout(
             prev_margin_bottom = collapsed_self;
         } else {
             // Block: vertical stacking + margin collapse
-            if !line_items.is_empty() {
-                let lbh = calculate_line_box_height(&line_items);
-                flush_line_box(&line_items, current_y, &mut out);
-                current_y += lbh;
+            if line.open {
+                end_line(&mut line_items, &mut current_y, &mut out, &mut line, &mut floats, available_width, current_x, false);
                 current_x = 0.0;
-                line_items.clear();
             }
+            pending_space = 0.0;
 
             if is_first_block {
                 if can_collapse_top && !child_creates_bfc {
@@ -221,6 +926,9 @@ pub fn block_layout(
                 collapse_margins(prev_margin_bottom, m_top)
             };
             current_y += collapsed_margin_top;
+            if let Some(clear_y) = floats.clearance(clear) {
+                current_y = current_y.max(clear_y);
+            }
 
             // Block width
             // fit-content: use content intrinsic width (shrink-to-fit)
@@ -241,7 +949,7 @@ pub fn block_layout(
 
             // Auto-width already includes padding+border conceptually
             // Explicit width / fit-content needs padding+border added
-            let child_w = if width_val == FIT_CONTENT {
+            let mut child_w = if width_val == FIT_CONTENT {
                 child_content_w + pad_border_h              // fit-content: content + padding + border
             } else if width_val != AUTO {
                 child_content_w + pad_border_h              // explicit px
@@ -250,7 +958,22 @@ pub fn block_layout(
             };
             let child_h = child_content_h + pad_border_v;
 
-            out[out_off] = m_left;
+            // A BFC root keeps clear of floats: narrowed beside them
+            // (auto width) or moved down until it fits
+            let (mut room_start, mut room) = (0.0, available_width);
+            if child_creates_bfc && !floats.is_empty() {
+                let needed = m_left + m_right + if width_val == AUTO { clamp_size(0.0, min_w, max_w) } else { child_w };
+                let (top, (left, right)) = floats.fit(current_y, needed, child_h, available_width);
+                current_y = top;
+                (room_start, room) = (left, right - left);
+                if width_val == AUTO {
+                    child_w = clamp_size(room - m_left - m_right, min_w, max_w);
+                }
+            }
+            let x = room_start
+                + used_start_margin(room - child_w, m_left, m_right, data[off + 6].is_nan(), data[off + 4].is_nan());
+
+            out[out_off] = x;
             out[out_off + 1] = current_y;
             out[out_off + 2] = child_w;
             out[out_off + 3] = child_h;
@@ -267,9 +990,57 @@ pub fn block_layout(
         }
     }
 
-    // Flush remaining line box
-    if !line_items.is_empty() {
-        flush_line_box(&line_items, current_y, &mut out);
+    // Flush remaining line box and the floats waiting for it
+    if line.open {
+        end_line(&mut line_items, &mut current_y, &mut out, &mut line, &mut floats, available_width, current_x, false);
+    }
+
+    // Positioned children. Absolute insets resolve against the padding box;
+    // an auto height container ends after its in-flow content.
+    let content_height = if padding.height != AUTO {
+        padding.height
+    } else {
+        current_y + if can_collapse_bottom { 0.0 } else { prev_margin_bottom }
+    };
+    for (i, row) in data.chunks_exact(FIELD_COUNT).enumerate() {
+        if row[0] as u8 == DISPLAY_TEXT {
+            continue;
+        }
+        let rect = &mut out[i * OUT_FIELDS..(i + 1) * OUT_FIELDS];
+        match row[21] as u8 {
+            POSITION_RELATIVE => {
+                rect[0] += relative_offset(row[25], row[23]);
+                rect[1] += relative_offset(row[22], row[24]);
+            }
+            POSITION_ABSOLUTE => {
+                let cb_height = padding.top + content_height + padding.bottom;
+                let row = &*resolve_absolute_percentages(row, cb_height);
+                let (x, width) = resolve_absolute(row, &HORIZONTAL, rect[0], -padding.left, padding.left + available_width + padding.right);
+                let (y, height) = resolve_absolute(row, &VERTICAL, rect[1], -padding.top, cb_height);
+                rect.copy_from_slice(&[x, y, width, height]);
+            }
+            _ => {}
+        }
+    }
+
+    // Text child rect = union of its fragments
+    for (out_off, frags) in text_children {
+        let mut rect: Option<(f32, f32, f32, f32)> = None;
+        for f in frags {
+            let off = frag_base + f * FRAGMENT_FIELDS;
+            let (x, y) = (out[off + 3], out[off + 4]);
+            let (r, b) = (x + out[off + 5], y + out[off + 6]);
+            rect = Some(match rect {
+                Some((x0, y0, r0, b0)) => (x0.min(x), y0.min(y), r0.max(r), b0.max(b)),
+                None => (x, y, r, b),
+            });
+        }
+        if let Some((x0, y0, r0, b0)) = rect {
+            out[out_off] = x0;
+            out[out_off + 1] = y0;
+            out[out_off + 2] = r0 - x0;
+            out[out_off + 3] = b0 - y0;
+        }
     }
 
     // Trailing metadata
@@ -283,8 +1054,210 @@ pub fn block_layout(
     let meta_off = child_count * OUT_FIELDS;
     out[meta_off] = first_child_margin_top;
     out[meta_off + 1] = last_child_margin_bottom;
+    out[meta_off + 2] = floats.bottom();
 
-    out.into_boxed_slice()
+    out
+}
+
+/// Resolve the percentage fields of `data` to px in a content box
+/// `available_width` wide. Margins, padding and widths resolve against the
+/// width, heights and vertical insets against `padding.height` (auto when
+/// it is AUTO) and `valign_length` against the child's line height (the
+/// parent's when auto).
+///
+/// Absolutely positioned children resolve against the padding box instead:
+/// their horizontal fields, margins and padding here, their heights and
+/// vertical insets (left flagged in `percent_mask`) by
+/// `resolve_absolute_percentages` once the container's height is known.
+fn resolve_percentages<'a>(data: &'a [f32], available_width: f32, padding: &PaddingBox, font: &ParentFont) -> Cow<'a, [f32]> {
+    let has_percent = |row: &[f32]| row[26] != 0.0 || row[27] != 0.0 || row[28] != 0.0;
+    if !data.chunks_exact(FIELD_COUNT).any(has_percent) {
+        return Cow::Borrowed(data);
+    }
+    let height = (padding.height != AUTO).then_some(padding.height);
+    let box_width = padding.left + available_width + padding.right;
+    let mut resolved = data.to_vec();
+    for row in resolved.chunks_exact_mut(FIELD_COUNT) {
+        let absolute = row[21] as u8 == POSITION_ABSOLUTE && row[0] as u8 != DISPLAY_TEXT;
+        let width = if absolute { box_width } else { available_width };
+        let mask = row[26] as u32;
+        let mut deferred = 0;
+        for (bit, &field) in PERCENT_FIELDS.iter().enumerate() {
+            if mask & (1 << bit) == 0 {
+                continue;
+            }
+            let ratio = row[field] / 100.0;
+            row[field] = match field {
+                1 | 3..=6 | 10 | 11 | 23 | 25 => width * ratio,
+                2 | 12 | 13 | 22 | 24 if absolute => {
+                    deferred |= 1 << bit;
+                    row[field]
+                }
+                2 | 12 | 13 => height.map_or(AUTO, |h| h * ratio),
+                29 => (if row[18] != AUTO { row[18] } else { font.line_height }) * ratio,
+                _ => height.map_or(INSET_AUTO, |h| h * ratio),
+            };
+        }
+        row[8] += width * row[27] / 100.0;
+        row[9] += width * row[28] / 100.0;
+        row[26] = deferred as f32;
+        row[27..29].fill(0.0);
+    }
+    Cow::Owned(resolved)
+}
+
+/// Resolve the percentages `resolve_percentages` left on an absolutely
+/// positioned child against the height of its containing block.
+fn resolve_absolute_percentages(row: &[f32], cb_height: f32) -> Cow<'_, [f32]> {
+    let mask = row[26] as u32;
+    if mask == 0 {
+        return Cow::Borrowed(row);
+    }
+    let mut resolved = row.to_vec();
+    for (bit, &field) in PERCENT_FIELDS.iter().enumerate() {
+        if mask & (1 << bit) != 0 {
+            resolved[field] = cb_height * (resolved[field] / 100.0);
+        }
+    }
+    resolved[26] = 0.0;
+    Cow::Owned(resolved)
+}
+
+/// Shift of a relatively positioned box along one axis: the start inset
+/// wins over the end one. NaN insets are auto.
+fn relative_offset(start: f32, end: f32) -> f32 {
+    if !start.is_nan() {
+        start
+    } else if !end.is_nan() {
+        -end
+    } else {
+        0.0
+    }
+}
+
+/// Field indices of one axis of a child row.
+struct Axis {
+    size: usize,
+    margin_start: usize,
+    margin_end: usize,
+    pad_border: usize,
+    min: usize,
+    max: usize,
+    content: usize,
+    inset_start: usize,
+    inset_end: usize,
+    /// Inline axis: auto sizes shrink to fit the containing block
+    inline: bool,
+}
+
+const HORIZONTAL: Axis = Axis {
+    size: 1, margin_start: 6, margin_end: 4, pad_border: 9, min: 10, max: 11,
+    content: 14, inset_start: 25, inset_end: 23, inline: true,
+};
+
+const VERTICAL: Axis = Axis {
+    size: 2, margin_start: 3, margin_end: 5, pad_border: 8, min: 12, max: 13,
+    content: 15, inset_start: 22, inset_end: 24, inline: false,
+};
+
+/// Border box offset and size of an absolutely positioned child along
+/// `axis`, in a containing block starting at `cb_start` and `cb_size`
+/// long. `static_pos` is the margin box offset of its static position.
+fn resolve_absolute(row: &[f32], axis: &Axis, static_pos: f32, cb_start: f32, cb_size: f32) -> (f32, f32) {
+    let (auto_start, auto_end) = (row[axis.margin_start].is_nan(), row[axis.margin_end].is_nan());
+    let (m_start, m_end) = (used_margin(row[axis.margin_start]), used_margin(row[axis.margin_end]));
+    let (start, end) = (row[axis.inset_start], row[axis.inset_end]);
+    let (size, pad_border) = (row[axis.size], row[axis.pad_border]);
+    let inset = |value: f32| if value.is_nan() { 0.0 } else { value };
+    let room = cb_size - inset(start) - inset(end) - m_start - m_end - pad_border;
+
+    let content = if size != AUTO && size != FIT_CONTENT {
+        size
+    } else if !start.is_nan() && !end.is_nan() {
+        room.max(0.0) // stretched between the insets, never negative
+    } else if axis.inline {
+        shrink_to_fit(size, row[axis.content], room)
+    } else {
+        row[axis.content]
+    };
+    let border_box = clamp_size(content, row[axis.min], row[axis.max]) + pad_border;
+
+    let offset = if !start.is_nan() && !end.is_nan() {
+        // Auto margins take up what the insets and the size leave
+        cb_start + start + used_start_margin(cb_size - start - end - border_box, m_start, m_end, auto_start, auto_end)
+    } else if !start.is_nan() {
+        cb_start + start + m_start
+    } else if !end.is_nan() {
+        cb_start + cb_size - end - m_end - border_box
+    } else {
+        static_pos + m_start
+    };
+    (offset, border_box)
+}
+
+/// Part of a text run placed on a single line (byte offsets into the run).
+#[derive(Default)]
+struct TextFragment {
+    start: usize,
+    end: usize,
+    x: f32,
+    width: f32,
+    space_before: bool,
+}
+
+/// Append a fragment record to `out` and register it in the current line box
+/// as a copy of the run's `strut` item.
+fn push_fragment(
+    frag: &TextFragment,
+    child_index: usize,
+    run: &TextContent,
+    strut: &LineItem,
+    out: &mut Vec<f32>,
+    line_items: &mut Vec<LineItem>,
+) {
+    let utf16 = |byte: usize| run.text[..byte].encode_utf16().count() as f32;
+    let rec = out.len();
+    out.extend_from_slice(&[
+        child_index as f32,
+        utf16(frag.start),
+        utf16(frag.end),
+        frag.x,
+        0.0, // y: set by flush_line_box
+        frag.width,
+        strut.height,
+    ]);
+    line_items.push(LineItem {
+        out_index: rec + 3,
+        x: frag.x,
+        width: frag.width,
+        space_before: frag.space_before,
+        ..*strut
+    });
+}
+
+/// Flush the current line box, advance `current_y` past it and place the
+/// floats that did not fit beside it. `used` is the inline size of its
+/// content; `wrapped` is set when the line ends in a soft wrap.
+#[allow(clippy::too_many_arguments)]
+fn end_line(
+    items: &mut Vec<LineItem>,
+    current_y: &mut f32,
+    out: &mut [f32],
+    line: &mut LineBox,
+    floats: &mut Floats,
+    available_width: f32,
+    used: f32,
+    wrapped: bool,
+) {
+    let height = calculate_line_box_height(items);
+    flush_line_box(items, *current_y, line, used, wrapped, out);
+    *current_y += height;
+    items.clear();
+    line.open = false;
+    for out_index in line.waiting.drain(..) {
+        out[out_index + 1] = *current_y;
+    }
+    floats.place_pending(*current_y, available_width, out);
 }
 
 /// Calculate line box height from items
@@ -305,16 +1278,16 @@ fn calculate_line_box_height(items: &[LineItem]) -> f32 {
             max_total_height = max_total_height.max(lh_with_margin);
         }
 
-        if item.vertical_align == VALIGN_BASELINE {
-            let baseline_from_top = item.margin_top + item.baseline;
+        if is_baseline_relative(item.vertical_align) {
+            let baseline_from_top = item.margin_top + item.baseline + item.shift;
             max_baseline_from_top = max_baseline_from_top.max(baseline_from_top);
         }
     }
 
     let mut max_below_baseline: f32 = 0.0;
     for item in items {
-        if item.vertical_align == VALIGN_BASELINE {
-            let below = item.height - item.baseline + item.margin_bottom;
+        if is_baseline_relative(item.vertical_align) {
+            let below = item.height - item.baseline + item.margin_bottom - item.shift;
             max_below_baseline = max_below_baseline.max(below);
         }
     }
@@ -323,19 +1296,53 @@ fn calculate_line_box_height(items: &[LineItem]) -> f32 {
     max_total_height.max(baseline_height)
 }
 
-/// Flush line box items: compute vertical positions and write x/y to output
-fn flush_line_box(items: &[LineItem], start_y: f32, out: &mut [f32]) {
+/// Whether `vertical_align` positions an item by its baseline (shifted or
+/// not), rather than against the line box.
+fn is_baseline_relative(vertical_align: u8) -> bool {
+    !matches!(vertical_align, VALIGN_TOP | VALIGN_MIDDLE | VALIGN_BOTTOM)
+}
+
+/// Raise of an item's baseline above the line's baseline (CSS 2.1 §10.8.1).
+/// `above` / `below` are the extents of its margin box (or line height, for
+/// text) above and below its own baseline. Text-top / text-bottom put that
+/// box against the parent's ascent / descent; `length` is the raise of
+/// VALIGN_LENGTH (none for NaN).
+fn baseline_shift(vertical_align: u8, length: f32, font: &ParentFont, above: f32, below: f32) -> f32 {
+    match vertical_align {
+        VALIGN_TEXT_TOP => font.ascent - above,
+        VALIGN_TEXT_BOTTOM => below - font.descent,
+        VALIGN_SUB => -font.size / 5.0,
+        VALIGN_SUPER => font.size / 3.0,
+        VALIGN_LENGTH if !length.is_nan() => length,
+        _ => 0.0,
+    }
+}
+
+/// Flush line box items: compute vertical positions and write x/y to output.
+/// Item x offsets are relative to the left edge of `line`; the `used` px of
+/// content are aligned in its width (justified only when `wrapped`).
+fn flush_line_box(items: &[LineItem], start_y: f32, line: &LineBox, used: f32, wrapped: bool, out: &mut [f32]) {
     if items.is_empty() {
         return;
     }
 
+    // Overflowing content stays start-aligned
+    let free = (line.width - used).max(0.0);
+    let opportunities = items.iter().filter(|item| item.space_before).count();
+    let (mut shift, spacing) = match line.align {
+        TEXT_ALIGN_END | TEXT_ALIGN_RIGHT => (free, 0.0),
+        TEXT_ALIGN_CENTER => (free / 2.0, 0.0),
+        TEXT_ALIGN_JUSTIFY if wrapped && opportunities > 0 => (0.0, free / opportunities as f32),
+        _ => (0.0, 0.0),
+    };
+
     let line_box_height = calculate_line_box_height(items);
 
     // Calculate baseline for the line box
     let mut line_baseline: f32 = 0.0;
     for item in items {
-        if item.vertical_align == VALIGN_BASELINE {
-            let baseline_from_top = item.margin_top + item.baseline;
+        if is_baseline_relative(item.vertical_align) {
+            let baseline_from_top = item.margin_top + item.baseline + item.shift;
             line_baseline = line_baseline.max(baseline_from_top);
         }
     }
@@ -350,12 +1357,15 @@ fn flush_line_box(items: &[LineItem], start_y: f32, out: &mut [f32]) {
                     + item.margin_top
             }
             _ => {
-                // baseline (default)
-                start_y + line_baseline - item.baseline
+                // baseline (default) and the values shifting it
+                start_y + line_baseline - item.shift - item.baseline
             }
         };
 
-        out[item.out_index] = item.x;
+        if item.space_before {
+            shift += spacing;
+        }
+        out[item.out_index] = line.left + shift + item.x;
         out[item.out_index + 1] = final_y;
         // width and height already written
     }
@@ -370,6 +1380,7 @@ pub fn wasm_collapse_margins(a: f32, b: f32) -> f32 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::floats::CLEAR_BOTH;
 
     fn make_block(width: f32, height: f32, m_top: f32, m_bottom: f32) -> Vec<f32> {
         vec![
@@ -392,6 +1403,17 @@ mod tests {
             0.0,                  // vertical_align
             0.0,                  // baseline
             AUTO,                 // line_height
+            0.0,                  // float
+            0.0,                  // clear
+            0.0,                  // position
+            INSET_AUTO,           // inset_top
+            INSET_AUTO,           // inset_right
+            INSET_AUTO,           // inset_bottom
+            INSET_AUTO,           // inset_left
+            0.0,                  // percent_mask
+            0.0,                  // pad_pct_v
+            0.0,                  // pad_pct_h
+            0.0,                  // valign_length
         ]
     }
 
@@ -407,6 +1429,10 @@ mod tests {
             valign as f32,
             height.max(0.0) * 0.8, // baseline ~80%
             AUTO,                  // line_height
+            0.0, 0.0,              // float, clear
+            0.0, INSET_AUTO, INSET_AUTO, INSET_AUTO, INSET_AUTO, // position, insets
+            0.0, 0.0, 0.0,         // percent_mask, pad_pct_v/h
+            0.0,                   // valign_length
         ]
     }
 
@@ -416,7 +1442,7 @@ mod tests {
         data.extend(make_block(AUTO, 100.0, 0.0, 0.0));
         data.extend(make_block(AUTO, 200.0, 0.0, 0.0));
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // child 0: y=0, h=100
         assert_eq!(result[1], 0.0);
         assert_eq!(result[3], 100.0);
@@ -431,7 +1457,7 @@ mod tests {
         data.extend(make_block(AUTO, 100.0, 0.0, 20.0));
         data.extend(make_block(AUTO, 100.0, 30.0, 0.0));
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // child 1 y = 100 + max(20, 30) = 130
         assert_eq!(result[5], 130.0);
     }
@@ -442,7 +1468,7 @@ mod tests {
         data.extend(make_block(AUTO, 100.0, 0.0, -10.0));
         data.extend(make_block(AUTO, 100.0, -20.0, 0.0));
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // child 1 y = 100 + min(-10, -20) = 80
         assert_eq!(result[5], 80.0);
     }
@@ -453,7 +1479,7 @@ mod tests {
         data.extend(make_block(AUTO, 100.0, 0.0, 20.0));
         data.extend(make_block(AUTO, 100.0, -10.0, 0.0));
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // child 1 y = 100 + (20 + -10) = 110
         assert_eq!(result[5], 110.0);
     }
@@ -467,7 +1493,7 @@ mod tests {
         child2[7] = 1.0; // bfc_flag
         data.extend(child2);
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // BFC: no collapse, y = 100 + 20 + 30 = 150
         assert_eq!(result[5], 150.0);
     }
@@ -478,7 +1504,7 @@ mod tests {
         data.extend(make_inline_block(50.0, 30.0, VALIGN_BASELINE));
         data.extend(make_inline_block(60.0, 40.0, VALIGN_BASELINE));
 
-        let result = block_layout(&data, 400.0, 400.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // child 0: x=0
         assert_eq!(result[0], 0.0);
         // child 1: x=50
@@ -491,7 +1517,7 @@ mod tests {
         data.extend(make_inline_block(200.0, 30.0, VALIGN_BASELINE));
         data.extend(make_inline_block(250.0, 40.0, VALIGN_BASELINE));
 
-        let result = block_layout(&data, 400.0, 400.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // child 0: x=0, y on first line
         assert_eq!(result[0], 0.0);
         // child 1: x=0 (wrapped to next line)
@@ -512,7 +1538,7 @@ mod tests {
         data.extend(empty);
         data.extend(make_block(AUTO, 100.0, 5.0, 0.0));
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // Empty block height = 0
         assert_eq!(result[7], 0.0);
     }
@@ -522,7 +1548,7 @@ mod tests {
         let mut data = Vec::new();
         data.extend(make_block(AUTO, 100.0, 20.0, 0.0));
 
-        let result = block_layout(&data, 400.0, 800.0, true, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, true, false, 0.0, false, 0, 0, &[], &[]);
         let meta_off = 1 * OUT_FIELDS;
         // firstChildMarginTop should be 20 (collapsed to parent)
         assert_eq!(result[meta_off], 20.0);
@@ -543,7 +1569,7 @@ mod tests {
         child[14] = 120.0; // content_w = 120
         data.extend(child);
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // fit-content: width = contentWidth(120) + padBorderH(0) = 120 (not 400)
         assert_eq!(result[2], 120.0);
     }
@@ -557,7 +1583,7 @@ mod tests {
         child[14] = 100.0; // content_w = 100
         data.extend(child);
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // fit-content: width = contentWidth(100) + padBorderH(20) = 120
         assert_eq!(result[2], 120.0);
     }
@@ -571,7 +1597,7 @@ mod tests {
         child[14] = 200.0; // content_w = 200 (exceeds max)
         data.extend(child);
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // clamp(200, AUTO, 80) = 80, then + padBorderH(0) = 80
         assert_eq!(result[2], 80.0);
     }
@@ -585,7 +1611,7 @@ mod tests {
         child[14] = 50.0;  // content_w = 50 (below min)
         data.extend(child);
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // clamp(50, 150, AUTO) = 150, then + padBorderH(0) = 150
         assert_eq!(result[2], 150.0);
     }
@@ -598,7 +1624,7 @@ mod tests {
         child[14] = 80.0; // content_w = 80
         data.extend(child);
 
-        let result = block_layout(&data, 400.0, 400.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // fit-content inline-block: width = contentWidth(80)
         assert_eq!(result[2], 80.0);
     }
@@ -612,7 +1638,7 @@ mod tests {
         fit_child[14] = 150.0; // content_w = 150
         data.extend(fit_child);
 
-        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0);
+        let result = block_layout(&data, 400.0, 800.0, false, false, 0.0, false, 0, 0, &[], &[]);
         // child 0: y=0, w=400 (auto stretch), h=50
         assert_eq!(result[0], 0.0);  // x
         assert_eq!(result[1], 0.0);  // y
@@ -622,4 +1648,689 @@ mod tests {
         assert_eq!(result[5], 70.0);  // y (after margin collapse)
         assert_eq!(result[6], 150.0); // w (fit-content = contentWidth)
     }
+
+    fn make_text() -> Vec<f32> {
+        let mut row = vec![0.0; FIELD_COUNT];
+        row[0] = DISPLAY_TEXT as f32;
+        row
+    }
+
+    /// Fragment records following the child rects and trailing metadata.
+    fn fragments(result: &[f32], child_count: usize) -> Vec<&[f32]> {
+        result[child_count * OUT_FIELDS + META_FIELDS..]
+            .chunks(FRAGMENT_FIELDS)
+            .collect()
+    }
+
+    #[test]
+    fn test_text_run_wraps_into_fragments() {
+        // Fallback measurer: 20px font → 10px per char
+        let data = make_text();
+        let runs = r#"[{"text":"aaaa bbbb cccc","fontSize":20,"lineHeight":24}]"#;
+
+        let result = block_layout_with_text(&data, runs, 100.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        let frags = fragments(&result, 1);
+        assert_eq!(frags.len(), 2);
+        // "aaaa bbbb" (chars 0..9) on line 0, "cccc" (10..14) on line 1
+        assert_eq!(frags[0], &[0.0, 0.0, 9.0, 0.0, 0.0, 90.0, 24.0]);
+        assert_eq!(frags[1], &[0.0, 10.0, 14.0, 0.0, 24.0, 40.0, 24.0]);
+        // Child rect = union of fragments
+        assert_eq!(&result[0..4], &[0.0, 0.0, 90.0, 48.0]);
+    }
+
+    #[test]
+    fn test_text_shares_line_box_with_inline_block() {
+        let mut data = Vec::new();
+        data.extend(make_inline_block(30.0, 30.0, VALIGN_BASELINE)); // baseline 24
+        data.extend(make_text());
+        data.extend(make_block(AUTO, 10.0, 0.0, 0.0));
+        // lineHeight 20 = ascent 16 + descent 4 → text baseline 16
+        let runs = r#"[{"text":" ab cd","fontSize":20,"lineHeight":20}]"#;
+
+        let result = block_layout_with_text(&data, runs, 400.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        let frags = fragments(&result, 3);
+        assert_eq!(frags.len(), 1);
+        // Leading space → 10px gap after the inline-block
+        assert_eq!(frags[0][3], 40.0, "x");
+        assert_eq!(frags[0][4], 8.0, "baselines aligned: 24 - 16");
+        assert_eq!(frags[0][5], 50.0, "'ab cd' width");
+        assert_eq!(result[1], 0.0, "inline-block y");
+        // Block starts below the shared line box (height 30)
+        assert_eq!(result[9], 30.0);
+    }
+
+    #[test]
+    fn test_text_mandatory_break_and_utf16_offsets() {
+        let data = make_text();
+        let runs = r#"[{"text":"😀a\nb","fontSize":20,"lineHeight":20,"whiteSpace":"pre-line"}]"#;
+
+        let result = block_layout_with_text(&data, runs, 400.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        let frags = fragments(&result, 1);
+        assert_eq!(frags.len(), 2);
+        // 😀 is two UTF-16 code units
+        assert_eq!(&frags[0][..3], &[0.0, 0.0, 3.0]);
+        assert_eq!(&frags[1][..3], &[0.0, 4.0, 5.0]);
+        assert_eq!(frags[1][4], 20.0);
+    }
+
+    #[test]
+    fn test_text_white_space_and_overflow_wrap() {
+        let data = make_text();
+        let runs = r#"[{"text":"aaaa bbbb","fontSize":20,"lineHeight":20,"whiteSpace":"nowrap"}]"#;
+        let result = block_layout_with_text(&data, runs, 50.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        let frags = fragments(&result, 1);
+        assert_eq!(frags.len(), 1, "nowrap overflows instead of wrapping");
+        assert_eq!(frags[0][5], 90.0);
+
+        // The long word moves to its own line, then breaks every 3 chars
+        let runs = r#"[{"text":"a bbbbbbb","fontSize":20,"lineHeight":20,"overflowWrap":"break-word"}]"#;
+        let result = block_layout_with_text(&data, runs, 30.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        let frags = fragments(&result, 1);
+        let spans: Vec<_> = frags.iter().map(|f| (f[1], f[2], f[4])).collect();
+        assert_eq!(spans, vec![(0.0, 1.0, 0.0), (2.0, 5.0, 20.0), (5.0, 8.0, 40.0), (8.0, 9.0, 60.0)]);
+    }
+
+    #[test]
+    fn test_rtl_mirrors_line_boxes_and_blocks() {
+        let mut data = Vec::new();
+        data.extend(make_inline_block(100.0, 30.0, VALIGN_TOP));
+        data.extend(make_inline_block(50.0, 30.0, VALIGN_TOP));
+        let mut block = make_block(100.0, 10.0, 0.0, 0.0);
+        block[6] = 20.0; // margin-left: ignored when over-constrained in RTL
+        data.extend(block);
+
+        let result = block_layout(&data, 400.0, 400.0, false, false, 0.0, true, 0, 0, &[], &[]);
+        assert_eq!(result[0], 300.0, "first inline-block at the right edge");
+        assert_eq!(result[4], 250.0);
+        assert_eq!(result[8], 300.0, "block hugs the right edge");
+        assert_eq!(result[9], 30.0);
+
+        // Physical margins keep their side
+        let mut data = make_inline_block(100.0, 30.0, VALIGN_TOP);
+        data[4] = 10.0; // margin-right
+        let result = block_layout(&data, 400.0, 400.0, false, false, 0.0, true, 0, 0, &[], &[]);
+        assert_eq!(result[0], 290.0);
+    }
+
+    #[test]
+    fn test_rtl_mirrors_text_fragments() {
+        let data = make_text();
+        let runs = r#"[{"text":"aaaa bbbb cccc","fontSize":20,"lineHeight":24}]"#;
+        let result = block_layout_with_text(&data, runs, 100.0, 400.0, false, false, 0.0, true, 0, 0, &[], &[]);
+        let frags = fragments(&result, 1);
+        assert_eq!(frags[0][3], 10.0, "90px line ends at the right edge");
+        assert_eq!(frags[1][3], 60.0);
+        assert_eq!(&result[0..4], &[10.0, 0.0, 90.0, 48.0]);
+    }
+
+    #[test]
+    fn test_vertical_modes_stack_blocks_horizontally() {
+        let mut first = make_block(50.0, AUTO, 10.0, 0.0); // margin-top: inline-start
+        first[4] = 10.0; // margin-right: block-start in vertical-rl
+        let mut data = first;
+        data.extend(make_block(40.0, 60.0, 0.0, 0.0));
+
+        let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, false, WRITING_VERTICAL_RL, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[240.0, 10.0, 50.0, 190.0]);
+        assert_eq!(&result[4..8], &[200.0, 0.0, 40.0, 60.0]);
+
+        let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, false, WRITING_VERTICAL_LR, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[0.0, 10.0, 50.0, 190.0]);
+        assert_eq!(&result[4..8], &[60.0, 0.0, 40.0, 60.0], "margin-right is block-end");
+    }
+
+    #[test]
+    fn test_vertical_mode_auto_inline_size_uses_max_content() {
+        let mut auto = make_block(60.0, AUTO, 0.0, 0.0);
+        auto[15] = 30.0; // content_h: its inline max-content size
+        let mut data = auto;
+        data.extend(make_block(40.0, 50.0, 5.0, 0.0)); // 55px margin box
+
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, WRITING_VERTICAL_RL, 0, &[], &[]);
+        // Auto inline sizes stretch to the widest child, not to -1
+        assert_eq!(&result[0..4], &[340.0, 0.0, 60.0, 55.0]);
+        assert_eq!(&result[4..8], &[300.0, 5.0, 40.0, 50.0]);
+
+        // Inline content on one line: 50 + 20px of text
+        let mut data = make_block(60.0, AUTO, 0.0, 0.0);
+        data.extend(make_inline_block(20.0, 50.0, VALIGN_TOP));
+        data.extend(make_text());
+        let runs = r#"[{"text":"ab","fontSize":20,"lineHeight":20}]"#;
+        let result = block_layout_with_text(&data, runs, 400.0, AUTO, false, false, 0.0, false, WRITING_VERTICAL_LR, 0, &[], &[]);
+        assert_eq!(result[3], 70.0);
+    }
+
+    #[test]
+    fn test_vertical_rl_lines_run_downwards() {
+        let data = make_text();
+        let runs = r#"[{"text":"aaaa bbbb cccc","fontSize":20,"lineHeight":24}]"#;
+        let result = block_layout_with_text(&data, runs, 300.0, 100.0, false, false, 0.0, false, WRITING_VERTICAL_RL, 0, &[], &[]);
+        let frags = fragments(&result, 1);
+        assert_eq!(&frags[0][3..], &[276.0, 0.0, 24.0, 90.0], "first line at the right edge");
+        assert_eq!(&frags[1][3..], &[252.0, 0.0, 24.0, 40.0]);
+    }
+
+    fn make_float(side: u8, width: f32, height: f32) -> Vec<f32> {
+        let mut row = make_block(width, height, 0.0, 0.0);
+        row[19] = side as f32;
+        row
+    }
+
+    #[test]
+    fn test_text_wraps_around_float() {
+        // Float 50x30 on the left, text lines 20px high
+        let mut data = make_float(FLOAT_LEFT, 50.0, 30.0);
+        data.extend(make_text());
+        let runs = r#"[{"text":"aaaa bbbb cccc dddd eeee","fontSize":20,"lineHeight":20}]"#;
+
+        let result = block_layout_with_text(&data, runs, 150.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[0.0, 0.0, 50.0, 30.0]);
+        let frags = fragments(&result, 2);
+        let lines: Vec<_> = frags.iter().map(|f| (f[1], f[3], f[4])).collect();
+        // Lines beside the float start at x=50 and hold 100px; the third is below it
+        assert_eq!(lines, vec![(0.0, 50.0, 0.0), (10.0, 50.0, 20.0), (20.0, 0.0, 40.0)]);
+        assert_eq!(result[2 * OUT_FIELDS + 2], 30.0, "floatBottom");
+    }
+
+    #[test]
+    fn test_right_float_mid_line_and_rtl() {
+        // A right float met in the middle of a line shortens that line
+        let mut data = make_text();
+        data.extend(make_float(FLOAT_RIGHT, 40.0, 50.0));
+        data.extend(make_text());
+        let runs = r#"[{"text":"aa ","fontSize":20,"lineHeight":20},{"text":"bb cc","fontSize":20,"lineHeight":20}]"#;
+        let result = block_layout_with_text(&data, runs, 100.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[4..8], &[60.0, 0.0, 40.0, 50.0], "beside the first line");
+        let frags = fragments(&result, 3);
+        assert_eq!(frags[1][3], 30.0, "'bb' after the collapsed space");
+        assert_eq!((frags[2][1], frags[2][3], frags[2][4]), (3.0, 0.0, 20.0), "'cc' wraps in 60px");
+
+        // float: right stays physical under rtl
+        let data = make_float(FLOAT_RIGHT, 40.0, 50.0);
+        let result = block_layout(&data, 100.0, 400.0, false, false, 0.0, true, 0, 0, &[], &[]);
+        assert_eq!(result[0], 60.0);
+    }
+
+    #[test]
+    fn test_clear_moves_block_below_floats() {
+        let mut data = make_float(FLOAT_LEFT, 50.0, 30.0);
+        data.extend(make_float(FLOAT_RIGHT, 50.0, 60.0));
+        let mut left = make_block(AUTO, 10.0, 5.0, 0.0);
+        left[20] = CLEAR_LEFT as f32;
+        data.extend(left);
+        let mut both = make_block(AUTO, 10.0, 0.0, 0.0);
+        both[20] = CLEAR_BOTH as f32;
+        data.extend(both);
+
+        let result = block_layout(&data, 300.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(result[9], 30.0, "border edge at the left float's bottom");
+        assert_eq!(result[13], 60.0);
+        assert_eq!(result[4 * OUT_FIELDS + 2], 60.0, "floatBottom");
+    }
+
+    #[test]
+    fn test_bfc_block_keeps_clear_of_floats() {
+        let mut data = make_float(FLOAT_LEFT, 100.0, 40.0);
+        let mut auto = make_block(AUTO, 20.0, 0.0, 0.0);
+        auto[7] = 1.0;
+        data.extend(auto);
+        let mut wide = make_block(250.0, 20.0, 0.0, 0.0);
+        wide[7] = 1.0;
+        data.extend(wide);
+        data.extend(make_block(AUTO, 10.0, 0.0, 0.0));
+
+        let result = block_layout(&data, 300.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[4..8], &[100.0, 0.0, 200.0, 20.0], "narrowed beside the float");
+        assert_eq!(&result[8..12], &[0.0, 40.0, 250.0, 20.0], "too wide: below the float");
+        assert_eq!(&result[12..16], &[0.0, 60.0, 300.0, 10.0], "in-flow block ignores floats");
+    }
+
+    /// Positioned block with [top, right, bottom, left] insets.
+    fn make_positioned(position: u8, width: f32, height: f32, insets: [f32; 4]) -> Vec<f32> {
+        let mut row = make_block(width, height, 0.0, 0.0);
+        row[14] = 80.0; // content_w
+        row[21] = position as f32;
+        row[22..26].copy_from_slice(&insets);
+        row
+    }
+
+    #[test]
+    fn test_relative_offsets_keep_flow() {
+        let mut data = make_positioned(POSITION_RELATIVE, AUTO, 100.0, [5.0, INSET_AUTO, INSET_AUTO, 10.0]);
+        data.extend(make_positioned(POSITION_RELATIVE, AUTO, 20.0, [INSET_AUTO, 4.0, 8.0, INSET_AUTO]));
+        data.extend(make_block(AUTO, 10.0, 0.0, 0.0));
+
+        let result = block_layout(&data, 300.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[0..2], &[10.0, 5.0]);
+        assert_eq!(&result[4..6], &[-4.0, 92.0], "right / bottom shift backwards");
+        assert_eq!(result[9], 120.0, "siblings keep their in-flow place");
+    }
+
+    #[test]
+    fn test_absolute_insets_against_padding_box() {
+        let padding = [10.0, 20.0, 10.0, 20.0];
+        let mut data = make_block(AUTO, 50.0, 0.0, 0.0);
+        // Shrink-to-fit in the bottom-right corner
+        data.extend(make_positioned(POSITION_ABSOLUTE, AUTO, AUTO, [INSET_AUTO, 0.0, 0.0, INSET_AUTO]));
+        // Stretched between left and right, fixed height
+        data.extend(make_positioned(POSITION_ABSOLUTE, AUTO, 20.0, [0.0, 0.0, INSET_AUTO, 0.0]));
+        data.extend(make_block(AUTO, 10.0, 0.0, 0.0));
+
+        let result = block_layout(&data, 300.0, AUTO, false, false, 0.0, false, 0, 0, &[], &padding);
+        // Padding box: x -20..320, y -10..70 (auto height: 60px of content)
+        assert_eq!(&result[4..8], &[240.0, 70.0, 80.0, 0.0]);
+        assert_eq!(&result[8..12], &[-20.0, -10.0, 340.0, 20.0]);
+        assert_eq!(result[13], 50.0, "out of flow");
+
+        // A definite height moves the bottom edge; rtl keeps physical insets
+        let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, true, 0, 0, &[], &padding);
+        assert_eq!(&result[4..8], &[240.0, 210.0, 80.0, 0.0]);
+        assert_eq!(result[8], -20.0);
+    }
+
+    #[test]
+    fn test_absolute_static_position() {
+        let mut data = make_block(AUTO, 50.0, 0.0, 10.0);
+        let mut block = make_positioned(POSITION_ABSOLUTE, 40.0, 40.0, [INSET_AUTO; 4]);
+        block[6] = 5.0; // margin-left
+        data.extend(block);
+        data.extend(make_inline_block(30.0, 20.0, VALIGN_TOP));
+        let mut inline = make_positioned(POSITION_ABSOLUTE, 40.0, 40.0, [INSET_AUTO; 4]);
+        inline[0] = DISPLAY_INLINE_BLOCK as f32;
+        data.extend(inline);
+        data.extend(make_positioned(POSITION_ABSOLUTE, 40.0, 40.0, [INSET_AUTO; 4]));
+
+        let result = block_layout(&data, 300.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[4..6], &[5.0, 60.0], "below the previous margin");
+        assert_eq!(&result[8..10], &[0.0, 50.0]);
+        assert_eq!(&result[12..14], &[30.0, 50.0], "after the inline-block on its line");
+        assert_eq!(&result[16..18], &[0.0, 70.0], "block-level: below the line");
+    }
+
+    #[test]
+    fn test_positioned_sentinels_direction_and_writing_mode() {
+        // All-auto insets: no shift, no stretch
+        let data = make_positioned(POSITION_RELATIVE, 40.0, 10.0, [INSET_AUTO; 4]);
+        let result = block_layout(&data, 300.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[0.0, 0.0, 40.0, 10.0]);
+
+        // Both horizontal insets: the start one wins, left in ltr, right in rtl
+        let mut data = make_positioned(POSITION_RELATIVE, 40.0, 10.0, [INSET_AUTO, 4.0, INSET_AUTO, 10.0]);
+        data.extend(make_positioned(POSITION_ABSOLUTE, 40.0, 10.0, [INSET_AUTO, 20.0, INSET_AUTO, 10.0]));
+        let result = block_layout(&data, 300.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!([result[0], result[4]], [10.0, 10.0]);
+        let result = block_layout(&data, 300.0, AUTO, false, false, 0.0, true, 0, 0, &[], &[]);
+        assert_eq!([result[0], result[4]], [256.0, 240.0]);
+
+        // Vertical modes: physical insets survive the transposition
+        let data = make_positioned(POSITION_ABSOLUTE, 40.0, 50.0, [10.0, 20.0, INSET_AUTO, INSET_AUTO]);
+        for mode in [WRITING_VERTICAL_RL, WRITING_VERTICAL_LR] {
+            let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, false, mode, 0, &[], &[]);
+            assert_eq!(&result[0..4], &[240.0, 10.0, 40.0, 50.0], "writing mode {mode}");
+        }
+    }
+
+    #[test]
+    fn test_positioned_in_zero_and_negative_space() {
+        // Shrink-to-fit collapses to nothing in a zero-width container
+        let mut data = make_positioned(POSITION_ABSOLUTE, AUTO, 10.0, [0.0, 0.0, INSET_AUTO, INSET_AUTO]);
+        data.extend(make_positioned(POSITION_ABSOLUTE, AUTO, 10.0, [0.0, INSET_AUTO, INSET_AUTO, INSET_AUTO]));
+        let result = block_layout(&data, 0.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[0.0, 0.0, 0.0, 10.0]);
+        assert_eq!(&result[4..8], &[0.0, 0.0, 0.0, 10.0]);
+
+        // Insets wider than the containing block leave no negative size
+        let data = make_positioned(POSITION_ABSOLUTE, AUTO, AUTO, [60.0, 200.0, 60.0, 200.0]);
+        let result = block_layout(&data, 300.0, 100.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[200.0, 60.0, 0.0, 0.0]);
+    }
+
+    /// Mark `fields` of `row` as percentages.
+    fn with_percent(mut row: Vec<f32>, fields: &[usize]) -> Vec<f32> {
+        for &field in fields {
+            let bit = PERCENT_FIELDS.iter().position(|&f| f == field).unwrap();
+            row[26] += (1 << bit) as f32;
+        }
+        row
+    }
+
+    #[test]
+    fn test_percent_widths_margins_and_padding() {
+        let mut row = make_block(50.0, 10.0, 5.0, 0.0);
+        row[6] = 10.0; // margin-left: 10%
+        row[28] = 5.0; // padding-left 5%
+        let data = with_percent(row, &[1, 3, 6]);
+
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        // width 200 + padding 20, margin-left 40, margin-top 5% of the width
+        assert_eq!(&result[0..4], &[40.0, 20.0, 220.0, 10.0]);
+    }
+
+    #[test]
+    fn test_percent_heights_need_definite_height() {
+        let mut row = make_block(AUTO, 50.0, 0.0, 0.0);
+        row[15] = 30.0; // content_h
+        row[13] = 40.0; // max-height: 40%
+        let data = with_percent(row, &[2, 13]);
+
+        let result = block_layout(&data, 400.0, 300.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(result[3], 120.0, "50% of 300, capped at 40%");
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(result[3], 30.0, "auto: content height");
+    }
+
+    #[test]
+    fn test_percent_in_vertical_mode() {
+        // Width against the physical width, margins against the inline size
+        let data = with_percent(make_block(50.0, AUTO, 10.0, 0.0), &[1, 3]);
+        let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, false, WRITING_VERTICAL_RL, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[150.0, 20.0, 150.0, 180.0]);
+    }
+
+    #[test]
+    fn test_percent_absolute_against_padding_box() {
+        // Horizontal percentages of the 500px wide padding box
+        let padding = [0.0, 50.0, 0.0, 50.0];
+        let row = make_positioned(POSITION_ABSOLUTE, 50.0, 20.0, [INSET_AUTO, INSET_AUTO, INSET_AUTO, 10.0]);
+        let data = with_percent(row, &[1, 25]);
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &[], &padding);
+        assert_eq!(&result[0..3], &[0.0, 0.0, 250.0]);
+
+        // Vertical ones of the final padding box of an auto height container:
+        // 10 + 100px of content + 10
+        let padding = [10.0, 0.0, 10.0, 0.0];
+        let mut data = make_block(AUTO, 100.0, 0.0, 0.0);
+        let row = make_positioned(POSITION_ABSOLUTE, 40.0, 50.0, [50.0, INSET_AUTO, INSET_AUTO, 0.0]);
+        data.extend(with_percent(row, &[2, 22]));
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &[], &padding);
+        assert_eq!(&result[4..8], &[0.0, 50.0, 40.0, 60.0]);
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, true, 0, 0, &[], &padding);
+        assert_eq!(&result[5..8], &[50.0, 40.0, 60.0], "rtl");
+    }
+
+    #[test]
+    fn test_percent_relative_insets_against_content_box() {
+        // 400×100 content box inside 20px / 50px padding
+        let padding = [20.0, 50.0, 20.0, 50.0];
+        let row = make_positioned(POSITION_RELATIVE, AUTO, 10.0, [50.0, INSET_AUTO, INSET_AUTO, 10.0]);
+        let data = with_percent(row, &[22, 25]);
+        let result = block_layout(&data, 400.0, 100.0, false, false, 0.0, false, 0, 0, &[], &padding);
+        assert_eq!(&result[0..2], &[40.0, 50.0]);
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &[], &padding);
+        assert_eq!(&result[0..2], &[40.0, 0.0], "auto height: top is auto");
+    }
+
+    /// Block with `margin-left` / `margin-right` (MARGIN_AUTO for auto).
+    fn make_inline_margins(width: f32, margin_left: f32, margin_right: f32) -> Vec<f32> {
+        let mut row = make_block(width, 10.0, 0.0, 0.0);
+        row[6] = margin_left;
+        row[4] = margin_right;
+        row
+    }
+
+    #[test]
+    fn test_auto_margins_share_free_space() {
+        let mut data = make_inline_margins(100.0, MARGIN_AUTO, MARGIN_AUTO);
+        data.extend(make_inline_margins(100.0, MARGIN_AUTO, 20.0));
+        data.extend(make_inline_margins(100.0, 20.0, MARGIN_AUTO));
+        data.extend(make_inline_margins(400.0, MARGIN_AUTO, MARGIN_AUTO));
+        let mut capped = make_inline_margins(AUTO, MARGIN_AUTO, MARGIN_AUTO);
+        capped[11] = 200.0; // max-width
+        data.extend(capped);
+
+        let result = block_layout(&data, 300.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[0..3], &[100.0, 0.0, 100.0], "centered");
+        assert_eq!(result[4], 180.0, "pushed against margin-right");
+        assert_eq!(result[8], 20.0);
+        assert_eq!(result[12], 0.0, "overflowing: auto margins are zero");
+        assert_eq!(&result[16..19], &[50.0, 40.0, 200.0], "capped by max-width, then centered");
+
+        // rtl: a one-sided auto margin still pushes towards the other side
+        let result = block_layout(&data, 300.0, AUTO, false, false, 0.0, true, 0, 0, &[], &[]);
+        assert_eq!(result[0], 100.0);
+        assert_eq!(result[4], 180.0);
+        assert_eq!(result[12], -100.0, "overflowing to the left");
+    }
+
+    #[test]
+    fn test_auto_margins_edge_cases() {
+        // Auto width absorbs the free space; block-axis auto margins are zero
+        let mut data = make_inline_margins(AUTO, MARGIN_AUTO, MARGIN_AUTO);
+        let mut stacked = make_block(100.0, 10.0, MARGIN_AUTO, MARGIN_AUTO);
+        stacked[6] = MARGIN_AUTO;
+        data.extend(stacked);
+        data.extend(make_block(AUTO, 10.0, 5.0, 0.0));
+        // A negative end margin widens the room the auto start margin takes
+        data.extend(make_inline_margins(100.0, MARGIN_AUTO, -20.0));
+
+        let result = block_layout(&data, 300.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[0.0, 0.0, 300.0, 10.0]);
+        assert_eq!(&result[4..8], &[200.0, 10.0, 100.0, 10.0]);
+        assert_eq!(result[9], 25.0, "auto margins collapse as zero");
+        assert_eq!(result[12], 220.0);
+
+        // A zero-width container leaves nothing to share
+        let mut data = make_inline_margins(100.0, MARGIN_AUTO, MARGIN_AUTO);
+        data.extend(make_inline_margins(AUTO, MARGIN_AUTO, MARGIN_AUTO));
+        let result = block_layout(&data, 0.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!([result[0], result[4], result[6]], [0.0, 0.0, 0.0]);
+        let result = block_layout(&data, 0.0, AUTO, false, false, 0.0, true, 0, 0, &[], &[]);
+        assert_eq!([result[0], result[4]], [-100.0, 0.0], "rtl overflows to the left");
+    }
+
+    #[test]
+    fn test_auto_margins_in_vertical_modes() {
+        // Inline-axis (top / bottom) auto margins center; the left / right
+        // ones are block-axis and zero
+        let mut row = make_block(50.0, 100.0, MARGIN_AUTO, MARGIN_AUTO);
+        row[4] = MARGIN_AUTO;
+        row[6] = MARGIN_AUTO;
+        let mut data = row;
+        data.extend(make_block(50.0, 100.0, MARGIN_AUTO, 0.0)); // pushed to the bottom
+
+        let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, false, WRITING_VERTICAL_RL, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[250.0, 50.0, 50.0, 100.0]);
+        assert_eq!(&result[4..8], &[200.0, 100.0, 50.0, 100.0]);
+        let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, false, WRITING_VERTICAL_LR, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[0.0, 50.0, 50.0, 100.0]);
+        assert_eq!(result[5], 100.0);
+    }
+
+    #[test]
+    fn test_auto_margins_center_absolute() {
+        let mut row = make_positioned(POSITION_ABSOLUTE, 100.0, 40.0, [0.0; 4]);
+        row[3..7].copy_from_slice(&[MARGIN_AUTO; 4]);
+        let mut data = row.clone();
+        // Only one inset per axis: auto margins are zero
+        row[22..24].copy_from_slice(&[INSET_AUTO; 2]);
+        data.extend(row);
+
+        let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(&result[0..4], &[100.0, 80.0, 100.0, 40.0]);
+        assert_eq!(&result[4..6], &[0.0, 160.0]);
+    }
+
+    #[test]
+    fn test_text_align_inline_blocks() {
+        let mut data = Vec::new();
+        for _ in 0..3 {
+            data.extend(make_inline_block(100.0, 20.0, VALIGN_TOP));
+        }
+        let x = |result: &[f32]| [result[0], result[4], result[8]];
+
+        let result = block_layout(&data, 250.0, AUTO, false, false, 0.0, false, 0, TEXT_ALIGN_CENTER, &[], &[]);
+        assert_eq!(x(&result), [25.0, 125.0, 75.0]);
+        let result = block_layout(&data, 250.0, AUTO, false, false, 0.0, false, 0, TEXT_ALIGN_END, &[], &[]);
+        assert_eq!(x(&result), [50.0, 150.0, 150.0]);
+        // rtl: end is the left edge, physical sides do not move
+        let result = block_layout(&data, 250.0, AUTO, false, false, 0.0, true, 0, TEXT_ALIGN_END, &[], &[]);
+        assert_eq!(x(&result), [100.0, 0.0, 0.0]);
+        let result = block_layout(&data, 250.0, AUTO, false, false, 0.0, true, 0, TEXT_ALIGN_RIGHT, &[], &[]);
+        assert_eq!(x(&result), [150.0, 50.0, 150.0]);
+        // Too wide for the line: start-aligned
+        let result = block_layout(&data, 80.0, AUTO, false, false, 0.0, false, 0, TEXT_ALIGN_CENTER, &[], &[]);
+        assert_eq!(x(&result), [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_text_align_justify() {
+        let data = make_text();
+        let runs = r#"[{"text":"aa bb cc dd\nee ff","fontSize":20,"lineHeight":20,"whiteSpace":"pre-line"}]"#;
+
+        let result = block_layout_with_text(&data, runs, 100.0, AUTO, false, false, 0.0, false, 0, TEXT_ALIGN_JUSTIFY, &[], &[]);
+        let x: Vec<(f32, f32)> = fragments(&result, 1).iter().map(|f| (f[1], f[3])).collect();
+        // One fragment per word; the 20px left on the first line go to its
+        // two spaces. The line before the forced break is not justified
+        assert_eq!(x, [(0.0, 0.0), (3.0, 40.0), (6.0, 80.0), (9.0, 0.0), (12.0, 0.0), (15.0, 30.0)]);
+    }
+
+    #[test]
+    fn test_text_align_edge_cases() {
+        let mut data = Vec::new();
+        for _ in 0..3 {
+            data.extend(make_inline_block(20.0, 30.0, VALIGN_TOP));
+        }
+        let x = |result: &[f32]| [result[0], result[4], result[8]];
+        let y = |result: &[f32]| [result[1], result[5], result[9]];
+
+        // Unknown codes fall back to start
+        let result = block_layout(&data, 100.0, AUTO, false, false, 0.0, false, 0, 9, &[], &[]);
+        assert_eq!(x(&result), [0.0, 20.0, 40.0]);
+
+        // No room at all: every line overflows and stays start-aligned
+        let result = block_layout(&data, 0.0, AUTO, false, false, 0.0, false, 0, TEXT_ALIGN_CENTER, &[], &[]);
+        assert_eq!(x(&result), [0.0, 0.0, 0.0]);
+        assert_eq!(y(&result), [0.0, 30.0, 60.0]);
+        let result = block_layout(&data, 0.0, AUTO, false, false, 0.0, true, 0, TEXT_ALIGN_CENTER, &[], &[]);
+        assert_eq!(x(&result), [-20.0, -20.0, -20.0]);
+
+        // Vertical modes align along the physical vertical inline axis
+        let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, false, WRITING_VERTICAL_RL, TEXT_ALIGN_CENTER, &[], &[]);
+        assert_eq!(x(&result), [280.0, 280.0, 280.0]);
+        assert_eq!(y(&result), [55.0, 85.0, 115.0]);
+        let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, false, WRITING_VERTICAL_LR, TEXT_ALIGN_END, &[], &[]);
+        assert_eq!(x(&result), [0.0, 0.0, 0.0]);
+        assert_eq!(y(&result), [110.0, 140.0, 170.0]);
+        // An auto inline size is the max-content one: nothing to distribute
+        let result = block_layout(&data, 300.0, AUTO, false, false, 0.0, false, WRITING_VERTICAL_RL, TEXT_ALIGN_CENTER, &[], &[]);
+        assert_eq!(y(&result), [0.0, 30.0, 60.0]);
+    }
+
+    #[test]
+    fn test_text_align_justify_rtl() {
+        let data = make_text();
+        let runs = r#"[{"text":"aa bb cc dd\nee ff","fontSize":20,"lineHeight":20,"whiteSpace":"pre-line"}]"#;
+
+        let result = block_layout_with_text(&data, runs, 100.0, AUTO, false, false, 0.0, true, 0, TEXT_ALIGN_JUSTIFY, &[], &[]);
+        let x: Vec<f32> = fragments(&result, 1).iter().map(|f| f[3]).collect();
+        // Mirror image of the ltr case: unjustified lines hug the right edge
+        assert_eq!(x, [80.0, 40.0, 0.0, 80.0, 80.0, 50.0]);
+    }
+
+    /// Parent font: size 30, ascent 24, descent 6, line height 36.
+    const FONT: [f32; 4] = [30.0, 24.0, 6.0, 36.0];
+
+    #[test]
+    fn test_vertical_align_keywords_shift_baseline() {
+        let mut data = Vec::new();
+        // 20px high, baseline 16
+        for valign in [VALIGN_BASELINE, VALIGN_SUPER, VALIGN_SUB, VALIGN_TEXT_TOP, VALIGN_TEXT_BOTTOM] {
+            data.extend(make_inline_block(20.0, 20.0, valign));
+        }
+        data.extend(make_block(AUTO, 10.0, 0.0, 0.0));
+
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &FONT, &[]);
+        let y: Vec<f32> = (0..5).map(|i| result[i * OUT_FIELDS + 1]).collect();
+        // Line baseline 26 (super: 16 + 10 above it). Sub lowers by 6,
+        // text-top puts the top 24px above the baseline, text-bottom the
+        // bottom 6px below it
+        assert_eq!(y, [10.0, 0.0, 16.0, 2.0, 12.0]);
+        assert_eq!(result[21], 36.0, "line box grows to the sub-shifted bottom");
+
+        // Without a parent font text-top / text-bottom hang from / stand
+        // on the baseline, which the latter pushes down to 20
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        let y: Vec<f32> = (0..5).map(|i| result[i * OUT_FIELDS + 1]).collect();
+        assert_eq!(y, [4.0, 4.0, 4.0, 20.0, 0.0]);
+    }
+
+    #[test]
+    fn test_vertical_align_lengths_and_text() {
+        let mut data = make_inline_block(20.0, 20.0, VALIGN_BASELINE);
+        let mut raised = make_inline_block(20.0, 20.0, VALIGN_LENGTH);
+        raised[29] = 5.0;
+        data.extend(raised.clone());
+        raised[29] = 50.0; // 50% of the parent line height
+        data.extend(with_percent(raised, &[29]));
+
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &FONT, &[]);
+        assert_eq!([result[1], result[5], result[9]], [18.0, 13.0, 0.0]);
+
+        // A superscript text run next to an icon: baseline 16 + 10
+        let mut data = make_inline_block(20.0, 20.0, VALIGN_BASELINE);
+        let mut text = make_text();
+        text[16] = VALIGN_SUPER as f32;
+        data.extend(text);
+        data.extend(make_block(AUTO, 10.0, 0.0, 0.0));
+        let runs = r#"[{"text":"x","fontSize":20,"lineHeight":20}]"#;
+        let result = block_layout_with_text(&data, runs, 400.0, AUTO, false, false, 0.0, false, 0, 0, &FONT, &[]);
+        assert_eq!(result[1], 10.0);
+        assert_eq!(fragments(&result, 3)[0][4], 0.0);
+        assert_eq!(result[9], 30.0);
+    }
+
+    #[test]
+    fn test_vertical_align_length_edge_cases() {
+        let with_length = |length: f32| {
+            let mut row = make_inline_block(20.0, 20.0, VALIGN_LENGTH);
+            row[29] = length;
+            row
+        };
+        let mut data = make_inline_block(20.0, 20.0, VALIGN_BASELINE);
+        data.extend(with_length(-5.0));
+        data.extend(with_length(f32::NAN));
+        data.extend(make_block(AUTO, 10.0, 0.0, 0.0));
+
+        // Lowered by 5: the line box grows below the baseline
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!([result[1], result[5], result[9]], [0.0, 5.0, 0.0], "NaN length: no shift");
+        assert_eq!(result[13], 25.0);
+
+        // Percentages: of the child's own line height when it has one, and
+        // nothing without a line height to refer to
+        let mut own = with_percent(with_length(50.0), &[29]);
+        own[18] = 20.0;
+        let mut data = make_inline_block(20.0, 20.0, VALIGN_BASELINE);
+        data.extend(own);
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &FONT, &[]);
+        assert_eq!([result[1], result[5]], [10.0, 0.0]);
+        let mut data = make_inline_block(20.0, 20.0, VALIGN_BASELINE);
+        data.extend(with_percent(with_length(50.0), &[29]));
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!([result[1], result[5]], [0.0, 0.0]);
+    }
+
+    #[test]
+    fn test_vertical_align_direction_and_writing_mode() {
+        let mut data = make_inline_block(20.0, 20.0, VALIGN_BASELINE);
+        data.extend(make_inline_block(20.0, 20.0, VALIGN_SUPER));
+
+        // rtl mirrors x only
+        let result = block_layout(&data, 400.0, AUTO, false, false, 0.0, true, 0, 0, &FONT, &[]);
+        assert_eq!([result[0], result[1], result[4], result[5]], [380.0, 10.0, 360.0, 0.0]);
+
+        // vertical-rl: the line-over side is the right, so super moves right
+        let result = block_layout(&data, 300.0, 200.0, false, false, 0.0, false, WRITING_VERTICAL_RL, 0, &FONT, &[]);
+        assert_eq!(&result[0..4], &[270.0, 0.0, 20.0, 20.0]);
+        assert_eq!(&result[4..8], &[280.0, 20.0, 20.0, 20.0]);
+    }
+
+    #[test]
+    fn test_block_layout_ignores_text_without_runs() {
+        let mut data = make_text();
+        data.extend(make_block(AUTO, 10.0, 0.0, 0.0));
+        let result = block_layout(&data, 400.0, 400.0, false, false, 0.0, false, 0, 0, &[], &[]);
+        assert_eq!(result.len(), 2 * OUT_FIELDS + META_FIELDS, "no fragment section");
+        assert_eq!(result[5], 0.0);
+    }
 }
@@ -0,0 +1,238 @@
+//! Floats: `float: left | right` and `clear` in `block_layout`.
+//!
+//! # Design decisions
+//! - Every `block_layout` call is the block formatting context of its own
+//!   floats: they are placed inside the container and never leak to its
+//!   siblings, nor do floats from an outer call intrude into it. A float's
+//!   margin box bottom is reported so a container that is a BFC root
+//!   (`bfc_flag`) can grow its auto height to contain them.
+//! - Placement follows CSS 2.1 §9.5.1 on margin boxes: a float goes as high
+//!   as it can, no higher than an earlier float, then as far left (right)
+//!   as it can. When it is wider than the room beside the floats already
+//!   there it moves down past the float that ends first.
+//! - Only the container's own line boxes are shortened. In-flow blocks are
+//!   placed across the floats as in CSS, but their content comes from a
+//!   separate call and does not wrap around them; BFC-root blocks are
+//!   narrowed (or moved down) to keep clear of them instead.
+//! - A band is probed with the height of what is being placed (the first
+//!   item of a line box), not the final line height.
+
+use crate::block_layout::FIT_EPSILON;
+
+// `float` field values
+pub(crate) const FLOAT_NONE: u8 = 0;
+pub(crate) const FLOAT_LEFT: u8 = 1;
+pub(crate) const FLOAT_RIGHT: u8 = 2;
+
+// `clear` field values
+#[allow(dead_code)]
+pub(crate) const CLEAR_NONE: u8 = 0;
+pub(crate) const CLEAR_LEFT: u8 = 1;
+pub(crate) const CLEAR_RIGHT: u8 = 2;
+pub(crate) const CLEAR_BOTH: u8 = 3;
+
+/// Margin box of a placed float.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct FloatBox {
+    pub left: bool,
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl FloatBox {
+    fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+
+    /// Whether the float overlaps the band `[y, y + height)`. A zero-height
+    /// band is the line at `y`.
+    fn overlaps(&self, y: f32, height: f32) -> bool {
+        self.height > 0.0 && self.y < y + height.max(FIT_EPSILON) && self.bottom() > y
+    }
+}
+
+/// A float met in the middle of a line box that did not fit beside it,
+/// waiting for the line to end.
+pub(crate) struct PendingFloat {
+    /// Index of the child's rect in the output array
+    pub out_index: usize,
+    pub left: bool,
+    pub clear: u8,
+    /// Margin box size
+    pub width: f32,
+    pub height: f32,
+    /// Offset of the border box inside the margin box
+    pub margin_left: f32,
+    pub margin_top: f32,
+}
+
+/// The floats of one block formatting context, in container coordinates.
+#[derive(Default)]
+pub(crate) struct Floats {
+    placed: Vec<FloatBox>,
+    pub pending: Vec<PendingFloat>,
+}
+
+impl Floats {
+    pub fn is_empty(&self) -> bool {
+        self.placed.is_empty()
+    }
+
+    /// Room left between the floats overlapping `[y, y + height)`:
+    /// `(left, right)` offsets within `[0, available]`.
+    pub fn span(&self, y: f32, height: f32, available: f32) -> (f32, f32) {
+        let (mut left, mut right) = (0.0f32, available);
+        for float in self.placed.iter().filter(|f| f.overlaps(y, height)) {
+            if float.left {
+                left = left.max(float.x + float.width);
+            } else {
+                right = right.min(float.x);
+            }
+        }
+        (left, right)
+    }
+
+    /// Top of the first band below `y` where something `width` wide and
+    /// `height` high fits beside the floats, with that band's room.
+    pub fn fit(&self, mut y: f32, width: f32, height: f32, available: f32) -> (f32, (f32, f32)) {
+        loop {
+            let span = self.span(y, height, available);
+            let next = self
+                .placed
+                .iter()
+                .filter(|f| f.overlaps(y, height))
+                .map(FloatBox::bottom)
+                .reduce(f32::min);
+            match next {
+                Some(bottom) if span.1 - span.0 + FIT_EPSILON < width => y = bottom,
+                _ => return (y, span),
+            }
+        }
+    }
+
+    /// Border edge a box with `clear` must start at or below, if any float
+    /// on a cleared side exists.
+    pub fn clearance(&self, clear: u8) -> Option<f32> {
+        let clears = |float: &&FloatBox| match clear {
+            CLEAR_LEFT => float.left,
+            CLEAR_RIGHT => !float.left,
+            CLEAR_BOTH => true,
+            _ => false,
+        };
+        self.placed.iter().filter(clears).map(FloatBox::bottom).reduce(f32::max)
+    }
+
+    /// Lowest margin box bottom of all floats (0 without floats).
+    pub fn bottom(&self) -> f32 {
+        self.placed.iter().map(FloatBox::bottom).fold(0.0, f32::max)
+    }
+
+    /// Place `float` at or below `min_y` and write its border box to `out`.
+    pub fn place(&mut self, float: &PendingFloat, min_y: f32, available: f32, out: &mut [f32]) -> FloatBox {
+        // Not above an earlier float, nor beside a cleared one
+        let mut y = self.placed.iter().map(|f| f.y).fold(min_y, f32::max);
+        if let Some(clear_y) = self.clearance(float.clear) {
+            y = y.max(clear_y);
+        }
+        let (y, (left, right)) = self.fit(y, float.width, float.height, available);
+        self.push(float, if float.left { left } else { right - float.width }, y, out)
+    }
+
+    /// Place `float` at `(x, y)` (margin box) without searching.
+    pub fn push(&mut self, float: &PendingFloat, x: f32, y: f32, out: &mut [f32]) -> FloatBox {
+        let placed = FloatBox { left: float.left, x, y, width: float.width, height: float.height };
+        out[float.out_index] = x + float.margin_left;
+        out[float.out_index + 1] = y + float.margin_top;
+        self.placed.push(placed);
+        placed
+    }
+
+    /// Place `float` at `y` beside a line box spanning `line` (left, right)
+    /// whose first `used` px are taken, if it fits there without going
+    /// above an earlier float or beside a cleared one.
+    pub fn place_beside_line(
+        &mut self,
+        float: &PendingFloat,
+        y: f32,
+        line: (f32, f32),
+        used: f32,
+        available: f32,
+        out: &mut [f32],
+    ) -> Option<FloatBox> {
+        if self.placed.iter().any(|f| f.y > y) || self.clearance(float.clear).is_some_and(|clear_y| clear_y > y) {
+            return None;
+        }
+        let (left, right) = self.span(y, float.height, available);
+        let (left, right) = (left.max(line.0), right.min(line.1));
+        if float.width > right - left - used + FIT_EPSILON {
+            return None;
+        }
+        Some(self.push(float, if float.left { left } else { right - float.width }, y, out))
+    }
+
+    /// Place the floats deferred to the end of a line box, below it.
+    pub fn place_pending(&mut self, min_y: f32, available: f32, out: &mut [f32]) {
+        for float in std::mem::take(&mut self.pending) {
+            self.place(&float, min_y, available, out);
+        }
+    }
+}
+
+/// Used inline size of a float's content box: its explicit width, or
+/// shrink-to-fit (content width, no wider than the container) for the
+/// negative `auto` / `fit-content` sentinels.
+pub(crate) fn shrink_to_fit(width: f32, content_width: f32, available: f32) -> f32 {
+    if width >= 0.0 {
+        width
+    } else {
+        content_width.min(available.max(0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn float(left: bool, width: f32, height: f32) -> PendingFloat {
+        PendingFloat { out_index: 0, left, clear: CLEAR_NONE, width, height, margin_left: 0.0, margin_top: 0.0 }
+    }
+
+    #[test]
+    fn test_floats_stack_sideways_then_move_down() {
+        let mut floats = Floats::default();
+        let mut out = [0.0; 4];
+        let a = floats.place(&float(true, 100.0, 50.0), 0.0, 300.0, &mut out);
+        let b = floats.place(&float(false, 100.0, 80.0), 0.0, 300.0, &mut out);
+        assert_eq!((a.x, a.y, b.x, b.y), (0.0, 0.0, 200.0, 0.0));
+
+        // 150px does not fit in the 100px between them: below the left one
+        let c = floats.place(&float(true, 150.0, 10.0), 0.0, 300.0, &mut out);
+        assert_eq!((c.x, c.y), (0.0, 50.0));
+        assert_eq!(floats.span(20.0, 10.0, 300.0), (100.0, 200.0));
+        assert_eq!(floats.span(55.0, 0.0, 300.0), (150.0, 200.0));
+        assert_eq!(floats.bottom(), 80.0);
+    }
+
+    #[test]
+    fn test_float_not_above_earlier_float() {
+        let mut floats = Floats::default();
+        let mut out = [0.0; 4];
+        floats.place(&float(true, 50.0, 20.0), 30.0, 300.0, &mut out);
+        let b = floats.place(&float(false, 50.0, 20.0), 0.0, 300.0, &mut out);
+        assert_eq!(b.y, 30.0);
+    }
+
+    #[test]
+    fn test_clearance_by_side() {
+        let mut floats = Floats::default();
+        let mut out = [0.0; 4];
+        floats.place(&float(true, 50.0, 40.0), 0.0, 300.0, &mut out);
+        floats.place(&float(false, 50.0, 70.0), 0.0, 300.0, &mut out);
+        assert_eq!(floats.clearance(CLEAR_LEFT), Some(40.0));
+        assert_eq!(floats.clearance(CLEAR_RIGHT), Some(70.0));
+        assert_eq!(floats.clearance(CLEAR_BOTH), Some(70.0));
+        assert_eq!(floats.clearance(CLEAR_NONE), None);
+    }
+}
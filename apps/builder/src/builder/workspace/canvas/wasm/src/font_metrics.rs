@@ -0,0 +1,468 @@
+//! Font metrics registry for in-WASM text measurement.
+//!
+//! The host uploads each font face's advance-width table and vertical
+//! metrics once (`register_font_metrics`); afterwards strings are measured
+//! in Rust without crossing the JS boundary.
+//!
+//! # Design decisions
+//! - One registry per WASM instance (thread-local; WASM is single-threaded)
+//!   so `TaffyLayoutEngine`, `block_layout` and free functions share it.
+//! - Values are stored in font units and scaled by `font_size / units_per_em`
+//!   at measurement time, so one table serves every font size.
+//! - A generation counter lets engines invalidate cached leaf measurements
+//!   when faces are (re)registered.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use wasm_bindgen::prelude::*;
+
+use crate::text_measure::{FontSpec, TextMeasurer};
+
+/// Layout of the `metrics` slice passed to `register_font_metrics`.
+/// Fields: units_per_em, ascent, descent, line_gap, [missing_advance]
+const METRICS_MIN_LEN: usize = 4;
+
+// ─── Per-face metrics ────────────────────────────────────────────────
+
+/// Advance widths and vertical metrics of one font face, in font units.
+#[derive(Clone, Debug)]
+pub struct FontMetrics {
+    units_per_em: f32,
+    /// Distance above the baseline (positive).
+    ascent: f32,
+    /// Distance below the baseline (positive).
+    descent: f32,
+    line_gap: f32,
+    /// Fast path for ASCII; `None` = not in the uploaded table.
+    ascii: [Option<f32>; 128],
+    other: HashMap<u32, f32>,
+    /// Advance used for code points missing from the table.
+    missing_advance: f32,
+}
+
+impl FontMetrics {
+    /// Build a face from parallel `codepoints` / `advances` arrays.
+    ///
+    /// `descent` may be given with either sign (hhea stores it negative).
+    /// Returns `None` for a non-positive `units_per_em`.
+    pub fn new(
+        units_per_em: f32,
+        ascent: f32,
+        descent: f32,
+        line_gap: f32,
+        codepoints: &[u32],
+        advances: &[f32],
+        missing_advance: Option<f32>,
+    ) -> Option<Self> {
+        if units_per_em.is_nan() || units_per_em <= 0.0 {
+            return None;
+        }
+        let mut ascii = [None; 128];
+        let mut other = HashMap::new();
+        for (&cp, &adv) in codepoints.iter().zip(advances) {
+            if cp < 128 {
+                ascii[cp as usize] = Some(adv);
+            } else {
+                other.insert(cp, adv);
+            }
+        }
+        Some(Self {
+            units_per_em,
+            ascent: ascent.abs(),
+            descent: descent.abs(),
+            line_gap: line_gap.max(0.0),
+            ascii,
+            other,
+            missing_advance: missing_advance.unwrap_or(units_per_em * 0.5),
+        })
+    }
+
+    /// Advance of a single character in font units.
+    fn advance_units(&self, ch: char) -> f32 {
+        let cp = ch as u32;
+        let found = if cp < 128 {
+            self.ascii[cp as usize]
+        } else {
+            self.other.get(&cp).copied()
+        };
+        found.unwrap_or(if is_wide_char(ch) {
+            self.units_per_em
+        } else {
+            self.missing_advance
+        })
+    }
+
+    #[inline]
+    fn scale(&self, font_size: f32) -> f32 {
+        font_size / self.units_per_em
+    }
+
+    /// Advance width of `text` at `font_size` px.
+    pub fn measure(&self, text: &str, font_size: f32) -> f32 {
+        let units: f32 = text.chars().map(|ch| self.advance_units(ch)).sum();
+        units * self.scale(font_size)
+    }
+
+    /// Ascent in px at `font_size`.
+    pub fn ascent(&self, font_size: f32) -> f32 {
+        self.ascent * self.scale(font_size)
+    }
+
+    /// Descent in px at `font_size` (positive, below the baseline).
+    pub fn descent(&self, font_size: f32) -> f32 {
+        self.descent * self.scale(font_size)
+    }
+
+    /// `line-height: normal` in px: ascent + descent + line gap.
+    pub fn normal_line_height(&self, font_size: f32) -> f32 {
+        (self.ascent + self.descent + self.line_gap) * self.scale(font_size)
+    }
+}
+
+/// East Asian wide characters (CJK ideographs, kana, hangul, fullwidth forms)
+/// default to a 1em advance when absent from the uploaded table.
+pub(crate) fn is_wide_char(ch: char) -> bool {
+    matches!(ch as u32,
+        0x1100..=0x115F       // Hangul Jamo
+        | 0x2E80..=0x303E     // CJK radicals, punctuation
+        | 0x3041..=0x33FF     // Kana, CJK symbols
+        | 0x3400..=0x4DBF     // CJK Ext A
+        | 0x4E00..=0x9FFF     // CJK Unified Ideographs
+        | 0xA960..=0xA97F     // Hangul Jamo Ext A
+        | 0xAC00..=0xD7A3     // Hangul syllables
+        | 0xF900..=0xFAFF     // CJK compatibility ideographs
+        | 0xFE30..=0xFE4F     // CJK compatibility forms
+        | 0xFF00..=0xFF60     // Fullwidth forms
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD)  // CJK Ext B+
+}
+
+// ─── Registry ────────────────────────────────────────────────────────
+
+/// Registered faces keyed by lower-cased family name, then weight.
+#[derive(Default)]
+pub struct FontMetricsRegistry {
+    faces: HashMap<String, Vec<(u16, FontMetrics)>>,
+    generation: u64,
+}
+
+impl FontMetricsRegistry {
+    pub fn insert(&mut self, family: &str, weight: u16, metrics: FontMetrics) {
+        let faces = self.faces.entry(family.trim().to_lowercase()).or_default();
+        match faces.iter_mut().find(|(w, _)| *w == weight) {
+            Some(slot) => slot.1 = metrics,
+            None => faces.push((weight, metrics)),
+        }
+        self.generation += 1;
+    }
+
+    pub fn remove(&mut self, family: &str, weight: u16) -> bool {
+        let key = family.trim().to_lowercase();
+        let Some(faces) = self.faces.get_mut(&key) else {
+            return false;
+        };
+        let before = faces.len();
+        faces.retain(|(w, _)| *w != weight);
+        let removed = faces.len() != before;
+        if faces.is_empty() {
+            self.faces.remove(&key);
+        }
+        if removed {
+            self.generation += 1;
+        }
+        removed
+    }
+
+    pub fn clear(&mut self) {
+        if !self.faces.is_empty() {
+            self.faces.clear();
+            self.generation += 1;
+        }
+    }
+
+    /// Bumped on every change; used by engines to invalidate measurements.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Find the face for `family`, matching weight as CSS Fonts §5.2 does:
+    /// the exact weight, else for 400–500 the weights up to 500 (ascending),
+    /// then lighter ones (descending), then heavier ones (ascending); below
+    /// 400 lighter first, then heavier; above 500 heavier first, then
+    /// lighter.
+    ///
+    /// `family` may be a CSS font-family list; the first registered entry wins.
+    pub fn lookup(&self, family: &str, weight: u16) -> Option<&FontMetrics> {
+        family
+            .split(',')
+            .map(|f| f.trim().trim_matches(|c| c == '"' || c == '\'').to_lowercase())
+            .find_map(|key| self.faces.get(&key))
+            .and_then(|faces| faces.iter().min_by_key(|(w, _)| weight_rank(weight, *w)).map(|(_, m)| m))
+    }
+}
+
+/// Search order of the face weight `available` for a `desired` weight
+/// (lower is better): (pass, distance) per CSS Fonts §5.2.
+fn weight_rank(desired: u16, available: u16) -> (u8, u16) {
+    let distance = desired.abs_diff(available);
+    let pass = if available == desired {
+        0
+    } else if (400..=500).contains(&desired) {
+        match available {
+            w if w > desired && w <= 500 => 1,
+            w if w < desired => 2,
+            _ => 3,
+        }
+    } else if (desired < 400) == (available < desired) {
+        1
+    } else {
+        2
+    };
+    (pass, distance)
+}
+
+thread_local! {
+    static REGISTRY: RefCell<FontMetricsRegistry> = RefCell::new(FontMetricsRegistry::default());
+}
+
+/// Run `f` with shared access to the instance-wide registry.
+pub fn with_registry<R>(f: impl FnOnce(&FontMetricsRegistry) -> R) -> R {
+    REGISTRY.with(|r| f(&r.borrow()))
+}
+
+fn with_registry_mut<R>(f: impl FnOnce(&mut FontMetricsRegistry) -> R) -> R {
+    REGISTRY.with(|r| f(&mut r.borrow_mut()))
+}
+
+// ─── Measurer ────────────────────────────────────────────────────────
+
+/// Measures with registered metrics, delegating unknown faces to `fallback`.
+pub struct MetricsTextMeasurer {
+    pub fallback: Box<dyn TextMeasurer>,
+}
+
+impl TextMeasurer for MetricsTextMeasurer {
+    fn measure_width(&self, text: &str, font: &FontSpec) -> f32 {
+        with_registry(|reg| {
+            reg.lookup(&font.font_family, font.font_weight)
+                .map(|m| m.measure(text, font.font_size))
+        })
+        .unwrap_or_else(|| self.fallback.measure_width(text, font))
+    }
+
+    fn normal_line_height(&self, font: &FontSpec) -> f32 {
+        with_registry(|reg| {
+            reg.lookup(&font.font_family, font.font_weight)
+                .map(|m| m.normal_line_height(font.font_size))
+        })
+        .unwrap_or_else(|| self.fallback.normal_line_height(font))
+    }
+
+    fn vertical_metrics(&self, font: &FontSpec) -> (f32, f32) {
+        with_registry(|reg| {
+            reg.lookup(&font.font_family, font.font_weight)
+                .map(|m| (m.ascent(font.font_size), m.descent(font.font_size)))
+        })
+        .unwrap_or_else(|| self.fallback.vertical_metrics(font))
+    }
+}
+
+// ─── WASM exports ────────────────────────────────────────────────────
+
+/// Register (or replace) the metrics of one font face.
+///
+/// # Arguments
+/// * `family` - CSS family name (case-insensitive)
+/// * `weight` - CSS font weight (100–900)
+/// * `metrics` - [unitsPerEm, ascent, descent, lineGap, missingAdvance?] in font units
+/// * `codepoints` - Unicode scalar values covered by the table
+/// * `advances` - advance width per code point, in font units
+///
+/// # Returns
+/// `false` if `metrics` is too short or `unitsPerEm` is not positive.
+#[wasm_bindgen]
+pub fn register_font_metrics(
+    family: &str,
+    weight: u16,
+    metrics: &[f32],
+    codepoints: &[u32],
+    advances: &[f32],
+) -> bool {
+    if metrics.len() < METRICS_MIN_LEN {
+        return false;
+    }
+    let Some(face) = FontMetrics::new(
+        metrics[0],
+        metrics[1],
+        metrics[2],
+        metrics[3],
+        codepoints,
+        advances,
+        metrics.get(4).copied(),
+    ) else {
+        return false;
+    };
+    with_registry_mut(|reg| reg.insert(family, weight, face));
+    true
+}
+
+/// Remove one registered face. Returns whether it existed.
+#[wasm_bindgen]
+pub fn unregister_font_metrics(family: &str, weight: u16) -> bool {
+    with_registry_mut(|reg| reg.remove(family, weight))
+}
+
+/// Drop every registered face.
+#[wasm_bindgen]
+pub fn clear_font_metrics() {
+    with_registry_mut(|reg| reg.clear());
+}
+
+/// Whether a face matching `family` (any weight) is registered.
+#[wasm_bindgen]
+pub fn has_font_metrics(family: &str) -> bool {
+    with_registry(|reg| reg.lookup(family, 400).is_some())
+}
+
+/// Width of `text` in px using registered metrics, or -1 if the family is unknown.
+#[wasm_bindgen]
+pub fn measure_text_width(text: &str, family: &str, font_size: f32, weight: u16) -> f32 {
+    with_registry(|reg| {
+        reg.lookup(family, weight)
+            .map_or(-1.0, |m| m.measure(text, font_size))
+    })
+}
+
+/// Vertical metrics in px: [ascent, descent, normalLineHeight], or empty if unknown.
+#[wasm_bindgen]
+pub fn font_vertical_metrics(family: &str, font_size: f32, weight: u16) -> Box<[f32]> {
+    with_registry(|reg| {
+        reg.lookup(family, weight).map_or_else(Vec::new, |m| {
+            vec![
+                m.ascent(font_size),
+                m.descent(font_size),
+                m.normal_line_height(font_size),
+            ]
+        })
+    })
+    .into_boxed_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1000 upem face: 'a' = 500, 'b' = 600, ' ' = 250.
+    fn register_test_face(family: &str, weight: u16) {
+        assert!(register_font_metrics(
+            family,
+            weight,
+            &[1000.0, 800.0, -200.0, 0.0],
+            &['a' as u32, 'b' as u32, ' ' as u32],
+            &[500.0, 600.0, 250.0],
+        ));
+    }
+
+    #[test]
+    fn test_measure_registered_face() {
+        register_test_face("TestSans", 400);
+        // (500 + 600 + 250 + 500) / 1000 × 20px = 37
+        let w = measure_text_width("ab a", "TestSans", 20.0, 400);
+        assert!((w - 37.0).abs() < 1e-4, "got {w}");
+        assert_eq!(measure_text_width("ab", "Unknown", 20.0, 400), -1.0);
+    }
+
+    #[test]
+    fn test_missing_glyph_advances() {
+        register_test_face("TestSans", 400);
+        // Missing latin → 0.5em, missing CJK → 1em
+        assert_eq!(measure_text_width("z", "TestSans", 10.0, 400), 5.0);
+        assert_eq!(measure_text_width("漢", "TestSans", 10.0, 400), 10.0);
+    }
+
+    #[test]
+    fn test_vertical_metrics() {
+        register_test_face("TestSans", 400);
+        let v = font_vertical_metrics("TestSans", 10.0, 400);
+        assert_eq!(&*v, &[8.0, 2.0, 10.0]);
+        assert!(font_vertical_metrics("Unknown", 10.0, 400).is_empty());
+    }
+
+    #[test]
+    fn test_weight_matching_and_family_list() {
+        register_test_face("TestSans", 400);
+        assert!(register_font_metrics(
+            "TestSans",
+            700,
+            &[1000.0, 800.0, 200.0, 0.0],
+            &['a' as u32],
+            &[700.0],
+        ));
+        // 600 > 500 → prefer bolder face (700)
+        assert_eq!(measure_text_width("a", "TestSans", 10.0, 600), 7.0);
+        // 300 < 400 → nothing lighter, so the next heavier face (400)
+        assert_eq!(measure_text_width("a", "TestSans", 10.0, 300), 5.0);
+        // First registered family in a CSS list wins, case-insensitive
+        assert_eq!(measure_text_width("a", "'Missing', testsans, serif", 10.0, 400), 5.0);
+    }
+
+    #[test]
+    fn test_weight_matching_search_order() {
+        // Faces tell their weight through the ascent
+        let registry = |weights: &[u16]| {
+            let mut registry = FontMetricsRegistry::default();
+            for &w in weights {
+                registry.insert("Face", w, FontMetrics::new(1000.0, w as f32, 0.0, 0.0, &[], &[], None).unwrap());
+            }
+            registry
+        };
+        let matched = |registry: &FontMetricsRegistry, weight: u16| registry.lookup("Face", weight).unwrap().ascent(1000.0) as u16;
+
+        // Below 400: lighter faces first, even when a heavier one is nearer
+        let light = registry(&[100, 400]);
+        assert_eq!(matched(&light, 300), 100);
+        assert_eq!(matched(&light, 350), 100);
+        assert_eq!(matched(&light, 400), 400);
+
+        // 400–500: heavier up to 500, then lighter, then above 500
+        let regular = registry(&[300, 480, 600]);
+        assert_eq!(matched(&regular, 450), 480);
+        assert_eq!(matched(&regular, 500), 480, "then lighter, nearest first");
+        assert_eq!(matched(&registry(&[300, 600]), 450), 300);
+        assert_eq!(matched(&registry(&[600, 900]), 450), 600);
+
+        // Above 500: heavier faces first
+        assert_eq!(matched(&registry(&[500, 900]), 600), 900);
+        assert_eq!(matched(&registry(&[300, 500]), 600), 500);
+    }
+
+    #[test]
+    fn test_generation_and_unregister() {
+        let g0 = with_registry(|r| r.generation());
+        register_test_face("Gen", 400);
+        assert!(with_registry(|r| r.generation()) > g0);
+        assert!(unregister_font_metrics("Gen", 400));
+        assert!(!has_font_metrics("Gen"));
+        assert!(!unregister_font_metrics("Gen", 400));
+    }
+
+    #[test]
+    fn test_metrics_measurer_falls_back() {
+        use crate::text_measure::FallbackTextMeasurer;
+
+        register_test_face("TestSans", 400);
+        let measurer = MetricsTextMeasurer { fallback: Box::new(FallbackTextMeasurer) };
+        let known = FontSpec { font_family: "TestSans".into(), font_size: 10.0, font_weight: 400 };
+        let unknown = FontSpec { font_family: "Other".into(), font_size: 10.0, font_weight: 400 };
+        assert_eq!(measurer.measure_width("ab", &known), 11.0);
+        assert_eq!(measurer.measure_width("ab", &unknown), 10.0, "fallback: 2 × 10 × 0.5");
+        assert_eq!(measurer.normal_line_height(&known), 10.0);
+    }
+
+    #[test]
+    fn test_rejects_invalid_metrics() {
+        assert!(!register_font_metrics("Bad", 400, &[1000.0, 800.0], &[], &[]));
+        assert!(!register_font_metrics("Bad", 400, &[0.0, 800.0, 200.0, 0.0], &[], &[]));
+    }
+}
@@ -0,0 +1,417 @@
+//! `TaffyLayoutEngine`'s engine on the shared layout tree
+//! (`composition_core::tree`): text content leaves and tables.
+//!
+//! # Design decisions
+//! - Everything else — storage, flows and writing modes, relative units,
+//!   subgrid, masonry, baselines — lives in the shared tree, so the two
+//!   engines lay out the same way. What stays here depends on the browser
+//!   (text measurement through registered font metrics or a JS callback) or
+//!   on styles only this engine parses (`display: table-*`).
+//! - Tables: a node whose `TableStyle` role is `Table` is laid out by
+//!   `compute_table` (see `table`), which sizes and places its rows, row
+//!   groups, cells and captions itself; rows and groups never go through
+//!   `compute_child_layout`. Cells are laid out as the block (or leaf)
+//!   boxes they are in `style`.
+
+use composition_core::calc::resolve_calc;
+use composition_core::tree::Engine;
+use taffy::prelude::*;
+use taffy::{
+    compute_hidden_layout, CacheTree, LayoutInput, LayoutOutput, LayoutPartialTree, MaybeResolve, Point,
+    RequestedAxis, ResolveOrZero, RunMode, SizingMode,
+};
+
+use crate::font_metrics::MetricsTextMeasurer;
+use crate::table::{self, CellSlot, TableLayout, TableRole, TableStyle};
+use crate::text_measure::{measure_leaf, FallbackTextMeasurer, LeafContent};
+
+pub use composition_core::tree::{Direction, Flow, WritingMode};
+
+/// Layout tree backing `TaffyLayoutEngine`.
+pub type LayoutTree = composition_core::tree::LayoutTree<WasmEngine>;
+
+pub struct WasmEngine {
+    /// Measures text runs of content leaves during `compute_layout`.
+    /// Registered font metrics take precedence over the fallback measurer.
+    pub measurer: MetricsTextMeasurer,
+}
+
+impl Default for WasmEngine {
+    fn default() -> Self {
+        Self { measurer: MetricsTextMeasurer { fallback: Box::new(FallbackTextMeasurer) } }
+    }
+}
+
+/// What this engine keeps per node.
+#[derive(Default)]
+pub struct NodeExt {
+    content: Option<LeafContent>,
+    /// Table role and properties (`display: table-*`).
+    table: TableStyle,
+}
+
+impl Engine for WasmEngine {
+    type Node = NodeExt;
+
+    fn measure(
+        &self,
+        node: &NodeExt,
+        known: Size<Option<f32>>,
+        available: Size<AvailableSpace>,
+    ) -> (Size<f32>, Option<(f32, f32)>) {
+        match &node.content {
+            Some(content) => {
+                let measured = measure_leaf(known, available, content, &self.measurer);
+                (measured.size, measured.baselines)
+            }
+            None => (Size::ZERO, None),
+        }
+    }
+
+    fn compute_container(tree: &mut LayoutTree, id: NodeId, inputs: LayoutInput) -> Option<LayoutOutput> {
+        (tree.data(id).table.role == TableRole::Table).then(|| compute_table(tree, id, inputs))
+    }
+}
+
+/// Content leaves and table styles on `LayoutTree`.
+pub trait LayoutTreeExt {
+    fn new_leaf_with_content(&mut self, style: Style, content: LeafContent) -> NodeId;
+    fn content(&self, id: NodeId) -> Option<&LeafContent>;
+    fn set_content(&mut self, id: NodeId, content: Option<LeafContent>);
+    /// Set the table role and properties of a node.
+    fn set_table(&mut self, id: NodeId, table: TableStyle);
+}
+
+impl LayoutTreeExt for LayoutTree {
+    fn new_leaf_with_content(&mut self, style: Style, content: LeafContent) -> NodeId {
+        self.new_leaf_with_data(style, NodeExt { content: Some(content), ..NodeExt::default() })
+    }
+
+    fn content(&self, id: NodeId) -> Option<&LeafContent> {
+        self.data(id).content.as_ref()
+    }
+
+    fn set_content(&mut self, id: NodeId, content: Option<LeafContent>) {
+        self.update_data(id, |data| data.content = content);
+    }
+
+    fn set_table(&mut self, id: NodeId, table: TableStyle) {
+        if self.data(id).table != table {
+            self.update_data(id, |data| data.table = table);
+        }
+    }
+}
+
+/// Table layout (see `table`): slot the cells, size the columns from
+/// the cells' content widths, then the rows from the cells laid out at
+/// those widths.
+fn compute_table(tree: &mut LayoutTree, id: NodeId, inputs: LayoutInput) -> LayoutOutput {
+    let style = tree.node(id).container_style().clone();
+    let TableStyle { layout, spacing, .. } = tree.data(id).table;
+
+    // Captions, and sections of rows: headers, bodies, footers
+    let mut captions = Vec::new();
+    let (mut headers, mut bodies, mut footers) = (Vec::new(), Vec::new(), Vec::new());
+    for child in table_parts(tree, id) {
+        match tree.data(child).table.role {
+            TableRole::Caption => captions.push(child),
+            TableRole::HeaderGroup => headers.push((Some(child), table_parts(tree, child))),
+            TableRole::FooterGroup => footers.push((Some(child), table_parts(tree, child))),
+            TableRole::RowGroup => bodies.push((Some(child), table_parts(tree, child))),
+            _ => match bodies.last_mut() {
+                Some((None, rows)) => rows.push(child),
+                _ => bodies.push((None, vec![child])),
+            },
+        }
+    }
+    let sections: Vec<(Option<NodeId>, Vec<NodeId>)> =
+        headers.into_iter().chain(bodies).chain(footers).collect();
+    let mut rows = Vec::new();
+    let mut section_end = Vec::new();
+    for (_, section_rows) in &sections {
+        rows.extend(section_rows);
+        section_end.extend(std::iter::repeat_n(rows.len(), section_rows.len()));
+    }
+    let cells: Vec<Vec<NodeId>> = rows.iter().map(|&row| table_parts(tree, row)).collect();
+    let spans: Vec<Vec<(u16, u16)>> = cells
+        .iter()
+        .map(|row| row.iter().map(|&c| (tree.data(c).table.col_span, tree.data(c).table.row_span)).collect())
+        .collect();
+    let (slots, column_count) = table::place_cells(&spans, &section_end);
+
+    let inset = style.padding.resolve_or_zero(inputs.parent_size.width, resolve_calc)
+        + style.border.resolve_or_zero(inputs.parent_size.width, resolve_calc);
+    let (inset_h, inset_v) = (inset.left + inset.right, inset.top + inset.bottom);
+    let outer_spacing = |count: usize, gap: f32| if count == 0 { 0.0 } else { gap * (count + 1) as f32 };
+    let spacing_h = outer_spacing(column_count, spacing.width);
+    let spacing_v = outer_spacing(rows.len(), spacing.height);
+    let specified = match inputs.sizing_mode {
+        SizingMode::InherentSize => table::definite_size(&style, inputs.parent_size),
+        SizingMode::ContentSize => Size::NONE,
+    };
+    let definite_width = inputs.known_dimensions.width.or(specified.width);
+
+    // Column widths
+    let columns = match definite_width {
+        Some(width) if layout == TableLayout::Fixed => {
+            let available = width - inset_h - spacing_h;
+            let mut first_row = vec![None; column_count];
+            for (slot, &cell) in slots.first().into_iter().flatten().zip(cells.first().into_iter().flatten()) {
+                let cell_size = table::definite_size(tree.node(cell).item_style(), Size { width: Some(available), height: None });
+                if let Some(cell_width) = cell_size.width {
+                    let share = (cell_width - spacing.width * (slot.col_span - 1) as f32) / slot.col_span as f32;
+                    first_row[slot.column..slot.column + slot.col_span].fill(Some(share));
+                }
+            }
+            table::distribute_fixed(&first_row, available)
+        }
+        _ => {
+            let mut measured = Vec::new();
+            for (row_slots, row_cells) in slots.iter().zip(&cells) {
+                for (&slot, &cell) in row_slots.iter().zip(row_cells) {
+                    let min = measure_table_cell(tree, cell, AvailableSpace::MinContent);
+                    let max = measure_table_cell(tree, cell, AvailableSpace::MaxContent);
+                    measured.push((slot, min, max));
+                }
+            }
+            let bounds = table::column_bounds(column_count, &measured, spacing.width);
+            let min = bounds.iter().map(|c| c.min).sum::<f32>() + spacing_h;
+            let max = bounds.iter().map(|c| c.max).sum::<f32>() + spacing_h;
+            let width = match (definite_width, inputs.available_space.width) {
+                (Some(width), _) => width - inset_h,
+                (None, AvailableSpace::Definite(available)) => (available - inset_h).min(max).max(min),
+                (None, AvailableSpace::MinContent) => min,
+                (None, AvailableSpace::MaxContent) => max,
+            };
+            table::distribute_auto(&bounds, width - spacing_h)
+        }
+    };
+    let grid_width = columns.iter().sum::<f32>() + spacing_h;
+    let span_width = |slot: &CellSlot| {
+        columns[slot.column..slot.column + slot.col_span].iter().sum::<f32>()
+            + spacing.width * (slot.col_span - 1) as f32
+    };
+
+    // Row heights
+    let mut heights: Vec<f32> = rows
+        .iter()
+        .map(|&row| tree.node(row).item_style().size.height.maybe_resolve(None, resolve_calc).unwrap_or(0.0))
+        .collect();
+    let mut spanning = Vec::new();
+    for (row_slots, row_cells) in slots.iter().zip(&cells) {
+        for (slot, &cell) in row_slots.iter().zip(row_cells) {
+            let size = Size { width: Some(span_width(slot)), height: None };
+            let height = layout_table_part(tree, cell, size, grid_width, RunMode::ComputeSize).height;
+            if slot.row_span == 1 {
+                heights[slot.row] = heights[slot.row].max(height);
+            } else {
+                spanning.push((*slot, height));
+            }
+        }
+    }
+    for (slot, height) in spanning {
+        let spanned = &mut heights[slot.row..slot.row + slot.row_span];
+        let current = spanned.iter().sum::<f32>() + spacing.height * (slot.row_span - 1) as f32;
+        table::grow_evenly(spanned, height - current);
+    }
+
+    let mut caption_heights = Vec::with_capacity(captions.len());
+    for &caption in &captions {
+        let margin = tree.node(caption).item_style().margin.resolve_or_zero(Some(grid_width), resolve_calc);
+        let size = Size { width: Some(grid_width - margin.left - margin.right), height: None };
+        let height = layout_table_part(tree, caption, size, grid_width, RunMode::ComputeSize).height;
+        caption_heights.push(height + margin.top + margin.bottom);
+    }
+    let content_height = |heights: &[f32]| {
+        caption_heights.iter().sum::<f32>() + heights.iter().sum::<f32>() + spacing_v
+    };
+    let definite_height = inputs.known_dimensions.height.or(specified.height);
+    if let Some(height) = definite_height {
+        let extra = height - inset_v - content_height(&heights);
+        table::grow_evenly(&mut heights, extra);
+    }
+    let size = Size {
+        width: inputs.known_dimensions.width.unwrap_or(grid_width + inset_h).max(definite_width.unwrap_or(0.0)),
+        height: inputs
+            .known_dimensions
+            .height
+            .unwrap_or_else(|| (content_height(&heights) + inset_v).max(definite_height.unwrap_or(0.0))),
+    };
+    if inputs.run_mode != RunMode::PerformLayout {
+        return LayoutOutput::from_outer_size(size);
+    }
+
+    // Placement, in the table's layout space
+    let column_x: Vec<f32> = columns
+        .iter()
+        .scan(inset.left + spacing.width, |x, width| {
+            let start = *x;
+            *x += width + spacing.width;
+            Some(start)
+        })
+        .collect();
+    let grid_x = inset.left + spacing.width;
+    let inner_width = (grid_width - 2.0 * spacing.width).max(0.0);
+    let mut y = inset.top;
+    for (k, (&caption, &height)) in captions.iter().zip(&caption_heights).enumerate() {
+        if !tree.data(caption).table.caption_bottom {
+            place_table_caption(tree, caption, inset.left, y, grid_width, k as u32);
+            y += height;
+        }
+    }
+    let row_y: Vec<f32> = heights
+        .iter()
+        .scan(y + spacing.height, |row_y, height| {
+            let start = *row_y;
+            *row_y += height + spacing.height;
+            Some(start)
+        })
+        .collect();
+    let grid_end = y + heights.iter().sum::<f32>() + spacing_v;
+
+    let mut order = captions.len() as u32;
+    let mut first_row = 0;
+    for (group, section_rows) in sections {
+        let section = first_row..first_row + section_rows.len();
+        let top = row_y
+            .get(section.start)
+            .copied()
+            .unwrap_or(if rows.is_empty() { y } else { grid_end - spacing.height });
+        let bottom = section.clone().last().map_or(top, |last| row_y[last] + heights[last]);
+        let origin = match group {
+            Some(group) => {
+                let size = Size { width: inner_width, height: bottom - top };
+                set_table_part_layout(tree, group, order, Point { x: grid_x, y: top }, size, grid_width);
+                order += 1;
+                Point { x: grid_x, y: top }
+            }
+            None => Point::ZERO,
+        };
+        for (k, (&row, r)) in section_rows.iter().zip(section.clone()).enumerate() {
+            for (c, (slot, &cell)) in slots[r].iter().zip(&cells[r]).enumerate() {
+                let height = heights[r..r + slot.row_span].iter().sum::<f32>()
+                    + spacing.height * (slot.row_span - 1) as f32;
+                let size = Size { width: span_width(slot), height };
+                let known = Size { width: Some(size.width), height: Some(size.height) };
+                layout_table_part(tree, cell, known, grid_width, RunMode::PerformLayout);
+                let location = Point { x: column_x[slot.column] - grid_x, y: 0.0 };
+                set_table_part_layout(tree, cell, c as u32, location, size, grid_width);
+            }
+            let location = Point { x: grid_x - origin.x, y: row_y[r] - origin.y };
+            let size = Size { width: inner_width, height: heights[r] };
+            let row_order = if group.is_some() { k as u32 } else { order };
+            set_table_part_layout(tree, row, row_order, location, size, grid_width);
+            if group.is_none() {
+                order += 1;
+            }
+            if !tree.node(row).frame().is_physical() {
+                tree.place_children(row, size);
+            }
+        }
+        if let Some(group) = group {
+            if !tree.node(group).frame().is_physical() {
+                tree.place_children(group, Size { width: inner_width, height: bottom - top });
+            }
+        }
+        first_row = section.end;
+    }
+
+    y = grid_end;
+    for (k, (&caption, &height)) in captions.iter().zip(&caption_heights).enumerate() {
+        if tree.data(caption).table.caption_bottom {
+            place_table_caption(tree, caption, inset.left, y, grid_width, k as u32);
+            y += height;
+        }
+    }
+    LayoutOutput::from_outer_size(size)
+}
+
+/// Displayed children of a table part; `display: none` ones are laid
+/// out hidden.
+fn table_parts(tree: &mut LayoutTree, id: NodeId) -> Vec<NodeId> {
+    let children = tree.node(id).children().to_vec();
+    let (shown, hidden): (Vec<_>, Vec<_>) =
+        children.into_iter().partition(|&c| tree.node(c).style().display != Display::None);
+    for child in hidden {
+        compute_hidden_layout(tree, child);
+    }
+    shown
+}
+
+/// Min-content or max-content border-box width of a table cell.
+fn measure_table_cell(tree: &mut LayoutTree, id: NodeId, available: AvailableSpace) -> f32 {
+    let inputs = LayoutInput {
+        run_mode: RunMode::ComputeSize,
+        sizing_mode: SizingMode::InherentSize,
+        axis: RequestedAxis::Horizontal,
+        known_dimensions: Size::NONE,
+        parent_size: Size::NONE,
+        available_space: Size { width: available, height: AvailableSpace::MaxContent },
+        vertical_margins_are_collapsible: Line::FALSE,
+    };
+    tree.compute_child_layout(id, inputs).size.width
+}
+
+/// Size (or lay out) a cell or caption at a known border-box width, and
+/// height when given, in a table grid `grid_width` wide.
+fn layout_table_part(
+    tree: &mut LayoutTree,
+    id: NodeId,
+    known: Size<Option<f32>>,
+    grid_width: f32,
+    run_mode: RunMode,
+) -> Size<f32> {
+    let inputs = LayoutInput {
+        run_mode,
+        sizing_mode: SizingMode::InherentSize,
+        axis: if run_mode == RunMode::PerformLayout { RequestedAxis::Both } else { RequestedAxis::Vertical },
+        known_dimensions: known,
+        parent_size: Size { width: Some(grid_width), height: None },
+        available_space: Size {
+            width: AvailableSpace::Definite(known.width.unwrap_or(grid_width)),
+            height: AvailableSpace::MaxContent,
+        },
+        vertical_margins_are_collapsible: Line::FALSE,
+    };
+    tree.compute_child_layout(id, inputs).size
+}
+
+/// Lay out a caption across the grid at (`x`, `y`), its margin box's
+/// top-left corner.
+fn place_table_caption(tree: &mut LayoutTree, id: NodeId, x: f32, y: f32, grid_width: f32, order: u32) {
+    let margin = tree.node(id).item_style().margin.resolve_or_zero(Some(grid_width), resolve_calc);
+    let width = grid_width - margin.left - margin.right;
+    let known = Size { width: Some(width), height: None };
+    let size = layout_table_part(tree, id, known, grid_width, RunMode::PerformLayout);
+    let location = Point { x: x + margin.left, y: y + margin.top };
+    set_table_part_layout(tree, id, order, location, size, grid_width);
+}
+
+/// Record the layout of a row group, row, cell or caption. Rows and
+/// groups also get a cache entry, so that dirtying a cell still
+/// propagates up to the table.
+fn set_table_part_layout(tree: &mut LayoutTree, id: NodeId, order: u32, location: Point<f32>, size: Size<f32>, grid_width: f32) {
+    let style = tree.node(id).item_style();
+    let layout = Layout {
+        location,
+        size,
+        padding: style.padding.resolve_or_zero(Some(grid_width), resolve_calc),
+        border: style.border.resolve_or_zero(Some(grid_width), resolve_calc),
+        margin: style.margin.resolve_or_zero(Some(grid_width), resolve_calc),
+        ..Layout::with_order(order)
+    };
+    tree.set_unrounded_layout(id, &layout);
+    let role = tree.data(id).table.role;
+    if matches!(role, TableRole::Row) || role.is_row_group() {
+        let inputs = LayoutInput {
+            run_mode: RunMode::PerformLayout,
+            sizing_mode: SizingMode::InherentSize,
+            axis: RequestedAxis::Both,
+            known_dimensions: Size { width: Some(size.width), height: Some(size.height) },
+            parent_size: Size::NONE,
+            available_space: Size::MAX_CONTENT,
+            vertical_margins_are_collapsible: Line::FALSE,
+        };
+        tree.cache_store(id, &inputs, LayoutOutput::from_outer_size(size));
+    }
+}
@@ -2,9 +2,16 @@ use wasm_bindgen::prelude::*;
 
 pub mod spatial_index;
 pub mod block_layout;
+pub mod floats;
+pub mod text_layout;
 pub mod grid_layout;
 pub mod taffy_bridge;
+pub mod layout_tree;
+pub mod text_measure;
+pub mod font_metrics;
 pub mod binary_protocol;
+pub mod multicol;
+pub mod table;
 
 pub use spatial_index::SpatialIndex;
 pub use taffy_bridge::TaffyLayoutEngine;
@@ -0,0 +1,509 @@
+//! Multi-column layout: `column-count` / `column-width`.
+//!
+//! Splits the block content of a multi-column container into columns of
+//! equal width, with `column-gap` between them, `column-rule` geometry and
+//! `column-span: all` children spanning every column.
+//!
+//! # Design decisions
+//! - The column content is laid out by `block_layout` at the column width
+//!   as one tall column, so vertical stacking, margin collapse and line
+//!   boxes are exactly those of a block container. The result is then cut
+//!   into columns: block children and line boxes are monolithic, a break
+//!   only falls where no child rect or text fragment crosses it.
+//! - Margins adjoining a column break are truncated: content after a break
+//!   starts at the top of its column. The first column keeps the leading
+//!   margin and the last one the trailing margin, as in a block container
+//!   that establishes a BFC.
+//! - `column-fill: balance` searches for the smallest column height that
+//!   fits the content in the used column count, capped by the available
+//!   height when it is definite. `column-fill: auto` fills each column up
+//!   to the available height and behaves as `balance` without one.
+//! - Content that does not fit in the used column count goes to overflow
+//!   columns continuing in the inline direction, past the container edge.
+//! - `column-span: all` children split the content into column rows; each
+//!   run of spanners is laid out at the container width between them.
+//!   Margins do not collapse between spanners and column rows.
+//! - Percentages resolve against the column width (the container width for
+//!   spanners); percentage heights behave as auto.
+//! - Column rules are only reported between two columns that have content,
+//!   centred in the gap. Only horizontal-tb containers are supported, and
+//!   line boxes are start-aligned without a parent font (`vertical-align`
+//!   values relative to it do not shift).
+
+use wasm_bindgen::prelude::*;
+
+use crate::block_layout::{
+    layout_children, PaddingBox, ParentFont, AUTO, DISPLAY_TEXT, FIELD_COUNT, FRAGMENT_FIELDS, META_FIELDS, OUT_FIELDS,
+    TEXT_ALIGN_START,
+};
+use crate::text_measure::TextContent;
+
+/// Output fields per column box / column rule: x, y, width, height
+const RECT_FIELDS: usize = 4;
+
+// column-fill
+#[allow(dead_code)]
+const FILL_BALANCE: u8 = 0;
+const FILL_AUTO: u8 = 1;
+
+/// Iterations of the balancing search (column height bisection).
+const BALANCE_ITERATIONS: usize = 32;
+
+/// Tolerance for float accumulation when a unit fits a column height.
+const FIT_EPSILON: f32 = 0.01;
+
+/// Used column count and width for an `available` content width
+/// (CSS Multi-column §3.4). `count` / `width` are AUTO when `auto`.
+pub fn resolve_columns(available: f32, count: f32, width: f32, gap: f32) -> (usize, f32) {
+    let available = available.max(0.0);
+    let count = if count >= 1.0 { Some(count.floor() as usize) } else { None };
+    let used = if width > 0.0 {
+        let fit = (((available + gap) / (width + gap)).floor() as usize).max(1);
+        count.map_or(fit, |count| count.min(fit))
+    } else {
+        count.unwrap_or(1)
+    };
+    let column_width = ((available - (used - 1) as f32 * gap) / used as f32).max(0.0);
+    (used, column_width)
+}
+
+/// Lay out the children of a multi-column container.
+///
+/// # Arguments
+/// * `data` - Flat Float32Array with FIELD_COUNT fields per child, as in
+///   `block_layout`
+/// * `text_runs_json` - JSON array of text runs consumed by the
+///   `DISPLAY_TEXT` children, as in `block_layout_with_text`
+/// * `spans` - 1 per child with `column-span: all` (missing = 0)
+/// * `available_width` - Container content width
+/// * `available_height` - Container content height, AUTO when indefinite
+/// * `column_count` / `column_width` - AUTO for `auto`
+/// * `column_gap` - Used `column-gap` in px
+/// * `column_rule_width` - Used `column-rule-width` in px, 0 for no rule
+/// * `column_fill` - 0 = balance, 1 = auto
+/// * `rtl` - Container has `direction: rtl`: columns run from the right edge
+///
+/// # Returns
+/// Float32Array: [x, y, w, h, ...] for each child, then 3 values
+/// [contentHeight, columnBoxCount, ruleCount], then x, y, w, h per column
+/// box and per rule, then FRAGMENT_FIELDS values per text fragment.
+#[wasm_bindgen]
+#[allow(clippy::too_many_arguments)]
+pub fn multicol_layout(
+    data: &[f32],
+    text_runs_json: &str,
+    spans: &[u8],
+    available_width: f32,
+    available_height: f32,
+    column_count: f32,
+    column_width: f32,
+    column_gap: f32,
+    column_rule_width: f32,
+    column_fill: u8,
+    rtl: bool,
+) -> Box<[f32]> {
+    let runs: Vec<TextContent> = serde_json::from_str(text_runs_json).unwrap_or_default();
+    let (count, width) = resolve_columns(available_width, column_count, column_width, column_gap);
+    let mut flow = Flow {
+        columns: Columns { count, width, gap: column_gap, container: available_width, rtl },
+        rects: vec![0.0; data.len() / FIELD_COUNT * OUT_FIELDS],
+        boxes: Vec::new(),
+        rules: Vec::new(),
+        fragments: Vec::new(),
+        y: 0.0,
+    };
+
+    let child_count = data.len() / FIELD_COUNT;
+    let is_spanner = |i: usize| spans.get(i).is_some_and(|&span| span == 1);
+    let mut next_run = 0;
+    let mut start = 0;
+    while start < child_count {
+        let spanner = is_spanner(start);
+        let end = (start..child_count).find(|&i| is_spanner(i) != spanner).unwrap_or(child_count);
+        let segment = &data[start * FIELD_COUNT..end * FIELD_COUNT];
+        let text_count = segment.chunks_exact(FIELD_COUNT).filter(|row| row[0] as u8 == DISPLAY_TEXT).count();
+        let segment_runs = &runs[next_run.min(runs.len())..(next_run + text_count).min(runs.len())];
+        next_run += text_count;
+
+        if spanner {
+            flow.span_all(segment, segment_runs, start, rtl);
+        } else {
+            let remaining = (available_height != AUTO).then(|| (available_height - flow.y).max(0.0));
+            flow.column_row(segment, segment_runs, start, remaining, column_fill, column_rule_width);
+        }
+        start = end;
+    }
+
+    let mut out = flow.rects;
+    out.extend([flow.y, (flow.boxes.len() / RECT_FIELDS) as f32, (flow.rules.len() / RECT_FIELDS) as f32]);
+    out.extend(flow.boxes);
+    out.extend(flow.rules);
+    out.extend(flow.fragments);
+    out.into_boxed_slice()
+}
+
+/// Used columns of the container.
+struct Columns {
+    count: usize,
+    width: f32,
+    gap: f32,
+    container: f32,
+    rtl: bool,
+}
+
+impl Columns {
+    /// Offset of column `index` (overflow columns included) from the
+    /// content-box left edge.
+    fn x(&self, index: usize) -> f32 {
+        let start = index as f32 * (self.width + self.gap);
+        if self.rtl { self.container - start - self.width } else { start }
+    }
+}
+
+/// Output accumulated while the column rows and spanners are stacked.
+struct Flow {
+    columns: Columns,
+    rects: Vec<f32>,
+    boxes: Vec<f32>,
+    rules: Vec<f32>,
+    fragments: Vec<f32>,
+    /// Block offset where the next row or spanner starts
+    y: f32,
+}
+
+impl Flow {
+    /// A run of `column-span: all` children starting at child `first`.
+    fn span_all(&mut self, segment: &[f32], runs: &[TextContent], first: usize, rtl: bool) {
+        let laid = layout_children(segment, runs, self.columns.container, false, true, 0.0, rtl, TEXT_ALIGN_START, ParentFont::NONE, PaddingBox::NONE);
+        let n = segment.len() / FIELD_COUNT;
+        let mut bottom: f32 = 0.0;
+        for (k, rect) in laid[..n * OUT_FIELDS].chunks_exact(OUT_FIELDS).enumerate() {
+            let off = (first + k) * OUT_FIELDS;
+            self.rects[off..off + OUT_FIELDS].copy_from_slice(&[rect[0], rect[1] + self.y, rect[2], rect[3]]);
+            bottom = bottom.max(rect[1] + rect[3]);
+        }
+        for frag in laid[n * OUT_FIELDS + META_FIELDS..].chunks_exact(FRAGMENT_FIELDS) {
+            let mut frag = frag.to_vec();
+            frag[0] += first as f32;
+            frag[4] += self.y;
+            self.fragments.extend(frag);
+        }
+        self.y += bottom + laid[n * OUT_FIELDS + 1];
+    }
+
+    /// A run of column content starting at child `first`, filled into the
+    /// columns. `remaining` is the definite height left for it.
+    fn column_row(
+        &mut self,
+        segment: &[f32],
+        runs: &[TextContent],
+        first: usize,
+        remaining: Option<f32>,
+        fill: u8,
+        rule_width: f32,
+    ) {
+        let columns = &self.columns;
+        let laid = layout_children(segment, runs, columns.width, false, true, 0.0, columns.rtl, TEXT_ALIGN_START, ParentFont::NONE, PaddingBox::NONE);
+        let n = segment.len() / FIELD_COUNT;
+        let trailing_margin = laid[n * OUT_FIELDS + 1];
+        let units = break_units(&laid, segment);
+
+        let limit = match remaining {
+            Some(remaining) if fill == FILL_AUTO => remaining,
+            Some(remaining) => balance(&units, columns.count).min(remaining),
+            None => balance(&units, columns.count),
+        };
+        let placement = fill_columns(&units, limit);
+        let used = placement.last().map_or(0, |&(column, _)| column + 1);
+
+        // Children without units (text without runs) keep their place
+        for (k, rect) in laid[..n * OUT_FIELDS].chunks_exact(OUT_FIELDS).enumerate() {
+            let off = (first + k) * OUT_FIELDS;
+            self.rects[off..off + OUT_FIELDS].copy_from_slice(&[rect[0], rect[1] + self.y, rect[2], rect[3]]);
+        }
+        let mut fragments: Vec<f32> = laid[n * OUT_FIELDS + META_FIELDS..].to_vec();
+        for frag in fragments.chunks_exact_mut(FRAGMENT_FIELDS) {
+            frag[4] += self.y;
+        }
+        let mut column_ends = vec![0.0f32; used];
+        for (unit, &(column, start)) in units.iter().zip(&placement) {
+            for piece in &unit.pieces {
+                let rect = match *piece {
+                    Piece::Child(k) => &mut self.rects[(first + k) * OUT_FIELDS..(first + k + 1) * OUT_FIELDS],
+                    Piece::Fragment(k) => &mut fragments[k * FRAGMENT_FIELDS + 3..(k + 1) * FRAGMENT_FIELDS],
+                };
+                rect[0] += columns.x(column);
+                rect[1] -= start;
+            }
+            column_ends[column] = column_ends[column].max(unit.bottom - start);
+        }
+        if let Some(last) = column_ends.last_mut() {
+            *last += trailing_margin;
+        }
+
+        // Text child rect = union of its (relocated) fragments
+        let mut text_rects: Vec<Option<[f32; 4]>> = vec![None; n];
+        for frag in fragments.chunks_exact_mut(FRAGMENT_FIELDS) {
+            let k = frag[0] as usize;
+            let (x, y, r, b) = (frag[3], frag[4], frag[3] + frag[5], frag[4] + frag[6]);
+            text_rects[k] = Some(match text_rects[k] {
+                Some([x0, y0, r0, b0]) => [x0.min(x), y0.min(y), r0.max(r), b0.max(b)],
+                None => [x, y, r, b],
+            });
+            frag[0] += first as f32;
+        }
+        for (k, rect) in text_rects.into_iter().enumerate() {
+            if let Some([x0, y0, r0, b0]) = rect {
+                let off = (first + k) * OUT_FIELDS;
+                self.rects[off..off + OUT_FIELDS].copy_from_slice(&[x0, y0, r0 - x0, b0 - y0]);
+            }
+        }
+        self.fragments.extend(fragments);
+
+        let content_height = column_ends.iter().copied().fold(0.0, f32::max);
+        let height = match remaining {
+            Some(remaining) if fill == FILL_AUTO => remaining.max(content_height),
+            _ => content_height,
+        };
+        for column in 0..used.max(columns.count) {
+            self.boxes.extend([columns.x(column), self.y, columns.width, height]);
+        }
+        if rule_width > 0.0 {
+            for column in 1..used {
+                let gap_start = if columns.rtl { columns.x(column) + columns.width } else { columns.x(column) - columns.gap };
+                let center = gap_start + columns.gap / 2.0;
+                self.rules.extend([center - rule_width / 2.0, self.y, rule_width, height]);
+            }
+        }
+        self.y += height;
+    }
+}
+
+/// Child rect or text fragment of the single-column layout.
+enum Piece {
+    /// Index of a non-text child in the segment
+    Child(usize),
+    /// Index of a text fragment in the segment output
+    Fragment(usize),
+}
+
+/// Pieces that no column break can separate: their rects overlap in the
+/// block axis.
+struct Unit {
+    top: f32,
+    bottom: f32,
+    pieces: Vec<Piece>,
+}
+
+/// Group the single-column layout `laid` of `segment` into units, in
+/// block order.
+fn break_units(laid: &[f32], segment: &[f32]) -> Vec<Unit> {
+    let n = segment.len() / FIELD_COUNT;
+    let mut pieces: Vec<(f32, f32, Piece)> = Vec::new();
+    for (k, row) in segment.chunks_exact(FIELD_COUNT).enumerate() {
+        if row[0] as u8 != DISPLAY_TEXT {
+            let rect = &laid[k * OUT_FIELDS..(k + 1) * OUT_FIELDS];
+            pieces.push((rect[1], rect[1] + rect[3], Piece::Child(k)));
+        }
+    }
+    for (k, frag) in laid[n * OUT_FIELDS + META_FIELDS..].chunks_exact(FRAGMENT_FIELDS).enumerate() {
+        pieces.push((frag[4], frag[4] + frag[6], Piece::Fragment(k)));
+    }
+    pieces.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut units: Vec<Unit> = Vec::new();
+    for (top, bottom, piece) in pieces {
+        match units.last_mut() {
+            Some(unit) if top < unit.bottom => {
+                unit.bottom = unit.bottom.max(bottom);
+                unit.pieces.push(piece);
+            }
+            _ => units.push(Unit { top, bottom, pieces: vec![piece] }),
+        }
+    }
+    units
+}
+
+/// Column index and column start (block offset in the single-column
+/// layout) of each unit when columns are filled up to `height`. A column
+/// always takes at least one unit.
+fn fill_columns(units: &[Unit], height: f32) -> Vec<(usize, f32)> {
+    let mut placement = Vec::with_capacity(units.len());
+    let (mut column, mut start, mut filled) = (0, 0.0, false);
+    for unit in units {
+        if filled && unit.bottom - start > height + FIT_EPSILON {
+            column += 1;
+            start = unit.top;
+        }
+        placement.push((column, start));
+        filled = true;
+    }
+    placement
+}
+
+/// Smallest column height that fits `units` in `count` columns.
+fn balance(units: &[Unit], count: usize) -> f32 {
+    let end = units.iter().fold(0.0, |end: f32, unit| end.max(unit.bottom));
+    if count <= 1 {
+        return end;
+    }
+    let columns_used = |height: f32| fill_columns(units, height).last().map_or(0, |&(column, _)| column + 1);
+    let (mut low, mut high) = (0.0, end);
+    for _ in 0..BALANCE_ITERATIONS {
+        let mid = (low + high) / 2.0;
+        if columns_used(mid) <= count {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    high
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_layout::INSET_AUTO;
+
+    fn block(height: f32, m_top: f32, m_bottom: f32) -> Vec<f32> {
+        vec![
+            0.0, AUTO, height, m_top, 0.0, m_bottom, 0.0, 0.0, 0.0, 0.0,
+            AUTO, AUTO, AUTO, AUTO, 0.0, height, 0.0, 0.0, AUTO, 0.0, 0.0,
+            0.0, INSET_AUTO, INSET_AUTO, INSET_AUTO, INSET_AUTO, 0.0, 0.0, 0.0, 0.0,
+        ]
+    }
+
+    fn blocks(heights: &[f32]) -> Vec<f32> {
+        heights.iter().flat_map(|&h| block(h, 0.0, 0.0)).collect()
+    }
+
+    fn rect(out: &[f32], i: usize) -> &[f32] {
+        &out[i * OUT_FIELDS..(i + 1) * OUT_FIELDS]
+    }
+
+    /// (contentHeight, column boxes, rules)
+    fn meta(out: &[f32], child_count: usize) -> (f32, Vec<&[f32]>, Vec<&[f32]>) {
+        let off = child_count * OUT_FIELDS;
+        let (boxes, rules) = (out[off + 1] as usize, out[off + 2] as usize);
+        let rects = &out[off + 3..];
+        (
+            out[off],
+            rects[..boxes * RECT_FIELDS].chunks_exact(RECT_FIELDS).collect(),
+            rects[boxes * RECT_FIELDS..(boxes + rules) * RECT_FIELDS].chunks_exact(RECT_FIELDS).collect(),
+        )
+    }
+
+    #[test]
+    fn test_resolve_columns() {
+        assert_eq!(resolve_columns(320.0, 3.0, AUTO, 10.0), (3, 100.0));
+        // column-width: 100px fits floor(360 / 110) = 3 columns, widened to fill
+        assert_eq!(resolve_columns(350.0, AUTO, 100.0, 10.0), (3, 110.0));
+        // column-count caps the columns that fit
+        assert_eq!(resolve_columns(350.0, 2.0, 100.0, 10.0), (2, 170.0));
+        // narrower than one column: a single column of the available width
+        assert_eq!(resolve_columns(80.0, AUTO, 100.0, 10.0), (1, 80.0));
+        assert_eq!(resolve_columns(300.0, AUTO, AUTO, 10.0), (1, 300.0));
+    }
+
+    #[test]
+    fn test_balanced_columns() {
+        let data = blocks(&[50.0; 6]);
+        let out = multicol_layout(&data, "[]", &[], 320.0, AUTO, 3.0, AUTO, 10.0, 0.0, FILL_BALANCE, false);
+        for i in 0..6 {
+            let column = (i / 2) as f32;
+            assert_eq!(rect(&out, i), [column * 110.0, (i % 2) as f32 * 50.0, 100.0, 50.0], "child {i}");
+        }
+        let (height, boxes, rules) = meta(&out, 6);
+        assert_eq!(height, 100.0);
+        assert_eq!(boxes, [[0.0, 0.0, 100.0, 100.0], [110.0, 0.0, 100.0, 100.0], [220.0, 0.0, 100.0, 100.0]]);
+        assert!(rules.is_empty());
+    }
+
+    #[test]
+    fn test_margins_truncated_at_breaks() {
+        // 20px margins collapse between the blocks, and are dropped where
+        // the columns break
+        let data: Vec<f32> = (0..4).flat_map(|_| block(40.0, 20.0, 20.0)).collect();
+        let out = multicol_layout(&data, "[]", &[], 210.0, AUTO, 2.0, AUTO, 10.0, 0.0, FILL_BALANCE, false);
+        assert_eq!(rect(&out, 0)[1], 20.0);
+        assert_eq!(rect(&out, 1)[1], 80.0);
+        assert_eq!(rect(&out, 2), [110.0, 0.0, 100.0, 40.0]);
+        assert_eq!(rect(&out, 3), [110.0, 60.0, 100.0, 40.0]);
+        // the first column, with its leading margin, is the tallest
+        assert_eq!(meta(&out, 4).0, 120.0);
+    }
+
+    #[test]
+    fn test_column_span_all() {
+        let data = blocks(&[30.0, 30.0, 20.0, 40.0, 40.0]);
+        let spans = [0, 0, 1, 0, 0];
+        let out = multicol_layout(&data, "[]", &spans, 210.0, AUTO, 2.0, AUTO, 10.0, 2.0, FILL_BALANCE, false);
+        assert_eq!(rect(&out, 0), [0.0, 0.0, 100.0, 30.0]);
+        assert_eq!(rect(&out, 1), [110.0, 0.0, 100.0, 30.0]);
+        assert_eq!(rect(&out, 2), [0.0, 30.0, 210.0, 20.0]);
+        assert_eq!(rect(&out, 3), [0.0, 50.0, 100.0, 40.0]);
+        assert_eq!(rect(&out, 4), [110.0, 50.0, 100.0, 40.0]);
+
+        let (height, boxes, rules) = meta(&out, 5);
+        assert_eq!(height, 90.0);
+        assert_eq!(boxes.len(), 4);
+        assert_eq!(boxes[2], [0.0, 50.0, 100.0, 40.0]);
+        // one rule per row, centred in the 10px gap
+        assert_eq!(rules, [[104.0, 0.0, 2.0, 30.0], [104.0, 50.0, 2.0, 40.0]]);
+    }
+
+    #[test]
+    fn test_rules_only_between_columns_with_content() {
+        let data = blocks(&[50.0]);
+        let out = multicol_layout(&data, "[]", &[], 320.0, AUTO, 3.0, AUTO, 10.0, 1.0, FILL_BALANCE, false);
+        let (_, boxes, rules) = meta(&out, 1);
+        assert_eq!(boxes.len(), 3);
+        assert!(rules.is_empty());
+    }
+
+    #[test]
+    fn test_fill_auto_overflow_columns() {
+        let data = blocks(&[50.0; 5]);
+        let out = multicol_layout(&data, "[]", &[], 210.0, 100.0, 2.0, AUTO, 10.0, 0.0, FILL_AUTO, false);
+        assert_eq!(rect(&out, 1), [0.0, 50.0, 100.0, 50.0]);
+        assert_eq!(rect(&out, 3), [110.0, 50.0, 100.0, 50.0]);
+        // the fifth block overflows into a third column past the edge
+        assert_eq!(rect(&out, 4), [220.0, 0.0, 100.0, 50.0]);
+        let (height, boxes, _) = meta(&out, 5);
+        assert_eq!(height, 100.0);
+        assert_eq!(boxes.len(), 3);
+
+        // balance under a definite height still balances when it fits
+        let out = multicol_layout(&data[..4 * FIELD_COUNT], "[]", &[], 210.0, 300.0, 2.0, AUTO, 10.0, 0.0, FILL_BALANCE, false);
+        assert_eq!(rect(&out, 2), [110.0, 0.0, 100.0, 50.0]);
+        assert_eq!(meta(&out, 4).0, 100.0);
+    }
+
+    #[test]
+    fn test_rtl_columns_run_from_the_right() {
+        let data = blocks(&[50.0; 4]);
+        let out = multicol_layout(&data, "[]", &[], 210.0, AUTO, 2.0, AUTO, 10.0, 2.0, FILL_BALANCE, true);
+        assert_eq!(rect(&out, 0), [110.0, 0.0, 100.0, 50.0]);
+        assert_eq!(rect(&out, 2), [0.0, 0.0, 100.0, 50.0]);
+        let (_, boxes, rules) = meta(&out, 4);
+        assert_eq!(boxes[0], [110.0, 0.0, 100.0, 100.0]);
+        assert_eq!(rules, [[104.0, 0.0, 2.0, 100.0]]);
+    }
+
+    #[test]
+    fn test_text_lines_flow_across_columns() {
+        let mut data = vec![0.0; FIELD_COUNT];
+        data[0] = DISPLAY_TEXT as f32;
+        let runs = r#"[{"text":"aaaa bbbb cccc dddd","fontSize":10,"lineHeight":20}]"#;
+        let single = multicol_layout(&data, runs, &[], 30.0, AUTO, 1.0, AUTO, 0.0, 0.0, FILL_BALANCE, false);
+        let lines = meta(&single, 1).0 / 20.0;
+        assert!(lines >= 2.0);
+
+        let out = multicol_layout(&data, runs, &[], 70.0, AUTO, 2.0, AUTO, 10.0, 0.0, FILL_BALANCE, false);
+        let off = OUT_FIELDS + 3 + out[OUT_FIELDS + 1] as usize * RECT_FIELDS;
+        let fragments: Vec<&[f32]> = out[off..].chunks_exact(FRAGMENT_FIELDS).collect();
+        assert!(fragments.iter().any(|f| f[3] >= 40.0), "some lines move to the second column");
+        assert!(fragments.iter().all(|f| f[0] == 0.0 && f[4] + f[6] <= meta(&out, 1).0));
+        assert_eq!(meta(&out, 1).0, (lines / 2.0).ceil() * 20.0);
+    }
+}
@@ -0,0 +1,319 @@
+//! Table formatting context: `display: table` and its internal boxes.
+//!
+//! Taffy has no table layout. Table nodes are `Display::Block` in their
+//! `taffy::Style` (with `item_is_table`, so block parents shrink-to-fit
+//! them) and carry a `TableStyle` beside it; the layout tree lays out a
+//! node whose role is `Table` with this module's algorithms.
+//!
+//! # Design decisions
+//! - Anonymous table boxes are not generated. Children of a table are
+//!   captions, row groups or else rows; children of a row group are rows
+//!   and children of a row are cells, whatever their `display`.
+//! - Header groups go first and footer groups last, other rows and groups
+//!   keep document order. Consecutive rows outside a group form one
+//!   section for `rowspan` purposes; `rowspan: 0` spans to its end.
+//! - Cells are slotted into the first free column of their row, skipping
+//!   slots taken by `rowspan`s from above (HTML table model).
+//! - `table-layout: auto` uses the CSS 2.1 §17.5.2.2 column bounds: the
+//!   min-content / max-content widths of single-column cells, then the
+//!   excess of spanning cells spread evenly over their columns. The table
+//!   width lerps columns between their bounds, and grows past the
+//!   max-content widths in proportion to them.
+//! - `table-layout: fixed` takes column widths from the definite widths of
+//!   the first row's cells and splits the rest evenly; without a definite
+//!   table width it falls back to `auto`.
+//! - Row heights are the tallest single-row cell (or the row's definite
+//!   height); a taller `rowspan` cell, and a taller table, grow the rows
+//!   evenly. Cells are stretched to their slots; their content is not
+//!   aligned by `vertical-align`.
+//! - `border-spacing` separates cells and the table edge
+//!   (`border-collapse: separate`). Captions take the grid width, above or
+//!   below it per `caption-side`, inside the table's content box.
+
+use taffy::prelude::*;
+use taffy::{BoxSizing, MaybeResolve, ResolveOrZero};
+
+use composition_core::calc::resolve_calc;
+
+/// Table-internal `display` of a node.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TableRole {
+    #[default]
+    None,
+    Table,
+    HeaderGroup,
+    RowGroup,
+    FooterGroup,
+    Row,
+    Cell,
+    Caption,
+}
+
+impl TableRole {
+    pub fn is_row_group(self) -> bool {
+        matches!(self, TableRole::HeaderGroup | TableRole::RowGroup | TableRole::FooterGroup)
+    }
+}
+
+/// `table-layout`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TableLayout {
+    #[default]
+    Auto,
+    Fixed,
+}
+
+/// Table properties of a node that `taffy::Style` has no room for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TableStyle {
+    pub role: TableRole,
+    /// `table-layout` (tables)
+    pub layout: TableLayout,
+    /// `border-spacing`: horizontal / vertical (tables)
+    pub spacing: Size<f32>,
+    /// `caption-side: bottom` (captions)
+    pub caption_bottom: bool,
+    /// `colspan` / `rowspan` (cells); `row_span` 0 spans to the section end
+    pub col_span: u16,
+    pub row_span: u16,
+}
+
+impl Default for TableStyle {
+    fn default() -> Self {
+        Self {
+            role: TableRole::None,
+            layout: TableLayout::Auto,
+            spacing: Size::ZERO,
+            caption_bottom: false,
+            col_span: 1,
+            row_span: 1,
+        }
+    }
+}
+
+/// Table role of a CSS `display` value; `None` for non-table values.
+pub fn parse_table_display(s: &str) -> Option<TableRole> {
+    Some(match s {
+        "table" | "inline-table" => TableRole::Table,
+        "table-header-group" => TableRole::HeaderGroup,
+        "table-row-group" => TableRole::RowGroup,
+        "table-footer-group" => TableRole::FooterGroup,
+        "table-row" => TableRole::Row,
+        "table-cell" => TableRole::Cell,
+        "table-caption" => TableRole::Caption,
+        _ => return None,
+    })
+}
+
+/// `border-spacing`: one length for both axes or horizontal then vertical.
+/// Only px (or unitless) lengths are understood.
+pub fn parse_border_spacing(s: &str) -> Size<f32> {
+    let px = |v: &str| v.trim_end_matches("px").parse::<f32>().ok().filter(|v| *v >= 0.0);
+    let mut parts = s.split_whitespace().map(px);
+    match (parts.next().flatten(), parts.next().flatten()) {
+        (Some(h), Some(v)) => Size { width: h, height: v },
+        (Some(both), None) => Size { width: both, height: both },
+        _ => Size::ZERO,
+    }
+}
+
+/// Slot of a cell in the table grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellSlot {
+    pub row: usize,
+    pub column: usize,
+    pub col_span: usize,
+    pub row_span: usize,
+}
+
+/// Slot the cells of each row, given as their (colspan, rowspan), into the
+/// grid. `section_end[r]` is the row after the last of row `r`'s section.
+/// Returns the slots per row and the column count.
+pub fn place_cells(rows: &[Vec<(u16, u16)>], section_end: &[usize]) -> (Vec<Vec<CellSlot>>, usize) {
+    // occupied[r]: columns of row r taken by rowspans from rows above
+    let mut occupied: Vec<Vec<bool>> = vec![Vec::new(); rows.len()];
+    let mut columns = 0;
+    let mut slots = Vec::with_capacity(rows.len());
+    for (r, cells) in rows.iter().enumerate() {
+        let mut row_slots = Vec::with_capacity(cells.len());
+        let mut column = 0;
+        for &(col_span, row_span) in cells {
+            while occupied[r].get(column).copied().unwrap_or(false) {
+                column += 1;
+            }
+            let col_span = col_span.max(1) as usize;
+            let end = section_end[r].max(r + 1);
+            let row_span = if row_span == 0 { end - r } else { (row_span as usize).min(end - r) };
+            for taken in &mut occupied[r..r + row_span] {
+                if taken.len() < column + col_span {
+                    taken.resize(column + col_span, false);
+                }
+                taken[column..column + col_span].fill(true);
+            }
+            row_slots.push(CellSlot { row: r, column, col_span, row_span });
+            column += col_span;
+            columns = columns.max(column);
+        }
+        slots.push(row_slots);
+    }
+    (slots, columns)
+}
+
+/// Min-content / max-content bounds of a column.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ColumnBounds {
+    pub min: f32,
+    pub max: f32,
+}
+
+/// Column bounds from cells given as (slot, min-content, max-content
+/// border-box widths); `spacing` separates spanned columns.
+pub fn column_bounds(columns: usize, cells: &[(CellSlot, f32, f32)], spacing: f32) -> Vec<ColumnBounds> {
+    let mut bounds = vec![ColumnBounds::default(); columns];
+    let mut spanning: Vec<&(CellSlot, f32, f32)> = Vec::new();
+    for cell in cells {
+        let (slot, min, max) = *cell;
+        if slot.col_span == 1 {
+            let column = &mut bounds[slot.column];
+            column.min = column.min.max(min);
+            column.max = column.max.max(max);
+        } else {
+            spanning.push(cell);
+        }
+    }
+    spanning.sort_by_key(|(slot, ..)| slot.col_span);
+    for &(slot, min, max) in spanning {
+        let spanned = &mut bounds[slot.column..slot.column + slot.col_span];
+        let inner_spacing = spacing * (slot.col_span - 1) as f32;
+        let spread = |excess: f32| excess.max(0.0) / slot.col_span as f32;
+        let min_excess = spread(min - inner_spacing - spanned.iter().map(|c| c.min).sum::<f32>());
+        let max_excess = spread(max - inner_spacing - spanned.iter().map(|c| c.max).sum::<f32>());
+        for column in spanned {
+            column.min += min_excess;
+            column.max += max_excess;
+        }
+    }
+    for column in &mut bounds {
+        column.max = column.max.max(column.min);
+    }
+    bounds
+}
+
+/// `table-layout: auto` column widths filling `width` (spacing excluded).
+/// Columns never shrink below their min-content width.
+pub fn distribute_auto(bounds: &[ColumnBounds], width: f32) -> Vec<f32> {
+    let min: f32 = bounds.iter().map(|c| c.min).sum();
+    let max: f32 = bounds.iter().map(|c| c.max).sum();
+    if width <= min {
+        bounds.iter().map(|c| c.min).collect()
+    } else if width <= max {
+        let t = (width - min) / (max - min);
+        bounds.iter().map(|c| c.min + (c.max - c.min) * t).collect()
+    } else if max > 0.0 {
+        bounds.iter().map(|c| c.max * width / max).collect()
+    } else {
+        vec![width / bounds.len().max(1) as f32; bounds.len()]
+    }
+}
+
+/// `table-layout: fixed` column widths filling `width` (spacing excluded)
+/// from the specified widths of the first row's columns.
+pub fn distribute_fixed(specified: &[Option<f32>], width: f32) -> Vec<f32> {
+    let fixed: f32 = specified.iter().flatten().sum();
+    let auto = specified.iter().filter(|w| w.is_none()).count();
+    let rest = (width - fixed).max(0.0);
+    if auto > 0 {
+        specified.iter().map(|w| w.unwrap_or(rest / auto as f32)).collect()
+    } else if fixed > 0.0 {
+        specified.iter().map(|w| w.unwrap_or(0.0) * (fixed + rest) / fixed).collect()
+    } else {
+        vec![rest / specified.len().max(1) as f32; specified.len()]
+    }
+}
+
+/// Spread `extra` evenly over `sizes`.
+pub fn grow_evenly(sizes: &mut [f32], extra: f32) {
+    if extra > 0.0 && !sizes.is_empty() {
+        let share = extra / sizes.len() as f32;
+        sizes.iter_mut().for_each(|size| *size += share);
+    }
+}
+
+/// Definite border-box size of a box, resolving percentages against
+/// `parent_size`.
+pub fn definite_size(style: &Style, parent_size: Size<Option<f32>>) -> Size<Option<f32>> {
+    let padding = style.padding.resolve_or_zero(parent_size.width, resolve_calc);
+    let border = style.border.resolve_or_zero(parent_size.width, resolve_calc);
+    let inset = match style.box_sizing {
+        BoxSizing::ContentBox => Size {
+            width: padding.left + padding.right + border.left + border.right,
+            height: padding.top + padding.bottom + border.top + border.bottom,
+        },
+        BoxSizing::BorderBox => Size::ZERO,
+    };
+    Size {
+        width: style.size.width.maybe_resolve(parent_size.width, resolve_calc).map(|w| w + inset.width),
+        height: style.size.height.maybe_resolve(parent_size.height, resolve_calc).map(|h| h + inset.height),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(row: usize, column: usize, col_span: usize, row_span: usize) -> CellSlot {
+        CellSlot { row, column, col_span, row_span }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse_table_display("table-cell"), Some(TableRole::Cell));
+        assert_eq!(parse_table_display("block"), None);
+        assert_eq!(parse_border_spacing("4px"), Size { width: 4.0, height: 4.0 });
+        assert_eq!(parse_border_spacing("4px 2px"), Size { width: 4.0, height: 2.0 });
+        assert_eq!(parse_border_spacing("1em"), Size::ZERO);
+    }
+
+    #[test]
+    fn test_place_cells_with_spans() {
+        // | a (rowspan 2) | b (colspan 2)  |
+        // |               | c     | d      |
+        // | e             | f (rowspan 0)  |
+        let rows = vec![vec![(1, 2), (2, 1)], vec![(1, 1), (1, 1)], vec![(1, 1), (1, 0)]];
+        let (slots, columns) = place_cells(&rows, &[3, 3, 3]);
+        assert_eq!(columns, 3);
+        assert_eq!(slots[0], [slot(0, 0, 1, 2), slot(0, 1, 2, 1)]);
+        assert_eq!(slots[1], [slot(1, 1, 1, 1), slot(1, 2, 1, 1)]);
+        assert_eq!(slots[2], [slot(2, 0, 1, 1), slot(2, 1, 1, 1)]);
+
+        // rowspans stop at the section end
+        let (slots, _) = place_cells(&rows, &[1, 3, 3]);
+        assert_eq!(slots[0][0].row_span, 1);
+        assert_eq!(slots[1][0], slot(1, 0, 1, 1));
+    }
+
+    #[test]
+    fn test_column_bounds_spread_spanning_excess() {
+        let cells = [
+            (slot(0, 0, 1, 1), 20.0, 50.0),
+            (slot(0, 1, 1, 1), 10.0, 30.0),
+            (slot(1, 0, 2, 1), 60.0, 70.0),
+        ];
+        // spanning min 60 - 10 spacing - 30 = 20 spread; max 70 - 10 - 80 < 0
+        assert_eq!(
+            column_bounds(2, &cells, 10.0),
+            [ColumnBounds { min: 30.0, max: 50.0 }, ColumnBounds { min: 20.0, max: 30.0 }]
+        );
+    }
+
+    #[test]
+    fn test_distribute() {
+        let bounds = [ColumnBounds { min: 20.0, max: 60.0 }, ColumnBounds { min: 40.0, max: 40.0 }];
+        assert_eq!(distribute_auto(&bounds, 50.0), [20.0, 40.0]);
+        assert_eq!(distribute_auto(&bounds, 80.0), [40.0, 40.0]);
+        assert_eq!(distribute_auto(&bounds, 200.0), [120.0, 80.0]);
+
+        assert_eq!(distribute_fixed(&[Some(50.0), None, None], 250.0), [50.0, 100.0, 100.0]);
+        assert_eq!(distribute_fixed(&[Some(50.0), Some(150.0)], 400.0), [100.0, 300.0]);
+    }
+}
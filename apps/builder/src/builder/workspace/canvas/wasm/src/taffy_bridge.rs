@@ -1,18 +1,34 @@
 //! Taffy layout engine WASM bridge.
 //!
-//! Wraps `TaffyTree` and exposes a JSON-based style API to TypeScript.
+//! Wraps a Taffy layout tree (`layout_tree`) and exposes a JSON-based style
+//! API to TypeScript.
 //! Style values are parsed from JSON strings to minimize WASM boundary calls.
 //!
 //! # Design decisions
 //! - JSON string interface: one WASM call per node instead of N setter calls.
 //! - NodeId → usize mapping via Vec for O(1) lookup.
 //! - Batch compute: single `compute_layout` call resolves entire tree.
+//! - Content leaves: text/intrinsic descriptors are measured during
+//!   `compute_layout` via the engine's `TextMeasurer` (see `text_measure`).
+//!   Faces registered in `font_metrics` are measured in Rust; other fonts
+//!   fall back to the JS or character-count measurer.
 
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
 use taffy::prelude::*;
 use taffy::style::{GridTemplateRepetition, Overflow};
 use wasm_bindgen::prelude::*;
 
+use composition_core::calc::parse_calc;
+use crate::font_metrics;
+use composition_core::grid_names::{parse_grid_placement, parse_template_areas, split_line_names};
+use crate::layout_tree::{Direction, Flow, LayoutTree, LayoutTreeExt, WritingMode};
+use composition_core::masonry::is_masonry;
+use composition_core::shorthand::{expand_style, StyleEntries};
+use composition_core::subgrid::{parse_subgrid, Subgrid};
+use crate::table::{parse_border_spacing, parse_table_display, TableLayout, TableRole, TableStyle};
+use crate::text_measure::{FallbackTextMeasurer, JsTextMeasurer, LeafContent};
+use composition_core::units::{LengthField, LengthSlot, RelativeStyle};
+
 // ─── Style JSON schema ───────────────────────────────────────────────
 
 /// Intermediate style representation deserialized from JSON.
@@ -47,6 +63,8 @@ struct StyleInput {
     grid_auto_flow: Option<String>,
     grid_auto_columns: Option<Vec<String>>,
     grid_auto_rows: Option<Vec<String>>,
+    /// CSS text (`"a a" "b c"`) or an array of row strings
+    grid_template_areas: Option<serde_json::Value>,
 
     // Grid item
     grid_column_start: Option<String>,
@@ -88,22 +106,99 @@ struct StyleInput {
 
     // Aspect ratio
     aspect_ratio: Option<f32>,
+
+    // Writing direction ("ltr" | "rtl"; unset/"inherit" inherits)
+    direction: Option<String>,
+    // "horizontal-tb" | "vertical-rl" | "vertical-lr"; unset/"inherit" inherits
+    writing_mode: Option<String>,
+
+    // Font size for `em` lengths; unset inherits
+    font_size: Option<String>,
+
+    // Table ("auto" | "fixed"; "4px" | "4px 2px"; "top" | "bottom")
+    table_layout: Option<String>,
+    border_spacing: Option<String>,
+    caption_side: Option<String>,
+    // Table cell
+    col_span: Option<u16>,
+    row_span: Option<u16>,
+}
+
+impl StyleInput {
+    /// Parse a style JSON object, expanding shorthands (see `shorthand`).
+    fn from_json(json: &str) -> Self {
+        serde_json::from_value(expand_style(json)).unwrap_or_default()
+    }
+
+    /// Length fields keyed by style property name (see `LengthField`).
+    fn lengths(&self) -> [(&'static str, &Option<String>); 25] {
+        [
+            ("width", &self.width),
+            ("height", &self.height),
+            ("minWidth", &self.min_width),
+            ("minHeight", &self.min_height),
+            ("maxWidth", &self.max_width),
+            ("maxHeight", &self.max_height),
+            ("flexBasis", &self.flex_basis),
+            ("marginTop", &self.margin_top),
+            ("marginRight", &self.margin_right),
+            ("marginBottom", &self.margin_bottom),
+            ("marginLeft", &self.margin_left),
+            ("insetTop", &self.inset_top),
+            ("insetRight", &self.inset_right),
+            ("insetBottom", &self.inset_bottom),
+            ("insetLeft", &self.inset_left),
+            ("paddingTop", &self.padding_top),
+            ("paddingRight", &self.padding_right),
+            ("paddingBottom", &self.padding_bottom),
+            ("paddingLeft", &self.padding_left),
+            ("borderTop", &self.border_top),
+            ("borderRight", &self.border_right),
+            ("borderBottom", &self.border_bottom),
+            ("borderLeft", &self.border_left),
+            ("columnGap", &self.column_gap),
+            ("rowGap", &self.row_gap),
+        ]
+    }
+}
+
+/// Deserialize a `StyleInput` with its shorthands expanded.
+fn deserialize_style<'de, D: Deserializer<'de>>(deserializer: D) -> Result<StyleInput, D::Error> {
+    let style = StyleEntries::deserialize(deserializer)?.expand();
+    serde_json::from_value(style).map_err(serde::de::Error::custom)
 }
 
 /// Input for batch tree building: style + child indices in topological order.
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct BatchNodeInput {
+    #[serde(deserialize_with = "deserialize_style")]
     style: StyleInput,
     /// Child node indices within the batch array (topological order: leaves first).
     children: Vec<usize>,
+    /// Optional content descriptor for leaves (measured during layout).
+    #[serde(default)]
+    content: Option<LeafContent>,
 }
 
 // ─── Value parsers ───────────────────────────────────────────────────
 
+/// Map a `calc()`, `min()`, `max()` or `clamp()` string onto a Taffy length type.
+fn parse_calc_as<T>(
+    s: &str,
+    length: fn(f32) -> T,
+    percent: fn(f32) -> T,
+    calc: fn(*const ()) -> T,
+) -> Option<T> {
+    parse_calc(s).map(|v| v.into_taffy(length, percent, calc))
+}
+
 /// Parse a CSS-like dimension string into a Taffy `Dimension`.
 fn parse_dimension(s: &str) -> Dimension {
     let s = s.trim();
+    if let Some(v) = parse_calc_as(s, Dimension::length, Dimension::percent, Dimension::calc) {
+        return v;
+    }
     match s {
         "auto" => Dimension::auto(),
         _ if s.ends_with("px") => {
@@ -123,6 +218,14 @@ fn parse_dimension(s: &str) -> Dimension {
 /// Parse a CSS-like length-percentage-auto string into `LengthPercentageAuto`.
 fn parse_lpa(s: &str) -> LengthPercentageAuto {
     let s = s.trim();
+    if let Some(v) = parse_calc_as(
+        s,
+        LengthPercentageAuto::length,
+        LengthPercentageAuto::percent,
+        LengthPercentageAuto::calc,
+    ) {
+        return v;
+    }
     match s {
         "auto" => LengthPercentageAuto::auto(),
         _ if s.ends_with("px") => {
@@ -146,6 +249,14 @@ fn parse_lpa(s: &str) -> LengthPercentageAuto {
 /// Parse a CSS-like length-percentage string into `LengthPercentage`.
 fn parse_lp(s: &str) -> LengthPercentage {
     let s = s.trim();
+    if let Some(v) = parse_calc_as(
+        s,
+        LengthPercentage::length,
+        LengthPercentage::percent,
+        LengthPercentage::calc,
+    ) {
+        return v;
+    }
     if s.ends_with("px") {
         s.trim_end_matches("px")
             .parse::<f32>()
@@ -162,6 +273,22 @@ fn parse_lp(s: &str) -> LengthPercentage {
     }
 }
 
+/// Parse a grid template track list into its components and the line names
+/// between them. Items may hold several tokens and `[name ...]` groups.
+/// `subgrid` and `masonry` lists have no tracks of their own (see `subgrid`
+/// and `masonry`).
+pub(crate) fn parse_template(items: &[String]) -> (Vec<GridTemplateComponent<String>>, Vec<Vec<String>>) {
+    let tokens = || items.iter().map(String::as_str);
+    if parse_subgrid(tokens()).is_some() || is_masonry(tokens()) {
+        return (Vec::new(), Vec::new());
+    }
+    let (tracks, names) = split_line_names(items.iter().flat_map(|item| tokenize_grid_tracks(item)));
+    let tracks = tracks.into_iter().map(parse_track_as_template).collect();
+    // No names at all: leave Taffy's line-name list empty
+    let names = if names.iter().all(Vec::is_empty) { Vec::new() } else { names };
+    (tracks, names)
+}
+
 /// Parse a grid template token into a `GridTemplateComponent`.
 ///
 /// Supports:
@@ -203,12 +330,8 @@ pub(crate) fn parse_track_as_template(s: &str) -> GridTemplateComponent<String>
             };
 
             // Parse track list (space-separated, respecting nested parens)
-            let tracks = tokenize_grid_tracks(tracks_str)
-                .iter()
-                .map(|t| parse_track_sizing(t))
-                .collect::<Vec<_>>();
-
-            let line_names = vec![vec![]; tracks.len() + 1];
+            let (tracks, line_names) = split_line_names(tokenize_grid_tracks(tracks_str));
+            let tracks = tracks.into_iter().map(parse_track_sizing).collect();
 
             return GridTemplateComponent::Repeat(GridTemplateRepetition {
                 count,
@@ -220,9 +343,10 @@ pub(crate) fn parse_track_as_template(s: &str) -> GridTemplateComponent<String>
     GridTemplateComponent::Single(parse_track_sizing(s))
 }
 
-/// Tokenize a space-separated grid track list, respecting nested parentheses.
+/// Tokenize a space-separated grid track list, respecting nested parentheses
+/// and `[line names]`.
 ///
-/// e.g., "minmax(100px, 1fr) 200px" → ["minmax(100px, 1fr)", "200px"]
+/// e.g., "minmax(100px, 1fr) [a b] 200px" → ["minmax(100px, 1fr)", "[a b]", "200px"]
 fn tokenize_grid_tracks(s: &str) -> Vec<&str> {
     let mut tokens = Vec::new();
     let mut depth = 0u32;
@@ -230,8 +354,8 @@ fn tokenize_grid_tracks(s: &str) -> Vec<&str> {
     let s = s.trim();
     for (i, ch) in s.char_indices() {
         match ch {
-            '(' => depth += 1,
-            ')' => depth = depth.saturating_sub(1),
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
             ' ' | '\t' if depth == 0 => {
                 let token = s[start..i].trim();
                 if !token.is_empty() {
@@ -332,21 +456,24 @@ fn parse_max_track(s: &str) -> MaxTrackSizingFunction {
     }
 }
 
-/// Parse a grid line placement string (e.g., "1", "span 2", "auto").
-fn parse_grid_placement(s: &str) -> GridPlacement {
-    let s = s.trim();
-    if s == "auto" {
-        return GridPlacement::Auto;
+/// Parse a CSS `direction` value. `None` (including `inherit`) inherits.
+pub(crate) fn parse_direction(s: &str) -> Option<Direction> {
+    match s.trim() {
+        "ltr" => Some(Direction::Ltr),
+        "rtl" => Some(Direction::Rtl),
+        _ => None,
     }
-    if let Some(rest) = s.strip_prefix("span ") {
-        if let Ok(v) = rest.parse::<u16>() {
-            return GridPlacement::Span(v);
-        }
-    }
-    if let Ok(v) = s.parse::<i16>() {
-        return GridPlacement::from_line_index(v);
+}
+
+/// Parse a CSS `writing-mode` value. `sideways-*` lay out like their
+/// `vertical-*` counterparts. `None` (including `inherit`) inherits.
+pub(crate) fn parse_writing_mode(s: &str) -> Option<WritingMode> {
+    match s.trim() {
+        "horizontal-tb" => Some(WritingMode::HorizontalTb),
+        "vertical-rl" | "sideways-rl" => Some(WritingMode::VerticalRl),
+        "vertical-lr" | "sideways-lr" => Some(WritingMode::VerticalLr),
+        _ => None,
     }
-    GridPlacement::Auto
 }
 
 // ─── StyleInput → taffy::Style conversion ────────────────────────────
@@ -361,8 +488,12 @@ fn convert_style(input: &StyleInput) -> Style {
             "grid" => Display::Grid,
             "block" => Display::Block,
             "none" => Display::None,
+            // Table boxes are laid out by the layout tree (see `table`)
+            d if parse_table_display(d).is_some() => Display::Block,
+            "table-column" | "table-column-group" => Display::None,
             _ => Display::Flex,
         };
+        style.item_is_table = parse_table_display(d) == Some(TableRole::Table);
     }
 
     // Position
@@ -469,10 +600,7 @@ fn convert_style(input: &StyleInput) -> Style {
     if let Some(fs) = input.flex_shrink {
         style.flex_shrink = fs;
     }
-    if let Some(ref fb) = input.flex_basis {
-        style.flex_basis = parse_dimension(fb);
-    }
-    // AlignSelf = AlignItems alias in Taffy 0.9 (no Auto variant)
+    // AlignSelf = AlignItems alias in Taffy (no Auto variant)
     if let Some(ref a_s) = input.align_self {
         style.align_self = match a_s.as_str() {
             "flex-start" | "start" => Some(AlignSelf::Start),
@@ -483,7 +611,7 @@ fn convert_style(input: &StyleInput) -> Style {
             _ => None, // "auto" maps to None (inherit from parent)
         };
     }
-    // JustifySelf = AlignSelf alias in Taffy 0.9
+    // JustifySelf = AlignSelf alias in Taffy
     if let Some(ref j_s) = input.justify_self {
         style.justify_self = match j_s.as_str() {
             "flex-start" | "start" => Some(AlignSelf::Start),
@@ -497,10 +625,13 @@ fn convert_style(input: &StyleInput) -> Style {
 
     // Grid container
     if let Some(ref cols) = input.grid_template_columns {
-        style.grid_template_columns = cols.iter().map(|s| parse_track_as_template(s)).collect();
+        (style.grid_template_columns, style.grid_template_column_names) = parse_template(cols);
     }
     if let Some(ref rows) = input.grid_template_rows {
-        style.grid_template_rows = rows.iter().map(|s| parse_track_as_template(s)).collect();
+        (style.grid_template_rows, style.grid_template_row_names) = parse_template(rows);
+    }
+    if let Some(ref areas) = input.grid_template_areas {
+        style.grid_template_areas = parse_template_areas(areas).unwrap_or_default();
     }
     if let Some(ref gaf) = input.grid_auto_flow {
         style.grid_auto_flow = match gaf.as_str() {
@@ -544,96 +675,107 @@ fn convert_style(input: &StyleInput) -> Style {
         };
     }
 
-    // Size
-    if let Some(ref v) = input.width {
-        style.size.width = parse_dimension(v);
-    }
-    if let Some(ref v) = input.height {
-        style.size.height = parse_dimension(v);
-    }
-    if let Some(ref v) = input.min_width {
-        style.min_size.width = parse_dimension(v);
-    }
-    if let Some(ref v) = input.min_height {
-        style.min_size.height = parse_dimension(v);
-    }
-    if let Some(ref v) = input.max_width {
-        style.max_size.width = parse_dimension(v);
-    }
-    if let Some(ref v) = input.max_height {
-        style.max_size.height = parse_dimension(v);
+    // Lengths: size, flex-basis, margin, inset, padding, border, gap.
+    // Relative units are left at the default here (see `input_relative`).
+    for (key, value) in input.lengths() {
+        if let Some(v) = value {
+            apply_length_field(&mut style, key, v);
+        }
     }
 
-    // Margin
-    if let Some(ref v) = input.margin_top {
-        style.margin.top = parse_lpa(v);
-    }
-    if let Some(ref v) = input.margin_right {
-        style.margin.right = parse_lpa(v);
-    }
-    if let Some(ref v) = input.margin_bottom {
-        style.margin.bottom = parse_lpa(v);
-    }
-    if let Some(ref v) = input.margin_left {
-        style.margin.left = parse_lpa(v);
+    // Aspect ratio
+    if let Some(ar) = input.aspect_ratio {
+        style.aspect_ratio = Some(ar);
     }
 
-    // Padding
-    if let Some(ref v) = input.padding_top {
-        style.padding.top = parse_lp(v);
-    }
-    if let Some(ref v) = input.padding_right {
-        style.padding.right = parse_lp(v);
-    }
-    if let Some(ref v) = input.padding_bottom {
-        style.padding.bottom = parse_lp(v);
-    }
-    if let Some(ref v) = input.padding_left {
-        style.padding.left = parse_lp(v);
-    }
+    style
+}
 
-    // Border
-    if let Some(ref v) = input.border_top {
-        style.border.top = parse_lp(v);
-    }
-    if let Some(ref v) = input.border_right {
-        style.border.right = parse_lp(v);
-    }
-    if let Some(ref v) = input.border_bottom {
-        style.border.bottom = parse_lp(v);
-    }
-    if let Some(ref v) = input.border_left {
-        style.border.left = parse_lp(v);
-    }
+/// Apply one camelCase length field (size, min/max, margin, padding,
+/// border, inset, gap or flex-basis) given as a CSS string. The binary
+/// protocol carries values it has no encoding for, such as `calc()`, this way.
+/// Returns false for other keys.
+pub(crate) fn apply_length_field(style: &mut Style, key: &str, value: &str) -> bool {
+    let Some(field) = LengthField::from_key(key) else {
+        return false;
+    };
+    match field.slot(style) {
+        LengthSlot::Dimension(v) => *v = parse_dimension(value),
+        LengthSlot::Lpa(v) => *v = parse_lpa(value),
+        LengthSlot::Lp(v) => *v = parse_lp(value),
+    }
+    true
+}
 
-    // Inset
-    if let Some(ref v) = input.inset_top {
-        style.inset.top = parse_lpa(v);
-    }
-    if let Some(ref v) = input.inset_right {
-        style.inset.right = parse_lpa(v);
-    }
-    if let Some(ref v) = input.inset_bottom {
-        style.inset.bottom = parse_lpa(v);
-    }
-    if let Some(ref v) = input.inset_left {
-        style.inset.left = parse_lpa(v);
-    }
+/// The parts of a parsed style that live outside `taffy::Style`.
+struct ExtendedStyle {
+    flow: Flow,
+    relative: RelativeStyle,
+    subgrid: Subgrid,
+    masonry: bool,
+    table: TableStyle,
+}
 
-    // Gap
-    if let Some(ref v) = input.column_gap {
-        style.gap.width = parse_lp(v);
+impl ExtendedStyle {
+    fn from_input(input: &StyleInput) -> Self {
+        Self {
+            flow: input_flow(input),
+            relative: input_relative(input),
+            subgrid: input_subgrid(input),
+            masonry: input_masonry(input),
+            table: input_table(input),
+        }
     }
-    if let Some(ref v) = input.row_gap {
-        style.gap.height = parse_lp(v);
+}
+
+/// Specified `direction` / `writing-mode` of a style input.
+fn input_flow(input: &StyleInput) -> Flow {
+    Flow {
+        direction: input.direction.as_deref().and_then(parse_direction),
+        writing_mode: input.writing_mode.as_deref().and_then(parse_writing_mode),
     }
+}
 
-    // Aspect ratio
-    if let Some(ar) = input.aspect_ratio {
-        style.aspect_ratio = Some(ar);
+/// Subgridded axes of a style input.
+fn input_subgrid(input: &StyleInput) -> Subgrid {
+    let parse = |items: &Option<Vec<String>>| {
+        items.as_deref().and_then(|items| parse_subgrid(items.iter().map(String::as_str)))
+    };
+    Subgrid::new(parse(&input.grid_template_columns), parse(&input.grid_template_rows))
+}
+
+/// Whether the rows are `masonry`.
+fn input_masonry(input: &StyleInput) -> bool {
+    input.grid_template_rows.as_deref().is_some_and(|items| is_masonry(items.iter().map(String::as_str)))
+}
+
+/// Table role and properties of a style input.
+fn input_table(input: &StyleInput) -> TableStyle {
+    TableStyle {
+        role: input.display.as_deref().and_then(parse_table_display).unwrap_or_default(),
+        layout: match input.table_layout.as_deref() {
+            Some("fixed") => TableLayout::Fixed,
+            _ => TableLayout::Auto,
+        },
+        spacing: input.border_spacing.as_deref().map_or(Size::ZERO, parse_border_spacing),
+        caption_bottom: input.caption_side.as_deref() == Some("bottom"),
+        col_span: input.col_span.unwrap_or(1),
+        row_span: input.row_span.unwrap_or(1),
     }
+}
 
-    style
+/// `font-size` and the lengths given in font- or viewport-relative units.
+fn input_relative(input: &StyleInput) -> RelativeStyle {
+    let mut relative = RelativeStyle::default();
+    if let Some(v) = &input.font_size {
+        relative.record("fontSize", v);
+    }
+    for (key, value) in input.lengths() {
+        if let Some(v) = value {
+            relative.record(key, v);
+        }
+    }
+    relative
 }
 
 // ─── Layout result ───────────────────────────────────────────────────
@@ -652,11 +794,19 @@ struct LayoutOutput {
 /// Handles are stable across tree mutations; removed slots are recycled.
 #[wasm_bindgen]
 pub struct TaffyLayoutEngine {
-    tree: TaffyTree<()>,
+    tree: LayoutTree,
     /// handle → NodeId mapping. Freed handles become None and are recycled.
     nodes: Vec<Option<NodeId>>,
     /// Recycled (freed) handle indices for reuse.
     free_list: Vec<usize>,
+    /// Font registry generation seen by the last `compute_layout`.
+    font_generation: u64,
+}
+
+impl Default for TaffyLayoutEngine {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[wasm_bindgen]
@@ -665,47 +815,86 @@ impl TaffyLayoutEngine {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
         Self {
-            tree: TaffyTree::new(),
+            tree: LayoutTree::default(),
             nodes: Vec::new(),
             free_list: Vec::new(),
+            font_generation: font_metrics::with_registry(|r| r.generation()),
         }
     }
 
     /// Create a leaf node with the given style JSON and return its handle.
     pub fn create_node(&mut self, style_json: &str) -> usize {
-        let input: StyleInput = serde_json::from_str(style_json).unwrap_or_default();
+        let input = StyleInput::from_json(style_json);
+        let style = convert_style(&input);
+        let node_id = self.tree.new_leaf(style);
+        self.apply_extended_style(node_id, &ExtendedStyle::from_input(&input));
+        self.alloc_handle(node_id)
+    }
+
+    /// Create a content leaf (text or intrinsic box) measured during layout.
+    ///
+    /// `content_json`: `{"kind":"text","text":"...","fontFamily":"...",
+    /// "fontSize":14,"fontWeight":400,"lineHeight":20}`. An unparsable
+    /// descriptor yields a plain leaf.
+    pub fn create_leaf_with_content(&mut self, style_json: &str, content_json: &str) -> usize {
+        let input = StyleInput::from_json(style_json);
         let style = convert_style(&input);
-        let node_id = self.tree.new_leaf(style).expect("failed to create taffy node");
+        let node_id = match serde_json::from_str::<LeafContent>(content_json) {
+            Ok(content) => self.tree.new_leaf_with_content(style, content),
+            Err(_) => self.tree.new_leaf(style),
+        };
+        self.apply_extended_style(node_id, &ExtendedStyle::from_input(&input));
         self.alloc_handle(node_id)
     }
 
+    /// Replace (or clear, with an empty/invalid JSON) the content of a leaf.
+    /// The node is marked dirty so the next `compute_layout` re-measures it.
+    pub fn set_leaf_content(&mut self, handle: usize, content_json: &str) {
+        if let Some(node_id) = self.resolve(handle) {
+            let content = serde_json::from_str::<LeafContent>(content_json).ok();
+            self.tree.set_content(node_id, content);
+        }
+    }
+
+    /// Install a JS text measurer:
+    /// `(text, fontFamily, fontSize, fontWeight) => width`.
+    ///
+    /// All content leaves are marked dirty since cached sizes may change.
+    pub fn set_text_measurer(&mut self, callback: js_sys::Function) {
+        self.tree.engine.measurer.fallback = Box::new(JsTextMeasurer::new(callback));
+        self.mark_content_leaves_dirty();
+    }
+
+    /// Revert to the built-in character-count measurer.
+    pub fn reset_text_measurer(&mut self) {
+        self.tree.engine.measurer.fallback = Box::new(FallbackTextMeasurer);
+        self.mark_content_leaves_dirty();
+    }
+
     /// Create a node with the given style JSON and child handles.
     pub fn create_node_with_children(
         &mut self,
         style_json: &str,
         children_handles: &[usize],
     ) -> usize {
-        let input: StyleInput = serde_json::from_str(style_json).unwrap_or_default();
+        let input = StyleInput::from_json(style_json);
         let style = convert_style(&input);
         let child_ids: Vec<NodeId> = children_handles
             .iter()
             .filter_map(|&h| self.resolve(h))
             .collect();
-        let node_id = self
-            .tree
-            .new_with_children(style, &child_ids)
-            .expect("failed to create taffy node with children");
+        let node_id = self.tree.new_with_children(style, &child_ids);
+        self.apply_extended_style(node_id, &ExtendedStyle::from_input(&input));
         self.alloc_handle(node_id)
     }
 
     /// Update the style of an existing node.
     pub fn update_style(&mut self, handle: usize, style_json: &str) {
         if let Some(node_id) = self.resolve(handle) {
-            let input: StyleInput = serde_json::from_str(style_json).unwrap_or_default();
+            let input = StyleInput::from_json(style_json);
             let style = convert_style(&input);
-            self.tree
-                .set_style(node_id, style)
-                .expect("failed to set style");
+            self.tree.set_style(node_id, style);
+            self.apply_extended_style(node_id, &ExtendedStyle::from_input(&input));
         }
     }
 
@@ -716,15 +905,19 @@ impl TaffyLayoutEngine {
                 .iter()
                 .filter_map(|&h| self.resolve(h))
                 .collect();
-            self.tree
-                .set_children(node_id, &child_ids)
-                .expect("failed to set children");
+            self.tree.set_children(node_id, &child_ids);
         }
     }
 
     /// Compute layout for the tree rooted at `handle`.
     pub fn compute_layout(&mut self, handle: usize, available_width: f32, available_height: f32) {
         if let Some(node_id) = self.resolve(handle) {
+            // Fonts registered since the last pass invalidate cached text sizes.
+            let generation = font_metrics::with_registry(|r| r.generation());
+            if generation != self.font_generation {
+                self.font_generation = generation;
+                self.mark_content_leaves_dirty();
+            }
             // RC-1: sentinel(-1) → MaxContent (height:auto 부모)
             let height_space = if available_height < 0.0 {
                 AvailableSpace::MaxContent
@@ -735,24 +928,32 @@ impl TaffyLayoutEngine {
                 width: AvailableSpace::Definite(available_width),
                 height: height_space,
             };
-            self.tree
-                .compute_layout(node_id, available)
-                .expect("failed to compute layout");
+            self.tree.compute_layout(node_id, available);
         }
     }
 
+    /// Set the viewport that `vw`, `vh`, `vmin` and `vmax` resolve against.
+    /// Nodes using them are re-resolved on the next `compute_layout`.
+    pub fn set_viewport(&mut self, width: f32, height: f32) {
+        self.tree.viewport = Size { width, height };
+    }
+
+    /// Set the root font size (`rem`; inherited by layout roots). Default 16px.
+    pub fn set_root_font_size(&mut self, px: f32) {
+        self.tree.root_font_size = px;
+    }
+
     /// Retrieve the computed layout for a node as a JSON string.
     pub fn get_layout(&self, handle: usize) -> String {
         if let Some(node_id) = self.resolve(handle) {
-            if let Ok(layout) = self.tree.layout(node_id) {
-                let output = LayoutOutput {
-                    x: layout.location.x,
-                    y: layout.location.y,
-                    width: layout.size.width,
-                    height: layout.size.height,
-                };
-                return serde_json::to_string(&output).unwrap_or_default();
-            }
+            let layout = self.tree.layout(node_id);
+            let output = LayoutOutput {
+                x: layout.location.x,
+                y: layout.location.y,
+                width: layout.size.width,
+                height: layout.size.height,
+            };
+            return serde_json::to_string(&output).unwrap_or_default();
         }
         r#"{"x":0,"y":0,"width":0,"height":0}"#.to_string()
     }
@@ -763,23 +964,49 @@ impl TaffyLayoutEngine {
         let mut result = Vec::with_capacity(handles.len() * 4);
         for &h in handles {
             if let Some(node_id) = self.resolve(h) {
-                if let Ok(layout) = self.tree.layout(node_id) {
-                    result.push(layout.location.x);
-                    result.push(layout.location.y);
-                    result.push(layout.size.width);
-                    result.push(layout.size.height);
-                    continue;
-                }
+                let layout = self.tree.layout(node_id);
+                result.push(layout.location.x);
+                result.push(layout.location.y);
+                result.push(layout.size.width);
+                result.push(layout.size.height);
+                continue;
             }
             result.extend_from_slice(&[0.0, 0.0, 0.0, 0.0]);
         }
         result.into_boxed_slice()
     }
 
+    /// Batch retrieve layouts with baselines as a flat Float32Array.
+    /// Returns [x0, y0, w0, h0, firstBaseline0, lastBaseline0, ...].
+    ///
+    /// Baselines are offsets from the top of the node's border box. Nodes
+    /// without a baseline set (no text in flow) report their border-box
+    /// bottom, i.e. the baseline CSS synthesizes for alignment.
+    pub fn get_layouts_batch_with_baselines(&self, handles: &[usize]) -> Box<[f32]> {
+        let mut result = Vec::with_capacity(handles.len() * 6);
+        for &h in handles {
+            if let Some(node_id) = self.resolve(h) {
+                let layout = self.tree.layout(node_id);
+                let (first, last) = self.tree.baselines(node_id);
+                result.extend_from_slice(&[
+                    layout.location.x,
+                    layout.location.y,
+                    layout.size.width,
+                    layout.size.height,
+                    first.unwrap_or(layout.size.height),
+                    last.unwrap_or(layout.size.height),
+                ]);
+                continue;
+            }
+            result.extend_from_slice(&[0.0; 6]);
+        }
+        result.into_boxed_slice()
+    }
+
     /// Remove a node from the tree and free its handle for reuse.
     pub fn remove_node(&mut self, handle: usize) {
         if let Some(node_id) = self.resolve(handle) {
-            let _ = self.tree.remove(node_id);
+            self.tree.remove(node_id);
             self.nodes[handle] = None;
             self.free_list.push(handle);
         }
@@ -805,34 +1032,17 @@ impl TaffyLayoutEngine {
 
         for (i, node) in nodes.iter().enumerate() {
             let style = convert_style(&node.style);
-
-            if node.children.is_empty() {
-                let node_id = self
-                    .tree
-                    .new_leaf(style)
-                    .map_err(|e| JsValue::from_str(&format!("node[{i}]: taffy error: {e:?}")))?;
-                handles.push(self.alloc_handle(node_id));
-            } else {
-                let mut child_ids: Vec<NodeId> = Vec::with_capacity(node.children.len());
-                for &idx in &node.children {
-                    let handle = handles.get(idx).copied().ok_or_else(|| {
-                        JsValue::from_str(&format!(
-                            "node[{i}]: child index {idx} out of range (only {i} nodes built so far)"
-                        ))
-                    })?;
-                    let node_id = self.resolve(handle).ok_or_else(|| {
-                        JsValue::from_str(&format!(
-                            "node[{i}]: child index {idx} resolved to invalid handle {handle}"
-                        ))
-                    })?;
-                    child_ids.push(node_id);
+            let node_id = if node.children.is_empty() {
+                match &node.content {
+                    Some(content) => self.tree.new_leaf_with_content(style, content.clone()),
+                    None => self.tree.new_leaf(style),
                 }
-                let node_id = self
-                    .tree
-                    .new_with_children(style, &child_ids)
-                    .map_err(|e| JsValue::from_str(&format!("node[{i}]: taffy error: {e:?}")))?;
-                handles.push(self.alloc_handle(node_id));
-            }
+            } else {
+                let child_ids = self.batch_children(i, &node.children, &handles)?;
+                self.tree.new_with_children(style, &child_ids)
+            };
+            self.apply_extended_style(node_id, &ExtendedStyle::from_input(&node.style));
+            handles.push(self.alloc_handle(node_id));
         }
 
         Ok(handles.into_boxed_slice())
@@ -855,33 +1065,24 @@ impl TaffyLayoutEngine {
         let mut handles: Vec<usize> = Vec::with_capacity(nodes.len());
 
         for (i, node) in nodes.into_iter().enumerate() {
-            if node.children.is_empty() {
-                let node_id = self
-                    .tree
-                    .new_leaf(node.style)
-                    .map_err(|e| JsValue::from_str(&format!("node[{i}]: taffy error: {e:?}")))?;
-                handles.push(self.alloc_handle(node_id));
-            } else {
-                let mut child_ids: Vec<NodeId> = Vec::with_capacity(node.children.len());
-                for &idx in &node.children {
-                    let handle = handles.get(idx).copied().ok_or_else(|| {
-                        JsValue::from_str(&format!(
-                            "node[{i}]: child index {idx} out of range (only {i} nodes built so far)"
-                        ))
-                    })?;
-                    let node_id = self.resolve(handle).ok_or_else(|| {
-                        JsValue::from_str(&format!(
-                            "node[{i}]: child index {idx} resolved to invalid handle {handle}"
-                        ))
-                    })?;
-                    child_ids.push(node_id);
+            let node_id = if node.children.is_empty() {
+                match node.content {
+                    Some(content) => self.tree.new_leaf_with_content(node.style, content),
+                    None => self.tree.new_leaf(node.style),
                 }
-                let node_id = self
-                    .tree
-                    .new_with_children(node.style, &child_ids)
-                    .map_err(|e| JsValue::from_str(&format!("node[{i}]: taffy error: {e:?}")))?;
-                handles.push(self.alloc_handle(node_id));
-            }
+            } else {
+                let child_ids = self.batch_children(i, &node.children, &handles)?;
+                self.tree.new_with_children(node.style, &child_ids)
+            };
+            let parsed = ExtendedStyle {
+                flow: node.flow,
+                relative: node.relative,
+                subgrid: node.subgrid,
+                masonry: node.masonry,
+                table: node.table,
+            };
+            self.apply_extended_style(node_id, &parsed);
+            handles.push(self.alloc_handle(node_id));
         }
 
         Ok(handles.into_boxed_slice())
@@ -896,7 +1097,7 @@ impl TaffyLayoutEngine {
     /// so this method is only needed for explicit cache invalidation.
     pub fn mark_dirty(&mut self, handle: usize) {
         if let Some(node_id) = self.resolve(handle) {
-            let _ = self.tree.mark_dirty(node_id);
+            self.tree.mark_dirty(node_id);
         }
     }
 
@@ -932,6 +1133,44 @@ impl TaffyLayoutEngine {
     fn resolve(&self, handle: usize) -> Option<NodeId> {
         self.nodes.get(handle).copied().flatten()
     }
+
+    /// Apply the parts of a parsed style that live outside `taffy::Style`.
+    fn apply_extended_style(&mut self, node_id: NodeId, parsed: &ExtendedStyle) {
+        self.tree.set_flow(node_id, parsed.flow);
+        self.tree.set_relative_style(node_id, parsed.relative.clone());
+        self.tree.set_subgrid(node_id, parsed.subgrid.clone());
+        self.tree.set_masonry(node_id, parsed.masonry);
+        self.tree.set_table(node_id, parsed.table);
+    }
+
+    /// Node ids of batch node `i`'s children, given as indices of nodes
+    /// built earlier in the batch.
+    fn batch_children(&self, i: usize, children: &[usize], handles: &[usize]) -> Result<Vec<NodeId>, JsValue> {
+        children
+            .iter()
+            .map(|&idx| {
+                let handle = handles.get(idx).copied().ok_or_else(|| {
+                    JsValue::from_str(&format!(
+                        "node[{i}]: child index {idx} out of range (only {i} nodes built so far)"
+                    ))
+                })?;
+                self.resolve(handle).ok_or_else(|| {
+                    JsValue::from_str(&format!(
+                        "node[{i}]: child index {idx} resolved to invalid handle {handle}"
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    /// Invalidate every leaf that carries content (after a measurer swap).
+    fn mark_content_leaves_dirty(&mut self) {
+        for node_id in self.nodes.iter().flatten() {
+            if self.tree.content(*node_id).is_some() {
+                self.tree.mark_dirty(*node_id);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -963,6 +1202,204 @@ mod tests {
         assert_eq!(layout2["x"], 100.0);
     }
 
+    fn layout_of(engine: &TaffyLayoutEngine, handle: usize) -> (f32, f32, f32, f32) {
+        let l = engine.get_layouts_batch(&[handle]);
+        (l[0], l[1], l[2], l[3])
+    }
+
+    #[test]
+    fn test_rtl_flex_row_starts_at_right() {
+        let mut engine = TaffyLayoutEngine::new();
+        let c1 = engine.create_node(r#"{"width":"100px","height":"50px","marginLeft":"10px"}"#);
+        let c2 = engine.create_node(r#"{"width":"200px","height":"50px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","direction":"rtl","width":"400px","height":"100px","paddingRight":"5px"}"#,
+            &[c1, c2],
+        );
+        engine.compute_layout(root, 400.0, 100.0);
+
+        assert_eq!(layout_of(&engine, c1).0, 295.0, "first item at the right, after padding-right");
+        // margin-left stays on the physical left of c1
+        assert_eq!(layout_of(&engine, c2).0, 85.0);
+    }
+
+    #[test]
+    fn test_rtl_is_inherited_and_overridable() {
+        let mut engine = TaffyLayoutEngine::new();
+        let a = engine.create_node(r#"{"width":"50px","height":"10px"}"#);
+        let b = engine.create_node(r#"{"width":"50px","height":"10px"}"#);
+        let inner = engine.create_node_with_children(
+            r#"{"display":"flex","width":"200px","height":"10px"}"#,
+            &[a],
+        );
+        let inner_ltr = engine.create_node_with_children(
+            r#"{"display":"flex","direction":"ltr","width":"200px","height":"10px"}"#,
+            &[b],
+        );
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"column","direction":"rtl","width":"300px"}"#,
+            &[inner, inner_ltr],
+        );
+        engine.compute_layout(root, 300.0, -1.0);
+
+        assert_eq!(layout_of(&engine, inner).0, 100.0, "cross-start is the right edge");
+        assert_eq!(layout_of(&engine, a).0, 150.0, "inherits rtl");
+        assert_eq!(layout_of(&engine, b).0, 0.0, "explicit ltr");
+
+        // Switching the root back re-resolves the whole subtree
+        engine.update_style(root, r#"{"display":"flex","flexDirection":"column","width":"300px"}"#);
+        engine.compute_layout(root, 300.0, -1.0);
+        assert_eq!(layout_of(&engine, inner).0, 0.0);
+        assert_eq!(layout_of(&engine, a).0, 0.0);
+    }
+
+    #[test]
+    fn test_rtl_grid_columns_and_absolute_insets() {
+        let mut engine = TaffyLayoutEngine::new();
+        let c1 = engine.create_node(r#"{"height":"50px"}"#);
+        let c2 = engine.create_node(r#"{"height":"50px","gridColumnStart":"3"}"#);
+        let abs_left = engine.create_node(
+            r#"{"position":"absolute","insetLeft":"10px","width":"20px","height":"20px"}"#,
+        );
+        let abs_static = engine.create_node(r#"{"position":"absolute","width":"20px","height":"20px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"grid","direction":"rtl","gridTemplateColumns":["100px","100px","100px"],"width":"300px","height":"100px"}"#,
+            &[c1, c2, abs_left, abs_static],
+        );
+        engine.compute_layout(root, 300.0, 100.0);
+
+        assert_eq!(layout_of(&engine, c1).0, 200.0, "column 1 is rightmost");
+        assert_eq!(layout_of(&engine, c2).0, 0.0, "column 3 is leftmost");
+        assert_eq!(layout_of(&engine, abs_left).0, 10.0, "physical left inset");
+        assert_eq!(layout_of(&engine, abs_static).0, 280.0, "static position at inline-start");
+    }
+
+    #[test]
+    fn test_vertical_rl_blocks_flow_right_to_left() {
+        let mut engine = TaffyLayoutEngine::new();
+        let c1 = engine.create_node(r#"{"width":"50px","marginRight":"10px"}"#);
+        let c2 = engine.create_node(r#"{"width":"40px","height":"60px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"block","writingMode":"vertical-rl","width":"300px","height":"100px","paddingTop":"5px"}"#,
+            &[c1, c2],
+        );
+        engine.compute_layout(root, 300.0, 100.0);
+
+        // Block axis runs right to left, inline size fills the height
+        assert_eq!(layout_of(&engine, c1), (240.0, 5.0, 50.0, 95.0));
+        assert_eq!(layout_of(&engine, c2), (200.0, 5.0, 40.0, 60.0));
+    }
+
+    #[test]
+    fn test_vertical_lr_flex_row_runs_downwards() {
+        let mut engine = TaffyLayoutEngine::new();
+        let c1 = engine.create_node(r#"{"width":"30px","height":"40px"}"#);
+        let c2 = engine.create_node(r#"{"width":"30px","height":"50px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"row","writingMode":"vertical-lr","alignItems":"flex-start","width":"100px","height":"300px"}"#,
+            &[c1, c2],
+        );
+        engine.compute_layout(root, 100.0, 300.0);
+
+        assert_eq!(layout_of(&engine, c1), (0.0, 0.0, 30.0, 40.0));
+        assert_eq!(layout_of(&engine, c2), (0.0, 40.0, 30.0, 50.0));
+
+        // rtl flips the (vertical) inline axis
+        engine.update_style(
+            root,
+            r#"{"display":"flex","flexDirection":"row","writingMode":"vertical-lr","direction":"rtl","alignItems":"flex-start","width":"100px","height":"300px"}"#,
+        );
+        engine.compute_layout(root, 100.0, 300.0);
+        assert_eq!(layout_of(&engine, c1), (0.0, 260.0, 30.0, 40.0));
+    }
+
+    #[test]
+    fn test_orthogonal_flow_sizes_by_block_extent() {
+        let mut engine = TaffyLayoutEngine::new();
+        let a = engine.create_node(r#"{"width":"30px"}"#);
+        let b = engine.create_node(r#"{"width":"20px"}"#);
+        // Auto width of a vertical box is its block extent
+        let vertical = engine.create_node_with_children(
+            r#"{"display":"block","writingMode":"vertical-lr","height":"200px"}"#,
+            &[a, b],
+        );
+        let after = engine.create_node(r#"{"height":"10px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"column","alignItems":"flex-start","width":"400px"}"#,
+            &[vertical, after],
+        );
+        engine.compute_layout(root, 400.0, -1.0);
+
+        assert_eq!(layout_of(&engine, vertical), (0.0, 0.0, 50.0, 200.0));
+        assert_eq!(layout_of(&engine, a), (0.0, 0.0, 30.0, 200.0));
+        assert_eq!(layout_of(&engine, b), (30.0, 0.0, 20.0, 200.0));
+        assert_eq!(layout_of(&engine, after).1, 200.0);
+    }
+
+    #[test]
+    fn test_calc_lengths() {
+        let mut engine = TaffyLayoutEngine::new();
+        let c1 = engine.create_node(
+            r#"{"width":"calc(50% - 12px)","height":"calc(20px * 2)","marginLeft":"calc(10% - 10px)"}"#,
+        );
+        let c2 = engine.create_node(r#"{"flexBasis":"calc(25% + 5px)","height":"10px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","width":"400px","height":"100px","columnGap":"calc(2% + 2px)"}"#,
+            &[c1, c2],
+        );
+        engine.compute_layout(root, 400.0, 100.0);
+
+        assert_eq!(layout_of(&engine, c1), (30.0, 0.0, 188.0, 40.0));
+        // 30 + 188 + gap (8 + 2)
+        assert_eq!(layout_of(&engine, c2), (228.0, 0.0, 105.0, 10.0));
+    }
+
+    #[test]
+    fn test_comparison_lengths() {
+        let mut engine = TaffyLayoutEngine::new();
+        let c1 = engine.create_node(
+            r#"{"width":"clamp(320px, 50%, 960px)","height":"10px","marginLeft":"max(1%, 4px)"}"#,
+        );
+        let c2 = engine.create_node(r#"{"width":"min(20%, 20rem)","height":"10px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","width":"1000px","height":"100px","columnGap":"clamp(4px, 1%, 8px)"}"#,
+            &[c1, c2],
+        );
+        engine.compute_layout(root, 1000.0, 100.0);
+
+        assert_eq!(layout_of(&engine, c1), (10.0, 0.0, 500.0, 10.0));
+        // 10 + 500 + gap 8
+        assert_eq!(layout_of(&engine, c2), (518.0, 0.0, 200.0, 10.0));
+
+        engine.set_root_font_size(8.0);
+        engine.compute_layout(root, 1000.0, 100.0);
+        assert_eq!(layout_of(&engine, c2), (518.0, 0.0, 160.0, 10.0));
+    }
+
+    #[test]
+    fn test_relative_units() {
+        let mut engine = TaffyLayoutEngine::new();
+        engine.set_viewport(1000.0, 500.0);
+        let c1 = engine.create_node(r#"{"width":"10vw","height":"2em","marginLeft":"1rem"}"#);
+        let c2 =
+            engine.create_node(r#"{"fontSize":"2em","width":"calc(50% - 1em)","height":"10vmin"}"#);
+        let col = engine.create_node_with_children(
+            r#"{"display":"flex","fontSize":"1.25rem","flexDirection":"column","alignItems":"flex-start","width":"400px"}"#,
+            &[c1, c2],
+        );
+        engine.compute_layout(col, 400.0, 500.0);
+
+        // em = 20px (1.25rem) in c1 and 40px in c2
+        assert_eq!(layout_of(&engine, c1), (16.0, 0.0, 100.0, 40.0));
+        assert_eq!(layout_of(&engine, c2), (0.0, 40.0, 160.0, 50.0));
+
+        engine.set_viewport(800.0, 200.0);
+        engine.set_root_font_size(20.0);
+        engine.compute_layout(col, 400.0, 500.0);
+        assert_eq!(layout_of(&engine, c1), (20.0, 0.0, 80.0, 50.0));
+        assert_eq!(layout_of(&engine, c2), (0.0, 50.0, 150.0, 20.0));
+    }
+
     #[test]
     fn test_grid_layout() {
         let mut engine = TaffyLayoutEngine::new();
@@ -1119,6 +1556,419 @@ mod tests {
         assert_eq!(layout["width"], 100.0);
     }
 
+    #[test]
+    fn test_text_leaf_wraps_in_flex_column() {
+        // Fallback measurer: 20px font → 10px per char.
+        let mut engine = TaffyLayoutEngine::new();
+
+        let text = engine.create_leaf_with_content(
+            r#"{}"#,
+            r#"{"kind":"text","text":"aaaa bbbb cccc","fontSize":20,"lineHeight":24}"#,
+        );
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"column","width":"100px"}"#,
+            &[text],
+        );
+        engine.compute_layout(root, 100.0, -1.0);
+
+        let l: serde_json::Value = serde_json::from_str(&engine.get_layout(text)).unwrap();
+        // "aaaa bbbb" = 90px fits, "cccc" wraps → 2 lines × 24px
+        assert_eq!(l["width"], 100.0, "stretched to column width");
+        assert_eq!(l["height"], 48.0);
+        let root_l: serde_json::Value = serde_json::from_str(&engine.get_layout(root)).unwrap();
+        assert_eq!(root_l["height"], 48.0, "auto-height parent grows with wrapped text");
+    }
+
+    #[test]
+    fn test_text_leaf_max_content_in_flex_row() {
+        let mut engine = TaffyLayoutEngine::new();
+
+        let text = engine.create_leaf_with_content(
+            r#"{}"#,
+            r#"{"kind":"text","text":"ab cd","fontSize":20,"lineHeight":20}"#,
+        );
+        let sibling = engine.create_node(r#"{"width":"30px","height":"10px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"row","alignItems":"start","width":"400px"}"#,
+            &[text, sibling],
+        );
+        engine.compute_layout(root, 400.0, -1.0);
+
+        let results = engine.get_layouts_batch(&[text, sibling]);
+        assert_eq!(results[2], 50.0, "text sized to max-content (5 chars × 10px)");
+        assert_eq!(results[3], 20.0);
+        assert_eq!(results[4], 50.0, "sibling placed after text");
+    }
+
+    #[test]
+    fn test_registered_font_remeasures_text_leaf() {
+        let mut engine = TaffyLayoutEngine::new();
+
+        let text = engine.create_leaf_with_content(
+            r#"{}"#,
+            r#"{"kind":"text","text":"aaaa","fontFamily":"Metric Sans","fontSize":20,"lineHeight":20}"#,
+        );
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"row","alignItems":"start","width":"400px"}"#,
+            &[text],
+        );
+        engine.compute_layout(root, 400.0, -1.0);
+        assert_eq!(engine.get_layouts_batch(&[text])[2], 40.0, "fallback: 4 × 10px");
+
+        // 'a' = 300/1000 em → 6px at 20px; registration dirties cached sizes.
+        crate::font_metrics::register_font_metrics(
+            "Metric Sans",
+            400,
+            &[1000.0, 800.0, 200.0, 0.0],
+            &['a' as u32],
+            &[300.0],
+        );
+        engine.compute_layout(root, 400.0, -1.0);
+        assert_eq!(engine.get_layouts_batch(&[text])[2], 24.0);
+        crate::font_metrics::clear_font_metrics();
+    }
+
+    #[test]
+    fn test_text_baseline_output_and_propagation() {
+        // Fallback metrics: 20px font → ascent 16, descent 4; line-height 30
+        // → half-leading 5 → first baseline 21 below the content box top.
+        let mut engine = TaffyLayoutEngine::new();
+
+        let text = engine.create_leaf_with_content(
+            r#"{"paddingTop":"4px"}"#,
+            r#"{"kind":"text","text":"aaaa bbbb","fontSize":20,"lineHeight":30}"#,
+        );
+        let column = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"column","width":"60px","paddingTop":"10px"}"#,
+            &[text],
+        );
+        engine.compute_layout(column, 60.0, -1.0);
+
+        let r = engine.get_layouts_batch_with_baselines(&[text, column]);
+        assert_eq!(r[3], 64.0, "two lines + padding");
+        assert_eq!(r[4], 25.0, "first baseline: padding 4 + 21");
+        assert_eq!(r[5], 55.0, "last baseline: one line lower");
+        assert_eq!(r[10], 35.0, "container first baseline from its first item");
+        assert_eq!(r[11], 65.0, "container last baseline from its last item");
+    }
+
+    #[test]
+    fn test_align_items_baseline_uses_text_baseline() {
+        let mut engine = TaffyLayoutEngine::new();
+
+        // Small text: baseline 16 (20px font, 20px lines)
+        let small = engine.create_leaf_with_content(
+            r#"{}"#,
+            r#"{"kind":"text","text":"ab","fontSize":20,"lineHeight":20}"#,
+        );
+        // Large text: 40px font, 40px lines → baseline 32
+        let large = engine.create_leaf_with_content(
+            r#"{}"#,
+            r#"{"kind":"text","text":"ab","fontSize":40,"lineHeight":40}"#,
+        );
+        let block = engine.create_node_with_children(r#"{"display":"block"}"#, &[small]);
+        let row = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"row","alignItems":"baseline","width":"400px"}"#,
+            &[block, large],
+        );
+        engine.compute_layout(row, 400.0, -1.0);
+
+        let r = engine.get_layouts_batch_with_baselines(&[block, large]);
+        // Baselines line up at 32: the block (baseline 16) moves down 16px
+        assert_eq!(r[1], 16.0);
+        assert_eq!(r[7], 0.0);
+        assert_eq!(r[4], 16.0, "block container takes its first child's baseline");
+    }
+
+    #[test]
+    fn test_baseline_synthesized_without_text() {
+        let mut engine = TaffyLayoutEngine::new();
+        let node = engine.create_node(r#"{"width":"50px","height":"30px"}"#);
+        engine.compute_layout(node, 100.0, 100.0);
+
+        let r = engine.get_layouts_batch_with_baselines(&[node]);
+        assert_eq!(&r[4..6], &[30.0, 30.0], "border-box bottom");
+    }
+
+    #[test]
+    fn test_set_leaf_content_remeasures() {
+        let mut engine = TaffyLayoutEngine::new();
+
+        let text = engine.create_leaf_with_content(
+            r#"{}"#,
+            r#"{"kind":"text","text":"abc","fontSize":20,"lineHeight":20}"#,
+        );
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"row","alignItems":"start","width":"400px"}"#,
+            &[text],
+        );
+        engine.compute_layout(root, 400.0, -1.0);
+        assert_eq!(engine.get_layouts_batch(&[text])[2], 30.0);
+
+        engine.set_leaf_content(text, r#"{"kind":"text","text":"abcdef","fontSize":20}"#);
+        engine.compute_layout(root, 400.0, -1.0);
+        assert_eq!(engine.get_layouts_batch(&[text])[2], 60.0);
+    }
+
+    #[test]
+    fn test_build_tree_batch_with_content() {
+        let mut engine = TaffyLayoutEngine::new();
+
+        let nodes_json = r#"[
+            {"style":{},"children":[],"content":{"kind":"intrinsic","width":160,"height":90}},
+            {"style":{"display":"flex","flexDirection":"column","alignItems":"start","width":"400px"},"children":[0]}
+        ]"#;
+        let handles = engine.build_tree_batch(nodes_json).expect("should succeed");
+        engine.compute_layout(handles[1], 400.0, -1.0);
+
+        let results = engine.get_layouts_batch(&[handles[0]]);
+        assert_eq!(results[2], 160.0);
+        assert_eq!(results[3], 90.0);
+    }
+
+    #[test]
+    fn test_build_tree_batch_binary_with_content() {
+        use crate::binary_protocol::encode::{NodeEncoder, build_taff};
+
+        let mut engine = TaffyLayoutEngine::new();
+        let data = build_taff(&[
+            NodeEncoder::new()
+                .grid_json(r#"{"content":{"kind":"intrinsic","width":160,"height":90}}"#)
+                .build(),
+            NodeEncoder::new()
+                .display(0)            // display: flex
+                .flex_direction(1)     // flexDirection: column
+                .align_items(0)        // alignItems: start
+                .width(1, 400.0)       // width: 400px
+                .children(&[0])
+                .build(),
+        ]);
+        let handles = engine.build_tree_batch_binary(&data).expect("binary batch");
+        engine.compute_layout(handles[1], 400.0, -1.0);
+
+        let results = engine.get_layouts_batch(&[handles[0]]);
+        assert_eq!(results[2], 160.0);
+        assert_eq!(results[3], 90.0);
+    }
+
+    #[test]
+    fn test_shorthands() {
+        let mut engine = TaffyLayoutEngine::new();
+        let c1 = engine.create_node(r#"{"flex":"1","margin":"0 4px","height":"20px"}"#);
+        let c2 = engine.create_node(r#"{"flex":1,"margin":"0 4px","marginLeft":"0px","height":"20px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexFlow":"row wrap","padding":"10px 20px","gap":"8px","width":"300px"}"#,
+            &[c1, c2],
+        );
+        engine.compute_layout(root, 300.0, -1.0);
+
+        // 260 content - 8 gap - 12 margins, split evenly
+        assert_eq!(layout_of(&engine, c1), (24.0, 10.0, 120.0, 20.0));
+        assert_eq!(layout_of(&engine, c2), (156.0, 10.0, 120.0, 20.0));
+        assert_eq!(layout_of(&engine, root).3, 40.0);
+    }
+
+    #[test]
+    fn test_build_tree_batch_with_shorthands() {
+        let mut engine = TaffyLayoutEngine::new();
+
+        let nodes_json = r#"[
+            {"style":{"gridArea":"1 / 2","width":"10px","height":"10px"},"children":[]},
+            {"style":{"display":"grid","gridTemplateColumns":["1fr","1fr"],"placeItems":"center","width":"200px","height":"100px"},"children":[0]}
+        ]"#;
+        let handles = engine.build_tree_batch(nodes_json).expect("should succeed");
+        engine.compute_layout(handles[1], 200.0, 100.0);
+        assert_eq!(layout_of(&engine, handles[0]), (145.0, 45.0, 10.0, 10.0));
+    }
+
+    #[test]
+    fn test_grid_template_areas() {
+        let mut engine = TaffyLayoutEngine::new();
+        let header = engine.create_node(r#"{"gridArea":"header"}"#);
+        let main = engine.create_node(r#"{"gridRowStart":"main","gridColumnStart":"main"}"#);
+        let sidebar = engine.create_node(r#"{"gridColumn":"sidebar","gridRow":"sidebar-start / sidebar-end"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"grid","gridTemplateColumns":["100px","1fr"],"gridTemplateRows":["40px","1fr"],"gridTemplateAreas":"\"header header\" \"sidebar main\"","width":"400px","height":"200px"}"#,
+            &[main, header, sidebar],
+        );
+        engine.compute_layout(root, 400.0, 200.0);
+
+        assert_eq!(layout_of(&engine, header), (0.0, 0.0, 400.0, 40.0));
+        assert_eq!(layout_of(&engine, sidebar), (0.0, 40.0, 100.0, 160.0));
+        assert_eq!(layout_of(&engine, main), (100.0, 40.0, 300.0, 160.0));
+    }
+
+    #[test]
+    fn test_grid_named_lines() {
+        let mut engine = TaffyLayoutEngine::new();
+        let wide = engine.create_node(r#"{"gridColumn":"full-start / full-end","height":"10px"}"#);
+        let body = engine.create_node(r#"{"gridColumn":"main","height":"10px"}"#);
+        let tail = engine.create_node(r#"{"gridColumn":"col 2 / span col","height":"10px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"grid","gridTemplateColumns":["[full-start] 50px [main-start]","repeat(2, [col] 100px)","[main-end] 50px [full-end]"],"width":"300px"}"#,
+            &[wide, body, tail],
+        );
+        engine.compute_layout(root, 300.0, -1.0);
+
+        assert_eq!(layout_of(&engine, wide), (0.0, 0.0, 300.0, 10.0));
+        assert_eq!(layout_of(&engine, body), (50.0, 10.0, 200.0, 10.0));
+        // 2nd `col` line to the next `col` line (implicit after the last one)
+        assert_eq!(layout_of(&engine, tail).0, 150.0);
+    }
+
+    #[test]
+    fn test_subgrid() {
+        let mut engine = TaffyLayoutEngine::new();
+        let label = engine.create_node(r#"{"height":"20px"}"#);
+        let input = engine.create_node(r#"{"height":"20px"}"#);
+        let form_row = engine.create_node_with_children(
+            r#"{"display":"grid","gridColumn":"1 / 3","gridTemplateColumns":["subgrid"],"padding":"5px"}"#,
+            &[label, input],
+        );
+        let root = engine.create_node_with_children(
+            r#"{"display":"grid","gridTemplateColumns":["100px","1fr"],"gap":"10px","width":"400px"}"#,
+            &[form_row],
+        );
+        engine.compute_layout(root, 400.0, -1.0);
+        // padding comes out of the outer tracks; the parent's gap is inherited
+        assert_eq!(layout_of(&engine, label), (5.0, 5.0, 95.0, 20.0));
+        assert_eq!(layout_of(&engine, input), (110.0, 5.0, 285.0, 20.0));
+
+        let title = engine.create_node(r#"{"gridRow":"head"}"#);
+        let body = engine.create_node(r#"{"gridRow":"mid"}"#);
+        let card = engine.create_node_with_children(
+            r#"{"display":"grid","gridRow":"span 2","gridTemplateRows":["subgrid [head] [] [end]"]}"#,
+            &[title, body],
+        );
+        let root = engine.create_node_with_children(
+            r#"{"display":"grid","gridTemplateRows":["[top] 40px [mid]","1fr [bottom]"],"rowGap":"10px","width":"200px","height":"200px"}"#,
+            &[card],
+        );
+        engine.compute_layout(root, 200.0, 200.0);
+        // `head` is the card's own name, `mid` the parent's
+        assert_eq!(layout_of(&engine, card), (0.0, 0.0, 200.0, 200.0));
+        assert_eq!(layout_of(&engine, title), (0.0, 0.0, 200.0, 40.0));
+        assert_eq!(layout_of(&engine, body), (0.0, 50.0, 200.0, 150.0));
+    }
+
+    #[test]
+    fn test_masonry() {
+        for (direction, mirror) in [("ltr", false), ("rtl", true)] {
+            let mut engine = TaffyLayoutEngine::new();
+            let cards: Vec<usize> = [100, 50, 80, 40, 60]
+                .iter()
+                .map(|h| engine.create_node(&format!(r#"{{"height":"{h}px"}}"#)))
+                .collect();
+            let pinned = engine.create_node(r#"{"gridColumn":"3","height":"30px"}"#);
+            let wide = engine.create_node(r#"{"gridColumn":"span 2","height":"20px"}"#);
+            let mut children = cards.clone();
+            children.extend([pinned, wide]);
+            let board = engine.create_node_with_children(
+                &format!(
+                    r#"{{"display":"grid","direction":"{direction}","gridTemplateColumns":["repeat(3, 1fr)"],"gridTemplateRows":["masonry"],"gap":"10px","width":"320px"}}"#
+                ),
+                &children,
+            );
+            engine.compute_layout(board, 320.0, -1.0);
+
+            let rect = |h| {
+                let (x, y, w, h) = layout_of(&engine, h);
+                (if mirror { 320.0 - x - w } else { x }, y, w, h)
+            };
+            // `pinned` goes first, at the top of the third column
+            assert_eq!(rect(pinned), (220.0, 0.0, 100.0, 30.0), "{direction}");
+            assert_eq!(rect(cards[0]), (0.0, 0.0, 100.0, 100.0), "{direction}");
+            assert_eq!(rect(cards[1]), (110.0, 0.0, 100.0, 50.0), "{direction}");
+            assert_eq!(rect(cards[2]), (220.0, 40.0, 100.0, 80.0), "{direction}");
+            assert_eq!(rect(cards[3]), (110.0, 60.0, 100.0, 40.0), "{direction}");
+            // a tie goes to the first column
+            assert_eq!(rect(cards[4]), (0.0, 110.0, 100.0, 60.0), "{direction}");
+            // below the taller of the last two columns, which end higher
+            assert_eq!(rect(wide), (110.0, 130.0, 210.0, 20.0), "{direction}");
+            assert_eq!(layout_of(&engine, board).3, 170.0, "{direction}");
+        }
+    }
+
+    #[test]
+    fn test_table_auto_layout() {
+        let mut engine = TaffyLayoutEngine::new();
+        let content = |engine: &mut TaffyLayoutEngine, w: u32, h: u32| {
+            engine.create_node(&format!(r#"{{"width":"{w}px","height":"{h}px"}}"#))
+        };
+        let cell = |engine: &mut TaffyLayoutEngine, extra: &str, child: usize| {
+            engine.create_node_with_children(&format!(r#"{{"display":"table-cell"{extra}}}"#), &[child])
+        };
+        let a_content = content(&mut engine, 60, 20);
+        let a = cell(&mut engine, "", a_content);
+        let b_content = content(&mut engine, 100, 30);
+        let b = cell(&mut engine, "", b_content);
+        let c_content = content(&mut engine, 200, 10);
+        let c = cell(&mut engine, r#","colSpan":2"#, c_content);
+        let r0 = engine.create_node_with_children(r#"{"display":"table-row"}"#, &[a, b]);
+        let r1 = engine.create_node_with_children(r#"{"display":"table-row"}"#, &[c]);
+        let tbody = engine.create_node_with_children(r#"{"display":"table-row-group"}"#, &[r0, r1]);
+        let table =
+            engine.create_node_with_children(r#"{"display":"table","borderSpacing":"10px"}"#, &[tbody]);
+        let root = engine.create_node_with_children(r#"{"display":"block","width":"500px"}"#, &[table]);
+        engine.compute_layout(root, 500.0, -1.0);
+
+        // columns: 60 / 100 max-content, plus 15 each from `c` (200 - 10 - 160)
+        assert_eq!(layout_of(&engine, table), (0.0, 0.0, 220.0, 70.0), "shrink-to-fit");
+        assert_eq!(layout_of(&engine, tbody), (10.0, 10.0, 200.0, 50.0));
+        assert_eq!(layout_of(&engine, r0), (0.0, 0.0, 200.0, 30.0));
+        assert_eq!(layout_of(&engine, r1), (0.0, 40.0, 200.0, 10.0));
+        assert_eq!(layout_of(&engine, a), (0.0, 0.0, 75.0, 30.0));
+        assert_eq!(layout_of(&engine, b), (85.0, 0.0, 115.0, 30.0));
+        assert_eq!(layout_of(&engine, c), (0.0, 0.0, 200.0, 10.0));
+
+        // a change deep in a cell reaches the table
+        engine.update_style(a_content, r#"{"width":"80px","height":"20px"}"#);
+        engine.compute_layout(root, 500.0, -1.0);
+        assert_eq!(layout_of(&engine, a), (0.0, 0.0, 85.0, 30.0));
+        assert_eq!(layout_of(&engine, b), (95.0, 0.0, 105.0, 30.0));
+    }
+
+    #[test]
+    fn test_table_fixed_layout_with_rowspan_and_sections() {
+        for (direction, mirror) in [("ltr", false), ("rtl", true)] {
+            let mut engine = TaffyLayoutEngine::new();
+            let caption = engine.create_node(r#"{"display":"table-caption","height":"15px"}"#);
+            let footer_cell = engine.create_node(r#"{"display":"table-cell","height":"10px"}"#);
+            let footer_row = engine.create_node_with_children(r#"{"display":"table-row"}"#, &[footer_cell]);
+            let tfoot = engine.create_node_with_children(r#"{"display":"table-footer-group"}"#, &[footer_row]);
+            let tall = engine.create_node(r#"{"display":"table-cell","rowSpan":2,"width":"100px","height":"80px"}"#);
+            let x0 = engine.create_node(r#"{"display":"table-cell","height":"20px"}"#);
+            let y0 = engine.create_node(r#"{"display":"table-cell","height":"20px"}"#);
+            let x1 = engine.create_node(r#"{"display":"table-cell","height":"20px"}"#);
+            let y1 = engine.create_node(r#"{"display":"table-cell","height":"20px"}"#);
+            let r0 = engine.create_node_with_children(r#"{"display":"table-row"}"#, &[tall, x0, y0]);
+            let r1 = engine.create_node_with_children(r#"{"display":"table-row"}"#, &[x1, y1]);
+            let tbody = engine.create_node_with_children(r#"{"display":"table-row-group"}"#, &[r0, r1]);
+            let table = engine.create_node_with_children(
+                &format!(r#"{{"display":"table","tableLayout":"fixed","direction":"{direction}","width":"300px"}}"#),
+                &[tfoot, caption, tbody],
+            );
+            engine.compute_layout(table, 300.0, -1.0);
+
+            let rect = |h| {
+                let (x, y, w, h) = layout_of(&engine, h);
+                (if mirror { 300.0 - x - w } else { x }, y, w, h)
+            };
+            assert_eq!(layout_of(&engine, table), (0.0, 0.0, 300.0, 105.0), "{direction}");
+            assert_eq!(rect(caption), (0.0, 0.0, 300.0, 15.0), "{direction}");
+            // the body's rows grow evenly to fit the 80px rowspan cell
+            assert_eq!(rect(tbody), (0.0, 15.0, 300.0, 80.0), "{direction}");
+            assert_eq!(rect(r1), (0.0, 40.0, 300.0, 40.0), "{direction}");
+            assert_eq!(rect(tall), (0.0, 0.0, 100.0, 80.0), "{direction}");
+            assert_eq!(rect(y0), (200.0, 0.0, 100.0, 40.0), "{direction}");
+            // column 0 of the second row is taken by `tall`
+            assert_eq!(rect(x1), (100.0, 0.0, 100.0, 40.0), "{direction}");
+            // the footer goes last whatever its position among the children
+            assert_eq!(rect(tfoot), (0.0, 95.0, 300.0, 10.0), "{direction}");
+            assert_eq!(rect(footer_cell), (0.0, 0.0, 100.0, 10.0), "{direction}");
+        }
+    }
+
     #[test]
     fn test_grid_repeat_fixed() {
         // repeat(3, 1fr) → 3 equal columns
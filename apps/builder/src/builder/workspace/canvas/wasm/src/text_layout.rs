@@ -0,0 +1,824 @@
+//! Text line breaking and wrapped text metrics.
+//!
+//! Breaks a text run into lines at a given width and reports line count,
+//! per-line widths, total height, first/last baselines and intrinsic
+//! (min-content / max-content) widths. Content leaves of `TaffyLayoutEngine`
+//! are measured with this module, so intrinsic and wrapped sizes come from
+//! the same code that performs the layout.
+//!
+//! # Design decisions
+//! - Break opportunities follow a simplified UAX #14: after collapsible
+//!   spaces, around CJK ideographs / kana / hangul, after hyphens and ZWSP,
+//!   with mandatory breaks at newlines.
+//! - CJK line-start / line-end prohibitions (kinsoku) are honoured: no break
+//!   before closing punctuation or small kana, none after opening brackets.
+//! - Widths are measured per unbreakable segment through `TextMeasurer`, so
+//!   registered font metrics and the JS measurer both apply.
+//! - `white-space`, `word-break` and `overflow-wrap` follow CSS Text 3:
+//!   collapsing and forced breaks are applied during segmentation, wrapping
+//!   and emergency (per-character) breaks during line filling.
+//! - `-webkit-line-clamp` and `text-overflow: ellipsis` are applied after
+//!   line breaking by `fit_text`; only the last visible line is shortened.
+
+use wasm_bindgen::prelude::*;
+
+use crate::font_metrics::{is_wide_char, MetricsTextMeasurer};
+use crate::text_measure::{
+    resolve_line_height, FallbackTextMeasurer, FontSpec, TextContent, TextMeasurer, TextOverflow,
+    WhiteSpace, WordBreak,
+};
+
+/// Tolerance for float accumulation when testing whether a segment fits.
+const FIT_EPSILON: f32 = 0.01;
+
+/// Tab width in spaces for preserved white space (CSS `tab-size` initial value).
+const TAB_SIZE: f32 = 8.0;
+
+/// Number of header fields preceding line widths in `layout_text` output.
+pub const TEXT_LAYOUT_HEADER: usize = 6;
+
+// ─── Break classes ───────────────────────────────────────────────────
+
+/// Simplified UAX #14 line breaking classes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum BreakClass {
+    /// Ideographs, kana, hangul: break allowed on either side (ID).
+    Ideographic,
+    /// Opening punctuation: no break after (OP).
+    Open,
+    /// Closing punctuation and kinsoku line-start prohibited chars (CL/NS/EX).
+    Close,
+    /// Hyphens: break after when followed by letters (HY/BA).
+    Hyphen,
+    /// Zero width space: break after (ZW).
+    ZeroWidthSpace,
+    /// Non-breaking glue (GL/WJ).
+    Glue,
+    Numeric,
+    Alphabetic,
+}
+
+fn break_class(ch: char) -> BreakClass {
+    match ch {
+        '(' | '[' | '{' | '\u{2018}' | '\u{201C}' | '「' | '『' | '【' | '〔' | '〈' | '《'
+        | '（' | '［' | '｛' | '〘' | '〖' => BreakClass::Open,
+        ')' | ']' | '}' | ',' | '.' | ':' | ';' | '!' | '?' | '\u{2019}' | '\u{201D}' | '、'
+        | '。' | '，' | '．' | '：' | '；' | '？' | '！' | '」' | '』' | '】' | '〕' | '〉'
+        | '》' | '）' | '］' | '｝' | '〙' | '〗' | '・' | 'ー' | '々' | '〜' | '‼' | '⁇'
+        | '⁈' | '⁉' | 'ぁ' | 'ぃ' | 'ぅ' | 'ぇ' | 'ぉ' | 'っ' | 'ゃ' | 'ゅ' | 'ょ' | 'ゎ'
+        | 'ァ' | 'ィ' | 'ゥ' | 'ェ' | 'ォ' | 'ッ' | 'ャ' | 'ュ' | 'ョ' | 'ヮ' | 'ヵ' | 'ヶ' => {
+            BreakClass::Close
+        }
+        '-' | '\u{00AD}' | '\u{2010}' | '\u{2013}' => BreakClass::Hyphen,
+        '\u{200B}' => BreakClass::ZeroWidthSpace,
+        '\u{00A0}' | '\u{202F}' | '\u{2007}' | '\u{2060}' | '\u{FEFF}' => BreakClass::Glue,
+        '0'..='9' => BreakClass::Numeric,
+        c if is_wide_char(c) => BreakClass::Ideographic,
+        _ => BreakClass::Alphabetic,
+    }
+}
+
+/// Whether a line may break between two adjacent non-space characters.
+fn break_between(before: char, after: char, word_break: WordBreak) -> bool {
+    use BreakClass::*;
+    let class = |ch: char| match (break_class(ch), word_break) {
+        // break-all: letters and digits break like ideographs
+        (Alphabetic | Numeric, WordBreak::BreakAll) => Ideographic,
+        (c, _) => c,
+    };
+    match (class(before), class(after)) {
+        (_, Close) | (Open, _) | (Glue, _) | (_, Glue) | (_, ZeroWidthSpace) => false,
+        (ZeroWidthSpace, _) | (Close, Open) => true,
+        // keep-all: no breaks inside words of any script
+        (Ideographic | Alphabetic | Numeric, Ideographic | Alphabetic | Numeric)
+            if word_break == WordBreak::KeepAll =>
+        {
+            false
+        }
+        (Ideographic, _) | (_, Ideographic) => true,
+        (Hyphen, Alphabetic) => true,
+        _ => false,
+    }
+}
+
+/// Spaces collapsed under `white-space: normal` (newlines handled separately).
+pub(crate) fn is_collapsible_space(ch: char) -> bool {
+    matches!(ch, ' ' | '\t')
+}
+
+fn is_newline(ch: char) -> bool {
+    matches!(ch, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+// ─── Segmentation ────────────────────────────────────────────────────
+
+/// Unbreakable run between two break opportunities.
+#[derive(Clone, Debug)]
+pub(crate) struct Segment {
+    /// Byte range of the visible text (hanging spaces excluded).
+    pub start: usize,
+    pub end: usize,
+    pub width: f32,
+    /// Width of the spaces after the segment; they hang at the end of a line
+    /// and only count when more content follows on the same line.
+    pub space_after: f32,
+    /// A forced line break follows this segment.
+    pub mandatory: bool,
+}
+
+/// Split a text run into unbreakable segments at its break opportunities,
+/// applying `white-space` collapsing and `word-break` rules.
+pub(crate) fn segment_text(content: &TextContent, measurer: &dyn TextMeasurer) -> Vec<Segment> {
+    let text = content.text.as_str();
+    let font = &content.font;
+    let ws = content.white_space;
+    let collapse = ws.collapses_spaces();
+    let keep_newlines = ws.preserves_newlines();
+
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let n = chars.len();
+    let byte_at = |i: usize| if i < n { chars[i].0 } else { text.len() };
+    let space_w = measurer.measure_width(" ", font);
+    let is_space = |ch: char| is_collapsible_space(ch) || (!keep_newlines && is_newline(ch));
+    let space_width = |ch: char| if ch == '\t' && !collapse { space_w * TAB_SIZE } else { space_w };
+    let make = |start: usize, end: usize, space_after: f32, mandatory: bool| {
+        let (sb, eb) = (byte_at(start), byte_at(end));
+        let width = if eb > sb { measurer.measure_width(&text[sb..eb], font) } else { 0.0 };
+        Segment { start: sb, end: eb, width, space_after, mandatory }
+    };
+    let skip_spaces = |mut i: usize| {
+        while i < n && is_space(chars[i].1) {
+            i += 1;
+        }
+        i
+    };
+
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut i = if collapse { skip_spaces(0) } else { 0 };
+    let mut start = i;
+    while i < n {
+        let ch = chars[i].1;
+        if keep_newlines && is_newline(ch) {
+            let mut next = i + 1;
+            if ch == '\r' && next < n && chars[next].1 == '\n' {
+                next += 1;
+            }
+            segments.push(make(start, i, 0.0, true));
+            i = if collapse { skip_spaces(next) } else { next };
+            start = i;
+            continue;
+        }
+        if is_space(ch) {
+            let word_end = i;
+            i = skip_spaces(i);
+            let followed = i < n && !(keep_newlines && is_newline(chars[i].1));
+            if ws == WhiteSpace::BreakSpaces {
+                // Spaces take up width; a break is allowed after each of them
+                // (the first stays attached to the preceding word).
+                let mut word = make(start, word_end + 1, 0.0, false);
+                word.width = make(start, word_end, 0.0, false).width + space_width(ch);
+                segments.push(word);
+                for (k, &(_, sp_ch)) in chars.iter().enumerate().take(i).skip(word_end + 1) {
+                    let mut sp = make(k, k + 1, 0.0, false);
+                    sp.width = space_width(sp_ch);
+                    segments.push(sp);
+                }
+            } else if collapse {
+                // Spaces before a newline or the end of text are removed.
+                segments.push(make(start, word_end, if followed { space_w } else { 0.0 }, false));
+            } else {
+                // pre / pre-wrap: preserved spaces hang at the line end
+                let preserved = (word_end..i).map(|k| space_width(chars[k].1)).sum();
+                segments.push(make(start, word_end, preserved, false));
+            }
+            start = i;
+            continue;
+        }
+        if i + 1 < n {
+            let next = chars[i + 1].1;
+            if !(is_space(next) || keep_newlines && is_newline(next))
+                && break_between(ch, next, content.word_break)
+            {
+                segments.push(make(start, i + 1, 0.0, false));
+                start = i + 1;
+            }
+        }
+        i += 1;
+    }
+    if start < n {
+        segments.push(make(start, n, 0.0, false));
+    }
+
+    // A newline right after spaces terminates the preceding segment instead of
+    // producing an empty one (which would otherwise keep the hanging space).
+    let mut merged: Vec<Segment> = Vec::with_capacity(segments.len());
+    for seg in segments {
+        if let Some(prev) = merged.last_mut() {
+            if seg.mandatory && seg.start == seg.end && !prev.mandatory {
+                prev.mandatory = true;
+                continue;
+            }
+        }
+        merged.push(seg);
+    }
+    merged
+}
+
+/// Split a segment into one piece per character (emergency wrapping for
+/// `overflow-wrap: break-word | anywhere`).
+pub(crate) fn split_segment(content: &TextContent, seg: &Segment, measurer: &dyn TextMeasurer) -> Vec<Segment> {
+    let mut pieces: Vec<Segment> = content.text[seg.start..seg.end]
+        .char_indices()
+        .map(|(off, ch)| {
+            let start = seg.start + off;
+            let end = start + ch.len_utf8();
+            Segment {
+                start,
+                end,
+                width: measurer.measure_width(&content.text[start..end], &content.font),
+                space_after: 0.0,
+                mandatory: false,
+            }
+        })
+        .collect();
+    match pieces.last_mut() {
+        Some(last) => {
+            last.space_after = seg.space_after;
+            last.mandatory = seg.mandatory;
+            pieces
+        }
+        None => vec![seg.clone()],
+    }
+}
+
+// ─── Line filling ────────────────────────────────────────────────────
+
+/// One laid-out line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineBox {
+    /// Byte range in the source text (trailing spaces excluded).
+    pub start: usize,
+    pub end: usize,
+    pub width: f32,
+}
+
+/// Result of breaking a text run at a given width.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLayout {
+    pub lines: Vec<LineBox>,
+    pub line_height: f32,
+    pub height: f32,
+    /// Baseline of the first line, from the top of the text box.
+    pub first_baseline: f32,
+    /// Baseline of the last line, from the top of the text box.
+    pub last_baseline: f32,
+}
+
+impl TextLayout {
+    /// Width of the longest line.
+    pub fn widest(&self) -> f32 {
+        self.lines.iter().fold(0.0, |acc, l| acc.max(l.width))
+    }
+
+    /// Drop lines past `max_lines` (at least one line is kept).
+    /// Returns whether any line was removed.
+    pub fn clamp_lines(&mut self, max_lines: usize) -> bool {
+        let max_lines = max_lines.max(1);
+        if self.lines.len() <= max_lines {
+            return false;
+        }
+        self.lines.truncate(max_lines);
+        self.height = max_lines as f32 * self.line_height;
+        self.last_baseline = (max_lines - 1) as f32 * self.line_height + self.first_baseline;
+        true
+    }
+}
+
+/// Greedy line filler: a segment moves to the next line when it does not
+/// fit, unless it is the first on its line (overflowing segments stay whole).
+#[derive(Default)]
+struct LineFiller {
+    lines: Vec<LineBox>,
+    current: Option<LineBox>,
+    pending_space: f32,
+}
+
+impl LineFiller {
+    fn place(&mut self, seg: &Segment, max_width: f32) {
+        match self.current.as_mut() {
+            Some(line) if line.width + self.pending_space + seg.width <= max_width + FIT_EPSILON => {
+                line.width += self.pending_space + seg.width;
+                line.end = seg.end;
+            }
+            _ => {
+                self.lines.extend(self.current.take());
+                self.current = Some(LineBox { start: seg.start, end: seg.end, width: seg.width });
+            }
+        }
+        self.pending_space = seg.space_after;
+        if seg.mandatory {
+            self.break_line();
+        }
+    }
+
+    fn break_line(&mut self) {
+        self.lines.extend(self.current.take());
+        self.pending_space = 0.0;
+    }
+
+    fn is_empty_line(&self) -> bool {
+        self.current.is_none()
+    }
+
+    fn finish(mut self) -> Vec<LineBox> {
+        self.lines.extend(self.current.take());
+        self.lines
+    }
+}
+
+fn fill_lines(
+    content: &TextContent,
+    segments: &[Segment],
+    max_width: f32,
+    measurer: &dyn TextMeasurer,
+) -> Vec<LineBox> {
+    let emergency = content.emergency_wrap().is_some();
+    let mut filler = LineFiller::default();
+    for seg in segments {
+        if emergency && seg.width > max_width + FIT_EPSILON {
+            // Try a fresh line first, then break inside the segment.
+            if !filler.is_empty_line() {
+                filler.break_line();
+            }
+            for piece in split_segment(content, seg, measurer) {
+                filler.place(&piece, max_width);
+            }
+        } else {
+            filler.place(seg, max_width);
+        }
+    }
+    filler.finish()
+}
+
+/// Break `content` into lines no wider than `max_width` (∞ = no wrapping).
+pub fn layout(content: &TextContent, max_width: f32, measurer: &dyn TextMeasurer) -> TextLayout {
+    let wrap_width = if content.white_space.wraps() { max_width } else { f32::INFINITY };
+    let segments = segment_text(content, measurer);
+    let lines = fill_lines(content, &segments, wrap_width, measurer);
+    let line_height = resolve_line_height(content, measurer);
+
+    let (ascent, descent) = measurer.vertical_metrics(&content.font);
+    let half_leading = (line_height - (ascent + descent)) / 2.0;
+    let first_baseline = if lines.is_empty() { 0.0 } else { half_leading + ascent };
+    let last_baseline = if lines.is_empty() {
+        0.0
+    } else {
+        (lines.len() - 1) as f32 * line_height + first_baseline
+    };
+
+    TextLayout {
+        height: lines.len() as f32 * line_height,
+        lines,
+        line_height,
+        first_baseline,
+        last_baseline,
+    }
+}
+
+/// (min-content, max-content) widths of `content`.
+pub fn intrinsic_widths(content: &TextContent, measurer: &dyn TextMeasurer) -> (f32, f32) {
+    let segments = segment_text(content, measurer);
+    let max = fill_lines(content, &segments, f32::INFINITY, measurer)
+        .iter()
+        .fold(0.0f32, |acc, l| acc.max(l.width));
+    if !content.white_space.wraps() {
+        return (max, max);
+    }
+    let min = if content.emergency_wrap() == Some(true) {
+        // overflow-wrap: anywhere — every character is a break opportunity
+        segments
+            .iter()
+            .flat_map(|s| split_segment(content, s, measurer))
+            .fold(0.0f32, |acc, s| acc.max(s.width))
+    } else {
+        segments.iter().fold(0.0f32, |acc, s| acc.max(s.width))
+    };
+    (min, max)
+}
+
+// ─── Truncation ──────────────────────────────────────────────────────
+
+/// Appended to text cut by `text-overflow: ellipsis` or a line clamp.
+pub const ELLIPSIS: &str = "\u{2026}";
+
+/// A text run fitted into a box by `fit_text`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextFit {
+    /// Byte offset where the visible text ends (`text.len()` when uncut).
+    pub cut: usize,
+    /// Whether `ELLIPSIS` is drawn after `text[..cut]`.
+    pub ellipsis: bool,
+    /// Visible lines; the last line's width includes the ellipsis.
+    pub layout: TextLayout,
+}
+
+impl TextFit {
+    /// Text as rendered: the visible prefix plus the ellipsis, if any.
+    pub fn display_text(&self, content: &TextContent) -> String {
+        let visible = &content.text[..self.cut];
+        if self.ellipsis {
+            format!("{visible}{ELLIPSIS}")
+        } else {
+            visible.to_string()
+        }
+    }
+}
+
+/// Break `content` at `max_width`, then apply `line_clamp` and
+/// `text_overflow`.
+///
+/// A clamped run always ends in an ellipsis (as `-webkit-line-clamp` does);
+/// an unclamped one only when `text-overflow: ellipsis` is set and its last
+/// line overflows `max_width` (e.g. `white-space: nowrap`). The ellipsis
+/// replaces as many trailing characters as needed to fit.
+pub fn fit_text(content: &TextContent, max_width: f32, measurer: &dyn TextMeasurer) -> TextFit {
+    let mut layout = layout(content, max_width, measurer);
+    let clamped = content.line_clamp.is_some_and(|n| layout.clamp_lines(n as usize));
+    let Some(last) = layout.lines.last_mut() else {
+        return TextFit { cut: content.text.len(), ellipsis: false, layout };
+    };
+
+    let overflows = last.width > max_width + FIT_EPSILON;
+    let ellipsis = clamped || (overflows && content.text_overflow == TextOverflow::Ellipsis);
+    if !ellipsis {
+        let cut = if clamped { last.end } else { content.text.len() };
+        return TextFit { cut, ellipsis: false, layout };
+    }
+
+    // Longest prefix of the last line that still fits next to the ellipsis.
+    // Prefix widths grow with length, so binary search the char boundaries.
+    let text = &content.text;
+    let room = max_width - measurer.measure_width(ELLIPSIS, &content.font);
+    let measure = |end: usize| measurer.measure_width(text[last.start..end].trim_end(), &content.font);
+    let mut bounds: Vec<usize> = text[last.start..last.end]
+        .char_indices()
+        .map(|(off, _)| last.start + off)
+        .collect();
+    bounds.push(last.end);
+    let (mut lo, mut hi) = (0, bounds.len() - 1);
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if measure(bounds[mid]) <= room + FIT_EPSILON {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+
+    let cut = last.start + text[last.start..bounds[lo]].trim_end().len();
+    last.end = cut;
+    last.width = measure(cut) + measurer.measure_width(ELLIPSIS, &content.font);
+    TextFit { cut, ellipsis: true, layout }
+}
+
+// ─── WASM export ─────────────────────────────────────────────────────
+
+/// Lay out a text run with the registered font metrics (character-count
+/// fallback for unknown fonts).
+///
+/// # Arguments
+/// * `line_height` - px, or ≤ 0 for `normal`
+/// * `max_width` - wrap width in px, or < 0 for no wrapping
+///
+/// # Returns
+/// `[lineCount, height, firstBaseline, lastBaseline, minContent, maxContent,
+///   lineWidth0, lineWidth1, ...]`
+#[wasm_bindgen]
+pub fn layout_text(
+    text: &str,
+    font_family: &str,
+    font_size: f32,
+    font_weight: u16,
+    line_height: f32,
+    max_width: f32,
+) -> Box<[f32]> {
+    let content = TextContent {
+        text: text.to_string(),
+        font: FontSpec {
+            font_family: font_family.to_string(),
+            font_size,
+            font_weight,
+        },
+        line_height: (line_height > 0.0).then_some(line_height),
+        ..TextContent::default()
+    };
+    encode_text_layout(&content, max_width)
+}
+
+/// `layout_text` for a full text run descriptor, including `whiteSpace`,
+/// `wordBreak` and `overflowWrap`.
+///
+/// `content_json`: `{"text":"...","fontSize":14,"whiteSpace":"pre-wrap",...}`.
+/// An unparsable descriptor lays out as empty text. Output as `layout_text`.
+#[wasm_bindgen]
+pub fn layout_text_run(content_json: &str, max_width: f32) -> Box<[f32]> {
+    let content: TextContent = serde_json::from_str(content_json).unwrap_or_default();
+    encode_text_layout(&content, max_width)
+}
+
+/// Fit a text run descriptor into a box of `max_width`, honouring its
+/// `lineClamp` and `textOverflow`.
+///
+/// # Returns
+/// `[cut, ellipsis, lineCount, height, lastBaseline, lastLineWidth]` where
+/// `cut` is a UTF-16 offset: render `text.slice(0, cut)`, followed by "…"
+/// when `ellipsis` is 1.
+#[wasm_bindgen]
+pub fn fit_text_run(content_json: &str, max_width: f32) -> Box<[f32]> {
+    let content: TextContent = serde_json::from_str(content_json).unwrap_or_default();
+    let measurer = MetricsTextMeasurer { fallback: Box::new(FallbackTextMeasurer) };
+    let wrap = if max_width < 0.0 { f32::INFINITY } else { max_width };
+
+    let fit = fit_text(&content, wrap, &measurer);
+    Box::new([
+        content.text[..fit.cut].encode_utf16().count() as f32,
+        if fit.ellipsis { 1.0 } else { 0.0 },
+        fit.layout.lines.len() as f32,
+        fit.layout.height,
+        fit.layout.last_baseline,
+        fit.layout.lines.last().map_or(0.0, |l| l.width),
+    ])
+}
+
+fn encode_text_layout(content: &TextContent, max_width: f32) -> Box<[f32]> {
+    let measurer = MetricsTextMeasurer { fallback: Box::new(FallbackTextMeasurer) };
+    let wrap = if max_width < 0.0 { f32::INFINITY } else { max_width };
+
+    let result = layout(content, wrap, &measurer);
+    let (min_content, max_content) = intrinsic_widths(content, &measurer);
+
+    let mut out = Vec::with_capacity(TEXT_LAYOUT_HEADER + result.lines.len());
+    out.extend_from_slice(&[
+        result.lines.len() as f32,
+        result.height,
+        result.first_baseline,
+        result.last_baseline,
+        min_content,
+        max_content,
+    ]);
+    out.extend(result.lines.iter().map(|l| l.width));
+    out.into_boxed_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text_measure::OverflowWrap;
+
+    /// 10px per character regardless of font.
+    struct FixedMeasurer;
+
+    impl TextMeasurer for FixedMeasurer {
+        fn measure_width(&self, text: &str, _font: &FontSpec) -> f32 {
+            text.chars().count() as f32 * 10.0
+        }
+    }
+
+    fn content(s: &str) -> TextContent {
+        TextContent {
+            text: s.to_string(),
+            font: FontSpec { font_size: 10.0, ..FontSpec::default() },
+            line_height: Some(20.0),
+            ..TextContent::default()
+        }
+    }
+
+    fn line_texts<'a>(s: &'a str, layout: &TextLayout) -> Vec<&'a str> {
+        layout.lines.iter().map(|l| &s[l.start..l.end]).collect()
+    }
+
+    #[test]
+    fn test_wraps_at_spaces() {
+        let s = "aaa bbb ccc";
+        let l = layout(&content(s), 75.0, &FixedMeasurer);
+        assert_eq!(line_texts(s, &l), vec!["aaa bbb", "ccc"]);
+        assert_eq!(l.lines[0].width, 70.0);
+        assert_eq!(l.height, 40.0);
+    }
+
+    #[test]
+    fn test_collapses_spaces_and_trims_line_ends() {
+        let s = "  aa   bb  ";
+        let l = layout(&content(s), f32::INFINITY, &FixedMeasurer);
+        assert_eq!(line_texts(s, &l), vec!["aa   bb"]);
+        assert_eq!(l.lines[0].width, 50.0, "inner spaces collapse to one");
+    }
+
+    #[test]
+    fn test_mandatory_breaks() {
+        let s = "aa \n\nbb\n";
+        let c = TextContent { white_space: WhiteSpace::PreLine, ..content(s) };
+        let l = layout(&c, f32::INFINITY, &FixedMeasurer);
+        assert_eq!(line_texts(s, &l), vec!["aa", "", "bb"]);
+        assert_eq!(l.lines[0].width, 20.0, "space before newline is removed");
+
+        let l = layout(&content(s), f32::INFINITY, &FixedMeasurer);
+        assert_eq!(l.lines.len(), 1, "white-space: normal collapses newlines");
+        assert_eq!(l.lines[0].width, 50.0);
+    }
+
+    #[test]
+    fn test_cjk_breaks_between_ideographs_with_kinsoku() {
+        // 、 and 。 may not start a line; 「 may not end one.
+        let s = "日本語、「漢字」。";
+        let l = layout(&content(s), 40.0, &FixedMeasurer);
+        let lines = line_texts(s, &l);
+        assert!(lines.iter().all(|t| !t.starts_with(['、', '。', '」'])), "{lines:?}");
+        assert!(lines.iter().all(|t| !t.ends_with('「')), "{lines:?}");
+        assert_eq!(lines.concat(), s);
+    }
+
+    #[test]
+    fn test_hangul_breaks_between_syllables() {
+        let s = "안녕하세요";
+        let l = layout(&content(s), 25.0, &FixedMeasurer);
+        assert_eq!(l.lines.len(), 3);
+    }
+
+    #[test]
+    fn test_hyphen_and_glue() {
+        let s = "well-known\u{00A0}fact";
+        let (min, max) = intrinsic_widths(&content(s), &FixedMeasurer);
+        assert_eq!(min, 100.0, "break after hyphen: 'known\\u00A0fact'");
+        assert_eq!(max, 150.0);
+    }
+
+    #[test]
+    fn test_baselines_with_half_leading() {
+        // 10px font: ascent 8, descent 2; line-height 20 → half-leading 5.
+        let l = layout(&content("aaa bbb"), 35.0, &FixedMeasurer);
+        assert_eq!(l.first_baseline, 13.0);
+        assert_eq!(l.last_baseline, 33.0);
+    }
+
+    #[test]
+    fn test_empty_text_has_no_lines() {
+        let l = layout(&content(""), 100.0, &FixedMeasurer);
+        assert!(l.lines.is_empty());
+        assert_eq!(l.height, 0.0);
+    }
+
+    #[test]
+    fn test_layout_text_export() {
+        // Fallback measurer: 20px font → 10px per char, normal line height 24.
+        let out = layout_text("aaaa bb", "Unregistered", 20.0, 400, -1.0, 50.0);
+        assert_eq!(&out[..TEXT_LAYOUT_HEADER], &[2.0, 48.0, 18.0, 42.0, 40.0, 70.0]);
+        assert_eq!(&out[TEXT_LAYOUT_HEADER..], &[40.0, 20.0]);
+    }
+
+    fn with_modes(s: &str, ws: WhiteSpace, wb: WordBreak, ow: OverflowWrap) -> TextContent {
+        TextContent { white_space: ws, word_break: wb, overflow_wrap: ow, ..content(s) }
+    }
+
+    #[test]
+    fn test_nowrap_and_pre() {
+        let c = with_modes("aa bb\ncc", WhiteSpace::Nowrap, WordBreak::Normal, OverflowWrap::Normal);
+        let l = layout(&c, 30.0, &FixedMeasurer);
+        assert_eq!(l.lines.len(), 1, "newline collapses, no wrapping");
+        assert_eq!(l.lines[0].width, 80.0);
+        assert_eq!(intrinsic_widths(&c, &FixedMeasurer), (80.0, 80.0));
+
+        let s = "  aa  bb\ncc";
+        let c = with_modes(s, WhiteSpace::Pre, WordBreak::Normal, OverflowWrap::Normal);
+        let l = layout(&c, 30.0, &FixedMeasurer);
+        assert_eq!(l.lines.len(), 2);
+        assert_eq!(l.lines[0].width, 80.0, "leading and inner spaces preserved");
+    }
+
+    #[test]
+    fn test_pre_wrap_hangs_trailing_spaces() {
+        let s = "aa    bb";
+        let c = with_modes(s, WhiteSpace::PreWrap, WordBreak::Normal, OverflowWrap::Normal);
+        let l = layout(&c, 50.0, &FixedMeasurer);
+        assert_eq!(line_texts(s, &l), vec!["aa", "bb"]);
+        assert_eq!(l.lines[0].width, 20.0, "hanging spaces do not count");
+        assert_eq!(layout(&c, 100.0, &FixedMeasurer).lines[0].width, 80.0);
+    }
+
+    #[test]
+    fn test_pre_line_keeps_newlines_collapses_spaces() {
+        let s = "aa   bb\n  cc";
+        let c = with_modes(s, WhiteSpace::PreLine, WordBreak::Normal, OverflowWrap::Normal);
+        let l = layout(&c, 100.0, &FixedMeasurer);
+        assert_eq!(line_texts(s, &l), vec!["aa   bb", "cc"]);
+        assert_eq!(l.lines[0].width, 50.0);
+    }
+
+    #[test]
+    fn test_break_spaces_wraps_after_spaces() {
+        let s = "aa   bb";
+        let c = with_modes(s, WhiteSpace::BreakSpaces, WordBreak::Normal, OverflowWrap::Normal);
+        let l = layout(&c, 40.0, &FixedMeasurer);
+        // "aa " + " " fill 40; the third space and "bb" wrap
+        assert_eq!(l.lines.len(), 2);
+        assert_eq!(l.lines[0].width, 40.0, "spaces take up width");
+        assert_eq!(&s[l.lines[1].start..l.lines[1].end], " bb");
+    }
+
+    #[test]
+    fn test_word_break_modes() {
+        let c = with_modes("abcdef", WhiteSpace::Normal, WordBreak::BreakAll, OverflowWrap::Normal);
+        assert_eq!(layout(&c, 25.0, &FixedMeasurer).lines.len(), 3);
+        assert_eq!(intrinsic_widths(&c, &FixedMeasurer).0, 10.0);
+
+        let c = with_modes("안녕하세요 세계", WhiteSpace::Normal, WordBreak::KeepAll, OverflowWrap::Normal);
+        let l = layout(&c, 30.0, &FixedMeasurer);
+        assert_eq!(l.lines.len(), 2, "hangul words are not split");
+        assert_eq!(intrinsic_widths(&c, &FixedMeasurer).0, 50.0);
+    }
+
+    #[test]
+    fn test_overflow_wrap_emergency_breaks() {
+        let s = "a abcdefg";
+        let c = with_modes(s, WhiteSpace::Normal, WordBreak::Normal, OverflowWrap::BreakWord);
+        let l = layout(&c, 30.0, &FixedMeasurer);
+        // "a" stays alone, the long word moves down and breaks every 3 chars
+        assert_eq!(line_texts(s, &l), vec!["a", "abc", "def", "g"]);
+        assert_eq!(intrinsic_widths(&c, &FixedMeasurer).0, 70.0, "break-word keeps min-content");
+
+        let c = TextContent { overflow_wrap: OverflowWrap::Anywhere, ..c };
+        assert_eq!(intrinsic_widths(&c, &FixedMeasurer).0, 10.0, "anywhere shrinks min-content");
+        let l = layout(&content(s), 30.0, &FixedMeasurer);
+        assert_eq!(l.lines[1].width, 70.0, "normal: long word overflows");
+    }
+
+    #[test]
+    fn test_line_clamp_ends_with_ellipsis() {
+        let s = "aa bb cc dd ee";
+        let c = TextContent { line_clamp: Some(2), ..content(s) };
+        let fit = fit_text(&c, 50.0, &FixedMeasurer);
+        assert_eq!(fit.layout.lines.len(), 2);
+        assert_eq!(fit.layout.height, 40.0);
+        // "cc dd" fills the line; "cc d" + "…" fits in 50
+        assert_eq!(fit.display_text(&c), "aa bb cc d\u{2026}");
+        assert_eq!(fit.layout.lines[1].width, 50.0);
+
+        // Room for the whole line plus the ellipsis
+        let fit = fit_text(&c, 60.0, &FixedMeasurer);
+        assert_eq!(fit.display_text(&c), "aa bb cc dd\u{2026}");
+        assert_eq!(fit.layout.lines[1].width, 60.0);
+    }
+
+    #[test]
+    fn test_text_overflow_ellipsis_single_line() {
+        let c = TextContent {
+            white_space: WhiteSpace::Nowrap,
+            text_overflow: TextOverflow::Ellipsis,
+            ..content("abcdefgh")
+        };
+        let fit = fit_text(&c, 50.0, &FixedMeasurer);
+        assert_eq!((fit.cut, fit.ellipsis), (4, true));
+        assert_eq!(fit.layout.lines[0].width, 50.0);
+
+        let c = TextContent { text: "abc defgh".into(), ..c };
+        assert_eq!(
+            fit_text(&c, 50.0, &FixedMeasurer).display_text(&c),
+            "abc\u{2026}",
+            "space before the ellipsis is dropped"
+        );
+
+        // Fits: untouched
+        let fit = fit_text(&c, 100.0, &FixedMeasurer);
+        assert_eq!((fit.cut, fit.ellipsis), (c.text.len(), false));
+
+        // clip: overflowing text is left to the renderer
+        let c = TextContent { text_overflow: TextOverflow::Clip, ..c };
+        let fit = fit_text(&c, 50.0, &FixedMeasurer);
+        assert_eq!((fit.cut, fit.ellipsis), (c.text.len(), false));
+    }
+
+    #[test]
+    fn test_fit_text_run_export_utf16_cut() {
+        // Fallback measurer: 20px font → 10px per char
+        let out = fit_text_run(
+            r#"{"text":"😀😀😀😀😀","fontSize":20,"lineHeight":20,"whiteSpace":"nowrap","textOverflow":"ellipsis"}"#,
+            40.0,
+        );
+        assert_eq!(out[1], 1.0, "ellipsis");
+        assert_eq!(out[2], 1.0, "one line");
+        assert_eq!(out[0] % 2.0, 0.0, "cut on a code point boundary");
+        assert!(out[0] < 10.0);
+        assert!(out[5] <= 40.0);
+    }
+
+    #[test]
+    fn test_layout_text_run_export() {
+        let out = layout_text_run(
+            r#"{"text":"aa bb","fontSize":20,"lineHeight":20,"whiteSpace":"nowrap"}"#,
+            30.0,
+        );
+        assert_eq!(out[0], 1.0, "one line");
+        assert_eq!(out[TEXT_LAYOUT_HEADER], out[5], "line is max-content wide");
+        assert!(out[5] > 30.0);
+    }
+}
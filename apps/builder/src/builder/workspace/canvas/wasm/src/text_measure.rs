@@ -0,0 +1,421 @@
+//! Leaf content measurement for `TaffyLayoutEngine`.
+//!
+//! Leaves may carry a content descriptor (text run or intrinsic box) that
+//! Taffy sizes through a measure function during `compute_layout`, using the
+//! real available space instead of a size pre-baked on the TypeScript side.
+//!
+//! # Design decisions
+//! - Measurement is pluggable via the `TextMeasurer` trait; the engine owns
+//!   one boxed measurer (JS callback or the built-in fallback).
+//! - Only single-run width is delegated to the measurer. Line breaking stays
+//!   in Rust (`text_layout`) so that min-content / max-content / wrapped
+//!   sizes agree.
+
+use serde::Deserialize;
+use taffy::prelude::*;
+use wasm_bindgen::prelude::*;
+
+use crate::text_layout;
+
+/// Ratio of `line-height: normal` to font size when no metrics are known.
+pub(crate) const NORMAL_LINE_HEIGHT_RATIO: f32 = 1.2;
+
+/// Typical Latin ascent / descent ratios (same estimate as `textMeasure.ts`).
+pub(crate) const FALLBACK_ASCENT_RATIO: f32 = 0.8;
+pub(crate) const FALLBACK_DESCENT_RATIO: f32 = 0.2;
+
+// ─── Content descriptors ─────────────────────────────────────────────
+
+/// Font selection shared by every text measurement call.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FontSpec {
+    #[serde(default = "default_font_family")]
+    pub font_family: String,
+    #[serde(default = "default_font_size")]
+    pub font_size: f32,
+    #[serde(default = "default_font_weight")]
+    pub font_weight: u16,
+}
+
+fn default_font_family() -> String {
+    "Pretendard".to_string()
+}
+
+fn default_font_size() -> f32 {
+    14.0
+}
+
+fn default_font_weight() -> u16 {
+    400
+}
+
+impl Default for FontSpec {
+    fn default() -> Self {
+        Self {
+            font_family: default_font_family(),
+            font_size: default_font_size(),
+            font_weight: default_font_weight(),
+        }
+    }
+}
+
+/// CSS `white-space`.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WhiteSpace {
+    #[default]
+    Normal,
+    Nowrap,
+    Pre,
+    PreWrap,
+    PreLine,
+    BreakSpaces,
+}
+
+impl WhiteSpace {
+    /// Spaces and tabs collapse to a single space.
+    pub fn collapses_spaces(self) -> bool {
+        matches!(self, Self::Normal | Self::Nowrap | Self::PreLine)
+    }
+
+    /// Newlines are forced breaks (otherwise they collapse like spaces).
+    pub fn preserves_newlines(self) -> bool {
+        !matches!(self, Self::Normal | Self::Nowrap)
+    }
+
+    /// Lines may wrap at soft wrap opportunities.
+    pub fn wraps(self) -> bool {
+        !matches!(self, Self::Nowrap | Self::Pre)
+    }
+}
+
+/// CSS `word-break`.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WordBreak {
+    #[default]
+    Normal,
+    BreakAll,
+    KeepAll,
+    /// Legacy alias of `normal` + `overflow-wrap: anywhere`.
+    BreakWord,
+}
+
+/// CSS `overflow-wrap` (`word-wrap`).
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowWrap {
+    #[default]
+    Normal,
+    BreakWord,
+    Anywhere,
+}
+
+/// CSS `text-overflow`.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextOverflow {
+    #[default]
+    Clip,
+    Ellipsis,
+}
+
+/// A text run attached to a leaf node.
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextContent {
+    pub text: String,
+    #[serde(flatten)]
+    pub font: FontSpec,
+    /// Line height in px. `None` = `normal` (resolved by the measurer).
+    #[serde(default)]
+    pub line_height: Option<f32>,
+    #[serde(default)]
+    pub white_space: WhiteSpace,
+    #[serde(default)]
+    pub word_break: WordBreak,
+    #[serde(default)]
+    pub overflow_wrap: OverflowWrap,
+    #[serde(default)]
+    pub text_overflow: TextOverflow,
+    /// `-webkit-line-clamp`: maximum number of visible lines.
+    #[serde(default)]
+    pub line_clamp: Option<u32>,
+}
+
+impl TextContent {
+    /// Whether an unbreakable run wider than the line may be broken at an
+    /// arbitrary character, and whether those breaks also count for
+    /// min-content (`anywhere`) or not (`break-word`).
+    pub fn emergency_wrap(&self) -> Option<bool> {
+        match (self.overflow_wrap, self.word_break) {
+            (OverflowWrap::Anywhere, _) | (_, WordBreak::BreakWord) => Some(true),
+            (OverflowWrap::BreakWord, _) => Some(false),
+            (OverflowWrap::Normal, _) => None,
+        }
+    }
+}
+
+/// Content descriptor stored as the Taffy node context of a leaf.
+///
+/// JSON form: `{"kind":"text","text":"Hello","fontSize":16,...}` or
+/// `{"kind":"intrinsic","width":320,"height":180}`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum LeafContent {
+    Text(TextContent),
+    /// Replaced content with a natural size (images, icons, embeds).
+    Intrinsic { width: f32, height: f32 },
+}
+
+// ─── Measurers ───────────────────────────────────────────────────────
+
+/// Pluggable single-line text measurement.
+pub trait TextMeasurer {
+    /// Advance width of `text` rendered on one line with `font`.
+    fn measure_width(&self, text: &str, font: &FontSpec) -> f32;
+
+    /// Line height used when the content does not specify one.
+    fn normal_line_height(&self, font: &FontSpec) -> f32 {
+        font.font_size * NORMAL_LINE_HEIGHT_RATIO
+    }
+
+    /// (ascent, descent) in px, both positive. Used to place baselines.
+    fn vertical_metrics(&self, font: &FontSpec) -> (f32, f32) {
+        (
+            font.font_size * FALLBACK_ASCENT_RATIO,
+            font.font_size * FALLBACK_DESCENT_RATIO,
+        )
+    }
+}
+
+/// Character-count approximation, matching the Canvas 2D fallback in
+/// `textMeasure.ts` (`text.length * fontSize * 0.5`).
+#[derive(Default)]
+pub struct FallbackTextMeasurer;
+
+impl TextMeasurer for FallbackTextMeasurer {
+    fn measure_width(&self, text: &str, font: &FontSpec) -> f32 {
+        text.chars().count() as f32 * font.font_size * 0.5
+    }
+}
+
+/// Delegates width measurement to a JS callback:
+/// `(text, fontFamily, fontSize, fontWeight) => number`.
+pub struct JsTextMeasurer {
+    callback: js_sys::Function,
+}
+
+impl JsTextMeasurer {
+    pub fn new(callback: js_sys::Function) -> Self {
+        Self { callback }
+    }
+}
+
+impl TextMeasurer for JsTextMeasurer {
+    fn measure_width(&self, text: &str, font: &FontSpec) -> f32 {
+        let args = js_sys::Array::of4(
+            &JsValue::from_str(text),
+            &JsValue::from_str(&font.font_family),
+            &JsValue::from_f64(font.font_size as f64),
+            &JsValue::from_f64(font.font_weight as f64),
+        );
+        self.callback
+            .apply(&JsValue::NULL, &args)
+            .ok()
+            .and_then(|v| v.as_f64())
+            .map(|w| w as f32)
+            .unwrap_or_else(|| FallbackTextMeasurer.measure_width(text, font))
+    }
+}
+
+// ─── Measure function ────────────────────────────────────────────────
+
+/// Resolved line height for a text run.
+pub fn resolve_line_height(content: &TextContent, measurer: &dyn TextMeasurer) -> f32 {
+    content
+        .line_height
+        .unwrap_or_else(|| measurer.normal_line_height(&content.font))
+}
+
+/// Result of measuring a content leaf.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LeafMeasure {
+    pub size: Size<f32>,
+    /// (first, last) text baselines from the top of the content box.
+    /// `None` for replaced content, whose baseline is synthesized by the parent.
+    pub baselines: Option<(f32, f32)>,
+}
+
+impl From<Size<f32>> for LeafMeasure {
+    fn from(size: Size<f32>) -> Self {
+        Self { size, baselines: None }
+    }
+}
+
+/// Taffy measure function for a content leaf.
+///
+/// `known_dimensions` wins when set; otherwise text wraps at the known
+/// width, the definite available width, or at every soft wrap opportunity
+/// (min-content) / never (max-content).
+pub fn measure_leaf(
+    known_dimensions: Size<Option<f32>>,
+    available_space: Size<AvailableSpace>,
+    content: &LeafContent,
+    measurer: &dyn TextMeasurer,
+) -> LeafMeasure {
+    if let Size { width: Some(width), height: Some(height) } = known_dimensions {
+        return Size { width, height }.into();
+    }
+
+    match content {
+        LeafContent::Intrinsic { width, height } => {
+            // Preserve aspect ratio when only one axis is known.
+            let ratio = if *width > 0.0 { height / width } else { 0.0 };
+            match (known_dimensions.width, known_dimensions.height) {
+                (Some(w), None) => Size { width: w, height: w * ratio },
+                (None, Some(h)) if ratio > 0.0 => Size { width: h / ratio, height: h },
+                _ => Size { width: *width, height: *height },
+            }
+            .into()
+        }
+        LeafContent::Text(text) => {
+            let wrap_width = known_dimensions.width.unwrap_or(match available_space.width {
+                AvailableSpace::MinContent => 0.0,
+                AvailableSpace::MaxContent => f32::INFINITY,
+                AvailableSpace::Definite(w) => w,
+            });
+            let mut layout = text_layout::layout(text, wrap_width, measurer);
+            if let Some(max_lines) = text.line_clamp {
+                layout.clamp_lines(max_lines as usize);
+            }
+            LeafMeasure {
+                size: Size {
+                    width: known_dimensions.width.unwrap_or_else(|| layout.widest()),
+                    height: known_dimensions.height.unwrap_or(layout.height),
+                },
+                baselines: (!layout.lines.is_empty())
+                    .then_some((layout.first_baseline, layout.last_baseline)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 10px per character regardless of font.
+    struct FixedMeasurer;
+
+    impl TextMeasurer for FixedMeasurer {
+        fn measure_width(&self, text: &str, _font: &FontSpec) -> f32 {
+            text.chars().count() as f32 * 10.0
+        }
+    }
+
+    fn text(s: &str) -> LeafContent {
+        LeafContent::Text(TextContent {
+            text: s.to_string(),
+            font: FontSpec::default(),
+            line_height: Some(20.0),
+            ..TextContent::default()
+        })
+    }
+
+    #[test]
+    fn test_parse_text_content_json() {
+        let content: LeafContent = serde_json::from_str(
+            r#"{"kind":"text","text":"Hi","fontFamily":"Inter","fontSize":16,"fontWeight":600}"#,
+        )
+        .unwrap();
+        let LeafContent::Text(t) = content else { panic!("expected text") };
+        assert_eq!(t.font.font_family, "Inter");
+        assert_eq!(t.font.font_size, 16.0);
+        assert_eq!(t.font.font_weight, 600);
+        assert_eq!(t.line_height, None);
+        assert_eq!(t.white_space, WhiteSpace::Normal);
+    }
+
+    #[test]
+    fn test_parse_text_modes_json() {
+        let content: LeafContent = serde_json::from_str(
+            r#"{"kind":"text","text":"Hi","whiteSpace":"pre-wrap","wordBreak":"keep-all","overflowWrap":"anywhere"}"#,
+        )
+        .unwrap();
+        let LeafContent::Text(t) = content else { panic!("expected text") };
+        assert_eq!(t.white_space, WhiteSpace::PreWrap);
+        assert_eq!(t.word_break, WordBreak::KeepAll);
+        assert_eq!(t.emergency_wrap(), Some(true));
+    }
+
+    #[test]
+    fn test_max_content_single_line() {
+        let size = measure_leaf(
+            Size::NONE,
+            Size { width: AvailableSpace::MaxContent, height: AvailableSpace::MaxContent },
+            &text("aaa bbb"),
+            &FixedMeasurer,
+        )
+        .size;
+        assert_eq!(size.width, 70.0);
+        assert_eq!(size.height, 20.0);
+    }
+
+    #[test]
+    fn test_min_content_breaks_every_word() {
+        let size = measure_leaf(
+            Size::NONE,
+            Size { width: AvailableSpace::MinContent, height: AvailableSpace::MaxContent },
+            &text("aaa bbbbb c"),
+            &FixedMeasurer,
+        )
+        .size;
+        assert_eq!(size.width, 50.0, "longest word");
+        assert_eq!(size.height, 60.0, "3 lines × 20px");
+    }
+
+    #[test]
+    fn test_definite_width_wraps() {
+        let size = measure_leaf(
+            Size::NONE,
+            Size { width: AvailableSpace::Definite(75.0), height: AvailableSpace::MaxContent },
+            &text("aaa bbb ccc"),
+            &FixedMeasurer,
+        );
+        // "aaa bbb" = 70 fits, "ccc" wraps
+        assert_eq!(size.size.width, 70.0);
+        assert_eq!(size.size.height, 40.0);
+        // Baselines are one line height apart
+        let (first, last) = size.baselines.unwrap();
+        assert!((last - first - 20.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_line_clamp_limits_height() {
+        let LeafContent::Text(t) = text("aaa bbb ccc ddd") else { unreachable!() };
+        let content = LeafContent::Text(TextContent { line_clamp: Some(2), ..t });
+        let size = measure_leaf(
+            Size::NONE,
+            Size { width: AvailableSpace::MinContent, height: AvailableSpace::MaxContent },
+            &content,
+            &FixedMeasurer,
+        );
+        assert_eq!(size.size.height, 40.0, "4 lines clamped to 2");
+        assert_eq!(size.baselines.unwrap().1, 20.0 + size.baselines.unwrap().0);
+    }
+
+    #[test]
+    fn test_intrinsic_keeps_aspect_ratio() {
+        let content = LeafContent::Intrinsic { width: 200.0, height: 100.0 };
+        let size = measure_leaf(
+            Size { width: Some(100.0), height: None },
+            Size::MAX_CONTENT,
+            &content,
+            &FixedMeasurer,
+        )
+        .size;
+        assert_eq!(size.height, 50.0);
+    }
+}
@@ -0,0 +1,696 @@
+//! CSS math functions for lengths: `calc()`, `min()`, `max()`, `clamp()`.
+//!
+//! Expressions over `px`, `%`, the relative units in `units` and plain
+//! numbers with `+ - * /`, parentheses and nested functions. Sums and
+//! scalings reduce to a linear form — `percent × basis + px` plus a
+//! coefficient per relative unit — at parse time. Comparisons involving a
+//! percentage cannot, and stay an expression evaluated at layout time
+//! against the containing block.
+//!
+//! # Design decisions
+//! - A pure length or percentage becomes a plain Taffy value; anything else
+//!   needs Taffy's calc support.
+//! - Such values are interned in a per-thread table (WASM is
+//!   single-threaded) and handed to Taffy as an opaque handle — the table
+//!   index shifted past the 3 tag bits Taffy reserves. Equal values share a
+//!   handle. Slots are reference-counted by the styles that hold them
+//!   (`StyleHandles`): a freshly interned slot is unheld until the style it
+//!   was parsed or resolved into is given to a node, and is reused once the
+//!   last style holding it is replaced or dropped. Re-parsing or
+//!   re-resolving on every update therefore does not grow the table.
+//! - `clamp(a, b, c)` is `max(a, min(b, c))`, as specified, so a minimum
+//!   larger than the maximum wins.
+//! - Comparisons between plain px lengths are folded at parse time.
+//! - A bare number is a length in px, as everywhere else in style parsing.
+//! - Relative units stay symbolic until `Expr::resolve`; see `units`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use taffy::prelude::*;
+use taffy::GridTemplateComponent;
+
+use crate::units::{Unit, Units};
+
+/// A reduced length: `percent × basis + px + Σ relative[unit] × unit`
+/// (`percent` is a fraction).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Calc {
+    pub px: f32,
+    pub percent: f32,
+    /// Coefficient per relative unit, indexed by `Unit::index`.
+    pub relative: [f32; Unit::COUNT],
+}
+
+impl Calc {
+    fn px(px: f32) -> Self {
+        Self { px, ..Self::default() }
+    }
+
+    fn scale(self, k: f32) -> Self {
+        Self { px: self.px * k, percent: self.percent * k, relative: self.relative.map(|c| c * k) }
+    }
+
+    fn plus(self, other: Self) -> Self {
+        let mut relative = self.relative;
+        for (c, o) in relative.iter_mut().zip(other.relative) {
+            *c += o;
+        }
+        Self { px: self.px + other.px, percent: self.percent + other.percent, relative }
+    }
+
+    /// Relative units used, as a bit mask over `Unit::index`.
+    fn unit_mask(&self) -> u8 {
+        self.relative
+            .iter()
+            .enumerate()
+            .filter(|&(_, &c)| c != 0.0)
+            .fold(0, |mask, (i, _)| mask | (1 << i))
+    }
+
+    /// Fold the relative units into `px`.
+    fn resolve(&self, units: &Units) -> Calc {
+        let px = Unit::SUFFIXES
+            .iter()
+            .fold(self.px, |px, &(_, unit)| px + self.relative[unit.index()] * units.px(unit));
+        Calc { px, percent: self.percent, relative: [0.0; Unit::COUNT] }
+    }
+
+    /// The px value when the length is absolute.
+    fn fixed(&self) -> Option<f32> {
+        (self.percent == 0.0 && self.unit_mask() == 0).then_some(self.px)
+    }
+}
+
+/// A length expression. Sums and scalings of linear terms are kept reduced
+/// to `Linear`; the other variants only appear around a comparison.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Linear(Calc),
+    Sum(Box<Expr>, Box<Expr>),
+    Scale(Box<Expr>, f32),
+    Min(Vec<Expr>),
+    Max(Vec<Expr>),
+}
+
+impl Expr {
+    fn plus(self, other: Expr) -> Expr {
+        match (self, other) {
+            (Expr::Linear(a), Expr::Linear(b)) => Expr::Linear(a.plus(b)),
+            (a, b) => Expr::Sum(Box::new(a), Box::new(b)),
+        }
+    }
+
+    fn scale(self, k: f32) -> Expr {
+        match self {
+            Expr::Linear(calc) => Expr::Linear(calc.scale(k)),
+            Expr::Scale(expr, j) => Expr::Scale(expr, j * k),
+            expr => Expr::Scale(Box::new(expr), k),
+        }
+    }
+
+    /// `min(args)` or `max(args)`, folded when every argument is absolute.
+    fn compare(args: Vec<Expr>, max: bool) -> Expr {
+        let fixed: Option<Vec<f32>> = args
+            .iter()
+            .map(|arg| match arg {
+                Expr::Linear(calc) => calc.fixed(),
+                _ => None,
+            })
+            .collect();
+        match fixed {
+            Some(values) => Expr::Linear(Calc::px(fold_compare(values, max))),
+            None if max => Expr::Max(args),
+            None => Expr::Min(args),
+        }
+    }
+
+    /// Whether the value depends on a relative unit.
+    pub fn is_relative(&self) -> bool {
+        self.unit_mask() != 0
+    }
+
+    /// Relative units used, as a bit mask over `Unit::index`.
+    pub fn unit_mask(&self) -> u8 {
+        match self {
+            Expr::Linear(calc) => calc.unit_mask(),
+            Expr::Sum(a, b) => a.unit_mask() | b.unit_mask(),
+            Expr::Scale(expr, _) => expr.unit_mask(),
+            Expr::Min(args) | Expr::Max(args) => {
+                args.iter().fold(0, |mask, arg| mask | arg.unit_mask())
+            }
+        }
+    }
+
+    /// Fold the relative units into px, re-folding comparisons that became
+    /// absolute.
+    pub fn resolve(&self, units: &Units) -> Expr {
+        match self {
+            Expr::Linear(calc) => Expr::Linear(calc.resolve(units)),
+            Expr::Sum(a, b) => a.resolve(units).plus(b.resolve(units)),
+            Expr::Scale(expr, k) => expr.resolve(units).scale(*k),
+            Expr::Min(args) => Expr::compare(args.iter().map(|a| a.resolve(units)).collect(), false),
+            Expr::Max(args) => Expr::compare(args.iter().map(|a| a.resolve(units)).collect(), true),
+        }
+    }
+
+    /// Evaluate against the percentage basis, ignoring relative units.
+    pub fn eval(&self, basis: f32) -> f32 {
+        match self {
+            Expr::Linear(calc) => calc.percent * basis + calc.px,
+            Expr::Sum(a, b) => a.eval(basis) + b.eval(basis),
+            Expr::Scale(expr, k) => expr.eval(basis) * k,
+            Expr::Min(args) => fold_compare(args.iter().map(|a| a.eval(basis)), false),
+            Expr::Max(args) => fold_compare(args.iter().map(|a| a.eval(basis)), true),
+        }
+    }
+
+    /// Map onto Taffy's length types, ignoring relative units.
+    pub fn into_length(self) -> CalcLength {
+        match self {
+            Expr::Linear(calc) if calc.percent == 0.0 => CalcLength::Length(calc.px),
+            Expr::Linear(calc) if calc.px == 0.0 => CalcLength::Percent(calc.percent),
+            expr => CalcLength::Mixed(intern(expr)),
+        }
+    }
+
+    /// Bit-exact encoding used as the interning key.
+    fn encode(&self, out: &mut Vec<u32>) {
+        match self {
+            Expr::Linear(calc) => out.extend([0, calc.px.to_bits(), calc.percent.to_bits()]),
+            Expr::Sum(a, b) => {
+                out.push(1);
+                a.encode(out);
+                b.encode(out);
+            }
+            Expr::Scale(expr, k) => {
+                out.extend([2, k.to_bits()]);
+                expr.encode(out);
+            }
+            Expr::Min(args) | Expr::Max(args) => {
+                let tag = if matches!(self, Expr::Min(_)) { 3 } else { 4 };
+                out.extend([tag, args.len() as u32]);
+                for arg in args {
+                    arg.encode(out);
+                }
+            }
+        }
+    }
+}
+
+fn fold_compare(values: impl IntoIterator<Item = f32>, max: bool) -> f32 {
+    let init = if max { f32::NEG_INFINITY } else { f32::INFINITY };
+    values.into_iter().fold(init, |acc, v| if max { acc.max(v) } else { acc.min(v) })
+}
+
+/// How a parsed length expression maps onto Taffy's length types.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CalcLength {
+    Length(f32),
+    Percent(f32),
+    /// Opaque handle for `Dimension::calc` and friends.
+    Mixed(*const ()),
+}
+
+impl CalcLength {
+    /// Build a Taffy length type from its constructors.
+    pub fn into_taffy<T>(
+        self,
+        length: fn(f32) -> T,
+        percent: fn(f32) -> T,
+        calc: fn(*const ()) -> T,
+    ) -> T {
+        match self {
+            CalcLength::Length(px) => length(px),
+            CalcLength::Percent(p) => percent(p),
+            CalcLength::Mixed(handle) => calc(handle),
+        }
+    }
+}
+
+/// Math functions accepted at the top level of a length.
+const FUNCTIONS: [&str; 4] = ["calc(", "min(", "max(", "clamp("];
+
+/// Parse `calc(...)`, `min(...)`, `max(...)` or `clamp(...)` without
+/// relative units, returning `None` for anything else or invalid input.
+pub fn parse_calc(s: &str) -> Option<CalcLength> {
+    let s = s.trim();
+    if !FUNCTIONS.iter().any(|f| s.starts_with(f)) {
+        return None;
+    }
+    let expr = Parser::new(s).parse()?;
+    (!expr.is_relative()).then(|| expr.into_length())
+}
+
+/// Parse a length — a single number with an optional unit, or a math
+/// function.
+pub fn parse_length(s: &str) -> Option<Expr> {
+    Parser::new(s.trim()).parse()
+}
+
+/// Resolve a handle from `parse_calc` against the percentage basis.
+pub fn resolve_calc(handle: *const (), basis: f32) -> f32 {
+    let index = (handle as usize >> HANDLE_SHIFT).wrapping_sub(1);
+    TABLE.with(|t| t.borrow().values.get(index).map(|expr| expr.eval(basis))).unwrap_or(0.0)
+}
+
+// ─── Interning ───────────────────────────────────────────────────────
+
+/// Taffy keeps a tag in the low 3 bits of a calc handle.
+const HANDLE_SHIFT: u32 = 3;
+
+#[derive(Default)]
+struct CalcTable {
+    values: Vec<Expr>,
+    /// Holders per slot. A slot whose last holder let go is on `free` and
+    /// out of `index`; one interned but not yet held is at 0 too.
+    refs: Vec<u32>,
+    index: HashMap<Vec<u32>, usize>,
+    free: Vec<usize>,
+}
+
+thread_local! {
+    static TABLE: RefCell<CalcTable> = RefCell::new(CalcTable::default());
+}
+
+fn intern(expr: Expr) -> *const () {
+    let index = TABLE.with(|t| {
+        let mut table = t.borrow_mut();
+        let mut key = Vec::new();
+        expr.encode(&mut key);
+        if let Some(&index) = table.index.get(&key) {
+            return index;
+        }
+        let index = match table.free.pop() {
+            Some(index) => {
+                table.values[index] = expr;
+                index
+            }
+            None => {
+                table.values.push(expr);
+                table.refs.push(0);
+                table.values.len() - 1
+            }
+        };
+        table.index.insert(key, index);
+        index
+    });
+    std::ptr::without_provenance((index + 1) << HANDLE_SHIFT)
+}
+
+/// Take a hold on a handle returned by `Expr::into_length`.
+fn retain(handle: *const ()) {
+    let index = (handle as usize >> HANDLE_SHIFT).wrapping_sub(1);
+    TABLE.with(|t| {
+        if let Some(refs) = t.borrow_mut().refs.get_mut(index) {
+            *refs += 1;
+        }
+    });
+}
+
+/// Drop one hold on a handle; the slot is reused once nothing holds it.
+fn release(handle: *const ()) {
+    let index = (handle as usize >> HANDLE_SHIFT).wrapping_sub(1);
+    // The table may already be gone when a tree is dropped at thread exit.
+    let _ = TABLE.try_with(|t| {
+        let mut table = t.borrow_mut();
+        let Some(refs) = table.refs.get_mut(index).filter(|refs| **refs > 0) else {
+            return;
+        };
+        *refs -= 1;
+        if *refs == 0 {
+            let mut key = Vec::new();
+            table.values[index].encode(&mut key);
+            table.index.remove(&key);
+            table.free.push(index);
+        }
+    });
+}
+
+/// Calc handles a style refers to, each held once, and released when the
+/// next style is held or the owner is dropped.
+#[derive(Debug, Default)]
+pub struct StyleHandles(Vec<*const ()>);
+
+impl StyleHandles {
+    /// Hold the handles in `style` in place of the previous ones.
+    pub fn hold(&mut self, style: &Style) {
+        let handles = style_handles(style);
+        for &handle in &handles {
+            retain(handle);
+        }
+        for handle in std::mem::replace(&mut self.0, handles) {
+            release(handle);
+        }
+    }
+}
+
+impl Drop for StyleHandles {
+    fn drop(&mut self) {
+        for &handle in &self.0 {
+            release(handle);
+        }
+    }
+}
+
+/// Every calc handle in the length and track fields of `style`.
+fn style_handles(style: &Style) -> Vec<*const ()> {
+    let mut lengths = vec![
+        style.size.width.into_raw(),
+        style.size.height.into_raw(),
+        style.min_size.width.into_raw(),
+        style.min_size.height.into_raw(),
+        style.max_size.width.into_raw(),
+        style.max_size.height.into_raw(),
+        style.flex_basis.into_raw(),
+        style.gap.width.into_raw(),
+        style.gap.height.into_raw(),
+    ];
+    for rect in [style.margin, style.inset] {
+        lengths.extend([rect.top, rect.right, rect.bottom, rect.left].map(|v| v.into_raw()));
+    }
+    for rect in [style.padding, style.border] {
+        lengths.extend([rect.top, rect.right, rect.bottom, rect.left].map(|v| v.into_raw()));
+    }
+    let mut tracks: Vec<&TrackSizingFunction> =
+        style.grid_auto_rows.iter().chain(&style.grid_auto_columns).collect();
+    for component in style.grid_template_rows.iter().chain(&style.grid_template_columns) {
+        match component {
+            GridTemplateComponent::Single(track) => tracks.push(track),
+            GridTemplateComponent::Repeat(repeat) => tracks.extend(&repeat.tracks),
+        }
+    }
+    lengths.extend(tracks.iter().flat_map(|track| [track.min.into_raw(), track.max.into_raw()]));
+    lengths.into_iter().filter(|v| v.is_calc()).map(|v| v.calc_value()).collect()
+}
+
+/// Slots currently held.
+#[cfg(test)]
+pub(crate) fn live_handles() -> usize {
+    TABLE.with(|t| t.borrow().refs.iter().filter(|&&refs| refs > 0).count())
+}
+
+// ─── Parser ──────────────────────────────────────────────────────────
+
+/// Intermediate value: a plain number or a length.
+enum Value {
+    Number(f32),
+    Length(Expr),
+}
+
+impl Value {
+    fn into_length(self) -> Expr {
+        match self {
+            Value::Number(px) => Expr::Linear(Calc::px(px)),
+            Value::Length(expr) => expr,
+        }
+    }
+}
+
+/// Recursive descent over the expression grammar:
+/// `sum = product (('+' | '-') product)*`,
+/// `product = unary (('*' | '/') unary)*`,
+/// `unary = ('-' | '+')? (number unit? | '(' sum ')' | 'calc(' sum ')'
+///          | ('min(' | 'max(') sum (',' sum)* ')' | 'clamp(' sum ',' sum ',' sum ')')`.
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Self {
+        Self { rest: s }
+    }
+
+    fn parse(mut self) -> Option<Expr> {
+        let value = self.unary()?;
+        self.skip_ws();
+        self.rest.is_empty().then(|| value.into_length())
+    }
+
+    fn skip_ws(&mut self) {
+        self.rest = self.rest.trim_start();
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_ws();
+        match self.rest.strip_prefix(token) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn sum(&mut self) -> Option<Value> {
+        let mut acc = self.product()?;
+        loop {
+            let sign = if self.eat("+") {
+                1.0
+            } else if self.eat("-") {
+                -1.0
+            } else {
+                return Some(acc);
+            };
+            let rhs = self.product()?;
+            acc = match (acc, rhs) {
+                (Value::Number(a), Value::Number(b)) => Value::Number(a + sign * b),
+                (acc, rhs) => Value::Length(acc.into_length().plus(rhs.into_length().scale(sign))),
+            };
+        }
+    }
+
+    fn product(&mut self) -> Option<Value> {
+        let mut acc = self.unary()?;
+        loop {
+            if self.eat("*") {
+                acc = match (acc, self.unary()?) {
+                    (Value::Number(a), Value::Number(b)) => Value::Number(a * b),
+                    (Value::Number(n), Value::Length(l)) | (Value::Length(l), Value::Number(n)) => {
+                        Value::Length(l.scale(n))
+                    }
+                    (Value::Length(_), Value::Length(_)) => return None,
+                };
+            } else if self.eat("/") {
+                let Value::Number(n) = self.unary()? else {
+                    return None;
+                };
+                if n == 0.0 {
+                    return None;
+                }
+                acc = match acc {
+                    Value::Number(a) => Value::Number(a / n),
+                    Value::Length(l) => Value::Length(l.scale(1.0 / n)),
+                };
+            } else {
+                return Some(acc);
+            }
+        }
+    }
+
+    /// Comma-separated arguments up to the closing parenthesis.
+    fn args(&mut self) -> Option<Vec<Value>> {
+        let mut args = vec![self.sum()?];
+        while self.eat(",") {
+            args.push(self.sum()?);
+        }
+        self.eat(")").then_some(args)
+    }
+
+    /// `min()`/`max()` over numbers is a number; otherwise every argument is
+    /// a length.
+    fn compare(args: Vec<Value>, max: bool) -> Value {
+        let numbers: Option<Vec<f32>> = args
+            .iter()
+            .map(|arg| match arg {
+                Value::Number(n) => Some(*n),
+                Value::Length(_) => None,
+            })
+            .collect();
+        match numbers {
+            Some(numbers) => Value::Number(fold_compare(numbers, max)),
+            None => Value::Length(Expr::compare(
+                args.into_iter().map(Value::into_length).collect(),
+                max,
+            )),
+        }
+    }
+
+    fn unary(&mut self) -> Option<Value> {
+        if self.eat("-") {
+            return Some(match self.unary()? {
+                Value::Number(n) => Value::Number(-n),
+                Value::Length(l) => Value::Length(l.scale(-1.0)),
+            });
+        }
+        if self.eat("+") {
+            return self.unary();
+        }
+        if self.eat("calc(") || self.eat("(") {
+            let value = self.sum()?;
+            return self.eat(")").then_some(value);
+        }
+        if self.eat("min(") {
+            return Some(Self::compare(self.args()?, false));
+        }
+        if self.eat("max(") {
+            return Some(Self::compare(self.args()?, true));
+        }
+        if self.eat("clamp(") {
+            let [lo, value, hi]: [Value; 3] = self.args()?.try_into().ok()?;
+            let upper = Self::compare(vec![value, hi], false);
+            return Some(Self::compare(vec![lo, upper], true));
+        }
+        self.number()
+    }
+
+    fn number(&mut self) -> Option<Value> {
+        self.skip_ws();
+        let end = self
+            .rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(self.rest.len());
+        let n: f32 = self.rest[..end].parse().ok()?;
+        self.rest = &self.rest[end..];
+        if let Some(rest) = self.rest.strip_prefix("px") {
+            self.rest = rest;
+            return Some(Value::Length(Expr::Linear(Calc::px(n))));
+        }
+        if let Some(rest) = self.rest.strip_prefix('%') {
+            self.rest = rest;
+            return Some(Value::Length(Expr::Linear(Calc {
+                percent: n / 100.0,
+                ..Calc::default()
+            })));
+        }
+        for (suffix, unit) in Unit::SUFFIXES {
+            if let Some(rest) = self.rest.strip_prefix(suffix) {
+                self.rest = rest;
+                let mut calc = Calc::default();
+                calc.relative[unit.index()] = n;
+                return Some(Value::Length(Expr::Linear(calc)));
+            }
+        }
+        Some(Value::Number(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handle(s: &str) -> *const () {
+        match parse_calc(s) {
+            Some(CalcLength::Mixed(handle)) => handle,
+            other => panic!("expected a mixed value for {s}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_mixed_calc_resolves_against_basis() {
+        let handle = handle("calc(100% - 24px)");
+        assert_eq!(resolve_calc(handle, 200.0), 176.0);
+        assert_eq!(parse_calc(" calc( 100%  -  24px ) "), Some(CalcLength::Mixed(handle)), "interned");
+        assert_eq!(handle as usize & 0b111, 0, "tag bits free");
+    }
+
+    #[test]
+    fn test_operators_and_precedence() {
+        assert_eq!(parse_calc("calc(10px + 2 * 5px)"), Some(CalcLength::Length(20.0)));
+        assert_eq!(parse_calc("calc((10px + 2px) * 2)"), Some(CalcLength::Length(24.0)));
+        assert_eq!(parse_calc("calc(100% / 4)"), Some(CalcLength::Percent(0.25)));
+        assert_eq!(parse_calc("calc(-1 * (50% - 50%) + 8px)"), Some(CalcLength::Length(8.0)));
+        assert_eq!(parse_calc("calc(calc(20px * 3) - 10px)"), Some(CalcLength::Length(50.0)));
+        assert_eq!(resolve_calc(handle("calc(50% + (100% - 20px) / 2)"), 100.0), 90.0);
+    }
+
+    #[test]
+    fn test_comparison_functions() {
+        let clamp = handle("clamp(320px, 50%, 960px)");
+        assert_eq!(resolve_calc(clamp, 400.0), 320.0);
+        assert_eq!(resolve_calc(clamp, 1000.0), 500.0);
+        assert_eq!(resolve_calc(clamp, 4000.0), 960.0);
+
+        let min = handle("min(100%, 640px)");
+        assert_eq!(resolve_calc(min, 500.0), 500.0);
+        assert_eq!(resolve_calc(min, 800.0), 640.0);
+        assert_eq!(resolve_calc(handle("max(50%, 100px, 10% + 150px)"), 1000.0), 500.0);
+        assert_eq!(resolve_calc(handle("calc(min(100%, 640px) - 2 * 20px)"), 800.0), 600.0);
+        assert_eq!(resolve_calc(handle("calc(max(10%, 20px) / 2 + 1px)"), 100.0), 11.0);
+
+        assert_eq!(parse_calc("min(10px, 2px + 3px)"), Some(CalcLength::Length(5.0)), "folded");
+        assert_eq!(parse_calc("clamp(20px, 10px, 15px)"), Some(CalcLength::Length(20.0)), "min wins");
+        assert_eq!(parse_calc("calc(10px * max(2, 3))"), Some(CalcLength::Length(30.0)));
+        assert_eq!(parse_calc("min(100%, 100%)"), Some(CalcLength::Mixed(handle("min(100%, 100%)"))));
+    }
+
+    #[test]
+    fn test_invalid_calc() {
+        assert_eq!(parse_calc("100px"), None);
+        assert_eq!(parse_calc("calc(10px * 10px)"), None);
+        assert_eq!(parse_calc("calc(10px / 0)"), None);
+        assert_eq!(parse_calc("calc(10px / 2px)"), None);
+        assert_eq!(parse_calc("calc(10px + 2px"), None);
+        assert_eq!(parse_calc("calc(10em)"), None, "relative units need `Expr::resolve`");
+        assert_eq!(parse_calc("clamp(10px, 20px)"), None);
+        assert_eq!(parse_calc("min()"), None);
+        assert_eq!(parse_calc("min(10px 20px)"), None);
+        assert_eq!(parse_calc("calc(min(10px, 50%) * max(1%, 2px))"), None);
+    }
+
+    #[test]
+    fn test_relative_units() {
+        let units = Units {
+            font_size: 20.0,
+            root_font_size: 16.0,
+            viewport: taffy::Size { width: 1000.0, height: 500.0 },
+        };
+        let px = |s: &str| parse_length(s).map(|expr| expr.resolve(&units).eval(0.0));
+        assert_eq!(px("1.5rem"), Some(24.0));
+        assert_eq!(px("2em"), Some(40.0));
+        assert_eq!(px("10vw"), Some(100.0));
+        assert_eq!(px("10vh"), Some(50.0));
+        assert_eq!(px("10vmin"), Some(50.0));
+        assert_eq!(px("10vmax"), Some(100.0));
+        assert_eq!(px("calc(1rem + 2em - 10px)"), Some(46.0));
+        assert_eq!(px("clamp(1rem, 2.5vw, 2em)"), Some(25.0));
+
+        let expr = parse_length("calc(100% - 2rem)").unwrap();
+        assert_eq!(expr.unit_mask(), 1 << Unit::Rem.index());
+        assert_eq!(expr.resolve(&units).eval(100.0), 68.0);
+        let expr = parse_length("min(100%, 40rem)").unwrap();
+        assert_eq!(expr.unit_mask(), 1 << Unit::Rem.index());
+        assert_eq!(expr.resolve(&units).eval(1000.0), 640.0);
+        assert_eq!(parse_length("min(10vw, 2em)").unwrap().resolve(&units), parse_length("40px").unwrap());
+        assert!(!parse_length("12px").unwrap().is_relative());
+        assert_eq!(parse_length("auto"), None);
+    }
+
+    #[test]
+    fn test_style_handles_are_released() {
+        use crate::units::RelativeStyle;
+
+        let mut relative = RelativeStyle::default();
+        relative.record("width", "calc(100% - 2vw)");
+        relative.record("marginLeft", "min(10%, 3vw)");
+        let mut style = taffy::Style::default();
+        let mut handles = StyleHandles::default();
+        let live = live_handles();
+        for width in 0..100 {
+            let viewport = taffy::Size { width: 400.0 + width as f32, height: 300.0 };
+            let units = Units { font_size: 16.0, root_font_size: 16.0, viewport };
+            relative.apply(&mut style, &units);
+            handles.hold(&style);
+        }
+        assert_eq!(live_handles(), live + 2, "one slot per field across resizes");
+        let width = style.size.width.into_raw().calc_value();
+        let margin = style.margin.left.into_raw().calc_value();
+        assert!((resolve_calc(width, 1000.0) - 990.02).abs() < 1e-3);
+
+        drop(handles);
+        assert_eq!(live_handles(), live, "released with the owner");
+        let parsed = handle("calc(100% - 2px)");
+        assert_eq!(live_handles(), live, "unheld until a style holds it");
+        assert!([width, margin].contains(&parsed), "slot reused");
+    }
+}
@@ -0,0 +1,206 @@
+//! Named grid areas and lines: `grid-template-areas`, `[name]` line names in
+//! track lists and placement by name.
+//!
+//! # Design decisions
+//! - Names are only parsed here; Taffy resolves them during grid layout,
+//!   including the implicit `<area>-start`/`<area>-end` lines an area
+//!   defines, so `grid-area: header` places an item in the `header` area.
+//! - `gridTemplateAreas` is accepted as CSS text (`"a a" "b c"`) or as an
+//!   array of row strings. Like an invalid declaration, a template whose
+//!   rows are ragged or whose areas are not rectangles yields no areas.
+//! - A placement that does not parse is `auto`.
+
+use serde_json::Value;
+use taffy::style::GridTemplateArea;
+use taffy::style_helpers::TaffyGridLine;
+use taffy::GridPlacement;
+
+/// Parse `grid-template-areas` into Taffy areas (1-based grid lines).
+/// `None` for `none` or an invalid template.
+pub fn parse_template_areas(value: &Value) -> Option<Vec<GridTemplateArea<String>>> {
+    let rows: Vec<Vec<&str>> = match value {
+        Value::Array(rows) => rows
+            .iter()
+            .map(|row| row.as_str().map(|r| r.split_whitespace().collect()))
+            .collect::<Option<_>>()?,
+        Value::String(s) if s.contains(['"', '\'']) => s
+            .split(['"', '\''])
+            .skip(1)
+            .step_by(2)
+            .map(|row| row.split_whitespace().collect())
+            .collect(),
+        Value::String(s) if s.trim() != "none" => vec![s.split_whitespace().collect()],
+        _ => return None,
+    };
+    let columns = rows.first()?.len();
+    if columns == 0 || rows.iter().any(|row| row.len() != columns) {
+        return None;
+    }
+
+    // (name, bounding box, cell count) in order of first appearance
+    let mut areas: Vec<(&str, [usize; 4], usize)> = Vec::new();
+    for (r, row) in rows.iter().enumerate() {
+        for (c, &cell) in row.iter().enumerate() {
+            if cell.chars().all(|ch| ch == '.') {
+                continue;
+            }
+            match areas.iter_mut().find(|(name, _, _)| *name == cell) {
+                Some((_, [r0, r1, c0, c1], count)) => {
+                    *r0 = (*r0).min(r);
+                    *r1 = (*r1).max(r);
+                    *c0 = (*c0).min(c);
+                    *c1 = (*c1).max(c);
+                    *count += 1;
+                }
+                None => areas.push((cell, [r, r, c, c], 1)),
+            }
+        }
+    }
+
+    areas
+        .into_iter()
+        .map(|(name, [r0, r1, c0, c1], count)| {
+            // a filled bounding box is a rectangle
+            (count == (r1 - r0 + 1) * (c1 - c0 + 1)).then(|| GridTemplateArea {
+                name: name.to_string(),
+                row_start: r0 as u16 + 1,
+                row_end: r1 as u16 + 2,
+                column_start: c0 as u16 + 1,
+                column_end: c1 as u16 + 2,
+            })
+        })
+        .collect()
+}
+
+/// The names of a `[name ...]` token, or `None` for a track.
+pub fn line_names(token: &str) -> Option<Vec<String>> {
+    let inner = token.strip_prefix('[')?.strip_suffix(']')?;
+    Some(inner.split_whitespace().map(str::to_string).collect())
+}
+
+/// Separate the tracks of a tokenized track list from the line names around
+/// them: `names[i]` precedes `tracks[i]` and `names.len() == tracks.len() + 1`.
+pub fn split_line_names<'a>(
+    tokens: impl IntoIterator<Item = &'a str>,
+) -> (Vec<&'a str>, Vec<Vec<String>>) {
+    let mut tracks = Vec::new();
+    let mut names = vec![Vec::new()];
+    for token in tokens {
+        match line_names(token) {
+            Some(line) => names.last_mut().unwrap().extend(line),
+            None => {
+                tracks.push(token);
+                names.push(Vec::new());
+            }
+        }
+    }
+    (tracks, names)
+}
+
+/// Whether `s` can name a grid line or area.
+fn is_ident(s: &str) -> bool {
+    s != "auto"
+        && s != "span"
+        && s.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '-')
+        && s.parse::<f32>().is_err()
+}
+
+/// Parse a grid line placement: `auto`, `<integer> || <name>` or
+/// `span && [<integer> || <name>]`.
+pub fn parse_grid_placement(s: &str) -> GridPlacement<String> {
+    let mut span = false;
+    let mut number = None;
+    let mut name = None;
+    for token in s.split_whitespace() {
+        if token == "span" && !span {
+            span = true;
+        } else if let (Ok(n), None) = (token.parse::<i16>(), number) {
+            number = Some(n);
+        } else if is_ident(token) && name.is_none() {
+            name = Some(token.to_string());
+        } else {
+            return GridPlacement::Auto;
+        }
+    }
+    match (span, number, name) {
+        (true, Some(n), _) if n <= 0 => GridPlacement::Auto,
+        (true, n, Some(name)) => GridPlacement::NamedSpan(name, n.unwrap_or(1) as u16),
+        (true, n, None) => GridPlacement::Span(n.unwrap_or(1) as u16),
+        (false, Some(0), Some(_)) => GridPlacement::Auto,
+        (false, n, Some(name)) => GridPlacement::NamedLine(name, n.unwrap_or(0)),
+        (false, Some(n), None) => GridPlacement::from_line_index(n),
+        (false, None, None) => GridPlacement::Auto,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(name: &str, rows: (u16, u16), columns: (u16, u16)) -> GridTemplateArea<String> {
+        GridTemplateArea {
+            name: name.to_string(),
+            row_start: rows.0,
+            row_end: rows.1,
+            column_start: columns.0,
+            column_end: columns.1,
+        }
+    }
+
+    #[test]
+    fn test_template_areas() {
+        let expected = vec![
+            area("header", (1, 2), (1, 3)),
+            area("sidebar", (2, 3), (1, 2)),
+            area("main", (2, 3), (2, 3)),
+        ];
+        let text = Value::from(r#""header header" "sidebar main""#);
+        assert_eq!(parse_template_areas(&text), Some(expected.clone()));
+        let rows = serde_json::json!(["header header", "sidebar main"]);
+        assert_eq!(parse_template_areas(&rows), Some(expected));
+
+        let dots = Value::from(r#""a . ." "a ... b""#);
+        assert_eq!(parse_template_areas(&dots), Some(vec![area("a", (1, 3), (1, 2)), area("b", (2, 3), (3, 4))]));
+    }
+
+    #[test]
+    fn test_invalid_template_areas() {
+        assert_eq!(parse_template_areas(&Value::from("none")), None);
+        assert_eq!(parse_template_areas(&Value::from(r#""a b" "c""#)), None, "ragged");
+        assert_eq!(parse_template_areas(&Value::from(r#""a b" "b b""#)), None, "L-shaped");
+        assert_eq!(parse_template_areas(&Value::from(r#""a b a""#)), None, "split");
+    }
+
+    #[test]
+    fn test_line_names() {
+        let (tracks, names) = split_line_names(["[full-start]", "1fr"]);
+        assert_eq!(tracks, ["1fr"]);
+        assert_eq!(names, [vec!["full-start".to_string()], vec![]]);
+
+        let (tracks, names) = split_line_names(["1fr", "[a b]", "[c]", "100px"]);
+        assert_eq!(tracks, ["1fr", "100px"]);
+        assert_eq!(names[1], ["a", "b", "c"]);
+        assert_eq!(names.len(), 3);
+    }
+
+    #[test]
+    fn test_placement() {
+        let named = |name: &str, n| GridPlacement::NamedLine(name.to_string(), n);
+        assert_eq!(parse_grid_placement("auto"), GridPlacement::Auto);
+        assert_eq!(parse_grid_placement("3"), GridPlacement::from_line_index(3));
+        assert_eq!(parse_grid_placement("-1"), GridPlacement::from_line_index(-1));
+        assert_eq!(parse_grid_placement("span 2"), GridPlacement::Span(2));
+        assert_eq!(parse_grid_placement("main-start"), named("main-start", 0));
+        assert_eq!(parse_grid_placement("2 col"), named("col", 2));
+        assert_eq!(parse_grid_placement("col -1"), named("col", -1));
+        assert_eq!(parse_grid_placement("span main"), GridPlacement::NamedSpan("main".to_string(), 1));
+        assert_eq!(parse_grid_placement("span 2 col"), GridPlacement::NamedSpan("col".to_string(), 2));
+        assert_eq!(parse_grid_placement("col span 2"), GridPlacement::NamedSpan("col".to_string(), 2));
+
+        assert_eq!(parse_grid_placement("span 0"), GridPlacement::Auto);
+        assert_eq!(parse_grid_placement("0 col"), GridPlacement::Auto);
+        assert_eq!(parse_grid_placement("a b"), GridPlacement::Auto);
+        assert_eq!(parse_grid_placement("span span"), GridPlacement::Auto);
+        assert_eq!(parse_grid_placement(""), GridPlacement::Auto);
+    }
+}
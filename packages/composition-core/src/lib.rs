@@ -0,0 +1,17 @@
+//! Style parsing and the layout tree shared by composition-wasm's
+//! `TaffyLayoutEngine` and composition-layout's `LayoutEngine`.
+//!
+//! # Design decisions
+//! - Both engines parse styles and lay out through this crate, so a style
+//!   behaves the same in either. Their differences — text content leaves
+//!   and tables in composition-wasm — plug into the tree as an `Engine`.
+//! - Depends on Taffy's algorithms only; `wasm_bindgen` exports stay in the
+//!   engine crates.
+
+pub mod calc;
+pub mod grid_names;
+pub mod masonry;
+pub mod shorthand;
+pub mod subgrid;
+pub mod tree;
+pub mod units;
@@ -0,0 +1,231 @@
+//! Masonry: `grid-template-rows: masonry`.
+//!
+//! # Design decisions
+//! - Taffy has no masonry. The layout tree runs Taffy's grid algorithm with
+//!   the rows left implicit to size and align the column tracks, then stacks
+//!   the items in those columns itself: each goes where its tracks end
+//!   highest (the first such tracks in inline order on a tie), `row-gap`
+//!   below the items already there.
+//! - Items with a definite `grid-column` keep the tracks the grid placed
+//!   them in and are stacked first (`masonry-auto-flow: definite-first`);
+//!   the others only keep their span.
+//! - `grid-row`, `align-self` and `align-content` do not apply to the
+//!   items. An item is stretched or aligned across its tracks by
+//!   `justify-self` like a grid item; auto margins count as zero.
+//! - Only rows can be masonry. Subgrid children of a masonry container are
+//!   not fitted to its tracks.
+
+use std::ops::Range;
+
+use taffy::prelude::*;
+use taffy::{BoxSizing, DetailedGridTracksInfo, LayoutInput, MaybeMath, MaybeResolve, SizingMode};
+
+use crate::calc::resolve_calc;
+
+/// Whether the items of a track list are `masonry`.
+pub fn is_masonry<'a>(items: impl IntoIterator<Item = &'a str>) -> bool {
+    let mut items = items.into_iter().map(str::trim).filter(|item| !item.is_empty());
+    items.next() == Some("masonry") && items.next().is_none()
+}
+
+/// Offset of each column track from the border-box start, aligned by
+/// `justify-content` as Taffy's grid aligns them. `origin` and `width` are
+/// the content box's; `reversed` when the tracks are in physical order
+/// under `rtl`.
+pub fn track_offsets(
+    tracks: &DetailedGridTracksInfo,
+    origin: f32,
+    width: f32,
+    justify: Option<JustifyContent>,
+    reversed: bool,
+) -> Vec<f32> {
+    let count = tracks.sizes.len();
+    if count == 0 {
+        return Vec::new();
+    }
+    let free = width - tracks.sizes.iter().chain(&tracks.gutters).sum::<f32>();
+    let mut justify = justify.unwrap_or(JustifyContent::Stretch);
+    if count == 1 || free <= 0.0 {
+        justify = match justify {
+            JustifyContent::Stretch | JustifyContent::SpaceBetween => JustifyContent::Start,
+            JustifyContent::SpaceAround | JustifyContent::SpaceEvenly if free <= 0.0 => JustifyContent::Start,
+            JustifyContent::SpaceAround | JustifyContent::SpaceEvenly => JustifyContent::Center,
+            other => other,
+        };
+    }
+    if reversed {
+        justify = match justify {
+            JustifyContent::Start | JustifyContent::Stretch => JustifyContent::End,
+            JustifyContent::End => JustifyContent::Start,
+            JustifyContent::FlexStart => JustifyContent::FlexEnd,
+            JustifyContent::FlexEnd => JustifyContent::FlexStart,
+            other => other,
+        };
+    }
+    let (first, between) = match justify {
+        JustifyContent::Start | JustifyContent::FlexStart | JustifyContent::Stretch => (0.0, 0.0),
+        JustifyContent::End | JustifyContent::FlexEnd => (free, 0.0),
+        JustifyContent::Center => (free / 2.0, 0.0),
+        JustifyContent::SpaceBetween => (0.0, free / (count - 1) as f32),
+        JustifyContent::SpaceAround => (free / count as f32 / 2.0, free / count as f32),
+        JustifyContent::SpaceEvenly => (free / (count + 1) as f32, free / (count + 1) as f32),
+    };
+
+    let mut offset = origin + tracks.gutters[0] + first;
+    let mut offsets = Vec::with_capacity(count);
+    for (i, size) in tracks.sizes.iter().enumerate() {
+        if i > 0 {
+            offset += between;
+        }
+        offsets.push(offset);
+        offset += size + tracks.gutters[i + 1];
+    }
+    offsets
+}
+
+/// Offset of an item in its tracks for `justify-self`, given the space
+/// left over after it and its margins.
+pub fn justify_offset(free: f32, justify: Option<AlignItems>, reversed: bool) -> f32 {
+    match justify {
+        Some(AlignItems::Center) => free / 2.0,
+        Some(AlignItems::End | AlignItems::FlexEnd) => if reversed { 0.0 } else { free },
+        _ => if reversed { free } else { 0.0 },
+    }
+}
+
+/// Running block-axis end of each column track while items are stacked.
+pub struct Masonry {
+    ends: Vec<f32>,
+    start: f32,
+    gap: f32,
+}
+
+impl Masonry {
+    /// `tracks` columns whose content starts at `start`, with `gap` between
+    /// the items stacked in one.
+    pub fn new(tracks: usize, start: f32, gap: f32) -> Self {
+        Self { ends: vec![start; tracks], start, gap }
+    }
+
+    /// First of the `span` adjacent tracks where an item would sit highest.
+    /// Ties go to the first in inline order, the last track with `reversed`.
+    pub fn auto_position(&self, span: usize, reversed: bool) -> usize {
+        let span = span.clamp(1, self.ends.len().max(1));
+        let mut firsts: Vec<usize> = (0..=self.ends.len().saturating_sub(span)).collect();
+        if reversed {
+            firsts.reverse();
+        }
+        let mut best: Option<(usize, f32)> = None;
+        for first in firsts {
+            let top = self.top(first..first + span);
+            if best.is_none_or(|(_, best_top)| top < best_top) {
+                best = Some((first, top));
+            }
+        }
+        best.map_or(0, |(first, _)| first)
+    }
+
+    /// Stack an item of outer block size `size` in `tracks`, returning the
+    /// block-axis offset of its margin box.
+    pub fn place(&mut self, tracks: Range<usize>, size: f32) -> f32 {
+        let top = self.top(tracks.clone());
+        for end in &mut self.ends[tracks] {
+            *end = top + size + self.gap;
+        }
+        top
+    }
+
+    /// End of the tallest column: of its last item's margin box, or the
+    /// content start when nothing was stacked.
+    pub fn end(&self) -> f32 {
+        self.ends.iter().fold(self.start, |end, &e| end.max(e - self.gap))
+    }
+
+    fn top(&self, tracks: Range<usize>) -> f32 {
+        self.ends[tracks].iter().copied().fold(self.start, f32::max)
+    }
+}
+
+/// Border-box block size of a masonry container whose content ends at
+/// `end` from its border-box top; `inset` is its padding + border.
+pub fn block_size(style: &Style, inputs: &LayoutInput, inset: Rect<f32>, end: f32) -> f32 {
+    let inset_sum = inset.top + inset.bottom;
+    let adjustment = if style.box_sizing == BoxSizing::ContentBox { inset_sum } else { 0.0 };
+    let resolve = |size: Dimension| {
+        size.maybe_resolve(inputs.parent_size.height, resolve_calc).map(|size| size + adjustment)
+    };
+    let preferred = match inputs.sizing_mode {
+        SizingMode::InherentSize => resolve(style.size.height),
+        SizingMode::ContentSize => None,
+    };
+    inputs
+        .known_dimensions
+        .height
+        .or(preferred)
+        .unwrap_or(end + inset.bottom)
+        .maybe_clamp(resolve(style.min_size.height), resolve(style.max_size.height))
+        .max(inset_sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_masonry() {
+        assert!(is_masonry(["masonry"]));
+        assert!(is_masonry([" masonry "]));
+        assert!(!is_masonry(["masonry", "1fr"]));
+        assert!(!is_masonry(["1fr"]));
+        assert!(!is_masonry([]));
+    }
+
+    #[test]
+    fn test_track_offsets() {
+        let tracks = DetailedGridTracksInfo {
+            negative_implicit_tracks: 0,
+            explicit_tracks: 3,
+            positive_implicit_tracks: 0,
+            gutters: vec![0.0, 10.0, 10.0, 0.0],
+            sizes: vec![50.0, 50.0, 50.0],
+        };
+        // 170px of tracks and gutters in 230px
+        assert_eq!(track_offsets(&tracks, 5.0, 230.0, None, false), [5.0, 65.0, 125.0]);
+        assert_eq!(track_offsets(&tracks, 5.0, 230.0, None, true), [65.0, 125.0, 185.0]);
+        assert_eq!(track_offsets(&tracks, 0.0, 230.0, Some(JustifyContent::Center), false), [30.0, 90.0, 150.0]);
+        assert_eq!(
+            track_offsets(&tracks, 0.0, 230.0, Some(JustifyContent::SpaceBetween), false),
+            [0.0, 90.0, 180.0]
+        );
+        // no free space: distributed alignment falls back to start
+        assert_eq!(
+            track_offsets(&tracks, 0.0, 100.0, Some(JustifyContent::SpaceEvenly), false),
+            [0.0, 60.0, 120.0]
+        );
+    }
+
+    #[test]
+    fn test_stacking() {
+        let mut masonry = Masonry::new(3, 10.0, 5.0);
+        assert_eq!(masonry.end(), 10.0);
+        assert_eq!(masonry.place(0..1, 100.0), 10.0);
+        assert_eq!(masonry.auto_position(1, false), 1);
+        assert_eq!(masonry.auto_position(1, true), 2);
+        assert_eq!(masonry.place(1..2, 40.0), 10.0);
+        assert_eq!(masonry.place(2..3, 60.0), 10.0);
+        // the second column ends highest
+        assert_eq!(masonry.auto_position(1, false), 1);
+        // spanning two tracks: below the taller of each pair
+        assert_eq!(masonry.auto_position(2, false), 1);
+        assert_eq!(masonry.place(1..3, 20.0), 75.0);
+        assert_eq!(masonry.end(), 110.0);
+    }
+
+    #[test]
+    fn test_justify_offset() {
+        assert_eq!(justify_offset(20.0, None, false), 0.0);
+        assert_eq!(justify_offset(20.0, None, true), 20.0);
+        assert_eq!(justify_offset(20.0, Some(AlignItems::Center), true), 10.0);
+        assert_eq!(justify_offset(20.0, Some(AlignItems::End), false), 20.0);
+    }
+}
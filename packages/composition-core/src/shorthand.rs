@@ -0,0 +1,385 @@
+//! CSS shorthand properties in JSON styles.
+//!
+//! `margin`, `padding`, `inset`, `borderWidth`, `gap`, `flex`, `flexFlow`,
+//! `gridColumn`, `gridRow`, `gridArea`, `placeItems`, `placeContent` and
+//! `placeSelf` are expanded into the longhands the style parsers read.
+//!
+//! # Design decisions
+//! - Entries are applied in document order and a later key overwrites an
+//!   earlier one, so a longhand after its shorthand overrides it and a
+//!   shorthand after a longhand resets it — the cascade within one
+//!   declaration block. `serde_json::Map` does not keep key order, hence
+//!   `StyleEntries`.
+//! - A shorthand sets every longhand it covers, omitted parts to their
+//!   initial values (`flexFlow: "wrap"` resets `flexDirection` to `row`).
+//! - An invalid shorthand value is dropped without touching its longhands,
+//!   like an invalid declaration.
+//! - Longhand lengths are emitted as strings (numbers become bare-number
+//!   strings, i.e. px); `flexGrow`/`flexShrink` as numbers.
+
+use std::fmt;
+
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde_json::{Map, Value};
+
+/// The entries of a JSON style object, in document order.
+pub struct StyleEntries(pub Vec<(String, Value)>);
+
+impl<'de> Deserialize<'de> for StyleEntries {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = StyleEntries;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a style object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<StyleEntries, A::Error> {
+                let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(StyleEntries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor)
+    }
+}
+
+impl StyleEntries {
+    /// Expand shorthands into a style object of longhands.
+    pub fn expand(self) -> Value {
+        let mut out = Map::new();
+        for (key, value) in self.0 {
+            if !expand_shorthand(&key, &value, &mut out) {
+                out.insert(key, value);
+            }
+        }
+        Value::Object(out)
+    }
+}
+
+/// Parse a JSON style object with its shorthands expanded; `Value::Null` for
+/// anything but an object.
+pub fn expand_style(json: &str) -> Value {
+    serde_json::from_str::<StyleEntries>(json).map_or(Value::Null, StyleEntries::expand)
+}
+
+const MARGIN: [&str; 4] = ["marginTop", "marginRight", "marginBottom", "marginLeft"];
+const PADDING: [&str; 4] = ["paddingTop", "paddingRight", "paddingBottom", "paddingLeft"];
+const INSET: [&str; 4] = ["insetTop", "insetRight", "insetBottom", "insetLeft"];
+const BORDER: [&str; 4] = ["borderTop", "borderRight", "borderBottom", "borderLeft"];
+
+/// Write the longhands of `key` into `out`. Returns whether `key` is a
+/// shorthand (valid or not).
+fn expand_shorthand(key: &str, value: &Value, out: &mut Map<String, Value>) -> bool {
+    let longhands = match key {
+        "margin" => expand_box(value, MARGIN),
+        "padding" => expand_box(value, PADDING),
+        "inset" => expand_box(value, INSET),
+        "borderWidth" => expand_box(value, BORDER),
+        "gap" => expand_gap(value),
+        "flex" => expand_flex(value),
+        "flexFlow" => expand_flex_flow(value),
+        "gridColumn" => expand_grid_line(value, "gridColumnStart", "gridColumnEnd"),
+        "gridRow" => expand_grid_line(value, "gridRowStart", "gridRowEnd"),
+        "gridArea" => expand_grid_area(value),
+        "placeItems" => expand_place(value, "alignItems", "justifyItems"),
+        "placeContent" => expand_place(value, "alignContent", "justifyContent"),
+        "placeSelf" => expand_place(value, "alignSelf", "justifySelf"),
+        _ => return false,
+    };
+    for (longhand, v) in longhands.into_iter().flatten() {
+        out.insert(longhand.to_string(), v);
+    }
+    true
+}
+
+type Longhands = Option<Vec<(&'static str, Value)>>;
+
+/// Split a shorthand value into its whitespace-separated components, keeping
+/// function arguments (`calc(100% - 8px)`) together.
+fn components(value: &Value) -> Vec<String> {
+    let s = match value {
+        Value::Number(n) => return vec![n.to_string()],
+        Value::String(s) => s,
+        _ => return Vec::new(),
+    };
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = None;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if c.is_whitespace() && depth == 0 => {
+                if let Some(begin) = start.take() {
+                    parts.push(s[begin..i].to_string());
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(begin) = start {
+        parts.push(s[begin..].to_string());
+    }
+    parts
+}
+
+/// `top [right [bottom [left]]]`: right defaults to top, bottom to top, left
+/// to right.
+fn expand_box(value: &Value, sides: [&'static str; 4]) -> Longhands {
+    let parts = components(value);
+    let [top, right, bottom, left] = match parts.as_slice() {
+        [a] => [a, a, a, a],
+        [a, b] => [a, b, a, b],
+        [a, b, c] => [a, b, c, b],
+        [a, b, c, d] => [a, b, c, d],
+        _ => return None,
+    };
+    Some(sides.into_iter().zip([top, right, bottom, left].map(|v| Value::from(v.as_str()))).collect())
+}
+
+/// `row-gap [column-gap]`.
+fn expand_gap(value: &Value) -> Longhands {
+    let parts = components(value);
+    let (row, column) = match parts.as_slice() {
+        [a] => (a, a),
+        [a, b] => (a, b),
+        _ => return None,
+    };
+    Some(vec![("rowGap", Value::from(row.as_str())), ("columnGap", Value::from(column.as_str()))])
+}
+
+/// `none | auto | initial | <grow> <shrink>? || <basis>`. A unitless basis
+/// is only accepted after grow and shrink; an omitted basis is `0%`.
+fn expand_flex(value: &Value) -> Longhands {
+    let parts = components(value);
+    let (grow, shrink, basis) = match parts.as_slice() {
+        [k] if k == "none" => (0.0, 0.0, "auto".to_string()),
+        [k] if k == "auto" => (1.0, 1.0, "auto".to_string()),
+        [k] if k == "initial" => (0.0, 1.0, "auto".to_string()),
+        _ => {
+            let mut numbers = Vec::new();
+            let mut basis = None;
+            // grow and shrink are adjacent: a basis after them closes the group
+            let mut closed = false;
+            for part in &parts {
+                let number = part.parse::<f64>().ok();
+                match number {
+                    Some(n) if numbers.len() < 2 && !closed => numbers.push(n),
+                    _ if basis.is_some() || part == "none" => return None,
+                    Some(n) if n != 0.0 => return None,
+                    _ => {
+                        basis = Some(part.clone());
+                        closed = !numbers.is_empty();
+                    }
+                }
+            }
+            if parts.is_empty() {
+                return None;
+            }
+            (
+                numbers.first().copied().unwrap_or(1.0),
+                numbers.get(1).copied().unwrap_or(1.0),
+                basis.unwrap_or_else(|| "0%".to_string()),
+            )
+        }
+    };
+    Some(vec![
+        ("flexGrow", Value::from(grow)),
+        ("flexShrink", Value::from(shrink)),
+        ("flexBasis", Value::from(basis)),
+    ])
+}
+
+/// `<flex-direction> || <flex-wrap>`.
+fn expand_flex_flow(value: &Value) -> Longhands {
+    let mut direction = None;
+    let mut wrap = None;
+    for part in components(value) {
+        let slot = match part.as_str() {
+            "row" | "row-reverse" | "column" | "column-reverse" => &mut direction,
+            "nowrap" | "wrap" | "wrap-reverse" => &mut wrap,
+            _ => return None,
+        };
+        if slot.replace(part).is_some() {
+            return None;
+        }
+    }
+    if direction.is_none() && wrap.is_none() {
+        return None;
+    }
+    Some(vec![
+        ("flexDirection", Value::from(direction.unwrap_or_else(|| "row".to_string()))),
+        ("flexWrap", Value::from(wrap.unwrap_or_else(|| "nowrap".to_string()))),
+    ])
+}
+
+/// The `/`-separated lines of a grid placement shorthand.
+fn grid_lines(value: &Value) -> Vec<String> {
+    match value {
+        Value::Number(n) => vec![n.to_string()],
+        Value::String(s) => s.split('/').map(|line| line.trim().to_string()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether a grid line is a bare `<custom-ident>`, which an omitted line
+/// copies.
+fn is_custom_ident(line: &str) -> bool {
+    line != "auto" && !line.starts_with("span") && !line.contains(|c: char| c.is_ascii_digit())
+}
+
+/// The value an omitted line takes given the line it defaults from.
+fn omitted_line(from: &str) -> String {
+    if is_custom_ident(from) {
+        from.to_string()
+    } else {
+        "auto".to_string()
+    }
+}
+
+/// `<start> [/ <end>]`.
+fn expand_grid_line(value: &Value, start: &'static str, end: &'static str) -> Longhands {
+    let lines = grid_lines(value);
+    let (first, second) = match lines.as_slice() {
+        [a] if !a.is_empty() => (a.clone(), omitted_line(a)),
+        [a, b] if !a.is_empty() && !b.is_empty() => (a.clone(), b.clone()),
+        _ => return None,
+    };
+    Some(vec![(start, Value::from(first)), (end, Value::from(second))])
+}
+
+/// `<row-start> [/ <column-start> [/ <row-end> [/ <column-end>]]]`.
+fn expand_grid_area(value: &Value) -> Longhands {
+    let lines = grid_lines(value);
+    if lines.is_empty() || lines.len() > 4 || lines.iter().any(String::is_empty) {
+        return None;
+    }
+    let row_start = lines[0].clone();
+    let column_start = lines.get(1).cloned().unwrap_or_else(|| omitted_line(&row_start));
+    let row_end = lines.get(2).cloned().unwrap_or_else(|| omitted_line(&row_start));
+    let column_end = lines.get(3).cloned().unwrap_or_else(|| omitted_line(&column_start));
+    Some(vec![
+        ("gridRowStart", Value::from(row_start)),
+        ("gridColumnStart", Value::from(column_start)),
+        ("gridRowEnd", Value::from(row_end)),
+        ("gridColumnEnd", Value::from(column_end)),
+    ])
+}
+
+/// `<align> [<justify>]`; justify defaults to align.
+fn expand_place(value: &Value, align: &'static str, justify: &'static str) -> Longhands {
+    let parts = components(value);
+    let (a, j) = match parts.as_slice() {
+        [a] => (a, a),
+        [a, j] => (a, j),
+        _ => return None,
+    };
+    Some(vec![(align, Value::from(a.as_str())), (justify, Value::from(j.as_str()))])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expanded(json: &str) -> Value {
+        expand_style(json)
+    }
+
+    #[test]
+    fn test_box_and_gap() {
+        let style = expanded(r#"{"margin":"8px 16px","padding":"1px 2px 3px","inset":0,"gap":"4px calc(2% + 1px)"}"#);
+        assert_eq!(style["marginTop"], "8px");
+        assert_eq!(style["marginRight"], "16px");
+        assert_eq!(style["marginBottom"], "8px");
+        assert_eq!(style["marginLeft"], "16px");
+        assert_eq!(style["paddingLeft"], "2px");
+        assert_eq!(style["paddingBottom"], "3px");
+        assert_eq!(style["insetRight"], "0");
+        assert_eq!(style["rowGap"], "4px");
+        assert_eq!(style["columnGap"], "calc(2% + 1px)");
+        assert!(style.get("margin").is_none());
+    }
+
+    #[test]
+    fn test_key_order_decides_precedence() {
+        let style = expanded(r#"{"margin":"8px","marginLeft":"2px"}"#);
+        assert_eq!(style["marginLeft"], "2px");
+        assert_eq!(style["marginTop"], "8px");
+
+        let style = expanded(r#"{"marginLeft":"2px","margin":"8px"}"#);
+        assert_eq!(style["marginLeft"], "8px");
+
+        let style = expanded(r#"{"marginLeft":"2px","margin":"1px 2px 3px 4px 5px"}"#);
+        assert_eq!(style["marginLeft"], "2px", "invalid shorthand is dropped");
+    }
+
+    #[test]
+    fn test_flex() {
+        let flex = |v: &str| {
+            let style = expanded(&format!(r#"{{"flex":{v}}}"#));
+            (style["flexGrow"].as_f64(), style["flexShrink"].as_f64(), style["flexBasis"].clone())
+        };
+        assert_eq!(flex("1"), (Some(1.0), Some(1.0), Value::from("0%")));
+        assert_eq!(flex(r#""1 1 0""#), (Some(1.0), Some(1.0), Value::from("0")));
+        assert_eq!(flex(r#""2 100px""#), (Some(2.0), Some(1.0), Value::from("100px")));
+        assert_eq!(flex(r#""100px 2""#), (Some(2.0), Some(1.0), Value::from("100px")));
+        assert_eq!(flex(r#""2 3""#), (Some(2.0), Some(3.0), Value::from("0%")));
+        assert_eq!(flex(r#""none""#), (Some(0.0), Some(0.0), Value::from("auto")));
+        assert_eq!(flex(r#""auto""#), (Some(1.0), Some(1.0), Value::from("auto")));
+        assert_eq!(flex(r#""initial""#), (Some(0.0), Some(1.0), Value::from("auto")));
+        assert_eq!(flex(r#""100px""#), (Some(1.0), Some(1.0), Value::from("100px")));
+        assert_eq!(flex(r#""1 2 3 4""#), (None, None, Value::Null));
+        assert_eq!(flex(r#""10px 20px""#), (None, None, Value::Null));
+        assert_eq!(flex(r#""2 100px 3""#), (None, None, Value::Null));
+        assert_eq!(flex(r#""100px 2 3""#), (Some(2.0), Some(3.0), Value::from("100px")));
+    }
+
+    #[test]
+    fn test_flex_flow_and_place() {
+        let style = expanded(r#"{"flexDirection":"column","flexFlow":"wrap","placeItems":"center","placeContent":"start space-between"}"#);
+        assert_eq!(style["flexDirection"], "row", "omitted part resets");
+        assert_eq!(style["flexWrap"], "wrap");
+        assert_eq!(style["alignItems"], "center");
+        assert_eq!(style["justifyItems"], "center");
+        assert_eq!(style["alignContent"], "start");
+        assert_eq!(style["justifyContent"], "space-between");
+        assert!(expanded(r#"{"flexFlow":"wrap nowrap"}"#).get("flexWrap").is_none());
+    }
+
+    #[test]
+    fn test_grid_placement() {
+        let style = expanded(r#"{"gridColumn":"1 / span 2","gridRow":3}"#);
+        assert_eq!(style["gridColumnStart"], "1");
+        assert_eq!(style["gridColumnEnd"], "span 2");
+        assert_eq!(style["gridRowStart"], "3");
+        assert_eq!(style["gridRowEnd"], "auto");
+
+        let style = expanded(r#"{"gridArea":"2 / 1 / span 2 / 4"}"#);
+        assert_eq!(style["gridRowStart"], "2");
+        assert_eq!(style["gridColumnStart"], "1");
+        assert_eq!(style["gridRowEnd"], "span 2");
+        assert_eq!(style["gridColumnEnd"], "4");
+
+        let style = expanded(r#"{"gridArea":"header"}"#);
+        for key in ["gridRowStart", "gridColumnStart", "gridRowEnd", "gridColumnEnd"] {
+            assert_eq!(style[key], "header");
+        }
+        let style = expanded(r#"{"gridArea":"2 / main"}"#);
+        assert_eq!(style["gridRowEnd"], "auto");
+        assert_eq!(style["gridColumnEnd"], "main");
+    }
+
+    #[test]
+    fn test_non_object() {
+        assert_eq!(expand_style("[1, 2]"), Value::Null);
+        assert_eq!(expand_style("not json"), Value::Null);
+    }
+}
@@ -0,0 +1,382 @@
+//! Subgrid: `grid-template-columns/rows: subgrid [name] ...`.
+//!
+//! # Design decisions
+//! - Taffy has no subgrid. A subgridded axis is laid out as an ordinary grid
+//!   axis whose tracks are fixed to the used sizes of the parent tracks the
+//!   subgrid spans. The layout tree lays a grid container out twice when it
+//!   has subgrid children: once to size its tracks, with each subgridded
+//!   axis as one `auto` track per spanned parent track (`prepare`) so the
+//!   subgrid's content still contributes, then again after copying the
+//!   track sizes in (`adopt`).
+//! - The subgrid's own margin, border and padding come out of its first and
+//!   last track, so its items line up with the parent's.
+//! - Line names: the subgrid sees the parent's names for the lines it spans
+//!   plus the ones it lists after `subgrid`. Area names of the parent are
+//!   not inherited.
+//! - A subgrid with no gap in an axis uses the parent's gap there (CSS
+//!   `normal`); an explicit zero gap is indistinguishable and inherits too.
+//! - An auto-placed subgrid spans one track per listed line name interval
+//!   (at least one), as in CSS.
+//! - Items of a subgrid contribute to the parent's tracks only through the
+//!   subgrid as a whole.
+
+use taffy::prelude::*;
+use taffy::{DetailedGridInfo, DetailedGridTracksInfo, GridTemplateRepetition, RepetitionCount};
+
+use crate::grid_names::line_names;
+
+/// Subgridded axes of a grid container: the `[name]` lists after `subgrid`,
+/// one per line of the subgrid.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Subgrid {
+    pub columns: Option<Vec<Vec<String>>>,
+    pub rows: Option<Vec<Vec<String>>>,
+    /// The gap `adopt` copied from the parent, per axis; `prepare` resets it.
+    inherited_gap: Size<bool>,
+}
+
+impl Subgrid {
+    pub fn new(columns: Option<Vec<Vec<String>>>, rows: Option<Vec<Vec<String>>>) -> Self {
+        Self { columns, rows, ..Self::default() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_none() && self.rows.is_none()
+    }
+}
+
+/// Parse `subgrid [name ...]*` from the items of a track list. `None` for an
+/// ordinary track list.
+pub fn parse_subgrid<'a>(items: impl IntoIterator<Item = &'a str>) -> Option<Vec<Vec<String>>> {
+    let text = items.into_iter().collect::<Vec<_>>().join(" ");
+    let mut rest = text.trim().strip_prefix("subgrid")?;
+    if !rest.is_empty() && !rest.starts_with([' ', '[']) {
+        return None;
+    }
+    let mut names = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return Some(names);
+        }
+        let end = rest.find(']')? + 1;
+        names.push(line_names(&rest[..end])?);
+        rest = &rest[end..];
+    }
+}
+
+/// The in-flow children of a grid container in the order Taffy's placement
+/// algorithm records them, which is the order of `DetailedGridInfo::items`:
+/// items definite in both axes, then those definite only in the secondary
+/// (row for a row flow) axis, then the rest, each in document order.
+pub fn placement_order<'a>(children: &[(NodeId, &'a Style)], flow: GridAutoFlow) -> Vec<NodeId> {
+    let axes = |style: &'a Style| match flow {
+        GridAutoFlow::Row | GridAutoFlow::RowDense => (&style.grid_column, &style.grid_row),
+        GridAutoFlow::Column | GridAutoFlow::ColumnDense => (&style.grid_row, &style.grid_column),
+    };
+    let in_flow: Vec<_> = children
+        .iter()
+        .filter(|(_, style)| style.display != Display::None && style.position != Position::Absolute)
+        .map(|&(id, style)| {
+            let (primary, secondary) = axes(style);
+            (id, primary.is_definite(), secondary.is_definite())
+        })
+        .collect();
+    let both = in_flow.iter().filter(|&&(_, p, s)| p && s);
+    let secondary = in_flow.iter().filter(|&&(_, p, s)| !p && s);
+    let rest = in_flow.iter().filter(|&&(_, _, s)| !s);
+    both.chain(secondary).chain(rest).map(|&(id, _, _)| id).collect()
+}
+
+/// Before the parent's tracks are sized: one `auto` track per spanned parent
+/// track in each subgridded axis, and the gaps `adopt` inherited reset.
+pub fn prepare(style: &mut Style, subgrid: &mut Subgrid) {
+    if let Some(names) = &subgrid.columns {
+        let span = span(&mut style.grid_column, names);
+        style.grid_template_columns = (0..span).map(|_| GridTemplateComponent::Single(auto())).collect();
+        style.grid_template_column_names = fit_names(names, span);
+    }
+    if let Some(names) = &subgrid.rows {
+        let span = span(&mut style.grid_row, names);
+        style.grid_template_rows = (0..span).map(|_| GridTemplateComponent::Single(auto())).collect();
+        style.grid_template_row_names = fit_names(names, span);
+    }
+    if std::mem::take(&mut subgrid.inherited_gap.width) {
+        style.gap.width = zero();
+    }
+    if std::mem::take(&mut subgrid.inherited_gap.height) {
+        style.gap.height = zero();
+    }
+}
+
+/// Whether the parent's column tracks run opposite to its line names
+/// (`parent`) or to the subgrid's columns (`subgrid`), as with `rtl`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ColumnOrder {
+    pub parent: bool,
+    pub subgrid: bool,
+}
+
+/// After the parent is laid out: fix each subgridded axis to the parent
+/// tracks the subgrid spans. `item` indexes `info.items` (see
+/// `placement_order`) and `layout` is the subgrid's layout in the parent.
+pub fn adopt(
+    style: &mut Style,
+    subgrid: &mut Subgrid,
+    parent: &Style,
+    info: &DetailedGridInfo,
+    item: usize,
+    layout: &Layout,
+    order: ColumnOrder,
+) {
+    let Some(item) = info.items.get(item) else {
+        return;
+    };
+    if let Some(own) = &subgrid.columns {
+        let axis = ParentAxis {
+            template: &parent.grid_template_columns,
+            names: &parent.grid_template_column_names,
+            tracks: &info.columns,
+            names_reversed: order.parent,
+        };
+        let edges = (
+            layout.margin.left + layout.border.left + layout.padding.left,
+            layout.margin.right + layout.border.right + layout.padding.right,
+        );
+        let lines = (item.column_start, item.column_end);
+        if let Some(AdoptedAxis { tracks, names, gap }) = axis.adopt(own, lines, edges, order.subgrid) {
+            style.grid_template_columns = tracks;
+            style.grid_template_column_names = names;
+            if let (Some(gap), true) = (gap, style.gap.width == zero()) {
+                style.gap.width = length(gap);
+                subgrid.inherited_gap.width = true;
+            }
+        }
+    }
+    if let Some(own) = &subgrid.rows {
+        let axis = ParentAxis {
+            template: &parent.grid_template_rows,
+            names: &parent.grid_template_row_names,
+            tracks: &info.rows,
+            names_reversed: false,
+        };
+        let edges = (
+            layout.margin.top + layout.border.top + layout.padding.top,
+            layout.margin.bottom + layout.border.bottom + layout.padding.bottom,
+        );
+        let lines = (item.row_start, item.row_end);
+        if let Some(AdoptedAxis { tracks, names, gap }) = axis.adopt(own, lines, edges, false) {
+            style.grid_template_rows = tracks;
+            style.grid_template_row_names = names;
+            if let (Some(gap), true) = (gap, style.gap.height == zero()) {
+                style.gap.height = length(gap);
+                subgrid.inherited_gap.height = true;
+            }
+        }
+    }
+}
+
+/// The number of parent tracks a subgrid spans in an axis. A placement that
+/// is auto on both ends is given the span of the listed line names.
+fn span(placement: &mut Line<GridPlacement<String>>, names: &[Vec<String>]) -> usize {
+    let listed = names.len().saturating_sub(1).max(1);
+    match (&placement.start, &placement.end) {
+        (GridPlacement::Line(start), GridPlacement::Line(end)) => {
+            usize::from((end.as_i16() - start.as_i16()).unsigned_abs()).max(1)
+        }
+        (GridPlacement::Span(n) | GridPlacement::NamedSpan(_, n), _)
+        | (_, GridPlacement::Span(n) | GridPlacement::NamedSpan(_, n)) => usize::from(*n),
+        (GridPlacement::Auto, GridPlacement::Auto) => {
+            placement.end = GridPlacement::Span(listed as u16);
+            listed
+        }
+        _ => listed,
+    }
+}
+
+/// `names` padded or cut to the lines of `span` tracks.
+fn fit_names(names: &[Vec<String>], span: usize) -> Vec<Vec<String>> {
+    if names.is_empty() {
+        return Vec::new();
+    }
+    let mut names = names.to_vec();
+    names.resize(span + 1, Vec::new());
+    names
+}
+
+/// A subgridded axis fitted to the parent: fixed tracks, line names and the
+/// parent's gap (if the subgrid spans more than one track).
+struct AdoptedAxis {
+    tracks: Vec<GridTemplateComponent<String>>,
+    names: Vec<Vec<String>>,
+    gap: Option<f32>,
+}
+
+/// One axis of the parent grid after layout.
+struct ParentAxis<'a> {
+    template: &'a [GridTemplateComponent<String>],
+    names: &'a [Vec<String>],
+    tracks: &'a DetailedGridTracksInfo,
+    names_reversed: bool,
+}
+
+impl ParentAxis<'_> {
+    /// Tracks, line names and gap of a subgrid spanning `lines` (1-based,
+    /// counted over all tracks), given its own listed names and its
+    /// margin + border + padding at the start and end.
+    fn adopt(
+        &self,
+        own: &[Vec<String>],
+        lines: (u16, u16),
+        edges: (f32, f32),
+        reversed: bool,
+    ) -> Option<AdoptedAxis> {
+        let (start, end) = (usize::from(lines.0).checked_sub(1)?, usize::from(lines.1).checked_sub(1)?);
+        let mut sizes = self.tracks.sizes.get(start..end)?.to_vec();
+        let last = sizes.len().checked_sub(1)?;
+        sizes[0] = (sizes[0] - edges.0).max(0.0);
+        sizes[last] = (sizes[last] - edges.1).max(0.0);
+        let mut names = self.line_names().get(start..=end)?.to_vec();
+        if reversed {
+            sizes.reverse();
+            names.reverse();
+        }
+        for (line, own) in names.iter_mut().zip(own) {
+            line.extend(own.iter().cloned());
+        }
+        if names.iter().all(Vec::is_empty) {
+            names.clear();
+        }
+        let gap = if last > 0 { self.tracks.gutters.get(start + 1).copied() } else { None };
+        let tracks = sizes.into_iter().map(|size| GridTemplateComponent::Single(length(size))).collect();
+        Some(AdoptedAxis { tracks, names, gap })
+    }
+
+    /// Names of every line of the axis, implicit tracks included.
+    fn line_names(&self) -> Vec<Vec<String>> {
+        let mut explicit = self.explicit_line_names();
+        if self.names_reversed {
+            explicit.reverse();
+        }
+        let mut lines = vec![Vec::new(); self.tracks.sizes.len() + 1];
+        let offset = usize::from(self.tracks.negative_implicit_tracks);
+        for (line, names) in lines.iter_mut().skip(offset).zip(explicit) {
+            *line = names;
+        }
+        lines
+    }
+
+    /// Names of the explicit grid lines, with `repeat()` expanded.
+    fn explicit_line_names(&self) -> Vec<Vec<String>> {
+        let outer = |i: usize| self.names.get(i).cloned().unwrap_or_default();
+        let fixed: usize = self
+            .template
+            .iter()
+            .map(|component| match component {
+                GridTemplateComponent::Single(_) => 1,
+                GridTemplateComponent::Repeat(GridTemplateRepetition { count: RepetitionCount::Count(n), tracks, .. }) => {
+                    usize::from(*n) * tracks.len()
+                }
+                GridTemplateComponent::Repeat(_) => 0,
+            })
+            .sum();
+        let mut lines = vec![outer(0)];
+        for (i, component) in self.template.iter().enumerate() {
+            match component {
+                GridTemplateComponent::Single(_) => lines.push(Vec::new()),
+                GridTemplateComponent::Repeat(repeat) => {
+                    let count = match repeat.count {
+                        RepetitionCount::Count(n) => usize::from(n),
+                        _ => usize::from(self.tracks.explicit_tracks).saturating_sub(fixed)
+                            / repeat.tracks.len().max(1),
+                    };
+                    let inner = |j: usize| repeat.line_names.get(j).cloned().unwrap_or_default();
+                    for _ in 0..count {
+                        lines.last_mut().unwrap().extend(inner(0));
+                        lines.extend((1..=repeat.tracks.len()).map(inner));
+                    }
+                }
+            }
+            lines.last_mut().unwrap().extend(outer(i + 1));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use taffy::style_helpers::TaffyGridLine;
+
+    fn names(lines: &[&[&str]]) -> Vec<Vec<String>> {
+        lines.iter().map(|line| line.iter().map(|n| n.to_string()).collect()).collect()
+    }
+
+    #[test]
+    fn test_parse_subgrid() {
+        assert_eq!(parse_subgrid(["subgrid"]), Some(vec![]));
+        assert_eq!(parse_subgrid(["subgrid [a] [] [b c]"]), Some(names(&[&["a"], &[], &["b", "c"]])));
+        assert_eq!(parse_subgrid(["subgrid", "[a]", "[b]"]), Some(names(&[&["a"], &["b"]])));
+        assert_eq!(parse_subgrid(["1fr", "1fr"]), None);
+        assert_eq!(parse_subgrid(["subgrid 1fr"]), None);
+        assert_eq!(parse_subgrid(["subgrids"]), None);
+    }
+
+    #[test]
+    fn test_placement_order() {
+        let [a, b, c, d, e] = [0usize, 1, 2, 3, 4].map(NodeId::from);
+        let auto = Style::DEFAULT;
+        let row = Style { grid_row: Line { start: GridPlacement::from_line_index(2), end: GridPlacement::Auto }, ..Style::DEFAULT };
+        let both = Style { grid_column: Line { start: GridPlacement::from_line_index(1), end: GridPlacement::Auto }, ..row.clone() };
+        let hidden = Style { display: Display::None, ..Style::DEFAULT };
+        let children = [(a, &auto), (b, &row), (c, &both), (d, &hidden), (e, &auto)];
+        assert_eq!(placement_order(&children, GridAutoFlow::Row), [c, b, a, e]);
+        assert_eq!(placement_order(&children, GridAutoFlow::Column), [c, a, b, e]);
+    }
+
+    #[test]
+    fn test_prepare_spans_listed_lines() {
+        let mut style = Style::DEFAULT;
+        let mut subgrid = Subgrid::new(Some(names(&[&["a"], &[], &["b"]])), None);
+        prepare(&mut style, &mut subgrid);
+        assert_eq!(style.grid_column.end, GridPlacement::Span(2));
+        assert_eq!(style.grid_template_columns.len(), 2);
+        assert_eq!(style.grid_template_column_names.len(), 3);
+
+        style.grid_column = Line { start: GridPlacement::from_line_index(1), end: GridPlacement::from_line_index(5) };
+        prepare(&mut style, &mut subgrid);
+        assert_eq!(style.grid_template_columns.len(), 4);
+        assert_eq!(style.grid_template_column_names[3], Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_parent_line_names() {
+        let template = vec![
+            GridTemplateComponent::Single(length(10.0)),
+            GridTemplateComponent::Repeat(GridTemplateRepetition {
+                count: RepetitionCount::Count(2),
+                tracks: vec![length(20.0)],
+                line_names: names(&[&["r"], &["s"]]),
+            }),
+        ];
+        let tracks = DetailedGridTracksInfo {
+            negative_implicit_tracks: 1,
+            explicit_tracks: 3,
+            positive_implicit_tracks: 0,
+            gutters: vec![0.0, 5.0, 5.0, 5.0, 0.0],
+            sizes: vec![30.0, 10.0, 20.0, 20.0],
+        };
+        let axis = ParentAxis { template: &template, names: &names(&[&["a"], &["b"], &["c"]]), tracks: &tracks, names_reversed: false };
+        assert_eq!(axis.line_names(), names(&[&[], &["a"], &["b", "r"], &["s", "r"], &["s", "c"]]));
+
+        let adopted = axis.adopt(&names(&[&[], &["mid"]]), (2, 4), (4.0, 0.0), false).unwrap();
+        assert_eq!(adopted.tracks, [GridTemplateComponent::Single(length(6.0)), GridTemplateComponent::Single(length(20.0))]);
+        assert_eq!(adopted.names, names(&[&["a"], &["b", "r", "mid"], &["s", "r"]]));
+        assert_eq!(adopted.gap, Some(5.0));
+
+        let adopted = axis.adopt(&[], (3, 4), (0.0, 0.0), true).unwrap();
+        assert_eq!(adopted.tracks, [GridTemplateComponent::Single(length(20.0))]);
+        assert_eq!(adopted.names, names(&[&["s", "r"], &["b", "r"]]));
+        assert_eq!(adopted.gap, None);
+    }
+}
@@ -0,0 +1,1132 @@
+//! Node storage and Taffy trait implementations shared by both engines.
+//!
+//! Replaces `TaffyTree` with a tree we own so that layout can carry data
+//! the high-level API drops (text baselines), resolve mixed `calc()` values
+//! (`TaffyTree::resolve_calc_value` always returns 0) and host formatting
+//! contexts Taffy lacks.
+//!
+//! # Design decisions
+//! - Slab storage (`Vec<Option<Node>>` + free list); a `NodeId` is the slot
+//!   index. Mutation API mirrors `TaffyTree` (dirty propagation to
+//!   ancestors, child re-parenting in `set_children`); a stale id panics.
+//! - Algorithms are Taffy's own (`compute_flexbox_layout`, ...); only the
+//!   dispatch in `compute_child_layout` is ours. An `Engine` adds per-node
+//!   data, measures leaf content and may take over containers (tables).
+//! - Baselines: leaves report the baselines their engine measured;
+//!   containers take Taffy's flex/grid first baseline, block containers the
+//!   first in-flow child's, and the last baseline comes from the last
+//!   in-flow child that has one (CSS 2.1 §10.8.1, css-align §9.1).
+//! - `direction: rtl`: an RTL container is laid out as its own mirror image
+//!   — physical left/right padding and border swapped on the container,
+//!   margins and insets swapped on its children — and the children's x
+//!   positions are flipped back afterwards. Flex rows, grid columns and
+//!   block/absolute static positions all start at the right edge while
+//!   physical properties keep their side. `Style::direction` stays `ltr`.
+//! - `writing-mode: vertical-*`: generalizes the RTL mirroring. Each node has
+//!   a layout space where its inline axis is horizontal — transposed for
+//!   vertical modes, with the block axis flipped for `vertical-rl` and the
+//!   inline axis for `rtl`. Container styles are mapped into the node's own
+//!   space and item styles into the parent's; inputs and outputs are
+//!   transposed where an orthogonal flow starts, and child rects are mapped
+//!   back to physical coordinates once the container is laid out.
+//!   Baselines are horizontal-only and dropped inside vertical flows.
+//! - Relative units: a pre-layout pass resolves inherited font sizes and
+//!   writes `em`/`rem`/viewport lengths into `style` (see `units`).
+//! - Subgrid: a grid container with subgrid children is laid out twice and
+//!   the track sizes it settles on are written into the children's styles
+//!   in between (see `subgrid`). Subgrids in an orthogonal flow keep their
+//!   `auto` tracks.
+//! - Masonry: the grid pass sizes the column tracks in layout space and the
+//!   items are stacked in them there (see `masonry`), so `rtl` and vertical
+//!   writing modes need nothing extra.
+
+use taffy::prelude::*;
+use taffy::{
+    compute_block_layout, compute_cached_layout, compute_flexbox_layout, compute_grid_layout,
+    compute_hidden_layout, compute_leaf_layout, compute_root_layout, round_layout, BlockContext, Cache,
+    CacheTree, ClearState, CollapsibleMarginSet, DetailedGridInfo, LayoutInput, LayoutOutput, Point,
+    RequestedAxis, ResolveOrZero, RunMode, SizingMode,
+};
+
+use crate::calc::{resolve_calc, StyleHandles};
+use crate::masonry::{self, Masonry};
+use crate::subgrid::{self, ColumnOrder, Subgrid};
+use crate::units::{RelativeStyle, Units};
+
+/// What an engine adds to the shared tree.
+pub trait Engine: Sized {
+    /// Data the engine keeps per node, next to the style.
+    type Node: Default;
+
+    /// Content size of a leaf, and the (first, last) baselines of its text
+    /// from the top of the content box.
+    fn measure(
+        &self,
+        _node: &Self::Node,
+        _known: Size<Option<f32>>,
+        _available: Size<AvailableSpace>,
+    ) -> (Size<f32>, Option<(f32, f32)>) {
+        (Size::ZERO, None)
+    }
+
+    /// Lay out a container the engine formats itself; `None` leaves it to
+    /// Taffy's block, flex and grid algorithms.
+    fn compute_container(_tree: &mut LayoutTree<Self>, _id: NodeId, _inputs: LayoutInput) -> Option<LayoutOutput> {
+        None
+    }
+}
+
+/// Style, children and content sizes only.
+impl Engine for () {
+    type Node = ();
+}
+
+/// CSS `direction`, inherited down the tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+/// CSS `writing-mode`, inherited down the tree.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WritingMode {
+    #[default]
+    HorizontalTb,
+    VerticalRl,
+    VerticalLr,
+}
+
+/// Specified flow properties of a node; `None` inherits from the parent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Flow {
+    pub direction: Option<Direction>,
+    pub writing_mode: Option<WritingMode>,
+}
+
+/// Resolved flow of a node: how its layout space maps to the physical one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Frame {
+    writing_mode: WritingMode,
+    direction: Direction,
+}
+
+impl Frame {
+    pub fn is_physical(self) -> bool {
+        self == Frame::default()
+    }
+
+    pub fn is_vertical(self) -> bool {
+        self.writing_mode != WritingMode::HorizontalTb
+    }
+
+    /// Physical box sides → layout-space sides.
+    fn rect_to_layout<T>(self, rect: Rect<T>) -> Rect<T> {
+        let mut rect = if self.is_vertical() { transpose_rect(rect) } else { rect };
+        if self.writing_mode == WritingMode::VerticalRl {
+            std::mem::swap(&mut rect.top, &mut rect.bottom);
+        }
+        if self.direction == Direction::Rtl {
+            std::mem::swap(&mut rect.left, &mut rect.right);
+        }
+        rect
+    }
+
+    /// Layout-space sides → physical box sides.
+    fn rect_to_physical<T>(self, mut rect: Rect<T>) -> Rect<T> {
+        if self.direction == Direction::Rtl {
+            std::mem::swap(&mut rect.left, &mut rect.right);
+        }
+        if self.writing_mode == WritingMode::VerticalRl {
+            std::mem::swap(&mut rect.top, &mut rect.bottom);
+        }
+        if self.is_vertical() { transpose_rect(rect) } else { rect }
+    }
+
+    /// Map a physical style into this layout space.
+    fn style_to_layout(self, style: &Style) -> Style {
+        let mut out = style.clone();
+        if self.is_vertical() {
+            out.size = transpose_size(style.size);
+            out.min_size = transpose_size(style.min_size);
+            out.max_size = transpose_size(style.max_size);
+            out.overflow = Point { x: style.overflow.y, y: style.overflow.x };
+            out.aspect_ratio = style.aspect_ratio.map(|ratio| 1.0 / ratio);
+        }
+        out.margin = self.rect_to_layout(style.margin);
+        out.padding = self.rect_to_layout(style.padding);
+        out.border = self.rect_to_layout(style.border);
+        out.inset = self.rect_to_layout(style.inset);
+        out
+    }
+
+    /// Map a child's layout, placed in this frame's layout space inside a
+    /// container of (layout-space) `container` size, to physical coordinates.
+    fn layout_to_physical(self, layout: &Layout, container: Size<f32>) -> Layout {
+        let mut out = *layout;
+        if self.direction == Direction::Rtl {
+            out.location.x = container.width - layout.location.x - layout.size.width;
+        }
+        if self.writing_mode == WritingMode::VerticalRl {
+            out.location.y = container.height - layout.location.y - layout.size.height;
+        }
+        if self.is_vertical() {
+            out.location = Point { x: out.location.y, y: out.location.x };
+            out.size = transpose_size(out.size);
+            out.scrollbar_size = transpose_size(out.scrollbar_size);
+        }
+        out.margin = self.rect_to_physical(layout.margin);
+        out.padding = self.rect_to_physical(layout.padding);
+        out.border = self.rect_to_physical(layout.border);
+        out
+    }
+}
+
+fn transpose_size<T>(size: Size<T>) -> Size<T> {
+    Size { width: size.height, height: size.width }
+}
+
+fn transpose_rect<T>(rect: Rect<T>) -> Rect<T> {
+    Rect { left: rect.top, right: rect.bottom, top: rect.left, bottom: rect.right }
+}
+
+/// Inputs from a parent whose writing mode differs, in the child's space.
+fn enter_flow(inputs: LayoutInput, orthogonal: bool) -> LayoutInput {
+    let mut inputs = LayoutInput { vertical_margins_are_collapsible: Line::FALSE, ..inputs };
+    if orthogonal {
+        inputs.known_dimensions = transpose_size(inputs.known_dimensions);
+        inputs.parent_size = transpose_size(inputs.parent_size);
+        inputs.available_space = transpose_size(inputs.available_space);
+        inputs.axis = match inputs.axis {
+            RequestedAxis::Horizontal => RequestedAxis::Vertical,
+            RequestedAxis::Vertical => RequestedAxis::Horizontal,
+            RequestedAxis::Both => RequestedAxis::Both,
+        };
+    }
+    inputs
+}
+
+/// Output for a parent whose writing mode differs: no baselines or margin
+/// collapsing across the boundary.
+fn leave_flow(mut output: LayoutOutput, orthogonal: bool) -> LayoutOutput {
+    if orthogonal {
+        output.size = transpose_size(output.size);
+    }
+    output.first_baselines = Point::NONE;
+    output.top_margin = CollapsibleMarginSet::ZERO;
+    output.bottom_margin = CollapsibleMarginSet::ZERO;
+    output.margins_can_collapse_through = false;
+    output
+}
+
+/// Per-node storage; `data` is the engine's.
+pub struct Node<T> {
+    style: Style,
+    /// Specified `direction` / `writing-mode`.
+    flow: Flow,
+    /// Resolved frame of this node / of its parent (last layout pass).
+    frame: Frame,
+    parent_frame: Frame,
+    /// `style` mapped into the node's own layout space (read when it lays
+    /// out its children or its content) and into its parent's (read by the
+    /// parent's algorithm). `None` when the space is the physical one.
+    container_style: Option<Box<Style>>,
+    item_style: Option<Box<Style>>,
+    /// `font-size` and relative-unit lengths, and the units they were last
+    /// written into `style` with (`None`: not yet).
+    relative: RelativeStyle,
+    units: Option<Units>,
+    /// Calc handles `style` refers to.
+    handles: StyleHandles,
+    /// Subgridded axes; their tracks in `style` are rewritten during layout.
+    subgrid: Subgrid,
+    /// `grid-template-rows: masonry`.
+    masonry: bool,
+    /// Track sizes and item placements of the last grid layout.
+    grid_info: Option<Box<DetailedGridInfo>>,
+    pub data: T,
+    children: Vec<NodeId>,
+    parent: Option<NodeId>,
+    cache: Cache,
+    unrounded_layout: Layout,
+    final_layout: Layout,
+    /// Baselines from the top of the border box, set by the last full layout.
+    first_baseline: Option<f32>,
+    last_baseline: Option<f32>,
+}
+
+impl<T> Node<T> {
+    fn new(style: Style, data: T) -> Self {
+        let mut handles = StyleHandles::default();
+        handles.hold(&style);
+        Self {
+            style,
+            flow: Flow::default(),
+            frame: Frame::default(),
+            parent_frame: Frame::default(),
+            container_style: None,
+            item_style: None,
+            relative: RelativeStyle::default(),
+            units: None,
+            handles,
+            subgrid: Subgrid::default(),
+            masonry: false,
+            grid_info: None,
+            data,
+            children: Vec::new(),
+            parent: None,
+            cache: Cache::new(),
+            unrounded_layout: Layout::with_order(0),
+            final_layout: Layout::with_order(0),
+            first_baseline: None,
+            last_baseline: None,
+        }
+    }
+
+    /// Specified (physical) style.
+    pub fn style(&self) -> &Style {
+        &self.style
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        &self.children
+    }
+
+    /// Resolved flow of the node (last layout pass).
+    pub fn frame(&self) -> Frame {
+        self.frame
+    }
+
+    fn is_in_flow(&self) -> bool {
+        self.style.position != Position::Absolute && self.style.display != Display::None
+    }
+
+    /// Style in the node's own layout space.
+    pub fn container_style(&self) -> &Style {
+        self.container_style.as_deref().unwrap_or(&self.style)
+    }
+
+    /// Style in the parent's layout space.
+    pub fn item_style(&self) -> &Style {
+        self.item_style.as_deref().unwrap_or(&self.style)
+    }
+
+    /// Replace `style`, holding the calc handles of the new one instead.
+    fn replace_style(&mut self, style: Style) {
+        self.handles.hold(&style);
+        self.style = style;
+        self.refresh_mapped_styles();
+    }
+
+    /// Rebuild the mapped styles from `style` and the resolved frames.
+    fn refresh_mapped_styles(&mut self) {
+        let map = |frame: Frame| {
+            (!frame.is_physical()).then(|| Box::new(frame.style_to_layout(&self.style)))
+        };
+        self.container_style = map(self.frame);
+        self.item_style = map(self.parent_frame);
+    }
+}
+
+/// Layout tree backing both engines.
+pub struct LayoutTree<E: Engine> {
+    nodes: Vec<Option<Node<E::Node>>>,
+    free_list: Vec<usize>,
+    pub engine: E,
+    /// What `rem` and the viewport units resolve against.
+    pub root_font_size: f32,
+    pub viewport: Size<f32>,
+}
+
+impl<E: Engine + Default> Default for LayoutTree<E> {
+    fn default() -> Self {
+        Self::new(E::default())
+    }
+}
+
+impl<E: Engine> LayoutTree<E> {
+    pub fn new(engine: E) -> Self {
+        Self { nodes: Vec::new(), free_list: Vec::new(), engine, root_font_size: 16.0, viewport: Size::ZERO }
+    }
+
+    // ─── Mutation ────────────────────────────────────────────────────
+
+    fn insert(&mut self, node: Node<E::Node>) -> NodeId {
+        let idx = match self.free_list.pop() {
+            Some(idx) => {
+                self.nodes[idx] = Some(node);
+                idx
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        NodeId::from(idx)
+    }
+
+    pub fn new_leaf(&mut self, style: Style) -> NodeId {
+        self.insert(Node::new(style, E::Node::default()))
+    }
+
+    /// A leaf carrying engine data (measured content).
+    pub fn new_leaf_with_data(&mut self, style: Style, data: E::Node) -> NodeId {
+        self.insert(Node::new(style, data))
+    }
+
+    pub fn new_with_children(&mut self, style: Style, children: &[NodeId]) -> NodeId {
+        let id = self.new_leaf(style);
+        self.set_children(id, children);
+        id
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        matches!(self.nodes.get(usize::from(id)), Some(Some(_)))
+    }
+
+    pub fn style(&self, id: NodeId) -> &Style {
+        &self.node(id).style
+    }
+
+    pub fn set_style(&mut self, id: NodeId, style: Style) {
+        let node = self.node_mut(id);
+        node.replace_style(style);
+        node.units = None;
+        self.mark_dirty(id);
+    }
+
+    /// Set the node's `font-size` and relative-unit lengths. They are written
+    /// into its style by the next `compute_layout`.
+    pub fn set_relative_style(&mut self, id: NodeId, relative: RelativeStyle) {
+        let node = self.node_mut(id);
+        if node.relative != relative {
+            node.relative = relative;
+            node.units = None;
+            self.mark_dirty(id);
+        }
+    }
+
+    /// Set the subgridded axes of a grid container.
+    pub fn set_subgrid(&mut self, id: NodeId, subgrid: Subgrid) {
+        let node = self.node_mut(id);
+        if node.subgrid != subgrid {
+            node.subgrid = subgrid;
+            self.mark_dirty(id);
+        }
+    }
+
+    /// Set whether a grid container's rows are `masonry`.
+    pub fn set_masonry(&mut self, id: NodeId, masonry: bool) {
+        let node = self.node_mut(id);
+        if node.masonry != masonry {
+            node.masonry = masonry;
+            self.mark_dirty(id);
+        }
+    }
+
+    /// Set the specified `direction` / `writing-mode`. Takes effect for the
+    /// node and its inheriting descendants on the next `compute_layout`.
+    pub fn set_flow(&mut self, id: NodeId, flow: Flow) {
+        let node = self.node_mut(id);
+        if node.flow != flow {
+            node.flow = flow;
+            self.mark_dirty(id);
+        }
+    }
+
+    /// The engine's data of a node.
+    pub fn data(&self, id: NodeId) -> &E::Node {
+        &self.node(id).data
+    }
+
+    /// Update the engine's data of a node, dirtying it.
+    pub fn update_data(&mut self, id: NodeId, update: impl FnOnce(&mut E::Node)) {
+        update(&mut self.node_mut(id).data);
+        self.mark_dirty(id);
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.node(id).children
+    }
+
+    /// Replace the children of `parent`, detaching them from previous parents.
+    pub fn set_children(&mut self, parent: NodeId, children: &[NodeId]) {
+        for child in std::mem::take(&mut self.node_mut(parent).children) {
+            self.node_mut(child).parent = None;
+        }
+        for &child in children {
+            if let Some(previous) = self.node(child).parent {
+                self.node_mut(previous).children.retain(|&c| c != child);
+                self.mark_dirty(previous);
+            }
+            self.node_mut(child).parent = Some(parent);
+        }
+        self.node_mut(parent).children = children.to_vec();
+        self.mark_dirty(parent);
+    }
+
+    /// Remove a node; its children stay in the tree, detached.
+    pub fn remove(&mut self, id: NodeId) {
+        if !self.contains(id) {
+            return;
+        }
+        if let Some(parent) = self.node(id).parent {
+            self.node_mut(parent).children.retain(|&c| c != id);
+            self.mark_dirty(parent);
+        }
+        for child in std::mem::take(&mut self.node_mut(id).children) {
+            self.node_mut(child).parent = None;
+        }
+        self.nodes[usize::from(id)] = None;
+        self.free_list.push(usize::from(id));
+    }
+
+    /// Clear the cache of `id` and every ancestor.
+    pub fn mark_dirty(&mut self, id: NodeId) {
+        let mut current = Some(id);
+        while let Some(node_id) = current {
+            let node = self.node_mut(node_id);
+            let was_dirty = matches!(node.cache.clear(), ClearState::AlreadyEmpty);
+            if was_dirty && node_id != id {
+                break;
+            }
+            current = node.parent;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free_list.clear();
+    }
+
+    // ─── Layout ──────────────────────────────────────────────────────
+
+    pub fn compute_layout(&mut self, root: NodeId, available_space: Size<AvailableSpace>) {
+        self.resolve_units(root);
+        self.resolve_frames(root);
+        compute_root_layout(self, root, available_space);
+        round_layout(self, root);
+    }
+
+    /// Whether the node's layout needs to be recomputed.
+    pub fn dirty(&self, id: NodeId) -> bool {
+        self.node(id).cache.is_empty()
+    }
+
+    /// Final (rounded) layout of a node.
+    pub fn layout(&self, id: NodeId) -> &Layout {
+        &self.node(id).final_layout
+    }
+
+    /// (first, last) baselines from the top of the border box, if any.
+    pub fn baselines(&self, id: NodeId) -> (Option<f32>, Option<f32>) {
+        let node = self.node(id);
+        (node.first_baseline, node.last_baseline)
+    }
+
+    /// Resolve font sizes below `root` and write relative lengths into the
+    /// styles of nodes where a unit they use changed, dirtying those.
+    fn resolve_units(&mut self, root: NodeId) {
+        let (root_font_size, viewport) = (self.root_font_size, self.viewport);
+        let root_units = Units { font_size: root_font_size, root_font_size, viewport };
+        let mut stack = vec![(root, root_units)];
+        while let Some((id, parent_units)) = stack.pop() {
+            let node = self.node_mut(id);
+            let font_size = node.relative.resolve_font_size(&parent_units);
+            let units = Units { font_size, ..parent_units };
+            let stale = node
+                .units
+                .is_none_or(|last| last.differs(&units, node.relative.unit_mask()));
+            node.units = Some(units);
+            if stale && !node.relative.lengths.is_empty() {
+                let mut style = node.style.clone();
+                node.relative.apply(&mut style, &units);
+                node.replace_style(style);
+                self.mark_dirty(id);
+            }
+            stack.extend(self.node(id).children.iter().map(|&c| (c, units)));
+        }
+    }
+
+    /// Resolve inherited `direction` / `writing-mode` below `root`,
+    /// refreshing mapped styles (and dirtying) nodes whose frames changed.
+    fn resolve_frames(&mut self, root: NodeId) {
+        let mut stack = vec![(root, Frame::default())];
+        while let Some((id, parent_frame)) = stack.pop() {
+            let node = self.node_mut(id);
+            let frame = Frame {
+                writing_mode: node.flow.writing_mode.unwrap_or(parent_frame.writing_mode),
+                direction: node.flow.direction.unwrap_or(parent_frame.direction),
+            };
+            if (node.frame, node.parent_frame) != (frame, parent_frame) {
+                node.frame = frame;
+                node.parent_frame = parent_frame;
+                node.refresh_mapped_styles();
+                self.mark_dirty(id);
+            }
+            stack.extend(self.node(id).children.iter().map(|&c| (c, frame)));
+        }
+    }
+
+    /// Map the children of a container laid out in its own layout space
+    /// (of `container` size) back to physical coordinates.
+    pub fn place_children(&mut self, id: NodeId, container: Size<f32>) {
+        let frame = self.node(id).frame;
+        for i in 0..self.node(id).children.len() {
+            let child = self.node_mut(self.node(id).children[i]);
+            if child.style.display == Display::None {
+                continue;
+            }
+            child.unrounded_layout = frame.layout_to_physical(&child.unrounded_layout, container);
+        }
+    }
+
+    /// Grid layout, with subgrid children fitted to the parent tracks they
+    /// span: a first pass sizes the tracks, a second lays out the children
+    /// with those sizes.
+    fn compute_grid(&mut self, id: NodeId, inputs: LayoutInput) -> LayoutOutput {
+        if self.node(id).masonry {
+            return self.compute_masonry(id, inputs);
+        }
+        let subgrids: Vec<NodeId> = self
+            .node(id)
+            .children
+            .iter()
+            .copied()
+            .filter(|&c| self.node(c).style.display == Display::Grid && !self.node(c).subgrid.is_empty())
+            .collect();
+        if subgrids.is_empty() {
+            return compute_grid_layout(self, id, inputs);
+        }
+        for &child in &subgrids {
+            self.update_subgrid_style(child, subgrid::prepare);
+        }
+        let output = compute_grid_layout(self, id, inputs);
+        if inputs.run_mode != RunMode::PerformLayout {
+            return output;
+        }
+        let Some(info) = self.node_mut(id).grid_info.take() else {
+            return output;
+        };
+
+        let parent = self.node(id).container_style().clone();
+        let frame = self.node(id).frame;
+        let children: Vec<_> =
+            self.node(id).children.iter().map(|&c| (c, self.node(c).item_style())).collect();
+        let order = subgrid::placement_order(&children, parent.grid_auto_flow);
+        for (item, child) in order.into_iter().enumerate() {
+            let child_frame = self.node(child).frame;
+            if !subgrids.contains(&child) || child_frame.is_vertical() != frame.is_vertical() {
+                continue;
+            }
+            let layout = self.node(child).unrounded_layout;
+            let order = ColumnOrder { parent: false, subgrid: child_frame.direction != frame.direction };
+            self.update_subgrid_style(child, |style, subgrid| {
+                subgrid::adopt(style, subgrid, &parent, &info, item, &layout, order)
+            });
+        }
+        compute_grid_layout(self, id, inputs)
+    }
+
+    /// Masonry layout: a grid pass sizes and aligns the column tracks, then
+    /// the items are stacked in them.
+    fn compute_masonry(&mut self, id: NodeId, inputs: LayoutInput) -> LayoutOutput {
+        let grid = compute_grid_layout(self, id, LayoutInput { run_mode: RunMode::PerformLayout, ..inputs });
+        let Some(info) = self.node_mut(id).grid_info.take() else {
+            return grid;
+        };
+
+        let style = self.node(id).container_style().clone();
+        let inset = style.padding.resolve_or_zero(inputs.parent_size.width, resolve_calc)
+            + style.border.resolve_or_zero(inputs.parent_size.width, resolve_calc);
+        let content_width = grid.size.width - inset.left - inset.right;
+        let offsets =
+            masonry::track_offsets(&info.columns, inset.left, content_width, style.justify_content, false);
+        let content_height = inputs.known_dimensions.height.map(|height| height - inset.top - inset.bottom);
+        let gap = style.gap.height.resolve_or_zero(content_height, resolve_calc);
+        let mut stack = Masonry::new(offsets.len(), inset.top, gap);
+
+        let children: Vec<_> =
+            self.node(id).children.iter().map(|&c| (c, self.node(c).item_style())).collect();
+        let mut items: Vec<_> = subgrid::placement_order(&children, style.grid_auto_flow)
+            .into_iter()
+            .zip(&info.items)
+            .map(|(child, item)| (child, item.column_start as usize - 1..item.column_end as usize - 1))
+            .collect();
+        items.sort_by_key(|(child, _)| !self.node(*child).item_style().grid_column.is_definite());
+
+        for (order, (child, tracks)) in items.into_iter().enumerate() {
+            let item = self.node(child).item_style();
+            let tracks = if item.grid_column.is_definite() {
+                tracks
+            } else {
+                let first = stack.auto_position(tracks.len(), false);
+                first..first + tracks.len()
+            };
+            let width = offsets[tracks.end - 1] + info.columns.sizes[tracks.end - 1] - offsets[tracks.start];
+            let margin = item.margin.resolve_or_zero(Some(width), resolve_calc);
+            let padding = item.padding.resolve_or_zero(Some(width), resolve_calc);
+            let border = item.border.resolve_or_zero(Some(width), resolve_calc);
+            let justify = item.justify_self.or(style.justify_items);
+            let stretch = item.size.width.is_auto()
+                && !item.margin.left.is_auto()
+                && !item.margin.right.is_auto()
+                && justify.is_none_or(|justify| justify == AlignItems::Stretch);
+
+            let output = self.compute_child_layout(
+                child,
+                LayoutInput {
+                    run_mode: RunMode::PerformLayout,
+                    sizing_mode: SizingMode::InherentSize,
+                    axis: RequestedAxis::Both,
+                    known_dimensions: Size {
+                        width: stretch.then_some(width - margin.left - margin.right),
+                        height: None,
+                    },
+                    parent_size: Size { width: Some(width), height: None },
+                    available_space: Size {
+                        width: AvailableSpace::Definite(width),
+                        height: AvailableSpace::MaxContent,
+                    },
+                    vertical_margins_are_collapsible: Line::FALSE,
+                },
+            );
+            let free = width - output.size.width - margin.left - margin.right;
+            let location = Point {
+                x: offsets[tracks.start] + margin.left + masonry::justify_offset(free, justify, false),
+                y: stack.place(tracks, output.size.height + margin.top + margin.bottom) + margin.top,
+            };
+            let layout = Layout {
+                location,
+                size: output.size,
+                border,
+                padding,
+                margin,
+                ..Layout::with_order(order as u32)
+            };
+            self.set_unrounded_layout(child, &layout);
+        }
+
+        let height = masonry::block_size(&style, &inputs, inset, stack.end());
+        LayoutOutput::from_outer_size(Size { width: grid.size.width, height })
+    }
+
+    /// Rewrite the style of a subgrid, clearing its cache if it changed.
+    fn update_subgrid_style(&mut self, id: NodeId, update: impl FnOnce(&mut Style, &mut Subgrid)) {
+        let node = self.node_mut(id);
+        let mut style = node.style.clone();
+        update(&mut style, &mut node.subgrid);
+        if style != node.style {
+            node.replace_style(style);
+            node.cache.clear();
+        }
+    }
+
+    #[inline]
+    pub fn node(&self, id: NodeId) -> &Node<E::Node> {
+        self.nodes[usize::from(id)].as_ref().expect("stale layout node id")
+    }
+
+    #[inline]
+    fn node_mut(&mut self, id: NodeId) -> &mut Node<E::Node> {
+        self.nodes[usize::from(id)].as_mut().expect("stale layout node id")
+    }
+
+    /// Lay out a leaf, measuring its content. Returns the output and the
+    /// (first, last) baselines of its text relative to the border box.
+    fn compute_leaf(&self, id: NodeId, inputs: LayoutInput) -> (LayoutOutput, Option<(f32, f32)>) {
+        let node = self.node(id);
+        let mut text_baselines = None;
+        let style = node.container_style();
+        let output = compute_leaf_layout(inputs, style, resolve_calc, |known, available| {
+            let (size, baselines) = self.engine.measure(&node.data, known, available);
+            text_baselines = baselines;
+            size
+        });
+        let inset_top = style.padding.top.resolve_or_zero(inputs.parent_size.width, resolve_calc)
+            + style.border.top.resolve_or_zero(inputs.parent_size.width, resolve_calc);
+        (output, text_baselines.map(|(first, last)| (inset_top + first, inset_top + last)))
+    }
+
+    /// First baseline of the first in-flow child that has one (block containers).
+    fn first_child_baseline(&self, id: NodeId) -> Option<f32> {
+        self.node(id).children.iter().find_map(|&c| {
+            let child = self.node(c);
+            if !child.is_in_flow() {
+                return None;
+            }
+            child.first_baseline.map(|b| child.unrounded_layout.location.y + b)
+        })
+    }
+
+    /// Last baseline of the last in-flow child that has one.
+    fn last_child_baseline(&self, id: NodeId) -> Option<f32> {
+        self.node(id).children.iter().rev().find_map(|&c| {
+            let child = self.node(c);
+            if !child.is_in_flow() {
+                return None;
+            }
+            child.last_baseline.map(|b| child.unrounded_layout.location.y + b)
+        })
+    }
+
+    /// Lay out a node in its own layout space; `block_ctx` is the block
+    /// formatting context of an in-flow block child.
+    fn compute_child(
+        &mut self,
+        node_id: NodeId,
+        inputs: LayoutInput,
+        block_ctx: Option<&mut BlockContext<'_>>,
+    ) -> LayoutOutput {
+        if inputs.run_mode == RunMode::PerformHiddenLayout {
+            return compute_hidden_layout(self, node_id);
+        }
+
+        let (frame, parent_frame) = (self.node(node_id).frame, self.node(node_id).parent_frame);
+        let new_flow = frame.writing_mode != parent_frame.writing_mode;
+        let orthogonal = frame.is_vertical() != parent_frame.is_vertical();
+        let (inputs, block_ctx) = if new_flow { (enter_flow(inputs, orthogonal), None) } else { (inputs, block_ctx) };
+
+        let output = compute_cached_layout(self, node_id, inputs, |tree, node_id, inputs| {
+            let display = tree.node(node_id).style.display;
+            let has_children = !tree.node(node_id).children.is_empty();
+
+            let custom = match display {
+                Display::None => None,
+                _ if has_children => E::compute_container(tree, node_id, inputs),
+                _ => None,
+            };
+            let (mut output, baselines) = match (custom, display, has_children) {
+                (Some(output), _, _) => (output, None),
+                (None, Display::None, _) => (compute_hidden_layout(tree, node_id), None),
+                (None, Display::Block, true) => (compute_block_layout(tree, node_id, inputs, block_ctx), None),
+                (None, Display::Flex, true) => (compute_flexbox_layout(tree, node_id, inputs), None),
+                (None, Display::Grid, true) => (tree.compute_grid(node_id, inputs), None),
+                (None, _, false) => tree.compute_leaf(node_id, inputs),
+            };
+
+            if inputs.run_mode == RunMode::PerformLayout && has_children && !frame.is_physical() {
+                tree.place_children(node_id, output.size);
+            }
+
+            if frame.is_vertical() {
+                output.first_baselines = Point::NONE;
+                if inputs.run_mode == RunMode::PerformLayout {
+                    tree.node_mut(node_id).last_baseline = None;
+                }
+            } else if inputs.run_mode == RunMode::PerformLayout {
+                let last = match baselines {
+                    Some((first, last)) => {
+                        output.first_baselines.y = Some(first);
+                        Some(last)
+                    }
+                    None if has_children => {
+                        if display == Display::Block {
+                            output.first_baselines.y = tree.first_child_baseline(node_id);
+                        }
+                        tree.last_child_baseline(node_id)
+                    }
+                    None => None,
+                };
+                tree.node_mut(node_id).last_baseline = last;
+            }
+            output
+        });
+
+        if inputs.run_mode == RunMode::PerformLayout {
+            self.node_mut(node_id).first_baseline = output.first_baselines.y;
+        }
+        if new_flow { leave_flow(output, orthogonal) } else { output }
+    }
+}
+
+// ─── Taffy trait implementations ─────────────────────────────────────
+
+pub struct ChildIter<'a>(std::slice::Iter<'a, NodeId>);
+
+impl Iterator for ChildIter<'_> {
+    type Item = NodeId;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().copied()
+    }
+}
+
+impl<E: Engine> taffy::TraversePartialTree for LayoutTree<E> {
+    type ChildIter<'a> = ChildIter<'a> where Self: 'a;
+
+    fn child_ids(&self, node_id: NodeId) -> Self::ChildIter<'_> {
+        ChildIter(self.node(node_id).children.iter())
+    }
+
+    fn child_count(&self, node_id: NodeId) -> usize {
+        self.node(node_id).children.len()
+    }
+
+    fn get_child_id(&self, node_id: NodeId, index: usize) -> NodeId {
+        self.node(node_id).children[index]
+    }
+}
+
+impl<E: Engine> taffy::TraverseTree for LayoutTree<E> {}
+
+impl<E: Engine> taffy::LayoutPartialTree for LayoutTree<E> {
+    type CoreContainerStyle<'a> = &'a Style where Self: 'a;
+    type CustomIdent = String;
+
+    // Only read by `compute_root_layout`, which sizes the root in its parent's space.
+    fn get_core_container_style(&self, node_id: NodeId) -> Self::CoreContainerStyle<'_> {
+        self.node(node_id).item_style()
+    }
+
+    fn set_unrounded_layout(&mut self, node_id: NodeId, layout: &Layout) {
+        self.node_mut(node_id).unrounded_layout = *layout;
+    }
+
+    fn resolve_calc_value(&self, val: *const (), basis: f32) -> f32 {
+        resolve_calc(val, basis)
+    }
+
+    fn compute_child_layout(&mut self, node_id: NodeId, inputs: LayoutInput) -> LayoutOutput {
+        self.compute_child(node_id, inputs, None)
+    }
+}
+
+impl<E: Engine> CacheTree for LayoutTree<E> {
+    fn cache_get(&self, node_id: NodeId, input: &LayoutInput) -> Option<LayoutOutput> {
+        self.node(node_id).cache.get(input)
+    }
+
+    fn cache_store(&mut self, node_id: NodeId, input: &LayoutInput, layout_output: LayoutOutput) {
+        self.node_mut(node_id).cache.store(input, layout_output)
+    }
+
+    fn cache_clear(&mut self, node_id: NodeId) {
+        self.node_mut(node_id).cache.clear();
+    }
+}
+
+impl<E: Engine> taffy::LayoutBlockContainer for LayoutTree<E> {
+    type BlockContainerStyle<'a> = &'a Style where Self: 'a;
+    type BlockItemStyle<'a> = &'a Style where Self: 'a;
+
+    fn get_block_container_style(&self, node_id: NodeId) -> Self::BlockContainerStyle<'_> {
+        self.node(node_id).container_style()
+    }
+
+    fn get_block_child_style(&self, child_node_id: NodeId) -> Self::BlockItemStyle<'_> {
+        self.node(child_node_id).item_style()
+    }
+
+    fn compute_block_child_layout(
+        &mut self,
+        node_id: NodeId,
+        inputs: LayoutInput,
+        block_ctx: Option<&mut BlockContext<'_>>,
+    ) -> LayoutOutput {
+        self.compute_child(node_id, inputs, block_ctx)
+    }
+}
+
+impl<E: Engine> taffy::LayoutFlexboxContainer for LayoutTree<E> {
+    type FlexboxContainerStyle<'a> = &'a Style where Self: 'a;
+    type FlexboxItemStyle<'a> = &'a Style where Self: 'a;
+
+    fn get_flexbox_container_style(&self, node_id: NodeId) -> Self::FlexboxContainerStyle<'_> {
+        self.node(node_id).container_style()
+    }
+
+    fn get_flexbox_child_style(&self, child_node_id: NodeId) -> Self::FlexboxItemStyle<'_> {
+        self.node(child_node_id).item_style()
+    }
+}
+
+impl<E: Engine> taffy::LayoutGridContainer for LayoutTree<E> {
+    type GridContainerStyle<'a> = &'a Style where Self: 'a;
+    type GridItemStyle<'a> = &'a Style where Self: 'a;
+
+    fn get_grid_container_style(&self, node_id: NodeId) -> Self::GridContainerStyle<'_> {
+        self.node(node_id).container_style()
+    }
+
+    fn get_grid_child_style(&self, child_node_id: NodeId) -> Self::GridItemStyle<'_> {
+        self.node(child_node_id).item_style()
+    }
+
+    fn set_detailed_grid_info(&mut self, node_id: NodeId, detailed_grid_info: DetailedGridInfo) {
+        self.node_mut(node_id).grid_info = Some(Box::new(detailed_grid_info));
+    }
+}
+
+impl<E: Engine> taffy::RoundTree for LayoutTree<E> {
+    fn get_unrounded_layout(&self, node_id: NodeId) -> Layout {
+        self.node(node_id).unrounded_layout
+    }
+
+    fn set_final_layout(&mut self, node_id: NodeId, layout: &Layout) {
+        self.node_mut(node_id).final_layout = *layout;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sized(width: f32, height: f32) -> Style {
+        Style { size: Size::from_lengths(width, height), ..Style::DEFAULT }
+    }
+
+    fn block(tree: &mut LayoutTree<()>, children: &[NodeId]) -> NodeId {
+        tree.new_with_children(Style { display: Display::Block, ..Style::DEFAULT }, children)
+    }
+
+    fn leaf_with(tree: &mut LayoutTree<()>, key: &str, value: &str) -> NodeId {
+        let id = tree.new_leaf(Style::DEFAULT);
+        let mut relative = RelativeStyle::default();
+        relative.record(key, value);
+        tree.set_relative_style(id, relative);
+        id
+    }
+
+    #[test]
+    fn test_unit_changes_dirty_only_dependent_nodes() {
+        let mut tree = LayoutTree::new(());
+        tree.viewport = Size { width: 1000.0, height: 500.0 };
+        let vw = leaf_with(&mut tree, "width", "10vw");
+        let em = leaf_with(&mut tree, "width", "2em");
+        let px = tree.new_leaf(Style::DEFAULT);
+        let block = Style { display: Display::Block, ..Style::DEFAULT };
+        let root = tree.new_with_children(block, &[vw, em, px]);
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(vw).size.width, 100.0);
+
+        tree.viewport = Size { width: 800.0, height: 500.0 };
+        tree.resolve_units(root);
+        assert!(tree.dirty(vw) && tree.dirty(root));
+        assert!(!tree.dirty(em) && !tree.dirty(px));
+
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(vw).size.width, 80.0);
+        tree.root_font_size = 20.0;
+        tree.resolve_units(root);
+        assert!(tree.dirty(em));
+        assert!(!tree.dirty(vw) && !tree.dirty(px));
+    }
+
+    #[test]
+    fn test_parsed_calc_handles_stay_bounded() {
+        use crate::calc::{live_handles, parse_calc};
+
+        let width = |px: usize| {
+            let calc = parse_calc(&format!("calc(100% - {}px)", px + 1)).unwrap();
+            let width = calc.into_taffy(Dimension::length, Dimension::percent, Dimension::calc);
+            Style { size: Size { width, height: Dimension::auto() }, ..Style::DEFAULT }
+        };
+        let mut tree = LayoutTree::new(());
+        let live = live_handles();
+        let node = tree.new_leaf(width(0));
+        let other = tree.new_leaf(width(0));
+        assert_eq!(live_handles(), live + 1, "equal values share a slot");
+        for px in 1..100 {
+            tree.set_style(node, width(px));
+        }
+        assert_eq!(live_handles(), live + 2, "replaced styles let go of their handles");
+
+        let block = Style { display: Display::Block, size: Size::from_lengths(400.0, 100.0), ..Style::DEFAULT };
+        let root = tree.new_with_children(block, &[node, other]);
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(node).size.width, 300.0);
+
+        tree.remove(node);
+        assert_eq!(live_handles(), live + 1, "released with the node");
+        tree.clear();
+        assert_eq!(live_handles(), live);
+    }
+
+    #[test]
+    fn test_remove_node_with_children() {
+        let mut tree = LayoutTree::new(());
+        let (a, b) = (tree.new_leaf(sized(10.0, 10.0)), tree.new_leaf(sized(10.0, 20.0)));
+        let inner = block(&mut tree, &[a, b]);
+        let sibling = tree.new_leaf(sized(10.0, 5.0));
+        let root = block(&mut tree, &[inner, sibling]);
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(sibling).location.y, 30.0);
+
+        tree.remove(inner);
+        assert!(!tree.contains(inner));
+        assert_eq!(tree.children(root), &[sibling]);
+        assert!(tree.dirty(root));
+        assert_eq!((tree.node(a).parent, tree.node(b).parent), (None, None), "children detached");
+
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(sibling).location.y, 0.0);
+        tree.set_children(root, &[a, sibling]);
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(sibling).location.y, 10.0, "detached children can be reattached");
+        assert_eq!(tree.new_leaf(Style::DEFAULT), inner, "slot reused");
+    }
+
+    #[test]
+    fn test_reparent() {
+        let mut tree = LayoutTree::new(());
+        let (a, b) = (tree.new_leaf(sized(10.0, 10.0)), tree.new_leaf(sized(10.0, 20.0)));
+        let first = block(&mut tree, &[a, b]);
+        let second = block(&mut tree, &[]);
+        let root = block(&mut tree, &[first, second]);
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(second).location.y, 30.0);
+
+        tree.set_children(second, &[b]);
+        assert_eq!(tree.children(first), &[a], "left its old parent");
+        assert_eq!(tree.node(b).parent, Some(second));
+        assert!(tree.dirty(first) && tree.dirty(second) && tree.dirty(root));
+        assert!(!tree.dirty(a));
+
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(first).size.height, 10.0);
+        assert_eq!(tree.layout(second).location.y, 10.0);
+        assert_eq!(tree.layout(second).size.height, 20.0);
+        assert_eq!(tree.layout(b).location.y, 0.0);
+    }
+
+    #[test]
+    fn test_child_style_change_dirties_ancestors() {
+        let mut tree = LayoutTree::new(());
+        let leaf = tree.new_leaf(sized(10.0, 10.0));
+        let sibling = tree.new_leaf(sized(10.0, 10.0));
+        let inner = block(&mut tree, &[leaf]);
+        let other = block(&mut tree, &[sibling]);
+        let root = block(&mut tree, &[inner, other]);
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert!(!tree.dirty(root) && !tree.dirty(leaf));
+
+        tree.set_style(leaf, sized(10.0, 40.0));
+        assert!(tree.dirty(leaf) && tree.dirty(inner) && tree.dirty(root));
+        assert!(!tree.dirty(other) && !tree.dirty(sibling), "siblings keep their cache");
+
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(inner).size.height, 40.0);
+        assert_eq!(tree.layout(other).location.y, 40.0);
+    }
+
+    #[test]
+    fn test_set_children_invalidates_cache() {
+        let mut tree = LayoutTree::new(());
+        let (a, b) = (tree.new_leaf(sized(10.0, 10.0)), tree.new_leaf(sized(10.0, 20.0)));
+        let inner = block(&mut tree, &[a]);
+        let root = block(&mut tree, &[inner]);
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(root).size.height, 10.0);
+
+        tree.set_children(inner, &[b, a]);
+        assert!(tree.dirty(inner) && tree.dirty(root));
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(root).size.height, 30.0);
+        assert_eq!(tree.layout(a).location.y, 20.0);
+
+        tree.set_children(inner, &[]);
+        tree.compute_layout(root, Size::MAX_CONTENT);
+        assert_eq!(tree.layout(root).size.height, 0.0);
+    }
+}
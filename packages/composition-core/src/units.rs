@@ -0,0 +1,252 @@
+//! Font- and viewport-relative length units: `em`, `rem`, `vw`, `vh`,
+//! `vmin`, `vmax`.
+//!
+//! # Design decisions
+//! - Such lengths cannot be resolved while parsing: `em` depends on the
+//!   inherited font size, the others on the engine's root font size and
+//!   viewport. Style parsers leave the field at its default and record it in
+//!   a `RelativeStyle`; the layout tree resolves it to px in a pre-layout
+//!   pass, like inherited `direction`.
+//! - A node remembers the `Units` it was resolved with and is re-resolved
+//!   (and dirtied) only when a unit it actually uses changed, so a viewport
+//!   change leaves `em`-only nodes cached.
+//! - Resolving a mixed expression (`calc(100% - 2vw)`) interns a calc
+//!   handle. The node holds it with the rest of its style's handles
+//!   (`calc::StyleHandles`) and lets go once the field is re-resolved.
+//! - `font-size` is inherited; its own `em` and `%` refer to the parent's
+//!   font size. The root of a layout pass inherits the root font size.
+
+use taffy::prelude::*;
+
+use crate::calc::{parse_length, CalcLength, Expr};
+
+/// A relative length unit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unit {
+    Em,
+    Rem,
+    Vw,
+    Vh,
+    Vmin,
+    Vmax,
+}
+
+impl Unit {
+    pub const COUNT: usize = 6;
+
+    /// Suffixes in match order (`rem` before `em`).
+    pub const SUFFIXES: [(&'static str, Unit); Unit::COUNT] = [
+        ("rem", Unit::Rem),
+        ("em", Unit::Em),
+        ("vmin", Unit::Vmin),
+        ("vmax", Unit::Vmax),
+        ("vw", Unit::Vw),
+        ("vh", Unit::Vh),
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// What relative units resolve against at a node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Units {
+    pub font_size: f32,
+    pub root_font_size: f32,
+    pub viewport: Size<f32>,
+}
+
+impl Units {
+    /// Size of one `unit` in px.
+    pub fn px(&self, unit: Unit) -> f32 {
+        let Size { width, height } = self.viewport;
+        match unit {
+            Unit::Em => self.font_size,
+            Unit::Rem => self.root_font_size,
+            Unit::Vw => width / 100.0,
+            Unit::Vh => height / 100.0,
+            Unit::Vmin => width.min(height) / 100.0,
+            Unit::Vmax => width.max(height) / 100.0,
+        }
+    }
+
+    /// Whether any unit in `mask` (bit per `Unit::index`) differs from `other`.
+    pub fn differs(&self, other: &Units, mask: u8) -> bool {
+        Unit::SUFFIXES
+            .iter()
+            .any(|&(_, unit)| mask & (1 << unit.index()) != 0 && self.px(unit) != other.px(unit))
+    }
+}
+
+/// A length property of `Style` addressed by its camelCase style key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthField {
+    Width,
+    Height,
+    MinWidth,
+    MinHeight,
+    MaxWidth,
+    MaxHeight,
+    FlexBasis,
+    MarginTop,
+    MarginRight,
+    MarginBottom,
+    MarginLeft,
+    InsetTop,
+    InsetRight,
+    InsetBottom,
+    InsetLeft,
+    PaddingTop,
+    PaddingRight,
+    PaddingBottom,
+    PaddingLeft,
+    BorderTop,
+    BorderRight,
+    BorderBottom,
+    BorderLeft,
+    ColumnGap,
+    RowGap,
+}
+
+/// Mutable access to the Taffy value behind a `LengthField`.
+pub enum LengthSlot<'a> {
+    Dimension(&'a mut Dimension),
+    Lpa(&'a mut LengthPercentageAuto),
+    Lp(&'a mut LengthPercentage),
+}
+
+impl LengthField {
+    pub fn from_key(key: &str) -> Option<Self> {
+        Some(match key {
+            "width" => Self::Width,
+            "height" => Self::Height,
+            "minWidth" => Self::MinWidth,
+            "minHeight" => Self::MinHeight,
+            "maxWidth" => Self::MaxWidth,
+            "maxHeight" => Self::MaxHeight,
+            "flexBasis" => Self::FlexBasis,
+            "marginTop" => Self::MarginTop,
+            "marginRight" => Self::MarginRight,
+            "marginBottom" => Self::MarginBottom,
+            "marginLeft" => Self::MarginLeft,
+            "insetTop" => Self::InsetTop,
+            "insetRight" => Self::InsetRight,
+            "insetBottom" => Self::InsetBottom,
+            "insetLeft" => Self::InsetLeft,
+            "paddingTop" => Self::PaddingTop,
+            "paddingRight" => Self::PaddingRight,
+            "paddingBottom" => Self::PaddingBottom,
+            "paddingLeft" => Self::PaddingLeft,
+            "borderTop" => Self::BorderTop,
+            "borderRight" => Self::BorderRight,
+            "borderBottom" => Self::BorderBottom,
+            "borderLeft" => Self::BorderLeft,
+            "columnGap" => Self::ColumnGap,
+            "rowGap" => Self::RowGap,
+            _ => return None,
+        })
+    }
+
+    pub fn slot(self, style: &mut Style) -> LengthSlot<'_> {
+        use LengthSlot::{Dimension as D, Lp, Lpa};
+        match self {
+            Self::Width => D(&mut style.size.width),
+            Self::Height => D(&mut style.size.height),
+            Self::MinWidth => D(&mut style.min_size.width),
+            Self::MinHeight => D(&mut style.min_size.height),
+            Self::MaxWidth => D(&mut style.max_size.width),
+            Self::MaxHeight => D(&mut style.max_size.height),
+            Self::FlexBasis => D(&mut style.flex_basis),
+            Self::MarginTop => Lpa(&mut style.margin.top),
+            Self::MarginRight => Lpa(&mut style.margin.right),
+            Self::MarginBottom => Lpa(&mut style.margin.bottom),
+            Self::MarginLeft => Lpa(&mut style.margin.left),
+            Self::InsetTop => Lpa(&mut style.inset.top),
+            Self::InsetRight => Lpa(&mut style.inset.right),
+            Self::InsetBottom => Lpa(&mut style.inset.bottom),
+            Self::InsetLeft => Lpa(&mut style.inset.left),
+            Self::PaddingTop => Lp(&mut style.padding.top),
+            Self::PaddingRight => Lp(&mut style.padding.right),
+            Self::PaddingBottom => Lp(&mut style.padding.bottom),
+            Self::PaddingLeft => Lp(&mut style.padding.left),
+            Self::BorderTop => Lp(&mut style.border.top),
+            Self::BorderRight => Lp(&mut style.border.right),
+            Self::BorderBottom => Lp(&mut style.border.bottom),
+            Self::BorderLeft => Lp(&mut style.border.left),
+            Self::ColumnGap => Lp(&mut style.gap.width),
+            Self::RowGap => Lp(&mut style.gap.height),
+        }
+    }
+
+    /// Write a resolved length into the field.
+    pub fn set(self, style: &mut Style, value: CalcLength) {
+        match self.slot(style) {
+            LengthSlot::Dimension(v) => {
+                *v = value.into_taffy(Dimension::length, Dimension::percent, Dimension::calc)
+            }
+            LengthSlot::Lpa(v) => {
+                *v = value.into_taffy(
+                    LengthPercentageAuto::length,
+                    LengthPercentageAuto::percent,
+                    LengthPercentageAuto::calc,
+                )
+            }
+            LengthSlot::Lp(v) => {
+                *v = value.into_taffy(
+                    LengthPercentage::length,
+                    LengthPercentage::percent,
+                    LengthPercentage::calc,
+                )
+            }
+        }
+    }
+}
+
+/// The parts of a node's style that depend on relative units.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RelativeStyle {
+    /// Specified `font-size`; `None` inherits.
+    pub font_size: Option<Expr>,
+    /// Length fields given in relative units.
+    pub lengths: Vec<(LengthField, Expr)>,
+}
+
+impl RelativeStyle {
+    /// Record `value` for the style property `key` if it is a relative
+    /// length (or `fontSize`). Returns whether it was recorded.
+    pub fn record(&mut self, key: &str, value: &str) -> bool {
+        if key == "fontSize" {
+            self.font_size = parse_length(value);
+            return self.font_size.is_some();
+        }
+        match (LengthField::from_key(key), parse_length(value)) {
+            (Some(field), Some(expr)) if expr.is_relative() => {
+                self.lengths.retain(|&(f, _)| f != field);
+                self.lengths.push((field, expr));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Units used by the length fields, as a bit mask over `Unit::index`.
+    pub fn unit_mask(&self) -> u8 {
+        self.lengths.iter().fold(0, |mask, (_, expr)| mask | expr.unit_mask())
+    }
+
+    /// The node's font size given its parent's units.
+    pub fn resolve_font_size(&self, parent: &Units) -> f32 {
+        match &self.font_size {
+            Some(expr) => expr.resolve(parent).eval(parent.font_size),
+            None => parent.font_size,
+        }
+    }
+
+    /// Write the length fields, resolved against `units`, into `style`.
+    pub fn apply(&self, style: &mut Style, units: &Units) {
+        for &(field, ref expr) in &self.lengths {
+            field.set(style, expr.resolve(units).into_length());
+        }
+    }
+}
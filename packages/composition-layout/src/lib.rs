@@ -5,8 +5,12 @@ use std::collections::HashMap;
 use taffy::prelude::*;
 use wasm_bindgen::prelude::*;
 
+// Style parsing and the layout tree are shared with composition-wasm, so
+// both engines parse and lay out styles identically.
+use composition_core::shorthand::{expand_style, StyleEntries};
+use composition_core::tree::{Flow, LayoutTree};
 use spatial::SpatialGrid;
-use style::parse_style;
+use style::{parse_node_style, NodeStyle};
 
 // ---------------------------------------------------------------------------
 // FNV-1a 64-bit hash (no external dependency)
@@ -40,19 +44,34 @@ struct NodeMeta {
     display: Display,
 }
 
+/// `build_tree_batch` 입력 항목. style은 키 순서를 보존해 파싱한다.
+#[derive(serde::Deserialize)]
+struct BatchItem {
+    #[serde(default)]
+    style: Option<StyleEntries>,
+    #[serde(default)]
+    children: Vec<serde_json::Value>,
+}
+
 // ---------------------------------------------------------------------------
 // LayoutEngine
 // ---------------------------------------------------------------------------
 
 #[wasm_bindgen]
 pub struct LayoutEngine {
-    tree: TaffyTree<()>,
+    tree: LayoutTree<()>,
     /// handle (u32) → NodeMeta
     nodes: HashMap<u32, NodeMeta>,
     next_handle: u32,
     spatial: SpatialGrid,
 }
 
+impl Default for LayoutEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[wasm_bindgen]
 impl LayoutEngine {
     // -----------------------------------------------------------------------
@@ -62,7 +81,7 @@ impl LayoutEngine {
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
         Self {
-            tree: TaffyTree::new(),
+            tree: LayoutTree::new(()),
             nodes: HashMap::new(),
             next_handle: 1,
             spatial: SpatialGrid::new(256.0),
@@ -89,10 +108,14 @@ impl LayoutEngine {
 
     /// Create a leaf node with the given style JSON.  Returns a u32 handle.
     pub fn create_node(&mut self, style_json: &str) -> u32 {
-        let style = parse_style(style_json);
+        let NodeStyle { style, direction, relative, subgrid, masonry } = parse_node_style(style_json);
         let display = style.display;
         let hash = fnv1a_hash(style_json);
-        let node_id = self.tree.new_leaf(style).expect("taffy new_leaf");
+        let node_id = self.tree.new_leaf(style);
+        self.tree.set_flow(node_id, Flow { direction, writing_mode: None });
+        self.tree.set_relative_style(node_id, relative);
+        self.tree.set_subgrid(node_id, subgrid);
+        self.tree.set_masonry(node_id, masonry);
         let handle = self.alloc_handle();
         self.nodes.insert(
             handle,
@@ -111,7 +134,7 @@ impl LayoutEngine {
         style_json: &str,
         children: &[u32],
     ) -> u32 {
-        let style = parse_style(style_json);
+        let NodeStyle { style, direction, relative, subgrid, masonry } = parse_node_style(style_json);
         let display = style.display;
         let hash = fnv1a_hash(style_json);
 
@@ -120,10 +143,11 @@ impl LayoutEngine {
             .filter_map(|h| self.node_id(*h))
             .collect();
 
-        let node_id = self
-            .tree
-            .new_with_children(style, &child_ids)
-            .expect("taffy new_with_children");
+        let node_id = self.tree.new_with_children(style, &child_ids);
+        self.tree.set_flow(node_id, Flow { direction, writing_mode: None });
+        self.tree.set_relative_style(node_id, relative);
+        self.tree.set_subgrid(node_id, subgrid);
+        self.tree.set_masonry(node_id, masonry);
 
         let handle = self.alloc_handle();
         self.nodes.insert(
@@ -155,14 +179,16 @@ impl LayoutEngine {
             return UPDATE_UNCHANGED;
         }
 
-        let new_style = parse_style(style_json);
+        let NodeStyle { style: new_style, direction, relative, subgrid, masonry } = parse_node_style(style_json);
         let new_display = new_style.display;
         let old_display = meta.display;
 
         let node_id = meta.node_id;
-        self.tree
-            .set_style(node_id, new_style)
-            .expect("taffy set_style");
+        self.tree.set_style(node_id, new_style);
+        self.tree.set_flow(node_id, Flow { direction, writing_mode: None });
+        self.tree.set_relative_style(node_id, relative);
+        self.tree.set_subgrid(node_id, subgrid);
+        self.tree.set_masonry(node_id, masonry);
 
         // Update metadata
         let meta = self.nodes.get_mut(&handle).unwrap();
@@ -189,9 +215,7 @@ impl LayoutEngine {
             .iter()
             .filter_map(|h| self.node_id(*h))
             .collect();
-        self.tree
-            .set_children(node_id, &child_ids)
-            .expect("taffy set_children");
+        self.tree.set_children(node_id, &child_ids);
     }
 
     /// CSS order 기반 자식 정렬 후 set_children.
@@ -221,13 +245,13 @@ impl LayoutEngine {
 
     pub fn remove_node(&mut self, handle: u32) {
         if let Some(meta) = self.nodes.remove(&handle) {
-            let _ = self.tree.remove(meta.node_id);
+            self.tree.remove(meta.node_id);
         }
     }
 
     pub fn mark_dirty(&mut self, handle: u32) {
         if let Some(node_id) = self.node_id(handle) {
-            let _ = self.tree.mark_dirty(node_id);
+            self.tree.mark_dirty(node_id);
         }
     }
 
@@ -250,8 +274,7 @@ impl LayoutEngine {
         child_handles: &[u32],
         child_explicit_shrink: &[u8], // 1=explicit, 0=default
     ) {
-        let value: serde_json::Value =
-            serde_json::from_str(parent_style_json).unwrap_or(serde_json::Value::Null);
+        let value = expand_style(parent_style_json);
         let obj = match &value {
             serde_json::Value::Object(m) => m,
             _ => return,
@@ -299,10 +322,9 @@ impl LayoutEngine {
             }
 
             if let Some(meta) = self.nodes.get(&child_handle) {
-                if let Ok(mut child_style) = self.tree.style(meta.node_id).cloned() {
-                    child_style.flex_shrink = 0.0;
-                    let _ = self.tree.set_style(meta.node_id, child_style);
-                }
+                let mut child_style = self.tree.style(meta.node_id).clone();
+                child_style.flex_shrink = 0.0;
+                self.tree.set_style(meta.node_id, child_style);
             }
         }
     }
@@ -324,10 +346,9 @@ impl LayoutEngine {
             return;
         }
         if let Some(meta) = self.nodes.get(&handle) {
-            if let Ok(mut style) = self.tree.style(meta.node_id).cloned() {
-                style.min_size.width = Dimension::length(width);
-                let _ = self.tree.set_style(meta.node_id, style);
-            }
+            let mut style = self.tree.style(meta.node_id).clone();
+            style.min_size.width = Dimension::length(width);
+            self.tree.set_style(meta.node_id, style);
         }
     }
 
@@ -352,9 +373,17 @@ impl LayoutEngine {
                 AvailableSpace::MaxContent
             },
         };
-        self.tree
-            .compute_layout(node_id, avail)
-            .expect("taffy compute_layout");
+        self.tree.compute_layout(node_id, avail);
+    }
+
+    /// vw/vh/vmin/vmax 기준 뷰포트. 해당 단위를 쓰는 노드만 다음 compute_layout에서 갱신된다.
+    pub fn set_viewport(&mut self, width: f32, height: f32) {
+        self.tree.viewport = Size { width, height };
+    }
+
+    /// rem 기준 루트 폰트 크기 (기본 16px). 레이아웃 루트도 이 값을 상속한다.
+    pub fn set_root_font_size(&mut self, px: f32) {
+        self.tree.root_font_size = px;
     }
 
     // -----------------------------------------------------------------------
@@ -367,23 +396,19 @@ impl LayoutEngine {
             Some(id) => id,
             None => return r#"{"x":0,"y":0,"width":0,"height":0}"#.to_string(),
         };
-        match self.tree.layout(node_id) {
-            Ok(layout) => {
-                let x = ceil_to_pixel(layout.location.x);
-                let y = ceil_to_pixel(layout.location.y);
-                let w = ceil_to_pixel(layout.size.width);
-                let h = ceil_to_pixel(layout.size.height);
-                format!(r#"{{"x":{x},"y":{y},"width":{w},"height":{h}}}"#)
-            }
-            Err(_) => r#"{"x":0,"y":0,"width":0,"height":0}"#.to_string(),
-        }
+        let layout = self.tree.layout(node_id);
+        let x = ceil_to_pixel(layout.location.x);
+        let y = ceil_to_pixel(layout.location.y);
+        let w = ceil_to_pixel(layout.size.width);
+        let h = ceil_to_pixel(layout.size.height);
+        format!(r#"{{"x":{x},"y":{y},"width":{w},"height":{h}}}"#)
     }
 
     /// Returns flat Vec<f32>: [x, y, w, h,  x, y, w, h, ...]
     pub fn get_layouts_batch(&self, handles: &[u32]) -> Vec<f32> {
         let mut out = Vec::with_capacity(handles.len() * 4);
         for &handle in handles {
-            match self.node_id(handle).and_then(|id| self.tree.layout(id).ok()) {
+            match self.node_id(handle).map(|id| self.tree.layout(id)) {
                 Some(layout) => {
                     out.push(ceil_to_pixel(layout.location.x));
                     out.push(ceil_to_pixel(layout.location.y));
@@ -407,27 +432,23 @@ impl LayoutEngine {
     /// `children` are indices into the batch array (post-order, so children always < current index).
     /// Returns Vec<u32> handles in the same order as the input array.
     pub fn build_tree_batch(&mut self, nodes_json: &str) -> Vec<u32> {
-        let items: Vec<serde_json::Value> =
-            serde_json::from_str(nodes_json).unwrap_or_default();
+        let items: Vec<BatchItem> = serde_json::from_str(nodes_json).unwrap_or_default();
 
         let mut handles: Vec<u32> = Vec::with_capacity(items.len());
 
-        for item in &items {
-            let style_json = match item.get("style") {
-                Some(s) => s.to_string(),
+        for item in items {
+            // shorthand 우선순위는 키 순서에 의존하므로 순서가 보존된 상태에서 먼저 전개
+            let style_json = match item.style {
+                Some(entries) => entries.expand().to_string(),
                 None => "{}".to_string(),
             };
 
             let child_handles: Vec<u32> = item
-                .get("children")
-                .and_then(|c| c.as_array())
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| v.as_u64())
-                        .filter_map(|idx| handles.get(idx as usize).copied())
-                        .collect()
-                })
-                .unwrap_or_default();
+                .children
+                .iter()
+                .filter_map(|v| v.as_u64())
+                .filter_map(|idx| handles.get(idx as usize).copied())
+                .collect();
 
             let handle = if child_handles.is_empty() {
                 self.create_node(&style_json)
@@ -450,7 +471,7 @@ impl LayoutEngine {
     }
 
     pub fn clear(&mut self) {
-        self.tree = TaffyTree::new();
+        self.tree.clear();
         self.nodes.clear();
         self.next_handle = 1;
         self.spatial.clear();
@@ -839,4 +860,281 @@ mod tests {
         assert_eq!(l1["x"].as_f64().unwrap() as i32, 0);
         assert_eq!(l2["x"].as_f64().unwrap() as i32, 100);
     }
+
+    #[test]
+    fn rtl_direction_is_inherited() {
+        let mut engine = LayoutEngine::new();
+
+        let c1 = engine.create_node(r#"{"width":"100px","height":"50px"}"#);
+        let c2 = engine.create_node(r#"{"width":"50px","height":"50px"}"#);
+        // direction 미명시 → 부모(rtl) 상속
+        let row = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"row","width":"300px","height":"50px"}"#,
+            &[c1, c2],
+        );
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"column","direction":"rtl","width":"400px","height":"50px","alignItems":"flex-start"}"#,
+            &[row],
+        );
+        engine.compute_layout(root, 400.0, 50.0);
+
+        let x = |h: u32| -> i32 {
+            let l: serde_json::Value = serde_json::from_str(&engine.get_layout(h)).unwrap();
+            l["x"].as_f64().unwrap() as i32
+        };
+        // column의 cross-start = 오른쪽
+        assert_eq!(x(row), 100);
+        // row의 main-start = 오른쪽
+        assert_eq!(x(c1), 200);
+        assert_eq!(x(c2), 150);
+
+        // 루트를 ltr로 되돌리면 상속값도 갱신
+        engine.update_style(
+            root,
+            r#"{"display":"flex","flexDirection":"column","width":"400px","height":"50px","alignItems":"flex-start"}"#,
+        );
+        engine.compute_layout(root, 400.0, 50.0);
+        let l: serde_json::Value = serde_json::from_str(&engine.get_layout(c1)).unwrap();
+        assert_eq!(l["x"].as_f64().unwrap() as i32, 0);
+    }
+
+    #[test]
+    fn calc_lengths_resolve_against_container() {
+        let mut engine = LayoutEngine::new();
+
+        let c1 = engine.create_node(
+            r#"{"width":"calc(50% - 24px)","height":"calc(50% + 10px)","marginLeft":"calc(5% + 2px)"}"#,
+        );
+        let c2 = engine.create_node(r#"{"flexGrow":1,"height":"10px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"row","width":"400px","height":"100px","columnGap":"calc(2 * 4px)","alignItems":"flex-start"}"#,
+            &[c1, c2],
+        );
+        engine.compute_layout(root, 400.0, 100.0);
+
+        let l1: serde_json::Value = serde_json::from_str(&engine.get_layout(c1)).unwrap();
+        assert_eq!(l1["x"].as_f64().unwrap() as i32, 22);
+        assert_eq!(l1["width"].as_f64().unwrap() as i32, 176);
+        assert_eq!(l1["height"].as_f64().unwrap() as i32, 60);
+        // gap calc(2 * 4px) = 8px
+        let l2: serde_json::Value = serde_json::from_str(&engine.get_layout(c2)).unwrap();
+        assert_eq!(l2["x"].as_f64().unwrap() as i32, 206);
+    }
+
+    #[test]
+    fn comparison_lengths_resolve_against_container() {
+        let mut engine = LayoutEngine::new();
+
+        let c1 = engine.create_node(
+            r#"{"width":"clamp(320px, 50%, 960px)","height":"10px","marginLeft":"max(1%, 4px)"}"#,
+        );
+        let c2 = engine.create_node(r#"{"width":"min(20%, 20rem)","height":"10px"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"row","width":"1000px","height":"100px","columnGap":"clamp(4px, 1%, 8px)","alignItems":"flex-start"}"#,
+            &[c1, c2],
+        );
+        engine.compute_layout(root, 1000.0, 100.0);
+
+        let l1: serde_json::Value = serde_json::from_str(&engine.get_layout(c1)).unwrap();
+        assert_eq!(l1["x"].as_f64().unwrap() as i32, 10);
+        assert_eq!(l1["width"].as_f64().unwrap() as i32, 500);
+        // gap clamp(4px, 10px, 8px) = 8px
+        let l2: serde_json::Value = serde_json::from_str(&engine.get_layout(c2)).unwrap();
+        assert_eq!(l2["x"].as_f64().unwrap() as i32, 518);
+        assert_eq!(l2["width"].as_f64().unwrap() as i32, 200);
+
+        // 20rem = 160px < 20%
+        engine.set_root_font_size(8.0);
+        engine.compute_layout(root, 1000.0, 100.0);
+        let l2: serde_json::Value = serde_json::from_str(&engine.get_layout(c2)).unwrap();
+        assert_eq!(l2["width"].as_f64().unwrap() as i32, 160);
+    }
+
+    #[test]
+    fn batch_shorthands_keep_key_order() {
+        let mut engine = LayoutEngine::new();
+        // BTreeMap 정렬이었다면 margin이 marginLeft 뒤에 적용됨
+        let batch = r#"[
+            {"style":{"width":"10px","height":"10px","margin":"5px","marginLeft":"20px"},"children":[]},
+            {"style":{"display":"flex","flexFlow":"column","padding":"10px","width":"100px"},"children":[0]}
+        ]"#;
+        let handles = engine.build_tree_batch(batch);
+        engine.compute_layout(handles[1], 100.0, -1.0);
+
+        let l: serde_json::Value = serde_json::from_str(&engine.get_layout(handles[0])).unwrap();
+        assert_eq!(l["x"].as_f64().unwrap() as i32, 30);
+        assert_eq!(l["y"].as_f64().unwrap() as i32, 15);
+        let root: serde_json::Value = serde_json::from_str(&engine.get_layout(handles[1])).unwrap();
+        assert_eq!(root["height"].as_f64().unwrap() as i32, 40);
+    }
+
+    #[test]
+    fn grid_areas_and_named_lines_place_items() {
+        let mut engine = LayoutEngine::new();
+
+        let header = engine.create_node(r#"{"gridArea":"header"}"#);
+        let main = engine.create_node(r#"{"gridArea":"main"}"#);
+        let sidebar = engine.create_node(r#"{"gridColumn":"side-start / side-end","gridRow":"sidebar"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"grid","gridTemplateColumns":["[side-start] 100px [side-end]","1fr"],"gridTemplateRows":["40px","1fr"],"gridTemplateAreas":"\"header header\" \"sidebar main\"","width":"400px","height":"200px"}"#,
+            &[main, header, sidebar],
+        );
+        engine.compute_layout(root, 400.0, 200.0);
+
+        let rect = |h| {
+            let l: serde_json::Value = serde_json::from_str(&engine.get_layout(h)).unwrap();
+            ["x", "y", "width", "height"].map(|k| l[k].as_f64().unwrap() as i32)
+        };
+        assert_eq!(rect(header), [0, 0, 400, 40]);
+        assert_eq!(rect(sidebar), [0, 40, 100, 160]);
+        assert_eq!(rect(main), [100, 40, 300, 160]);
+    }
+
+    #[test]
+    fn repeat_minmax_and_fit_content_tracks() {
+        let mut engine = LayoutEngine::new();
+
+        let cards: Vec<u32> = (0..5).map(|_| engine.create_node(r#"{"height":"50px"}"#)).collect();
+        let gallery = engine.create_node_with_children(
+            r#"{"display":"grid","gridTemplateColumns":"repeat(auto-fill, minmax(100px, 1fr))","width":"440px"}"#,
+            &cards,
+        );
+        let a = engine.create_node(r#"{"width":"80px","height":"10px"}"#);
+        let b = engine.create_node(r#"{"width":"80px","height":"10px"}"#);
+        let fit = engine.create_node_with_children(
+            r#"{"display":"grid","gridTemplateColumns":"fit-content(150px) fit-content(150px)","width":"450px"}"#,
+            &[a, b],
+        );
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"column","alignItems":"flex-start","width":"450px"}"#,
+            &[gallery, fit],
+        );
+        engine.compute_layout(root, 450.0, 500.0);
+
+        let rect = |h| {
+            let l: serde_json::Value = serde_json::from_str(&engine.get_layout(h)).unwrap();
+            ["x", "y", "width", "height"].map(|k| l[k].as_f64().unwrap() as i32)
+        };
+        // four 100px-minimum columns fit and share the remaining 40px
+        assert_eq!(rect(cards[1]), [110, 0, 110, 50]);
+        assert_eq!(rect(cards[3]), [330, 0, 110, 50]);
+        assert_eq!(rect(cards[4]), [0, 50, 110, 50]);
+        // fit-content tracks hug their items instead of stretching like `auto`
+        assert_eq!(rect(a), [0, 0, 80, 10]);
+        assert_eq!(rect(b), [80, 0, 80, 10]);
+    }
+
+    #[test]
+    fn subgrid_aligns_to_parent_tracks() {
+        let mut engine = LayoutEngine::new();
+
+        let rect = |engine: &LayoutEngine, h| {
+            let l: serde_json::Value = serde_json::from_str(&engine.get_layout(h)).unwrap();
+            ["x", "y", "width", "height"].map(|k| l[k].as_f64().unwrap() as i32)
+        };
+        for (direction, label_rect, input_rect) in
+            [("ltr", [5, 5, 95, 20], [110, 5, 285, 20]), ("rtl", [300, 5, 95, 20], [5, 5, 285, 20])]
+        {
+            let label = engine.create_node(r#"{"height":"20px"}"#);
+            let input = engine.create_node(r#"{"height":"20px"}"#);
+            let form_row = engine.create_node_with_children(
+                r#"{"display":"grid","gridColumn":"1 / 3","gridTemplateColumns":"subgrid","padding":"5px"}"#,
+                &[label, input],
+            );
+            let root = engine.create_node_with_children(
+                &format!(
+                    r#"{{"display":"grid","direction":"{direction}","gridTemplateColumns":"100px 1fr","gap":"10px","width":"400px"}}"#
+                ),
+                &[form_row],
+            );
+            engine.compute_layout(root, 400.0, 300.0);
+            // padding comes out of the outer tracks; the parent's gap is inherited
+            assert_eq!(rect(&engine, label), label_rect, "{direction}");
+            assert_eq!(rect(&engine, input), input_rect, "{direction}");
+        }
+
+        let title = engine.create_node(r#"{"gridRow":"head"}"#);
+        let body = engine.create_node(r#"{"gridRow":"mid"}"#);
+        let card = engine.create_node_with_children(
+            r#"{"display":"grid","gridRow":"span 2","gridTemplateRows":"subgrid [head] [] [end]"}"#,
+            &[title, body],
+        );
+        let root = engine.create_node_with_children(
+            r#"{"display":"grid","gridTemplateRows":"[top] 40px [mid] 1fr [bottom]","rowGap":"10px","width":"200px","height":"200px"}"#,
+            &[card],
+        );
+        engine.compute_layout(root, 200.0, 200.0);
+        // `head` is the card's own line name, `mid` the parent's
+        assert_eq!(rect(&engine, title), [0, 0, 200, 40]);
+        assert_eq!(rect(&engine, body), [0, 50, 200, 150]);
+    }
+
+    #[test]
+    fn masonry_stacks_items_in_shortest_column() {
+        for direction in ["ltr", "rtl"] {
+            let mut engine = LayoutEngine::new();
+            let cards: Vec<u32> = [100, 50, 80, 40, 60]
+                .iter()
+                .map(|h| engine.create_node(&format!(r#"{{"height":"{h}px"}}"#)))
+                .collect();
+            let pinned = engine.create_node(r#"{"gridColumn":"3","height":"30px"}"#);
+            let wide = engine.create_node(r#"{"gridColumn":"span 2","height":"20px"}"#);
+            let mut children = cards.clone();
+            children.extend([pinned, wide]);
+            let board = engine.create_node_with_children(
+                &format!(
+                    r#"{{"display":"grid","direction":"{direction}","gridTemplateColumns":"repeat(3, 1fr)","gridTemplateRows":"masonry","gap":"10px","width":"320px"}}"#
+                ),
+                &children,
+            );
+            engine.compute_layout(board, 320.0, 1000.0);
+
+            // x from the inline start
+            let rect = |h| {
+                let l: serde_json::Value = serde_json::from_str(&engine.get_layout(h)).unwrap();
+                let [x, y, w, h] = ["x", "y", "width", "height"].map(|k| l[k].as_f64().unwrap() as i32);
+                [if direction == "rtl" { 320 - x - w } else { x }, y, w, h]
+            };
+            // `pinned` goes first, at the top of the third column
+            assert_eq!(rect(pinned), [220, 0, 100, 30], "{direction}");
+            assert_eq!(rect(cards[0]), [0, 0, 100, 100], "{direction}");
+            assert_eq!(rect(cards[1]), [110, 0, 100, 50], "{direction}");
+            assert_eq!(rect(cards[2]), [220, 40, 100, 80], "{direction}");
+            assert_eq!(rect(cards[3]), [110, 60, 100, 40], "{direction}");
+            // a tie goes to the first column
+            assert_eq!(rect(cards[4]), [0, 110, 100, 60], "{direction}");
+            // below the taller of the last two columns, which end higher
+            assert_eq!(rect(wide), [110, 130, 210, 20], "{direction}");
+            assert_eq!(rect(board)[3], 170, "{direction}");
+        }
+    }
+
+    #[test]
+    fn relative_units_follow_viewport_and_root_font_size() {
+        let mut engine = LayoutEngine::new();
+        engine.set_viewport(1000.0, 500.0);
+
+        let c1 = engine.create_node(r#"{"width":"10vw","height":"2em","marginLeft":"1rem"}"#);
+        let c2 = engine.create_node(r#"{"fontSize":"2em","width":"calc(50% - 1em)","height":"10vmin"}"#);
+        let root = engine.create_node_with_children(
+            r#"{"display":"flex","flexDirection":"column","alignItems":"flex-start","fontSize":"1.25rem","width":"400px"}"#,
+            &[c1, c2],
+        );
+        let rect = |engine: &LayoutEngine, h: u32| -> (i32, i32, i32, i32) {
+            let l: serde_json::Value = serde_json::from_str(&engine.get_layout(h)).unwrap();
+            let v = |k: &str| l[k].as_f64().unwrap() as i32;
+            (v("x"), v("y"), v("width"), v("height"))
+        };
+
+        engine.compute_layout(root, 400.0, 500.0);
+        // em: c1 = 20px (1.25rem), c2 = 40px
+        assert_eq!(rect(&engine, c1), (16, 0, 100, 40));
+        assert_eq!(rect(&engine, c2), (0, 40, 160, 50));
+
+        engine.set_viewport(800.0, 200.0);
+        engine.set_root_font_size(20.0);
+        engine.compute_layout(root, 400.0, 500.0);
+        assert_eq!(rect(&engine, c1), (20, 0, 80, 50));
+        assert_eq!(rect(&engine, c2), (0, 50, 150, 20));
+    }
 }
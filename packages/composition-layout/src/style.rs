@@ -1,23 +1,75 @@
 use serde_json::Value;
 use taffy::prelude::*;
-use taffy::style::Overflow;
-use taffy::style_helpers::{TaffyGridLine, TaffyGridSpan};
+use taffy::style::{GridTemplateRepetition, Overflow};
+use taffy::style_helpers::TaffyGridLine;
 use taffy::MinMax;
 
+use composition_core::calc::{parse_calc, CalcLength};
+use composition_core::grid_names::{self, parse_template_areas, split_line_names};
+use composition_core::masonry::is_masonry;
+use composition_core::shorthand::expand_style;
+use composition_core::subgrid::{parse_subgrid, Subgrid};
+use composition_core::tree::Direction;
+use composition_core::units::RelativeStyle;
+
 /// Parse a JSON style string into a Taffy `Style`.
 ///
 /// The JSON format matches the output of `normalizeStyle()` in taffyLayout.ts:
 /// - Dimensions: `"100px"`, `"50%"`, `"auto"`, or a raw number (treated as px)
 /// - All CSS layout properties that taffyLayout.ts supports
+/// - Shorthands (`margin`, `flex`, `gridArea`, ...) expand first; see `shorthand`
+#[cfg(test)]
 pub fn parse_style(json: &str) -> Style {
-    let value: Value = serde_json::from_str(json).unwrap_or(Value::Null);
+    parse_node_style(json).style
+}
+
+/// A parsed style plus the inherited inputs Taffy has no place for.
+pub struct NodeStyle {
+    pub style: Style,
+    /// Specified `direction`. `direction` is inherited, so the caller needs to
+    /// know whether it was set: `None` (absent or `"inherit"`) means the
+    /// parent's direction applies. `Style::direction` stays `ltr`: the tree
+    /// lays out RTL containers mirrored (see `composition_core::tree`).
+    pub direction: Option<Direction>,
+    /// `fontSize` and the lengths given in font- or viewport-relative units,
+    /// which are left at their defaults in `style` (see `units`).
+    pub relative: RelativeStyle,
+    /// `subgrid` axes of `gridTemplateColumns/Rows`, whose tracks are left
+    /// empty in `style` (see `subgrid`).
+    pub subgrid: Subgrid,
+    /// `gridTemplateRows: "masonry"`, which leaves the rows empty in `style`
+    /// (see `masonry`).
+    pub masonry: bool,
+}
+
+/// Parse a JSON style string, also returning the inherited inputs.
+pub fn parse_node_style(json: &str) -> NodeStyle {
+    let value = expand_style(json);
     let obj = match &value {
         Value::Object(m) => m,
-        _ => return Style::DEFAULT,
+        _ => {
+            return NodeStyle {
+                style: Style::DEFAULT,
+                direction: None,
+                relative: RelativeStyle::default(),
+                subgrid: Subgrid::default(),
+                masonry: false,
+            }
+        }
     };
 
     let mut style = Style::DEFAULT;
 
+    // --- direction ---
+    let direction = obj
+        .get("direction")
+        .and_then(|v| v.as_str())
+        .and_then(|v| match v {
+            "ltr" => Some(Direction::Ltr),
+            "rtl" => Some(Direction::Rtl),
+            _ => None,
+        });
+
     // --- display ---
     if let Some(v) = obj.get("display").and_then(|v| v.as_str()) {
         style.display = match v {
@@ -172,11 +224,19 @@ pub fn parse_style(json: &str) -> Style {
     }
 
     // --- grid template tracks ---
-    if let Some(v) = obj.get("gridTemplateColumns") {
-        style.grid_template_columns = parse_template_track_list(v);
+    let subgrid_columns = obj.get("gridTemplateColumns").and_then(|v| parse_subgrid(track_tokens(v)));
+    let subgrid_rows = obj.get("gridTemplateRows").and_then(|v| parse_subgrid(track_tokens(v)));
+    if let (Some(v), None) = (obj.get("gridTemplateColumns"), &subgrid_columns) {
+        (style.grid_template_columns, style.grid_template_column_names) =
+            parse_template_track_list(v);
+    }
+    let masonry = obj.get("gridTemplateRows").is_some_and(|v| is_masonry(track_tokens(v)));
+    if let (Some(v), None, false) = (obj.get("gridTemplateRows"), &subgrid_rows, masonry) {
+        (style.grid_template_rows, style.grid_template_row_names) = parse_template_track_list(v);
     }
-    if let Some(v) = obj.get("gridTemplateRows") {
-        style.grid_template_rows = parse_template_track_list(v);
+    let subgrid = Subgrid::new(subgrid_columns, subgrid_rows);
+    if let Some(v) = obj.get("gridTemplateAreas") {
+        style.grid_template_areas = parse_template_areas(v).unwrap_or_default();
     }
 
     if let Some(v) = obj.get("gridAutoFlow").and_then(|v| v.as_str()) {
@@ -209,7 +269,17 @@ pub fn parse_style(json: &str) -> Style {
         style.grid_row.end = parse_grid_placement(v);
     }
 
-    style
+    // --- relative units ---
+    let mut relative = RelativeStyle::default();
+    for (key, v) in obj {
+        match v {
+            Value::String(s) => relative.record(key, s),
+            Value::Number(n) if key == "fontSize" => relative.record(key, &n.to_string()),
+            _ => false,
+        };
+    }
+
+    NodeStyle { style, direction, relative, subgrid, masonry }
 }
 
 // ---------------------------------------------------------------------------
@@ -226,11 +296,24 @@ fn parse_dimension(v: Option<&Value>) -> Dimension {
     }
 }
 
+/// Map a `calc(...)` string onto a Taffy length type, if it is one.
+fn parse_calc_as<T>(
+    s: &str,
+    length: fn(f32) -> T,
+    percent: fn(f32) -> T,
+    calc: fn(*const ()) -> T,
+) -> Option<T> {
+    parse_calc(s).map(|v| v.into_taffy(length, percent, calc))
+}
+
 fn parse_dimension_str(s: &str) -> Dimension {
     let s = s.trim();
     if s == "auto" {
         return Dimension::auto();
     }
+    if let Some(d) = parse_calc_as(s, Dimension::length, Dimension::percent, Dimension::calc) {
+        return d;
+    }
     if let Some(px) = s.strip_suffix("px") {
         if let Ok(v) = px.parse::<f32>() {
             return Dimension::length(v);
@@ -264,6 +347,14 @@ fn parse_lpa_str(s: &str) -> LengthPercentageAuto {
     if s == "auto" {
         return LengthPercentageAuto::auto();
     }
+    if let Some(v) = parse_calc_as(
+        s,
+        LengthPercentageAuto::length,
+        LengthPercentageAuto::percent,
+        LengthPercentageAuto::calc,
+    ) {
+        return v;
+    }
     if let Some(px) = s.strip_suffix("px") {
         if let Ok(v) = px.parse::<f32>() {
             return LengthPercentageAuto::length(v);
@@ -292,6 +383,11 @@ fn parse_lp(v: Option<&Value>) -> LengthPercentage {
 
 fn parse_lp_str(s: &str) -> LengthPercentage {
     let s = s.trim();
+    if let Some(v) =
+        parse_calc_as(s, LengthPercentage::length, LengthPercentage::percent, LengthPercentage::calc)
+    {
+        return v;
+    }
     if let Some(px) = s.strip_suffix("px") {
         if let Ok(v) = px.parse::<f32>() {
             return LengthPercentage::length(v);
@@ -344,8 +440,6 @@ fn parse_align_content(s: &str) -> Option<AlignContent> {
     Some(match s {
         "flex-start" | "start" => AlignContent::Start,
         "flex-end" | "end" => AlignContent::End,
-        "start" => AlignContent::Start,
-        "end" => AlignContent::End,
         "center" => AlignContent::Center,
         "space-between" => AlignContent::SpaceBetween,
         "space-around" => AlignContent::SpaceAround,
@@ -360,8 +454,6 @@ fn parse_align_self(s: &str) -> Option<AlignSelf> {
         "auto" => return None,
         "flex-start" | "start" => AlignSelf::Start,
         "flex-end" | "end" => AlignSelf::End,
-        "start" => AlignSelf::Start,
-        "end" => AlignSelf::End,
         "center" => AlignSelf::Center,
         "baseline" => AlignSelf::Baseline,
         "stretch" => AlignSelf::Stretch,
@@ -387,84 +479,185 @@ fn parse_overflow(s: &str) -> Overflow {
 // Grid helpers
 // ---------------------------------------------------------------------------
 
-/// Parse a JSON value into `Vec<GridTemplateComponent>` for `grid_template_columns/rows`.
-/// `GridTemplateComponent` implements `From<TrackSizingFunction>`.
-fn parse_template_track_list(v: &Value) -> Vec<GridTemplateComponent<String>> {
-    let tracks = parse_track_list(v);
-    tracks.into_iter().map(GridTemplateComponent::from).collect()
+/// Parse a JSON value into `Vec<GridTemplateComponent>` for `grid_template_columns/rows`,
+/// plus the `[name]` line names between the tracks (empty when none are given).
+fn parse_template_track_list(
+    v: &Value,
+) -> (Vec<GridTemplateComponent<String>>, Vec<Vec<String>>) {
+    let tokens = track_tokens(v);
+    let (tracks, names) = split_line_names(tokens.iter().copied());
+    let tracks = tracks.into_iter().map(parse_template_component).collect();
+    let names = if names.iter().all(Vec::is_empty) { Vec::new() } else { names };
+    (tracks, names)
 }
 
 /// Parse a JSON value (string or array) into a `Vec<TrackSizingFunction>`.
 /// Used for `grid_auto_columns` / `grid_auto_rows`.
 fn parse_track_list(v: &Value) -> Vec<TrackSizingFunction> {
+    track_tokens(v).into_iter().map(parse_track).collect()
+}
+
+/// The tokens of a track list given as an array of strings or a
+/// space-separated string ("1fr [main-start] 100px"). Items may hold several
+/// tokens.
+fn track_tokens(v: &Value) -> Vec<&str> {
     match v {
-        Value::Array(arr) => arr
-            .iter()
-            .filter_map(|item| item.as_str().map(parse_track))
-            .collect(),
-        Value::String(s) => {
-            // Space-separated shorthand: "1fr 1fr 100px"
-            s.split_whitespace().map(parse_track).collect()
-        }
+        Value::Array(arr) => arr.iter().filter_map(Value::as_str).flat_map(split_tracks).collect(),
+        Value::String(s) => split_tracks(s),
         _ => vec![],
     }
 }
 
-/// Parse a single track sizing token into a `TrackSizingFunction`.
-///
-/// `TrackSizingFunction = MinMax<MinTrackSizingFunction, MaxTrackSizingFunction>`
-fn parse_track(s: &str) -> TrackSizingFunction {
-    let s = s.trim();
-
-    if s == "auto" {
-        return MinMax {
-            min: MinTrackSizingFunction::auto(),
-            max: MaxTrackSizingFunction::auto(),
-        };
+/// Split a track list on whitespace; parentheses and `[line names]` stay in
+/// one token.
+fn split_tracks(s: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut depth = 0u32;
+    let mut start = None;
+    for (i, ch) in s.char_indices() {
+        match ch {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if ch.is_whitespace() && depth == 0 => {
+                if let Some(begin) = start.take() {
+                    tokens.push(&s[begin..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(i);
+    }
+    if let Some(begin) = start {
+        tokens.push(&s[begin..]);
     }
+    tokens
+}
 
-    if let Some(fr_str) = s.strip_suffix("fr") {
-        if let Ok(v) = fr_str.parse::<f32>() {
-            return MinMax {
-                min: MinTrackSizingFunction::auto(),
-                max: MaxTrackSizingFunction::fr(v),
-            };
+/// The arguments of `name(...)`, split on top-level commas.
+fn function_args<'a>(s: &'a str, name: &str) -> Option<Vec<&'a str>> {
+    let inner = s.strip_prefix(name)?.strip_prefix('(')?.strip_suffix(')')?;
+    let mut args = Vec::new();
+    let mut depth = 0u32;
+    let mut begin = 0;
+    for (i, ch) in inner.char_indices() {
+        match ch {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                args.push(inner[begin..i].trim());
+                begin = i + 1;
+            }
+            _ => {}
         }
     }
+    args.push(inner[begin..].trim());
+    Some(args)
+}
 
-    if let Some(px_str) = s.strip_suffix("px") {
-        if let Ok(v) = px_str.parse::<f32>() {
-            return MinMax {
-                min: MinTrackSizingFunction::length(v),
-                max: MaxTrackSizingFunction::length(v),
-            };
-        }
+/// Parse a template track token: a track or
+/// `repeat(<integer> | auto-fill | auto-fit, <track list>)`.
+/// An invalid `repeat()` is a single `auto` track.
+fn parse_template_component(s: &str) -> GridTemplateComponent<String> {
+    let s = s.trim();
+    let Some(args) = function_args(s, "repeat") else {
+        return GridTemplateComponent::Single(parse_track(s));
+    };
+    let count = match args.first().copied() {
+        Some("auto-fill") => Some(RepetitionCount::AutoFill),
+        Some("auto-fit") => Some(RepetitionCount::AutoFit),
+        Some(n) => n.parse::<u16>().ok().filter(|&n| n > 0).map(RepetitionCount::Count),
+        None => None,
+    };
+    let (tracks, line_names) = match args[..] {
+        [_, list] => split_line_names(split_tracks(list)),
+        _ => (Vec::new(), Vec::new()),
+    };
+    match count {
+        Some(count) if !tracks.is_empty() => GridTemplateComponent::Repeat(GridTemplateRepetition {
+            count,
+            tracks: tracks.into_iter().map(parse_track).collect(),
+            line_names,
+        }),
+        _ => GridTemplateComponent::Single(auto()),
     }
+}
 
-    if let Some(pct_str) = s.strip_suffix('%') {
-        if let Ok(v) = pct_str.parse::<f32>() {
-            return MinMax {
-                min: MinTrackSizingFunction::percent(v / 100.0),
-                max: MaxTrackSizingFunction::percent(v / 100.0),
-            };
+/// Parse a single track sizing token into a `TrackSizingFunction`:
+/// a track breadth, `minmax(<min>, <max>)` or `fit-content(<length-percentage>)`.
+/// An invalid track is `auto`.
+///
+/// `TrackSizingFunction = MinMax<MinTrackSizingFunction, MaxTrackSizingFunction>`
+fn parse_track(s: &str) -> TrackSizingFunction {
+    let s = s.trim();
+    let track = if let Some(args) = function_args(s, "minmax") {
+        match args[..] {
+            // `fr` is only valid as the maximum
+            [min, max] => parse_track_breadth(min)
+                .filter(|min| !min.is_fr())
+                .zip(parse_track_breadth(max))
+                .map(|(min, max)| MinMax { min: min.into(), max }),
+            _ => None,
         }
-    }
+    } else if let Some(args) = function_args(s, "fit-content") {
+        match args[..] {
+            [limit] => parse_fit_content(limit).map(|max| MinMax { min: MinTrackSizingFunction::auto(), max }),
+            _ => None,
+        }
+    } else {
+        // a lone breadth is its own minimum, except that `fr` has an `auto` minimum
+        parse_track_breadth(s).map(|max| MinMax { min: max.into(), max })
+    };
+    track.unwrap_or_else(auto)
+}
 
-    // Numeric fallback → px
-    if let Ok(v) = s.parse::<f32>() {
-        return MinMax {
-            min: MinTrackSizingFunction::length(v),
-            max: MaxTrackSizingFunction::length(v),
-        };
+/// Parse `auto`, `min-content`, `max-content`, `<flex>` or a length-percentage.
+fn parse_track_breadth(s: &str) -> Option<MaxTrackSizingFunction> {
+    match s {
+        "auto" => return Some(MaxTrackSizingFunction::auto()),
+        "min-content" => return Some(MaxTrackSizingFunction::min_content()),
+        "max-content" => return Some(MaxTrackSizingFunction::max_content()),
+        _ => {}
+    }
+    if let Some(v) = parse_calc_as(
+        s,
+        MaxTrackSizingFunction::length,
+        MaxTrackSizingFunction::percent,
+        MaxTrackSizingFunction::calc,
+    ) {
+        return Some(v);
+    }
+    if let Some(v) = s.strip_suffix("fr").and_then(|v| v.parse::<f32>().ok()) {
+        return (v >= 0.0).then(|| MaxTrackSizingFunction::fr(v));
     }
+    if let Some(v) = s.strip_suffix("px").and_then(|v| v.parse::<f32>().ok()) {
+        return Some(MaxTrackSizingFunction::length(v));
+    }
+    if let Some(v) = s.strip_suffix('%').and_then(|v| v.parse::<f32>().ok()) {
+        return Some(MaxTrackSizingFunction::percent(v / 100.0));
+    }
+    // Numeric fallback → px
+    s.parse::<f32>().ok().map(MaxTrackSizingFunction::length)
+}
 
-    MinMax {
-        min: MinTrackSizingFunction::auto(),
-        max: MaxTrackSizingFunction::auto(),
+/// Parse the limit of `fit-content()`. Taffy has no calc form for it, so a
+/// `calc()` limit must reduce to a plain length or percentage.
+fn parse_fit_content(s: &str) -> Option<MaxTrackSizingFunction> {
+    let limit = match parse_calc(s) {
+        Some(v) => v,
+        None => match s.strip_suffix('%') {
+            Some(pct) => CalcLength::Percent(pct.parse::<f32>().ok()? / 100.0),
+            None => CalcLength::Length(s.strip_suffix("px").unwrap_or(s).parse().ok()?),
+        },
+    };
+    match limit {
+        CalcLength::Length(v) => Some(MaxTrackSizingFunction::fit_content_px(v)),
+        CalcLength::Percent(v) => Some(MaxTrackSizingFunction::fit_content_percent(v)),
+        CalcLength::Mixed(_) => None,
     }
 }
 
-/// Parse a grid placement value (number or "auto" or "span N").
+/// Parse a grid placement value (number, or a string such as "span 2" or "main-start").
 fn parse_grid_placement(v: &Value) -> GridPlacement {
     match v {
         Value::Number(n) => {
@@ -474,20 +667,7 @@ fn parse_grid_placement(v: &Value) -> GridPlacement {
                 GridPlacement::Auto
             }
         }
-        Value::String(s) if s == "auto" => GridPlacement::Auto,
-        Value::String(s) => {
-            if let Ok(i) = s.parse::<i16>() {
-                GridPlacement::from_line_index(i)
-            } else if let Some(span_str) = s.strip_prefix("span ") {
-                if let Ok(n) = span_str.parse::<u16>() {
-                    GridPlacement::from_span(n)
-                } else {
-                    GridPlacement::Auto
-                }
-            } else {
-                GridPlacement::Auto
-            }
-        }
+        Value::String(s) => grid_names::parse_grid_placement(s),
         _ => GridPlacement::Auto,
     }
 }
@@ -499,6 +679,7 @@ fn parse_grid_placement(v: &Value) -> GridPlacement {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use taffy::style_helpers::TaffyGridSpan;
 
     #[test]
     fn parse_flex_row() {
@@ -564,6 +745,129 @@ mod tests {
         assert_eq!(style.gap.height, LengthPercentage::length(8.0));
     }
 
+    #[test]
+    fn parse_calc_lengths() {
+        let style = parse_style(
+            r#"{"width":"calc(100% - 24px)","height":"calc(50% / 2)","marginTop":"calc(4px * 2)","paddingLeft":"calc(1px + 1px)","flexBasis":"calc(10px + 5%)"}"#,
+        );
+        let Some(CalcLength::Mixed(handle)) = parse_calc("calc(100% - 24px)") else {
+            panic!("expected a mixed calc value");
+        };
+        assert_eq!(style.size.width, Dimension::calc(handle));
+        assert!(style.flex_basis.into_raw().is_calc());
+        assert_eq!(style.size.height, Dimension::percent(0.25));
+        assert_eq!(style.margin.top, LengthPercentageAuto::length(8.0));
+        assert_eq!(style.padding.left, LengthPercentage::length(2.0));
+
+        let invalid = parse_style(r#"{"width":"calc(10px * 10px)"}"#);
+        assert_eq!(invalid.size.width, Dimension::auto());
+    }
+
+    #[test]
+    fn parse_comparison_lengths() {
+        let style = parse_style(
+            r#"{"width":"clamp(320px, 50%, 960px)","maxWidth":"min(100%, 640px)","columnGap":"max(8px, 12px)","paddingTop":"min(2%, 4px)"}"#,
+        );
+        let Some(CalcLength::Mixed(handle)) = parse_calc("clamp(320px, 50%, 960px)") else {
+            panic!("expected a mixed value");
+        };
+        assert_eq!(style.size.width, Dimension::calc(handle));
+        assert!(style.max_size.width.into_raw().is_calc());
+        assert!(style.padding.top.into_raw().is_calc());
+        assert_eq!(style.gap.width, LengthPercentage::length(12.0));
+
+        let invalid = parse_style(r#"{"width":"clamp(1px, 2px)"}"#);
+        assert_eq!(invalid.size.width, Dimension::auto());
+    }
+
+    #[test]
+    fn parse_shorthands() {
+        let style = parse_style(
+            r#"{"margin":"8px 16px","marginLeft":"2px","flex":"1 1 0","flexFlow":"column wrap","gridColumn":"1 / span 2","placeItems":"center end","gap":"4px 6px"}"#,
+        );
+        assert_eq!(style.margin.top, LengthPercentageAuto::length(8.0));
+        assert_eq!(style.margin.right, LengthPercentageAuto::length(16.0));
+        assert_eq!(style.margin.left, LengthPercentageAuto::length(2.0));
+        assert_eq!((style.flex_grow, style.flex_shrink), (1.0, 1.0));
+        assert_eq!(style.flex_basis, Dimension::length(0.0));
+        assert_eq!(style.flex_direction, FlexDirection::Column);
+        assert_eq!(style.flex_wrap, FlexWrap::Wrap);
+        assert_eq!(style.grid_column.start, GridPlacement::from_line_index(1));
+        assert_eq!(style.grid_column.end, GridPlacement::from_span(2));
+        assert_eq!(style.align_items, Some(AlignItems::Center));
+        assert_eq!(style.justify_items, Some(AlignItems::End));
+        assert_eq!(style.gap.height, LengthPercentage::length(4.0));
+        assert_eq!(style.gap.width, LengthPercentage::length(6.0));
+
+        // 뒤에 오는 shorthand가 앞선 longhand를 덮어씀
+        let style = parse_style(r#"{"paddingTop":"3px","padding":"1px"}"#);
+        assert_eq!(style.padding.top, LengthPercentage::length(1.0));
+    }
+
+    #[test]
+    fn parse_relative_units() {
+        let NodeStyle { style, relative, .. } =
+            parse_node_style(r#"{"width":"50vw","marginTop":"1rem","height":"10px","fontSize":18}"#);
+        assert_eq!(style.size.width, Dimension::auto(), "resolved before layout");
+        assert_eq!(style.size.height, Dimension::length(10.0));
+        assert_eq!(relative.lengths.len(), 2);
+        assert!(relative.font_size.is_some());
+    }
+
+    #[test]
+    fn parse_grid_names() {
+        let style = parse_style(
+            r#"{"display":"grid","gridTemplateColumns":"[full-start] 1fr [main-start] minmax(0, 2fr) [main-end full-end]","gridTemplateAreas":["a a","b c"],"gridColumn":"main","gridRowEnd":"span b"}"#,
+        );
+        assert_eq!(style.grid_template_columns.len(), 2);
+        assert_eq!(
+            style.grid_template_column_names,
+            [vec!["full-start".to_string()], vec!["main-start".to_string()], vec!["main-end".to_string(), "full-end".to_string()]]
+        );
+        assert_eq!(style.grid_template_areas.len(), 3);
+        assert_eq!(style.grid_column.start, GridPlacement::NamedLine("main".to_string(), 0));
+        assert_eq!(style.grid_column.end, GridPlacement::NamedLine("main".to_string(), 0));
+        assert_eq!(style.grid_row.end, GridPlacement::NamedSpan("b".to_string(), 1));
+
+        let plain = parse_style(r#"{"gridTemplateColumns":["1fr","1fr"]}"#);
+        assert!(plain.grid_template_column_names.is_empty());
+    }
+
+    #[test]
+    fn parse_track_grammar() {
+        let style = parse_style(
+            r#"{"gridTemplateColumns":"repeat(auto-fill, [card] minmax(200px, 1fr)) min-content fit-content(300px) repeat(2, 10% max-content)","gridAutoRows":"minmax(min-content, calc(20% + 10px))"}"#,
+        );
+        let columns = &style.grid_template_columns;
+        assert_eq!(columns.len(), 4);
+        let GridTemplateComponent::Repeat(fill) = &columns[0] else { panic!("expected repeat()") };
+        assert_eq!(fill.count, RepetitionCount::AutoFill);
+        assert_eq!(fill.tracks, [minmax(length(200.0), fr(1.0))]);
+        assert_eq!(fill.line_names, [vec!["card".to_string()], vec![]]);
+        assert_eq!(columns[1], GridTemplateComponent::Single(min_content()));
+        assert_eq!(
+            columns[2],
+            GridTemplateComponent::Single(MinMax {
+                min: MinTrackSizingFunction::auto(),
+                max: MaxTrackSizingFunction::fit_content_px(300.0),
+            })
+        );
+        let GridTemplateComponent::Repeat(twice) = &columns[3] else { panic!("expected repeat()") };
+        assert_eq!(twice.count, RepetitionCount::Count(2));
+        assert_eq!(twice.tracks, [percent(0.1), max_content()]);
+
+        let row = style.grid_auto_rows[0];
+        assert_eq!(row.min, MinTrackSizingFunction::min_content());
+        assert!(!row.max.is_fr() && !row.max.is_fit_content() && !row.max.is_max_content_alike());
+
+        // invalid tracks stay `auto`
+        let invalid = parse_style(
+            r#"{"gridTemplateColumns":["minmax(1fr, 100px)","fit-content(auto)","repeat(0, 1fr)","repeat(2)","1fr"]}"#,
+        );
+        assert!(invalid.grid_template_columns[..4].iter().all(|c| *c == GridTemplateComponent::Single(auto())));
+        assert_eq!(invalid.grid_template_columns[4], GridTemplateComponent::Single(fr(1.0)));
+    }
+
     #[test]
     fn parse_grid_auto_flow() {
         let style = parse_style(r#"{"display":"grid","gridAutoFlow":"column"}"#);
@@ -591,6 +895,44 @@ mod tests {
         assert_eq!(style.display, Display::None);
     }
 
+    #[test]
+    fn parse_direction() {
+        let NodeStyle { style, direction, .. } = parse_node_style(r#"{"direction":"rtl"}"#);
+        assert_eq!(style.direction, taffy::style::Direction::Ltr);
+        assert_eq!(direction, Some(Direction::Rtl));
+
+        let NodeStyle { style, direction, .. } = parse_node_style(r#"{"direction":"inherit"}"#);
+        assert_eq!(style.direction, taffy::style::Direction::Ltr);
+        assert_eq!(direction, None);
+    }
+
+    #[test]
+    fn parse_subgrid_templates() {
+        let NodeStyle { style, subgrid, .. } = parse_node_style(
+            r#"{"display":"grid","gridTemplateColumns":"subgrid [a] [] [b c]","gridTemplateRows":["40px","1fr"]}"#,
+        );
+        assert!(style.grid_template_columns.is_empty());
+        let names = |line: &[&str]| line.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(subgrid.columns, Some(vec![names(&["a"]), names(&[]), names(&["b", "c"])]));
+        assert_eq!(subgrid.rows, None);
+        assert_eq!(style.grid_template_rows.len(), 2);
+
+        let NodeStyle { subgrid, .. } = parse_node_style(r#"{"gridTemplateRows":["subgrid"]}"#);
+        assert_eq!(subgrid.rows, Some(vec![]));
+    }
+
+    #[test]
+    fn parse_masonry_rows() {
+        let NodeStyle { style, masonry, .. } =
+            parse_node_style(r#"{"gridTemplateColumns":"repeat(3, 1fr)","gridTemplateRows":"masonry"}"#);
+        assert!(masonry);
+        assert!(style.grid_template_rows.is_empty());
+        assert_eq!(style.grid_template_columns.len(), 1);
+
+        let NodeStyle { masonry, .. } = parse_node_style(r#"{"gridTemplateColumns":["masonry"]}"#);
+        assert!(!masonry);
+    }
+
     #[test]
     fn parse_empty_object() {
         let style = parse_style(r#"{}"#);